@@ -0,0 +1,3 @@
+// The server hosting the frontend serves the API under `/api`,
+// `VITE_SERVER_URL` points to it when the frontend is hosted elsewhere.
+export const API_URL: string = import.meta.env.VITE_SERVER_URL ?? "/api";
@@ -1,5 +1,6 @@
 import { useEffect, useState } from "react";
 import "../App.css";
+import { API_URL } from "../api";
 import TableChooser from "./tableChooser";
 import { Button, Table } from "react-bootstrap";
 import TableRow from "./tableRow";
@@ -64,7 +65,7 @@ function EntityTable() {
 
 async function getContent(table: TableName): Promise<Entity[]> {
   const res = await fetch(
-    `${import.meta.env.VITE_SERVER_URL}/${table.replaceAll("_", "-")}`
+    `${API_URL}/${table.replaceAll("_", "-")}`
   );
   const data = await res.json();
 
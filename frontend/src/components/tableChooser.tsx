@@ -1,6 +1,7 @@
 import { useEffect, useState } from "react";
 import { Form } from "react-bootstrap";
 import "../App.css";
+import { API_URL } from "../api";
 import { Table } from "../model";
 
 type Props = { callback: (table: Table) => void; defaultTable: Table };
@@ -29,7 +30,7 @@ function TableChooser({ callback, defaultTable }: Props) {
 }
 
 async function getTables(): Promise<Table[]> {
-  const res = await fetch(`${import.meta.env.VITE_SERVER_URL}/table`);
+  const res = await fetch(`${API_URL}/table`);
   const data = await res.json();
   return data as Table[];
 }
@@ -1,6 +1,7 @@
 import { useEffect, useState } from "react";
 import { Button, Form } from "react-bootstrap";
 import "../App.css";
+import { API_URL } from "../api";
 import { Entity, PrimaryKey, Table, defaultModel, getKeys } from "../model";
 
 type Props<T extends Entity> = {
@@ -92,7 +93,7 @@ function TableRow<T extends Entity>({
               if (oldPrimaryKey === 0 || oldPrimaryKey === "")
                 createContent(table, entity).then(setEntity);
               else if (isDeleted)
-                deleteContent(table, oldPrimaryKey).then(updateTable);
+                deleteContent(table, entity, oldPrimaryKey).then(updateTable);
               else update(table, entity, oldPrimaryKey).then(setEntity);
 
               setChanged(false);
@@ -129,10 +130,13 @@ async function update<T extends Entity>(
   oldKey: T[PrimaryKey<T>]
 ) {
   const res = await fetch(
-    `${import.meta.env.VITE_SERVER_URL}/${table}/${oldKey}`,
+    `${API_URL}/${table}/${oldKey}`,
     {
       method: "PUT",
-      headers: { "Content-Type": "application/json" },
+      headers: {
+        "Content-Type": "application/json",
+        "If-Match": `"${entity.version}"`,
+      },
       body: JSON.stringify(entity),
     }
   );
@@ -147,7 +151,7 @@ async function createContent<T extends Entity>(
   entity: T
 ): Promise<T> {
   const res = await fetch(
-    `${import.meta.env.VITE_SERVER_URL}/${table.replaceAll("_", "-")}`,
+    `${API_URL}/${table.replaceAll("_", "-")}`,
     {
       method: "POST",
       headers: { "Content-Type": "application/json" },
@@ -162,16 +166,20 @@ async function createContent<T extends Entity>(
 
 async function deleteContent<T extends Entity>(
   table: Table,
+  entity: T,
   oldKey: T[PrimaryKey<T>]
 ) {
   const res = await fetch(
-    `${import.meta.env.VITE_SERVER_URL}/${table.replaceAll(
+    `${API_URL}/${table.replaceAll(
       "_",
       "-"
     )}/${oldKey}`,
     {
       method: "DELETE",
-      headers: { "Content-Type": "application/json" },
+      headers: {
+        "Content-Type": "application/json",
+        "If-Match": `"${entity.version}"`,
+      },
     }
   );
 
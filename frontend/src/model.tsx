@@ -16,6 +16,32 @@ export enum TeacherStatus {
   Moved = "Moved",
 }
 
+export enum CardState {
+  Active = "Active",
+  Lost = "Lost",
+  Blocked = "Blocked",
+  Expired = "Expired",
+}
+
+export enum HoldStatus {
+  Pending = "Pending",
+  Ready = "Ready",
+  Fulfilled = "Fulfilled",
+  Cancelled = "Cancelled",
+  Expired = "Expired",
+}
+
+export enum TransferStatus {
+  InTransit = "InTransit",
+  Received = "Received",
+  Cancelled = "Cancelled",
+}
+
+export enum Classification {
+  Udc = "Udc",
+  Dewey = "Dewey",
+}
+
 export type Student = {
   id: number;
   name: string;
@@ -26,24 +52,29 @@ export type Student = {
   group: number;
   start_study_date: string;
   status: StudentStatus | null;
+  email: string | null;
+  version: number;
 };
 
 export type Faculty = {
   id: number;
   name: string;
   letter: string;
+  version: number;
 };
 
 export type Curriculum = {
   id: number;
   name: string;
   letter: string;
+  version: number;
 };
 
 export type FacultyCurriculum = {
   id: number;
   faculty: number;
   curriculum: number;
+  version: number;
 };
 
 export type Teacher = {
@@ -54,6 +85,8 @@ export type Teacher = {
   age: number;
   faculty: number;
   status: TeacherStatus | null;
+  email: string | null;
+  version: number;
 };
 
 export type Book = {
@@ -63,11 +96,19 @@ export type Book = {
   publisher: number;
   category: number;
   student_access: boolean;
+  isbn: string | null;
+  branch: number | null;
+  classification: Classification | null;
+  call_number: string | null;
+  call_number_sort: string | null;
+  shelf: number | null;
+  version: number;
 };
 
 export type Category = {
   id: number;
   name: string;
+  version: number;
 };
 
 export type Author = {
@@ -76,6 +117,7 @@ export type Author = {
   lastname: string;
   surname: string;
   country: string;
+  version: number;
 };
 
 export type AuthorBook = {
@@ -83,6 +125,7 @@ export type AuthorBook = {
   author_id: number;
   book_id: number;
   num: number;
+  version: number;
 };
 
 export type Librarian = {
@@ -91,29 +134,54 @@ export type Librarian = {
   lastname: string;
   surname: string;
   age: number;
+  branch: number | null;
+  version: number;
+};
+
+export type Branch = {
+  id: number;
+  name: string;
+  faculty: number | null;
+  version: number;
+};
+
+export type Shelf = {
+  id: number;
+  branch: number | null;
+  room: string;
+  name: string;
+  version: number;
 };
 
 export type Publisher = {
   id: number;
   name: string;
   country: string;
+  version: number;
 };
 
 export type Country = {
   code: string;
   name: string;
+  version: number;
 };
 
 export type StudentCard = {
   id: number;
   student: number;
   issue_date: string;
+  expiry_date: string;
+  state: CardState;
+  version: number;
 };
 
 export type TeacherCard = {
   id: number;
   teacher: number;
   issue_date: string;
+  expiry_date: string;
+  state: CardState;
+  version: number;
 };
 
 export type StudentsBorrowing = {
@@ -126,6 +194,9 @@ export type StudentsBorrowing = {
   borrow_date: string;
   return_date: string | null;
   required_return_date: string;
+  branch: number | null;
+  return_branch: number | null;
+  version: number;
 };
 
 export type TeachersBorrowing = {
@@ -137,6 +208,31 @@ export type TeachersBorrowing = {
   book_status_finish: BookStatus | null;
   borrow_date: string;
   return_date: string | null;
+  branch: number | null;
+  return_branch: number | null;
+  version: number;
+};
+
+export type Hold = {
+  id: number;
+  book: number;
+  student_card: number | null;
+  teacher_card: number | null;
+  request_date: string;
+  expire_date: string | null;
+  status: HoldStatus;
+  version: number;
+};
+
+export type BookTransfer = {
+  id: number;
+  book: number;
+  from_branch: number;
+  to_branch: number;
+  sent_date: string;
+  received_date: string | null;
+  status: TransferStatus;
+  version: number;
 };
 
 export type Entity =
@@ -150,12 +246,16 @@ export type Entity =
   | Author
   | AuthorBook
   | Librarian
+  | Branch
+  | Shelf
   | Publisher
   | Country
   | StudentCard
   | TeacherCard
   | StudentsBorrowing
-  | TeachersBorrowing;
+  | TeachersBorrowing
+  | Hold
+  | BookTransfer;
 
 export const getKeys = Object.keys as <T extends object>(
   obj: T
@@ -180,12 +280,16 @@ export type Table =
   | "author"
   | "author_book"
   | "librarian"
+  | "branch"
+  | "shelf"
   | "publisher"
   | "country"
   | "student_card"
   | "teacher_card"
   | "students_borrowing"
-  | "teachers_borrowing";
+  | "teachers_borrowing"
+  | "hold"
+  | "book_transfer";
 
 export type TablePrimaryKey<T extends Table> = T extends "country"
   ? "code"
@@ -210,21 +314,26 @@ export const defaultModel: Record<Table, Entity> = {
     group: 0,
     start_study_date: new Date().toISOString().split("T")[0],
     status: StudentStatus.Graduated,
+    email: null,
+    version: 0,
   },
   faculty: {
     id: 0,
     name: "",
     letter: "",
+    version: 0,
   },
   curriculum: {
     id: 0,
     name: "",
     letter: "",
+    version: 0,
   },
   faculty_curriculum: {
     id: 0,
     faculty: 0,
     curriculum: 0,
+    version: 0,
   },
   teacher: {
     id: 0,
@@ -234,6 +343,8 @@ export const defaultModel: Record<Table, Entity> = {
     age: 0,
     faculty: 0,
     status: TeacherStatus.Moved,
+    email: null,
+    version: 0,
   },
   book: {
     id: 0,
@@ -242,10 +353,18 @@ export const defaultModel: Record<Table, Entity> = {
     publisher: 0,
     category: 0,
     student_access: false,
+    isbn: null,
+    branch: null,
+    classification: null,
+    call_number: null,
+    call_number_sort: null,
+    shelf: null,
+    version: 0,
   },
   category: {
     id: 0,
     name: "",
+    version: 0,
   },
   author: {
     id: 0,
@@ -253,12 +372,14 @@ export const defaultModel: Record<Table, Entity> = {
     lastname: "",
     surname: "",
     country: "",
+    version: 0,
   },
   author_book: {
     id: 0,
     author_id: 0,
     book_id: 0,
     num: 0,
+    version: 0,
   },
   librarian: {
     id: 0,
@@ -266,25 +387,48 @@ export const defaultModel: Record<Table, Entity> = {
     lastname: "",
     surname: "",
     age: 0,
+    branch: null,
+    version: 0,
+  },
+  branch: {
+    id: 0,
+    name: "",
+    faculty: null,
+    version: 0,
+  },
+  shelf: {
+    id: 0,
+    branch: null,
+    room: "",
+    name: "",
+    version: 0,
   },
   publisher: {
     id: 0,
     name: "",
     country: "",
+    version: 0,
   },
   country: {
     code: "",
     name: "",
+    version: 0,
   },
   student_card: {
     id: 0,
     student: 0,
     issue_date: new Date().toISOString().split("T")[0],
+    expiry_date: new Date().toISOString().split("T")[0],
+    state: CardState.Active,
+    version: 0,
   },
   teacher_card: {
     id: 0,
     teacher: 0,
     issue_date: new Date().toISOString().split("T")[0],
+    expiry_date: new Date().toISOString().split("T")[0],
+    state: CardState.Active,
+    version: 0,
   },
   students_borrowing: {
     id: 0,
@@ -296,6 +440,9 @@ export const defaultModel: Record<Table, Entity> = {
     borrow_date: new Date().toISOString().split("T")[0],
     return_date: new Date().toISOString().split("T")[0],
     required_return_date: new Date().toISOString().split("T")[0],
+    branch: null,
+    return_branch: null,
+    version: 0,
   },
   teachers_borrowing: {
     id: 0,
@@ -306,5 +453,28 @@ export const defaultModel: Record<Table, Entity> = {
     book_status_finish: BookStatus.Excellent,
     borrow_date: new Date().toISOString().split("T")[0],
     return_date: new Date().toISOString().split("T")[0],
+    branch: null,
+    return_branch: null,
+    version: 0,
+  },
+  hold: {
+    id: 0,
+    book: 0,
+    student_card: null,
+    teacher_card: null,
+    request_date: new Date().toISOString().split("T")[0],
+    expire_date: null,
+    status: HoldStatus.Pending,
+    version: 0,
+  },
+  book_transfer: {
+    id: 0,
+    book: 0,
+    from_branch: 0,
+    to_branch: 0,
+    sent_date: new Date().toISOString().split("T")[0],
+    received_date: null,
+    status: TransferStatus.InTransit,
+    version: 0,
   },
 };
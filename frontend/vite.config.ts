@@ -4,4 +4,10 @@ import react from '@vitejs/plugin-react-swc'
 // https://vitejs.dev/config/
 export default defineConfig({
   plugins: [react()],
+  // The development server forwards API calls to the Rust server, like it serves them in production.
+  server: {
+    proxy: {
+      '/api': 'http://localhost:3000',
+    },
+  },
 })
@@ -0,0 +1,126 @@
+CREATE TYPE book_status AS ENUM ('excellent', 'good', 'satisfactory', 'unsatisfactory');
+CREATE TYPE student_status AS ENUM ('graduated', 'expelled', 'moved');
+CREATE TYPE teacher_status AS ENUM ('fired', 'moved');
+
+CREATE TABLE country (
+    code VARCHAR PRIMARY KEY,
+    name VARCHAR NOT NULL
+);
+
+CREATE TABLE faculty (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    letter VARCHAR NOT NULL
+);
+
+CREATE TABLE curriculum (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    letter VARCHAR NOT NULL
+);
+
+CREATE TABLE faculty_curriculum (
+    id SERIAL PRIMARY KEY,
+    faculty INTEGER NOT NULL REFERENCES faculty (id),
+    curriculum INTEGER NOT NULL REFERENCES curriculum (id)
+);
+
+CREATE TABLE student (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    lastname VARCHAR NOT NULL,
+    surname VARCHAR NOT NULL,
+    age SMALLINT NOT NULL,
+    faculty_curriculum INTEGER NOT NULL REFERENCES faculty_curriculum (id),
+    "group" SMALLINT NOT NULL,
+    start_study_date DATE NOT NULL,
+    status student_status
+);
+
+CREATE TABLE teacher (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    lastname VARCHAR NOT NULL,
+    surname VARCHAR NOT NULL,
+    age SMALLINT NOT NULL,
+    faculty INTEGER NOT NULL REFERENCES faculty (id),
+    status teacher_status
+);
+
+CREATE TABLE librarian (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    lastname VARCHAR NOT NULL,
+    surname VARCHAR NOT NULL,
+    age SMALLINT NOT NULL
+);
+
+CREATE TABLE category (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL
+);
+
+CREATE TABLE publisher (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    country VARCHAR NOT NULL REFERENCES country (code)
+);
+
+CREATE TABLE author (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    lastname VARCHAR NOT NULL,
+    surname VARCHAR NOT NULL,
+    country VARCHAR NOT NULL REFERENCES country (code)
+);
+
+CREATE TABLE book (
+    id SERIAL PRIMARY KEY,
+    title VARCHAR NOT NULL,
+    release DATE NOT NULL,
+    publisher INTEGER NOT NULL REFERENCES publisher (id),
+    category INTEGER NOT NULL REFERENCES category (id),
+    student_access BOOLEAN NOT NULL
+);
+
+CREATE TABLE author_book (
+    id SERIAL PRIMARY KEY,
+    author_id INTEGER NOT NULL REFERENCES author (id),
+    book_id INTEGER NOT NULL REFERENCES book (id),
+    num SMALLINT NOT NULL
+);
+
+CREATE TABLE student_card (
+    id SERIAL PRIMARY KEY,
+    student INTEGER NOT NULL REFERENCES student (id),
+    issue_date DATE NOT NULL
+);
+
+CREATE TABLE teacher_card (
+    id SERIAL PRIMARY KEY,
+    teacher INTEGER NOT NULL REFERENCES teacher (id),
+    issue_date DATE NOT NULL
+);
+
+CREATE TABLE students_borrowing (
+    id SERIAL PRIMARY KEY,
+    student_card INTEGER NOT NULL REFERENCES student_card (id),
+    librarian INTEGER NOT NULL REFERENCES librarian (id),
+    book INTEGER NOT NULL REFERENCES book (id),
+    book_status_start book_status NOT NULL,
+    book_status_finish book_status,
+    borrow_date DATE NOT NULL,
+    return_date DATE,
+    required_return_date DATE NOT NULL
+);
+
+CREATE TABLE teachers_borrowing (
+    id SERIAL PRIMARY KEY,
+    teacher_card INTEGER NOT NULL REFERENCES teacher_card (id),
+    librarian INTEGER NOT NULL REFERENCES librarian (id),
+    book INTEGER NOT NULL REFERENCES book (id),
+    book_status_start book_status NOT NULL,
+    book_status_finish book_status,
+    borrow_date DATE NOT NULL,
+    return_date DATE
+);
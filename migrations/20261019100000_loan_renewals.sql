@@ -0,0 +1,20 @@
+CREATE TYPE hold_status AS ENUM ('pending', 'ready', 'fulfilled', 'cancelled', 'expired');
+
+CREATE TABLE hold (
+    id SERIAL PRIMARY KEY,
+    book INTEGER NOT NULL REFERENCES book (id),
+    student_card INTEGER REFERENCES student_card (id),
+    teacher_card INTEGER REFERENCES teacher_card (id),
+    request_date DATE NOT NULL,
+    status hold_status NOT NULL,
+    CHECK ((student_card IS NULL) <> (teacher_card IS NULL))
+);
+
+CREATE TABLE students_borrowing_renewal (
+    id SERIAL PRIMARY KEY,
+    students_borrowing INTEGER NOT NULL REFERENCES students_borrowing (id) ON DELETE CASCADE,
+    librarian INTEGER NOT NULL REFERENCES librarian (id),
+    renewal_date DATE NOT NULL,
+    previous_return_date DATE NOT NULL,
+    required_return_date DATE NOT NULL
+);
@@ -0,0 +1,23 @@
+ALTER TABLE student ADD COLUMN email VARCHAR;
+ALTER TABLE teacher ADD COLUMN email VARCHAR;
+
+CREATE TYPE notification_kind AS ENUM ('due_soon', 'overdue', 'hold_ready');
+CREATE TYPE notification_status AS ENUM ('pending', 'sent', 'failed');
+
+CREATE TABLE notification (
+    id SERIAL PRIMARY KEY,
+    kind notification_kind NOT NULL,
+    recipient VARCHAR NOT NULL,
+    subject VARCHAR NOT NULL,
+    body TEXT NOT NULL,
+    students_borrowing INTEGER REFERENCES students_borrowing (id) ON DELETE SET NULL,
+    hold INTEGER REFERENCES hold (id) ON DELETE SET NULL,
+    status notification_status NOT NULL DEFAULT 'pending',
+    attempts SMALLINT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    sent_at TIMESTAMPTZ
+);
+
+CREATE INDEX notification_pending_idx ON notification (next_attempt_at) WHERE status = 'pending';
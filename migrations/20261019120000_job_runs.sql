@@ -0,0 +1,14 @@
+ALTER TABLE hold ADD COLUMN expire_date DATE;
+
+CREATE TYPE job_run_status AS ENUM ('running', 'succeeded', 'failed');
+
+CREATE TABLE job_run (
+    id SERIAL PRIMARY KEY,
+    job VARCHAR NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    finished_at TIMESTAMPTZ,
+    status job_run_status NOT NULL DEFAULT 'running',
+    message TEXT
+);
+
+CREATE INDEX job_run_job_idx ON job_run (job, started_at);
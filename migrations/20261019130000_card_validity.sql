@@ -0,0 +1,22 @@
+CREATE TYPE card_state AS ENUM ('active', 'lost', 'blocked', 'expired');
+
+ALTER TABLE student_card
+    ADD COLUMN expiry_date DATE,
+    ADD COLUMN state card_state NOT NULL DEFAULT 'active';
+UPDATE student_card SET expiry_date = issue_date + INTERVAL '1 year';
+ALTER TABLE student_card ALTER COLUMN expiry_date SET NOT NULL;
+
+ALTER TABLE teacher_card
+    ADD COLUMN expiry_date DATE,
+    ADD COLUMN state card_state NOT NULL DEFAULT 'active';
+UPDATE teacher_card SET expiry_date = issue_date + INTERVAL '1 year';
+ALTER TABLE teacher_card ALTER COLUMN expiry_date SET NOT NULL;
+
+-- Only one card of a student or teacher can be used at a time.
+UPDATE student_card SET state = 'blocked'
+WHERE id NOT IN (SELECT DISTINCT ON (student) id FROM student_card ORDER BY student, issue_date DESC, id DESC);
+UPDATE teacher_card SET state = 'blocked'
+WHERE id NOT IN (SELECT DISTINCT ON (teacher) id FROM teacher_card ORDER BY teacher, issue_date DESC, id DESC);
+
+CREATE UNIQUE INDEX student_card_active_idx ON student_card (student) WHERE state = 'active';
+CREATE UNIQUE INDEX teacher_card_active_idx ON teacher_card (teacher) WHERE state = 'active';
@@ -0,0 +1 @@
+ALTER TABLE book ADD COLUMN isbn VARCHAR(13) UNIQUE;
@@ -0,0 +1,58 @@
+-- Row version for optimistic concurrency, bumped on every update.
+CREATE FUNCTION bump_version() RETURNS trigger AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE country ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER country_version BEFORE UPDATE ON country FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE faculty ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER faculty_version BEFORE UPDATE ON faculty FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE curriculum ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER curriculum_version BEFORE UPDATE ON curriculum FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE faculty_curriculum ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER faculty_curriculum_version BEFORE UPDATE ON faculty_curriculum FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE student ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER student_version BEFORE UPDATE ON student FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE teacher ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER teacher_version BEFORE UPDATE ON teacher FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE librarian ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER librarian_version BEFORE UPDATE ON librarian FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE category ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER category_version BEFORE UPDATE ON category FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE publisher ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER publisher_version BEFORE UPDATE ON publisher FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE author ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER author_version BEFORE UPDATE ON author FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE book ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER book_version BEFORE UPDATE ON book FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE author_book ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER author_book_version BEFORE UPDATE ON author_book FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE student_card ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER student_card_version BEFORE UPDATE ON student_card FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE teacher_card ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER teacher_card_version BEFORE UPDATE ON teacher_card FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE students_borrowing ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER students_borrowing_version BEFORE UPDATE ON students_borrowing FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE teachers_borrowing ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER teachers_borrowing_version BEFORE UPDATE ON teachers_borrowing FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+ALTER TABLE hold ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+CREATE TRIGGER hold_version BEFORE UPDATE ON hold FOR EACH ROW EXECUTE FUNCTION bump_version();
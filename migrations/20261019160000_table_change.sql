@@ -0,0 +1,35 @@
+-- Announces every insert, update and delete of the tables on the `table_change` channel,
+-- so the change feed also sees the writes of other replicas and of plain SQL sessions.
+-- The payload names the row instead of carrying it, notifications are limited to 8000 bytes.
+CREATE FUNCTION notify_table_change() RETURNS trigger AS $$
+DECLARE
+    changed jsonb := to_jsonb(CASE WHEN TG_OP = 'DELETE' THEN OLD ELSE NEW END);
+BEGIN
+    PERFORM pg_notify('table_change', jsonb_build_object(
+        'table', TG_TABLE_NAME,
+        'action', lower(TG_OP),
+        'key', changed -> TG_ARGV[0],
+        'version', changed -> 'version'
+    )::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER country_change AFTER INSERT OR UPDATE OR DELETE ON country FOR EACH ROW EXECUTE FUNCTION notify_table_change('code');
+CREATE TRIGGER faculty_change AFTER INSERT OR UPDATE OR DELETE ON faculty FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER curriculum_change AFTER INSERT OR UPDATE OR DELETE ON curriculum FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER faculty_curriculum_change AFTER INSERT OR UPDATE OR DELETE ON faculty_curriculum FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER student_change AFTER INSERT OR UPDATE OR DELETE ON student FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER teacher_change AFTER INSERT OR UPDATE OR DELETE ON teacher FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER librarian_change AFTER INSERT OR UPDATE OR DELETE ON librarian FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER category_change AFTER INSERT OR UPDATE OR DELETE ON category FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER publisher_change AFTER INSERT OR UPDATE OR DELETE ON publisher FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER author_change AFTER INSERT OR UPDATE OR DELETE ON author FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER book_change AFTER INSERT OR UPDATE OR DELETE ON book FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER author_book_change AFTER INSERT OR UPDATE OR DELETE ON author_book FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER student_card_change AFTER INSERT OR UPDATE OR DELETE ON student_card FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER teacher_card_change AFTER INSERT OR UPDATE OR DELETE ON teacher_card FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER students_borrowing_change AFTER INSERT OR UPDATE OR DELETE ON students_borrowing FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER teachers_borrowing_change AFTER INSERT OR UPDATE OR DELETE ON teachers_borrowing FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER hold_change AFTER INSERT OR UPDATE OR DELETE ON hold FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER students_borrowing_renewal_change AFTER INSERT OR UPDATE OR DELETE ON students_borrowing_renewal FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
@@ -0,0 +1,83 @@
+CREATE TYPE webhook_event AS ENUM ('loan_created', 'loan_returned_late', 'student_card_issued');
+CREATE TYPE webhook_delivery_status AS ENUM ('pending', 'delivered', 'dead');
+
+CREATE TABLE webhook_subscription (
+    id SERIAL PRIMARY KEY,
+    url VARCHAR NOT NULL,
+    events webhook_event[] NOT NULL,
+    secret VARCHAR NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TRIGGER webhook_subscription_version BEFORE UPDATE ON webhook_subscription FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+-- Outbox of the webhooks, rows are added in the transaction of the change they announce.
+CREATE TABLE webhook_delivery (
+    id SERIAL PRIMARY KEY,
+    subscription INTEGER NOT NULL REFERENCES webhook_subscription (id) ON DELETE CASCADE,
+    event webhook_event NOT NULL,
+    payload JSONB NOT NULL,
+    status webhook_delivery_status NOT NULL DEFAULT 'pending',
+    attempts SMALLINT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    delivered_at TIMESTAMPTZ
+);
+
+CREATE INDEX webhook_delivery_pending_idx ON webhook_delivery (next_attempt_at) WHERE status = 'pending';
+CREATE INDEX webhook_delivery_subscription_idx ON webhook_delivery (subscription);
+
+CREATE TABLE webhook_attempt (
+    id SERIAL PRIMARY KEY,
+    delivery INTEGER NOT NULL REFERENCES webhook_delivery (id) ON DELETE CASCADE,
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    response_status SMALLINT,
+    error TEXT
+);
+
+CREATE INDEX webhook_attempt_delivery_idx ON webhook_attempt (delivery);
+
+-- Queues a delivery of `payload` for every active subscription to `event`.
+CREATE FUNCTION enqueue_webhook(event webhook_event, payload JSONB) RETURNS void AS $$
+    INSERT INTO webhook_delivery (subscription, event, payload)
+    SELECT id, event, payload FROM webhook_subscription WHERE active AND event = ANY(events);
+$$ LANGUAGE sql;
+
+-- Payloads are the rows as stored, `to_jsonb` of every column, not as served by the REST API:
+-- enums are their Postgres labels like `good` or `active` and dates are `YYYY-MM-DD`.
+-- Loans also name the kind of patron, late returns the number of days late.
+CREATE FUNCTION students_borrowing_webhook() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        PERFORM enqueue_webhook('loan_created', to_jsonb(NEW) || '{"patron": "student"}');
+    ELSIF OLD.return_date IS NULL AND NEW.return_date > NEW.required_return_date THEN
+        PERFORM enqueue_webhook('loan_returned_late', to_jsonb(NEW) || jsonb_build_object(
+            'patron', 'student',
+            'days_late', NEW.return_date - NEW.required_return_date
+        ));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Teachers borrow without a required return date, so their loans are never late.
+CREATE FUNCTION teachers_borrowing_webhook() RETURNS trigger AS $$
+BEGIN
+    PERFORM enqueue_webhook('loan_created', to_jsonb(NEW) || '{"patron": "teacher"}');
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION student_card_webhook() RETURNS trigger AS $$
+BEGIN
+    PERFORM enqueue_webhook('student_card_issued', to_jsonb(NEW));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER students_borrowing_webhook AFTER INSERT OR UPDATE ON students_borrowing FOR EACH ROW EXECUTE FUNCTION students_borrowing_webhook();
+CREATE TRIGGER teachers_borrowing_webhook AFTER INSERT ON teachers_borrowing FOR EACH ROW EXECUTE FUNCTION teachers_borrowing_webhook();
+CREATE TRIGGER student_card_webhook AFTER INSERT ON student_card FOR EACH ROW EXECUTE FUNCTION student_card_webhook();
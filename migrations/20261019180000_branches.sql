@@ -0,0 +1,44 @@
+-- Faculty libraries and the central library. Librarians work at a branch, book copies belong
+-- to one and loans record where they were lent and returned. Rows added before branches have none.
+CREATE TYPE transfer_status AS ENUM ('in_transit', 'received', 'cancelled');
+
+CREATE TABLE branch (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    -- Library of a faculty, `NULL` for the central library.
+    faculty INTEGER REFERENCES faculty (id),
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+ALTER TABLE librarian ADD COLUMN branch INTEGER REFERENCES branch (id);
+ALTER TABLE book ADD COLUMN branch INTEGER REFERENCES branch (id);
+ALTER TABLE students_borrowing
+    ADD COLUMN branch INTEGER REFERENCES branch (id),
+    ADD COLUMN return_branch INTEGER REFERENCES branch (id);
+ALTER TABLE teachers_borrowing
+    ADD COLUMN branch INTEGER REFERENCES branch (id),
+    ADD COLUMN return_branch INTEGER REFERENCES branch (id);
+
+-- Copies sent from one branch to another, the copy moves to `to_branch` when it is received.
+CREATE TABLE book_transfer (
+    id SERIAL PRIMARY KEY,
+    book INTEGER NOT NULL REFERENCES book (id),
+    from_branch INTEGER NOT NULL REFERENCES branch (id),
+    to_branch INTEGER NOT NULL REFERENCES branch (id),
+    sent_date DATE NOT NULL,
+    received_date DATE,
+    status transfer_status NOT NULL DEFAULT 'in_transit',
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+-- A copy travels to one branch at a time.
+CREATE UNIQUE INDEX book_transfer_in_transit_idx ON book_transfer (book) WHERE status = 'in_transit';
+
+CREATE INDEX book_branch_idx ON book (branch);
+CREATE INDEX librarian_branch_idx ON librarian (branch);
+
+CREATE TRIGGER branch_version BEFORE UPDATE ON branch FOR EACH ROW EXECUTE FUNCTION bump_version();
+CREATE TRIGGER book_transfer_version BEFORE UPDATE ON book_transfer FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+CREATE TRIGGER branch_change AFTER INSERT OR UPDATE OR DELETE ON branch FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
+CREATE TRIGGER book_transfer_change AFTER INSERT OR UPDATE OR DELETE ON book_transfer FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
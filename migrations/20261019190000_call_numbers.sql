@@ -0,0 +1,26 @@
+-- Call numbers of the books and the shelves they are filed on.
+CREATE TYPE classification AS ENUM ('udc', 'dewey');
+
+CREATE TABLE shelf (
+    id SERIAL PRIMARY KEY,
+    branch INTEGER REFERENCES branch (id),
+    room VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+ALTER TABLE book
+    ADD COLUMN classification classification,
+    ADD COLUMN call_number VARCHAR,
+    -- Derived from `call_number` by the application, call numbers do not sort as text.
+    -- Keys compare byte by byte, the collation of the database would skip their punctuation.
+    ADD COLUMN call_number_sort VARCHAR COLLATE "C",
+    ADD COLUMN shelf INTEGER REFERENCES shelf (id);
+
+CREATE INDEX book_call_number_sort_idx ON book (classification, call_number_sort);
+CREATE INDEX book_shelf_idx ON book (shelf);
+CREATE INDEX shelf_branch_idx ON shelf (branch);
+
+CREATE TRIGGER shelf_version BEFORE UPDATE ON shelf FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+CREATE TRIGGER shelf_change AFTER INSERT OR UPDATE OR DELETE ON shelf FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
@@ -0,0 +1,34 @@
+-- Money patrons owe the library, in cents. Charges add to the balance of a card, payments and
+-- waivers take from it. Entries are never changed, a mistaken charge is waived.
+CREATE TYPE ledger_entry_kind AS ENUM ('late_return', 'lost_book', 'damaged_book', 'payment', 'waiver');
+
+CREATE TABLE ledger_entry (
+    id SERIAL PRIMARY KEY,
+    student_card INTEGER REFERENCES student_card (id),
+    teacher_card INTEGER REFERENCES teacher_card (id),
+    kind ledger_entry_kind NOT NULL,
+    amount INTEGER NOT NULL CHECK (amount > 0),
+    students_borrowing INTEGER REFERENCES students_borrowing (id),
+    teachers_borrowing INTEGER REFERENCES teachers_borrowing (id),
+    librarian INTEGER REFERENCES librarian (id),
+    reason VARCHAR,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK ((student_card IS NULL) <> (teacher_card IS NULL)),
+    CHECK (kind <> 'waiver' OR (librarian IS NOT NULL AND reason IS NOT NULL))
+);
+
+CREATE INDEX ledger_entry_student_card_idx ON ledger_entry (student_card);
+CREATE INDEX ledger_entry_teacher_card_idx ON ledger_entry (teacher_card);
+
+-- Checkouts on a card owing more than `max_balance` are refused, cards of a table without a
+-- policy are never refused.
+CREATE TABLE fine_policy (
+    id SERIAL PRIMARY KEY,
+    card_table VARCHAR NOT NULL UNIQUE CHECK (card_table IN ('student_card', 'teacher_card')),
+    max_balance INTEGER NOT NULL CHECK (max_balance >= 0),
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TRIGGER fine_policy_version BEFORE UPDATE ON fine_policy FOR EACH ROW EXECUTE FUNCTION bump_version();
+
+CREATE TRIGGER ledger_entry_change AFTER INSERT OR UPDATE OR DELETE ON ledger_entry FOR EACH ROW EXECUTE FUNCTION notify_table_change('id');
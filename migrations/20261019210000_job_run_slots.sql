@@ -0,0 +1,5 @@
+-- Slot of the schedule a run is for, `NULL` for runs started by hand. A replica reaching a slot
+-- after another one ran it finds the slot taken.
+ALTER TABLE job_run ADD COLUMN scheduled_at TIMESTAMPTZ;
+
+CREATE UNIQUE INDEX job_run_slot_idx ON job_run (job, scheduled_at);
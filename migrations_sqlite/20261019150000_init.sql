@@ -0,0 +1,207 @@
+-- Schema of the Postgres migrations up to `20261019150000_row_version`.
+-- Postgres enums are TEXT columns checked against the same values, SERIAL keys are AUTOINCREMENT
+-- so ids are not reused, and versions are bumped by the UPDATE statements instead of triggers.
+
+CREATE TABLE country (
+    code VARCHAR PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE faculty (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR NOT NULL,
+    letter VARCHAR NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE curriculum (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR NOT NULL,
+    letter VARCHAR NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE faculty_curriculum (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    faculty INTEGER NOT NULL REFERENCES faculty (id),
+    curriculum INTEGER NOT NULL REFERENCES curriculum (id),
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE student (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR NOT NULL,
+    lastname VARCHAR NOT NULL,
+    surname VARCHAR NOT NULL,
+    age SMALLINT NOT NULL,
+    faculty_curriculum INTEGER NOT NULL REFERENCES faculty_curriculum (id),
+    "group" SMALLINT NOT NULL,
+    start_study_date DATE NOT NULL,
+    status TEXT CHECK (status IN ('graduated', 'expelled', 'moved')),
+    email VARCHAR,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE teacher (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR NOT NULL,
+    lastname VARCHAR NOT NULL,
+    surname VARCHAR NOT NULL,
+    age SMALLINT NOT NULL,
+    faculty INTEGER NOT NULL REFERENCES faculty (id),
+    status TEXT CHECK (status IN ('fired', 'moved')),
+    email VARCHAR,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE librarian (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR NOT NULL,
+    lastname VARCHAR NOT NULL,
+    surname VARCHAR NOT NULL,
+    age SMALLINT NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE category (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE publisher (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR NOT NULL,
+    country VARCHAR NOT NULL REFERENCES country (code),
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE author (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR NOT NULL,
+    lastname VARCHAR NOT NULL,
+    surname VARCHAR NOT NULL,
+    country VARCHAR NOT NULL REFERENCES country (code),
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE book (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title VARCHAR NOT NULL,
+    release DATE NOT NULL,
+    publisher INTEGER NOT NULL REFERENCES publisher (id),
+    category INTEGER NOT NULL REFERENCES category (id),
+    student_access BOOLEAN NOT NULL,
+    isbn VARCHAR(13) UNIQUE,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE author_book (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    author_id INTEGER NOT NULL REFERENCES author (id),
+    book_id INTEGER NOT NULL REFERENCES book (id),
+    num SMALLINT NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE student_card (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    student INTEGER NOT NULL REFERENCES student (id),
+    issue_date DATE NOT NULL,
+    expiry_date DATE NOT NULL,
+    state TEXT NOT NULL DEFAULT 'active' CHECK (state IN ('active', 'lost', 'blocked', 'expired')),
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE teacher_card (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    teacher INTEGER NOT NULL REFERENCES teacher (id),
+    issue_date DATE NOT NULL,
+    expiry_date DATE NOT NULL,
+    state TEXT NOT NULL DEFAULT 'active' CHECK (state IN ('active', 'lost', 'blocked', 'expired')),
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+-- Only one card of a student or teacher can be used at a time.
+CREATE UNIQUE INDEX student_card_active_idx ON student_card (student) WHERE state = 'active';
+CREATE UNIQUE INDEX teacher_card_active_idx ON teacher_card (teacher) WHERE state = 'active';
+
+CREATE TABLE students_borrowing (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    student_card INTEGER NOT NULL REFERENCES student_card (id),
+    librarian INTEGER NOT NULL REFERENCES librarian (id),
+    book INTEGER NOT NULL REFERENCES book (id),
+    book_status_start TEXT NOT NULL
+        CHECK (book_status_start IN ('excellent', 'good', 'satisfactory', 'unsatisfactory')),
+    book_status_finish TEXT
+        CHECK (book_status_finish IN ('excellent', 'good', 'satisfactory', 'unsatisfactory')),
+    borrow_date DATE NOT NULL,
+    return_date DATE,
+    required_return_date DATE NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE teachers_borrowing (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    teacher_card INTEGER NOT NULL REFERENCES teacher_card (id),
+    librarian INTEGER NOT NULL REFERENCES librarian (id),
+    book INTEGER NOT NULL REFERENCES book (id),
+    book_status_start TEXT NOT NULL
+        CHECK (book_status_start IN ('excellent', 'good', 'satisfactory', 'unsatisfactory')),
+    book_status_finish TEXT
+        CHECK (book_status_finish IN ('excellent', 'good', 'satisfactory', 'unsatisfactory')),
+    borrow_date DATE NOT NULL,
+    return_date DATE,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE TABLE hold (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    book INTEGER NOT NULL REFERENCES book (id),
+    student_card INTEGER REFERENCES student_card (id),
+    teacher_card INTEGER REFERENCES teacher_card (id),
+    request_date DATE NOT NULL,
+    expire_date DATE,
+    status TEXT NOT NULL CHECK (status IN ('pending', 'ready', 'fulfilled', 'cancelled', 'expired')),
+    version INTEGER NOT NULL DEFAULT 1,
+    CHECK ((student_card IS NULL) <> (teacher_card IS NULL))
+);
+
+CREATE TABLE students_borrowing_renewal (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    students_borrowing INTEGER NOT NULL REFERENCES students_borrowing (id) ON DELETE CASCADE,
+    librarian INTEGER NOT NULL REFERENCES librarian (id),
+    renewal_date DATE NOT NULL,
+    previous_return_date DATE NOT NULL,
+    required_return_date DATE NOT NULL
+);
+
+CREATE TABLE notification (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL CHECK (kind IN ('due_soon', 'overdue', 'hold_ready')),
+    recipient VARCHAR NOT NULL,
+    subject VARCHAR NOT NULL,
+    body TEXT NOT NULL,
+    students_borrowing INTEGER REFERENCES students_borrowing (id) ON DELETE SET NULL,
+    hold INTEGER REFERENCES hold (id) ON DELETE SET NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'sent', 'failed')),
+    attempts SMALLINT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    next_attempt_at DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    sent_at DATETIME
+);
+
+CREATE INDEX notification_pending_idx ON notification (next_attempt_at) WHERE status = 'pending';
+
+CREATE TABLE job_run (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    job VARCHAR NOT NULL,
+    started_at DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    finished_at DATETIME,
+    status TEXT NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'succeeded', 'failed')),
+    message TEXT
+);
+
+CREATE INDEX job_run_job_idx ON job_run (job, started_at);
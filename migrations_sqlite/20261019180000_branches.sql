@@ -0,0 +1,31 @@
+-- Schema of the Postgres migration `20261019180000_branches`.
+
+CREATE TABLE branch (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR NOT NULL,
+    faculty INTEGER REFERENCES faculty (id),
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+ALTER TABLE librarian ADD COLUMN branch INTEGER REFERENCES branch (id);
+ALTER TABLE book ADD COLUMN branch INTEGER REFERENCES branch (id);
+ALTER TABLE students_borrowing ADD COLUMN branch INTEGER REFERENCES branch (id);
+ALTER TABLE students_borrowing ADD COLUMN return_branch INTEGER REFERENCES branch (id);
+ALTER TABLE teachers_borrowing ADD COLUMN branch INTEGER REFERENCES branch (id);
+ALTER TABLE teachers_borrowing ADD COLUMN return_branch INTEGER REFERENCES branch (id);
+
+CREATE TABLE book_transfer (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    book INTEGER NOT NULL REFERENCES book (id),
+    from_branch INTEGER NOT NULL REFERENCES branch (id),
+    to_branch INTEGER NOT NULL REFERENCES branch (id),
+    sent_date DATE NOT NULL,
+    received_date DATE,
+    status TEXT NOT NULL DEFAULT 'in_transit' CHECK (status IN ('in_transit', 'received', 'cancelled')),
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE UNIQUE INDEX book_transfer_in_transit_idx ON book_transfer (book) WHERE status = 'in_transit';
+
+CREATE INDEX book_branch_idx ON book (branch);
+CREATE INDEX librarian_branch_idx ON librarian (branch);
@@ -0,0 +1,18 @@
+-- Schema of the Postgres migration `20261019190000_call_numbers`.
+
+CREATE TABLE shelf (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    branch INTEGER REFERENCES branch (id),
+    room VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+ALTER TABLE book ADD COLUMN classification TEXT CHECK (classification IN ('udc', 'dewey'));
+ALTER TABLE book ADD COLUMN call_number VARCHAR;
+ALTER TABLE book ADD COLUMN call_number_sort VARCHAR;
+ALTER TABLE book ADD COLUMN shelf INTEGER REFERENCES shelf (id);
+
+CREATE INDEX book_call_number_sort_idx ON book (classification, call_number_sort);
+CREATE INDEX book_shelf_idx ON book (shelf);
+CREATE INDEX shelf_branch_idx ON shelf (branch);
@@ -0,0 +1,163 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, RwLock};
+
+use axum::body::Bytes;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Duration, NaiveDateTime, SubsecRound, Utc};
+use color_eyre::eyre::{eyre, Context};
+use serde::Serialize;
+
+use crate::error::internal_error;
+
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// In-process cache of the list response of a table.
+///
+/// Every handler writing to the table must invalidate it, including those of other routers.
+#[derive(Clone)]
+pub struct ListCache {
+    inner: Arc<RwLock<Inner>>,
+}
+
+struct Inner {
+    entry: Option<Entry>,
+    // Bumped on every invalidation, so a list loaded before a write is not cached after it.
+    generation: u64,
+    modified: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+struct Entry {
+    body: Bytes,
+    etag: String,
+}
+
+impl Default for ListCache {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(Inner {
+                entry: None,
+                generation: 0,
+                modified: Utc::now().trunc_subsecs(0),
+            })),
+        }
+    }
+}
+
+impl ListCache {
+    /// Drops the cached list, must be called after every write to the table.
+    ///
+    /// `Last-Modified` has whole seconds, it moves a second forward when the list was already
+    /// modified this second so a client which read it this second does not keep the stale list.
+    pub fn invalidate(&self) {
+        let mut inner = self.inner.write().unwrap_or_else(|err| err.into_inner());
+        inner.entry = None;
+        inner.generation += 1;
+        inner.modified = Utc::now()
+            .trunc_subsecs(0)
+            .max(inner.modified + Duration::seconds(1));
+    }
+
+    /// Responds with the cached list, awaiting `load` only when nothing is cached.
+    ///
+    /// Answers `304 Not Modified` when `If-None-Match` or `If-Modified-Since` shows the client is up to date.
+    pub async fn respond<T: Serialize>(
+        &self,
+        headers: &HeaderMap,
+        load: impl Future<Output = Result<Vec<T>, (StatusCode, String)>>,
+    ) -> Result<Response, (StatusCode, String)> {
+        let (cached, generation, modified) = {
+            let inner = self.inner.read().unwrap_or_else(|err| err.into_inner());
+            (inner.entry.clone(), inner.generation, inner.modified)
+        };
+
+        let entry = match cached {
+            Some(entry) => entry,
+            None => {
+                let body = serde_json::to_vec(&load.await?)
+                    .wrap_err_with(|| eyre!("Unable to serialize list"))
+                    .map_err(internal_error)?;
+
+                let mut hasher = DefaultHasher::new();
+                body.hash(&mut hasher);
+
+                let entry = Entry {
+                    body: body.into(),
+                    etag: format!("\"{:016x}\"", hasher.finish()),
+                };
+
+                let mut inner = self.inner.write().unwrap_or_else(|err| err.into_inner());
+                if inner.generation == generation {
+                    inner.entry = Some(entry.clone());
+                }
+
+                entry
+            }
+        };
+
+        let cache_headers = [
+            (header::ETAG, entry.etag.clone()),
+            (
+                header::LAST_MODIFIED,
+                modified.format(HTTP_DATE_FORMAT).to_string(),
+            ),
+            (header::CACHE_CONTROL, "no-cache".to_string()),
+        ];
+
+        if is_fresh(headers, &entry.etag, modified) {
+            return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+        }
+
+        Ok((
+            StatusCode::OK,
+            cache_headers,
+            [(header::CONTENT_TYPE, "application/json")],
+            entry.body,
+        )
+            .into_response())
+    }
+}
+
+/// List caches by table name, so routers writing to a table find the cache of its list.
+#[derive(Clone, Default)]
+pub struct ListCaches {
+    caches: Arc<Mutex<HashMap<&'static str, ListCache>>>,
+}
+
+impl ListCaches {
+    /// Cache of the list of `table`, created on first use.
+    pub fn table(&self, table: &'static str) -> ListCache {
+        let mut caches = self.caches.lock().unwrap_or_else(|err| err.into_inner());
+        caches.entry(table).or_default().clone()
+    }
+
+    /// Invalidates the cache of `table`, if its list is cached.
+    pub fn invalidate(&self, table: &str) {
+        let caches = self.caches.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(cache) = caches.get(table) {
+            cache.invalidate();
+        }
+    }
+}
+
+fn is_fresh(headers: &HeaderMap, etag: &str, modified: DateTime<Utc>) -> bool {
+    // `If-None-Match` takes precedence over `If-Modified-Since` when both are sent.
+    if let Some(value) = headers.get(header::IF_NONE_MATCH) {
+        return value.to_str().is_ok_and(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+        });
+    }
+
+    headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| NaiveDateTime::parse_from_str(value, HTTP_DATE_FORMAT).ok())
+        .is_some_and(|since| modified.naive_utc() <= since)
+}
@@ -0,0 +1,253 @@
+//! Call numbers of the Universal Decimal Classification and the Dewey Decimal Classification.
+//!
+//! A call number is a class number followed by an optional book mark, like `821.161.2-1 SHE`
+//! or `891.79 SHE`. Call numbers do not sort as text, so each one has a sort key which does.
+
+use color_eyre::{eyre::bail, Result};
+
+use crate::model::Classification;
+
+/// Ends the class number in a sort key. It sorts before the further digits of the class and
+/// after the UDC signs filed before a bare number, so the key of `622` sorts after `622+669`
+/// and before `622:669`, `622(4)`, `622-1` and `622.1`.
+const END: char = '#';
+
+/// Validates a call number and returns it trimmed, with single spaces between its parts.
+pub fn normalize(classification: Classification, call_number: &str) -> Result<String> {
+    let normalized = call_number.split_whitespace().collect::<Vec<_>>().join(" ");
+    let class = normalized.split(' ').next().unwrap_or_default();
+
+    let is_valid = match classification {
+        Classification::Udc => is_valid_udc(class),
+        Classification::Dewey => is_valid_dewey(class),
+    };
+    if !is_valid {
+        bail!(
+            "`{call_number}` does not start with a {} class number",
+            classification.name()
+        );
+    }
+
+    Ok(normalized)
+}
+
+/// Key of a normalized call number which sorts call numbers of a classification in filing order.
+pub fn sort_key(classification: Classification, call_number: &str) -> String {
+    let (class, mark) = call_number.split_once(' ').unwrap_or((call_number, ""));
+
+    let mut key = match classification {
+        Classification::Udc => udc_key(class),
+        Classification::Dewey => class.chars().filter(|c| *c != '.').collect(),
+    };
+    key.push(END);
+    key.push_str(&mark.to_uppercase());
+
+    key
+}
+
+impl Classification {
+    fn name(self) -> &'static str {
+        match self {
+            Classification::Udc => "UDC",
+            Classification::Dewey => "Dewey",
+        }
+    }
+}
+
+/// Three digits, with an optional decimal part.
+fn is_valid_dewey(class: &str) -> bool {
+    let (main, decimals) = match class.split_once('.') {
+        Some((main, decimals)) => (main, Some(decimals)),
+        None => (class, None),
+    };
+
+    main.len() == 3
+        && main.chars().all(|c| c.is_ascii_digit())
+        && decimals.is_none_or(|decimals| {
+            !decimals.is_empty() && decimals.chars().all(|c| c.is_ascii_digit())
+        })
+}
+
+/// A main number, with auxiliaries of the signs [`udc_key`] knows.
+fn is_valid_udc(class: &str) -> bool {
+    let mut depth = 0;
+    for c in class.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return false,
+            ')' => depth -= 1,
+            '0'..='9' | '.' | '+' | '/' | ':' | '=' | '"' | '-' | '\'' | 'A'..='Z' | 'a'..='z' => {}
+            _ => return false,
+        }
+    }
+
+    depth == 0
+        && class.starts_with(|c: char| c.is_ascii_digit())
+        && class.matches('"').count().is_multiple_of(2)
+}
+
+/// Sort key of a UDC class number.
+///
+/// Digits sort as decimal fractions, the dots between groups of three are left out. Each
+/// auxiliary starts with a character below the digits, ranked in the filing order of its sign.
+fn udc_key(class: &str) -> String {
+    let mut key = String::with_capacity(class.len());
+    let mut chars = class.chars().peekable();
+    let mut in_time = false;
+
+    while let Some(c) = chars.next() {
+        let rank = match c {
+            '0'..='9' => {
+                key.push(c);
+                continue;
+            }
+            'A'..='Z' | 'a'..='z' => {
+                key.push(c.to_ascii_uppercase());
+                continue;
+            }
+            '.' | ')' => continue,
+            '"' if in_time => {
+                in_time = false;
+                continue;
+            }
+            '+' => '!',
+            '/' => '"',
+            ':' => '$',
+            '=' => '%',
+            '(' => match chars.peek() {
+                Some('0') => '&',
+                Some('=') => {
+                    chars.next();
+                    '('
+                }
+                _ => '\'',
+            },
+            '"' => {
+                in_time = true;
+                ')'
+            }
+            '-' => '*',
+            '\'' => ',',
+            _ => continue,
+        };
+        key.push(rank);
+    }
+
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(classification: Classification, call_numbers: &[&str]) -> Vec<String> {
+        let mut call_numbers = call_numbers
+            .iter()
+            .map(|call_number| normalize(classification, call_number).unwrap())
+            .collect::<Vec<_>>();
+        call_numbers.sort_by_key(|call_number| sort_key(classification, call_number));
+
+        call_numbers
+    }
+
+    #[test]
+    fn normalizes_whitespace() {
+        assert_eq!(
+            normalize(Classification::Dewey, "  891.79   she ").unwrap(),
+            "891.79 she"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_class_numbers() {
+        for call_number in ["", "89.1", "891.", "891.7a", "ABC 891"] {
+            assert!(normalize(Classification::Dewey, call_number).is_err());
+        }
+        for call_number in ["", "(477)821", "821(477", "821\"19", "821;1"] {
+            assert!(normalize(Classification::Udc, call_number).is_err());
+        }
+    }
+
+    #[test]
+    fn dewey_numbers_sort_as_decimals() {
+        assert_eq!(
+            sorted(
+                Classification::Dewey,
+                &[
+                    "891.8 KOB",
+                    "891.79 SHE",
+                    "500 AST",
+                    "891.7 ABC",
+                    "891 ZZZ",
+                    "891.79 FRA"
+                ]
+            ),
+            [
+                "500 AST",
+                "891 ZZZ",
+                "891.7 ABC",
+                "891.79 FRA",
+                "891.79 SHE",
+                "891.8 KOB"
+            ]
+        );
+    }
+
+    #[test]
+    fn udc_numbers_sort_in_filing_order() {
+        assert_eq!(
+            sorted(
+                Classification::Udc,
+                &[
+                    "622.1",
+                    "622-1",
+                    "622\"19\"",
+                    "622(4)",
+                    "622(=161.2)",
+                    "622(075)",
+                    "622=161.2",
+                    "622:669",
+                    "622",
+                    "622/669",
+                    "622+669",
+                    "62",
+                ]
+            ),
+            [
+                "62",
+                "622+669",
+                "622/669",
+                "622",
+                "622:669",
+                "622=161.2",
+                "622(075)",
+                "622(4)",
+                "622(=161.2)",
+                "622\"19\"",
+                "622-1",
+                "622.1",
+            ]
+        );
+    }
+
+    #[test]
+    fn udc_auxiliaries_sort_before_longer_numbers() {
+        assert_eq!(
+            sorted(
+                Classification::Udc,
+                &[
+                    "821.161.21",
+                    "821.161.2-31 SHE",
+                    "821.161.2(477) KOB",
+                    "821.161.2 ABC"
+                ]
+            ),
+            [
+                "821.161.2 ABC",
+                "821.161.2(477) KOB",
+                "821.161.2-31 SHE",
+                "821.161.21"
+            ]
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use color_eyre::eyre::{eyre, Context};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use sqlx::{Pool, Postgres};
+use tokio::sync::broadcast;
+
+/// Channel the `notify_table_change` trigger notifies.
+const CHANNEL: &str = "table_change";
+
+/// Changes kept for slow subscribers, a subscriber further behind is told to resync.
+const CAPACITY: usize = 1024;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Tables announcing their changes, the ones with a `notify_table_change` trigger.
+pub const TABLES: [&str; 22] = [
+    "country",
+    "faculty",
+    "curriculum",
+    "faculty_curriculum",
+    "student",
+    "teacher",
+    "librarian",
+    "category",
+    "publisher",
+    "author",
+    "book",
+    "author_book",
+    "student_card",
+    "teacher_card",
+    "students_borrowing",
+    "teachers_borrowing",
+    "hold",
+    "students_borrowing_renewal",
+    "branch",
+    "book_transfer",
+    "shelf",
+    "ledger_entry",
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// Insert, update or delete of a row, as announced by the trigger.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Change {
+    pub table: String,
+    pub action: Action,
+    pub key: serde_json::Value,
+    /// Version of the row after the change, `None` for tables without versions.
+    pub version: Option<i32>,
+}
+
+/// Event sent to the subscribers of the feed.
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeedEvent {
+    Change(Change),
+    /// Changes were missed, the subscriber should reload what it shows.
+    Resync,
+}
+
+impl FeedEvent {
+    /// Name of the event, the `type` of its JSON.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Change(_) => "change",
+            Self::Resync => "resync",
+        }
+    }
+}
+
+/// Changes of the tables, broadcast to every subscriber of this server.
+///
+/// Changes come from Postgres `LISTEN`, so writes of other replicas and of SQL sessions are
+/// included. The listening connection is opened by the first subscription.
+#[derive(Clone)]
+pub struct ChangeFeed {
+    db: Pool<Postgres>,
+    sender: broadcast::Sender<Arc<FeedEvent>>,
+    listening: Arc<AtomicBool>,
+}
+
+impl ChangeFeed {
+    pub fn new(db: Pool<Postgres>) -> Self {
+        Self {
+            db,
+            sender: broadcast::channel(CAPACITY).0,
+            listening: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Receiver of the changes made from now on.
+    pub async fn subscribe(&self) -> Result<broadcast::Receiver<Arc<FeedEvent>>> {
+        let receiver = self.sender.subscribe();
+
+        if !self.listening.swap(true, Ordering::SeqCst) {
+            match self.listener().await {
+                Ok(listener) => {
+                    tokio::spawn(forward(self.clone(), listener));
+                }
+                Err(err) => {
+                    self.listening.store(false, Ordering::SeqCst);
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(receiver)
+    }
+
+    async fn listener(&self) -> Result<PgListener> {
+        let mut listener = PgListener::connect_with(&self.db)
+            .await
+            .wrap_err_with(|| eyre!("Unable to connect the change feed to database"))?;
+
+        listener
+            .listen(CHANNEL)
+            .await
+            .wrap_err_with(|| eyre!("Unable to listen to `{CHANNEL}`"))?;
+
+        Ok(listener)
+    }
+
+    /// New listener replacing one which lost its connection, retrying until the database is back.
+    async fn reconnect(&self) -> PgListener {
+        loop {
+            tokio::time::sleep(RECONNECT_DELAY).await;
+
+            match self.listener().await {
+                Ok(listener) => return listener,
+                Err(err) => tracing::warn!("Change feed is unable to reconnect: {err:#}"),
+            }
+        }
+    }
+}
+
+/// Broadcasts the notifications of `listener` for as long as the server runs.
+async fn forward(feed: ChangeFeed, mut listener: PgListener) {
+    loop {
+        // `recv` would reconnect by itself and silently skip the notifications sent meanwhile,
+        // `try_recv` tells the connection was lost so subscribers can be told to resync.
+        let notification = match listener.try_recv().await {
+            Ok(Some(notification)) => notification,
+            result => {
+                if let Err(err) = result {
+                    tracing::warn!("Change feed lost its connection: {err}");
+                } else {
+                    tracing::warn!("Change feed lost its connection");
+                }
+                listener = feed.reconnect().await;
+                // Sending fails when nobody subscribed, which is fine.
+                let _ = feed.sender.send(Arc::new(FeedEvent::Resync));
+                continue;
+            }
+        };
+
+        match serde_json::from_str::<Change>(notification.payload()) {
+            Ok(change) => {
+                let _ = feed.sender.send(Arc::new(FeedEvent::Change(change)));
+            }
+            Err(err) => tracing::error!(
+                "Invalid change `{}` on `{CHANNEL}`: {err}",
+                notification.payload()
+            ),
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use axum::http::StatusCode;
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::fine::{self, format_amount};
+use crate::model::CardState;
+use crate::resource::label;
+
+/// Card a borrowing is checked out on, checked by the repositories before the borrowing is added.
+pub struct Checkout {
+    /// Table of the card, `student_card` or `teacher_card`.
+    pub card_table: &'static str,
+    pub card: i32,
+    pub book: i32,
+    /// Branch the copy is lent at, if recorded.
+    pub branch: Option<i32>,
+}
+
+impl Checkout {
+    /// Whether an update moves the loan to another card, copy or branch, which is checked out
+    /// again.
+    ///
+    /// Other updates, like returning the copy, are allowed on a card no longer active.
+    pub fn is_moved_from(&self, previous: &Checkout) -> bool {
+        self.card != previous.card || self.book != previous.book || self.branch != previous.branch
+    }
+}
+
+/// Columns of a card the checkout rules look at.
+#[derive(Deserialize, sqlx::FromRow)]
+pub struct CardValidity {
+    pub state: CardState,
+    pub expiry_date: NaiveDate,
+}
+
+/// A book can only be checked out on an existing card which is active today.
+///
+/// `today` is the date of the server, a back-dated `borrow_date` does not revive an expired card.
+pub fn check_card(
+    checkout: &Checkout,
+    card: Option<CardValidity>,
+    today: NaiveDate,
+) -> Result<(), (StatusCode, String)> {
+    let name = format!("{} {}", label(checkout.card_table), checkout.card);
+
+    let card = card.ok_or_else(|| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("{name} does not exist"),
+        )
+    })?;
+
+    if !matches!(card.state, CardState::Active) || card.expiry_date < today {
+        return Err((StatusCode::CONFLICT, format!("{name} is not active")));
+    }
+
+    Ok(())
+}
+
+/// A copy on its way to another branch can only be lent once it is received there, and a copy
+/// belonging to a branch is only lent at that branch.
+///
+/// `book_branch` is the branch of the copy, `None` when it has none or does not exist.
+pub fn check_book(
+    checkout: &Checkout,
+    in_transit: bool,
+    book_branch: Option<i32>,
+) -> Result<(), (StatusCode, String)> {
+    if in_transit {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Book {} is in transit between branches", checkout.book),
+        ));
+    }
+
+    match (checkout.branch, book_branch) {
+        (Some(branch), Some(book_branch)) if branch != book_branch => Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Book {} belongs to branch {book_branch}, it cannot be lent at branch {branch}",
+                checkout.book
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// A patron owing more than the limit of the fine policy of their cards' table cannot borrow on
+/// any of their cards until they pay.
+///
+/// `balance` is the balance of the patron across all their cards.
+pub fn check_balance(
+    checkout: &Checkout,
+    balance: i64,
+    max_balance: Option<i32>,
+) -> Result<(), (StatusCode, String)> {
+    match max_balance {
+        Some(max_balance) if balance > i64::from(max_balance) => Err((
+            StatusCode::CONFLICT,
+            format!(
+                "The {} of {} {} owes {}, more than the limit of {}",
+                fine::patron_column(checkout.card_table),
+                label(checkout.card_table),
+                checkout.card,
+                format_amount(balance),
+                format_amount(max_balance.into())
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use chrono::{Duration, Local, NaiveDate};
+
+    use crate::model::{
+        BookStatus, BookTransfer, CardState, LedgerEntry, LedgerEntryKind, StudentCard,
+        StudentsBorrowing, TeachersBorrowing, TransferStatus,
+    };
+    use crate::repository::memory::MemoryDatabase;
+    use crate::repository::Error;
+
+    use super::{check_balance, Checkout};
+
+    /// Date `days` from the server's today, which the card rules compare with.
+    fn date(days: i64) -> NaiveDate {
+        Local::now().date_naive() + Duration::days(days)
+    }
+
+    fn student_card(state: CardState, expiry_date: NaiveDate) -> StudentCard {
+        StudentCard {
+            id: 0,
+            student: 1,
+            issue_date: date(-30),
+            expiry_date,
+            state,
+            version: 0,
+        }
+    }
+
+    fn students_borrowing(student_card: i32, borrow_date: NaiveDate) -> StudentsBorrowing {
+        StudentsBorrowing {
+            id: 0,
+            student_card,
+            librarian: 1,
+            book: 1,
+            book_status_start: BookStatus::Good,
+            book_status_finish: None,
+            borrow_date,
+            return_date: None,
+            required_return_date: borrow_date + Duration::days(14),
+            branch: None,
+            return_branch: None,
+            version: 0,
+        }
+    }
+
+    /// Inserts a student card and checks out a book on it at `borrow_date`.
+    async fn checkout(
+        state: CardState,
+        expiry_date: NaiveDate,
+        borrow_date: NaiveDate,
+    ) -> Result<StudentsBorrowing, Error> {
+        let db = MemoryDatabase::default();
+        let card = db
+            .repository::<StudentCard>()
+            .insert(&student_card(state, expiry_date))
+            .await
+            .unwrap();
+
+        db.repository::<StudentsBorrowing>()
+            .insert(&students_borrowing(card.id, borrow_date))
+            .await
+    }
+
+    fn assert_rejected(result: Result<impl std::fmt::Debug, Error>, status: StatusCode) {
+        match result {
+            Err(Error::Rejected(rejected, _)) => assert_eq!(rejected, status),
+            other => panic!("expected rejection with {status}, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn checkout_on_active_card() {
+        let borrowing = checkout(CardState::Active, date(30), date(0))
+            .await
+            .unwrap();
+
+        assert_eq!(borrowing.id, 1);
+        assert_eq!(borrowing.version, 1);
+    }
+
+    #[tokio::test]
+    async fn checkout_on_last_day_of_card() {
+        checkout(CardState::Active, date(0), date(0)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn checkout_requires_existing_card() {
+        let db = MemoryDatabase::default();
+        let result = db
+            .repository::<StudentsBorrowing>()
+            .insert(&students_borrowing(7, date(0)))
+            .await;
+
+        assert_rejected(result, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(db
+            .repository::<StudentsBorrowing>()
+            .list()
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn checkout_rejects_inactive_card() {
+        for state in [CardState::Lost, CardState::Blocked, CardState::Expired] {
+            assert_rejected(
+                checkout(state, date(30), date(0)).await,
+                StatusCode::CONFLICT,
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn checkout_rejects_expired_card() {
+        assert_rejected(
+            checkout(CardState::Active, date(-1), date(0)).await,
+            StatusCode::CONFLICT,
+        );
+    }
+
+    #[tokio::test]
+    async fn checkout_rejects_back_dated_loan_on_expired_card() {
+        assert_rejected(
+            checkout(CardState::Active, date(-5), date(-10)).await,
+            StatusCode::CONFLICT,
+        );
+    }
+
+    #[tokio::test]
+    async fn update_rejects_move_to_inactive_card() {
+        let db = MemoryDatabase::default();
+        let cards = db.repository::<StudentCard>();
+        let active = cards
+            .insert(&student_card(CardState::Active, date(30)))
+            .await
+            .unwrap();
+        let blocked = cards
+            .insert(&student_card(CardState::Blocked, date(30)))
+            .await
+            .unwrap();
+        let borrowing = db
+            .repository::<StudentsBorrowing>()
+            .insert(&students_borrowing(active.id, date(0)))
+            .await
+            .unwrap();
+
+        let result = db
+            .repository::<StudentsBorrowing>()
+            .update(
+                &borrowing.id,
+                &StudentsBorrowing {
+                    student_card: blocked.id,
+                    ..borrowing.clone()
+                },
+                borrowing.version,
+            )
+            .await;
+        assert_rejected(result, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn update_returns_loan_on_card_blocked_since() {
+        let db = MemoryDatabase::default();
+        let cards = db.repository::<StudentCard>();
+        let card = cards
+            .insert(&student_card(CardState::Active, date(30)))
+            .await
+            .unwrap();
+        let borrowing = db
+            .repository::<StudentsBorrowing>()
+            .insert(&students_borrowing(card.id, date(0)))
+            .await
+            .unwrap();
+        cards
+            .update(
+                &card.id,
+                &StudentCard {
+                    state: CardState::Blocked,
+                    ..card.clone()
+                },
+                card.version,
+            )
+            .await
+            .unwrap();
+
+        db.repository::<StudentsBorrowing>()
+            .update(
+                &borrowing.id,
+                &StudentsBorrowing {
+                    return_date: Some(date(0)),
+                    book_status_finish: Some(BookStatus::Good),
+                    ..borrowing.clone()
+                },
+                borrowing.version,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn checkout_looks_up_teacher_cards() {
+        let db = MemoryDatabase::default();
+        db.repository::<StudentCard>()
+            .insert(&student_card(CardState::Active, date(30)))
+            .await
+            .unwrap();
+
+        let result = db
+            .repository::<TeachersBorrowing>()
+            .insert(&TeachersBorrowing {
+                id: 0,
+                teacher_card: 1,
+                librarian: 1,
+                book: 1,
+                book_status_start: BookStatus::Excellent,
+                book_status_finish: None,
+                borrow_date: date(0),
+                return_date: None,
+                branch: None,
+                return_branch: None,
+                version: 0,
+            })
+            .await;
+
+        assert_rejected(result, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn checkout_rejects_book_in_transit() {
+        let db = MemoryDatabase::default();
+        let card = db
+            .repository::<StudentCard>()
+            .insert(&student_card(CardState::Active, date(30)))
+            .await
+            .unwrap();
+        let transfer = db
+            .repository::<BookTransfer>()
+            .insert(&BookTransfer {
+                id: 0,
+                book: 1,
+                from_branch: 1,
+                to_branch: 2,
+                sent_date: date(-1),
+                received_date: None,
+                status: TransferStatus::InTransit,
+                version: 0,
+            })
+            .await
+            .unwrap();
+
+        let result = db
+            .repository::<StudentsBorrowing>()
+            .insert(&students_borrowing(card.id, date(0)))
+            .await;
+        assert_rejected(result, StatusCode::CONFLICT);
+
+        db.repository::<BookTransfer>()
+            .update(
+                &transfer.id,
+                &BookTransfer {
+                    received_date: Some(date(0)),
+                    status: TransferStatus::Received,
+                    ..transfer.clone()
+                },
+                transfer.version,
+            )
+            .await
+            .unwrap();
+        db.repository::<StudentsBorrowing>()
+            .insert(&students_borrowing(card.id, date(0)))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn checkout_counts_balance_of_every_card_of_patron() {
+        let db = MemoryDatabase::default();
+        let cards = db.repository::<StudentCard>();
+        let lost = cards
+            .insert(&student_card(CardState::Lost, date(30)))
+            .await
+            .unwrap();
+        let reissued = cards
+            .insert(&student_card(CardState::Active, date(30)))
+            .await
+            .unwrap();
+        let entry = |card, kind, amount| LedgerEntry {
+            id: 0,
+            student_card: Some(card),
+            teacher_card: None,
+            kind,
+            amount,
+            students_borrowing: None,
+            teachers_borrowing: None,
+            librarian: Some(1),
+            reason: None,
+            created_at: Default::default(),
+        };
+        db.set_fine_policy("student_card", 1000);
+        db.add_ledger_entry(&entry(lost.id, LedgerEntryKind::LostBook, 1250))
+            .unwrap();
+
+        let result = db
+            .repository::<StudentsBorrowing>()
+            .insert(&students_borrowing(reissued.id, date(0)))
+            .await;
+        assert_rejected(result, StatusCode::CONFLICT);
+
+        db.add_ledger_entry(&entry(reissued.id, LedgerEntryKind::Payment, 250))
+            .unwrap();
+        db.repository::<StudentsBorrowing>()
+            .insert(&students_borrowing(reissued.id, date(0)))
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn checkout_rejects_balance_above_limit() {
+        let checkout = Checkout {
+            card_table: "student_card",
+            card: 3,
+            book: 1,
+            branch: None,
+        };
+
+        assert!(check_balance(&checkout, 1250, None).is_ok());
+        assert!(check_balance(&checkout, 1000, Some(1000)).is_ok());
+        assert_eq!(
+            check_balance(&checkout, 1250, Some(1000)),
+            Err((
+                StatusCode::CONFLICT,
+                "The student of Student card 3 owes 12.50, more than the limit of 10.00"
+                    .to_string()
+            ))
+        );
+    }
+}
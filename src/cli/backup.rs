@@ -0,0 +1,350 @@
+//! Backups of the whole library which do not depend on the version of `pg_dump`.
+//!
+//! A backup is a directory with `manifest.json` and a file of newline delimited JSON per table.
+//! Rows of the tables served by the API are written as the API serializes them, so a backup of
+//! Postgres can be restored into SQLite and the other way round.
+
+use std::path::Path;
+
+use color_eyre::{
+    eyre::{bail, eyre, Context},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{PgConnection, SqliteConnection};
+
+use crate::database::Database;
+use crate::repository::snake_case;
+
+use super::table;
+
+/// Version of the layout of backups, the layout of rows follows the schema version.
+pub const FORMAT: u32 = 1;
+const MANIFEST: &str = "manifest.json";
+
+/// Tables only kept in Postgres, restored after the tables they reference.
+///
+/// Webhook subscriptions come after the loans and cards, so restoring them sends no webhooks.
+const POSTGRES_TABLES: [&str; 8] = [
+    "students_borrowing_renewal",
+    "ledger_entry",
+    "fine_policy",
+    "notification",
+    "job_run",
+    "webhook_subscription",
+    "webhook_delivery",
+    "webhook_attempt",
+];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Manifest {
+    pub format: u32,
+    /// [`Database::schema_version`] of the binary which wrote the backup.
+    pub schema_version: i64,
+    pub created_at: DateTime<Utc>,
+    pub tables: Vec<TableFile>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TableFile {
+    pub table: String,
+    pub file: String,
+    pub rows: usize,
+}
+
+/// Connection of the transaction a backup is taken or restored in.
+pub enum Connection<'c> {
+    Postgres(&'c mut PgConnection),
+    Sqlite(&'c mut SqliteConnection),
+}
+
+/// Writes every table of `db` to the directory `dir`.
+///
+/// Every table is read in one transaction, so the backup is a snapshot of the database even
+/// while it is being written to.
+pub async fn backup(db: &Database, dir: &Path) -> Result<Manifest> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .wrap_err_with(|| eyre!("Unable to create {}", dir.display()))?;
+
+    let mut tables = Vec::new();
+    match db {
+        Database::Postgres(db_pool) => {
+            let mut tx = db_pool
+                .begin()
+                .await
+                .wrap_err_with(|| eyre!("Unable to start transaction"))?;
+            // Every query of the transaction sees the snapshot taken by its first one.
+            sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+                .execute(&mut tx)
+                .await
+                .wrap_err_with(|| eyre!("Unable to set the isolation of the transaction"))?;
+
+            for table in table::all() {
+                tables.push((
+                    table.name(),
+                    table.dump(Connection::Postgres(&mut tx)).await?,
+                ));
+            }
+            for table in POSTGRES_TABLES {
+                // Kept as Postgres writes them, enums in snake case, secrets included.
+                let rows = sqlx::query_scalar::<_, Value>(&format!(
+                    "SELECT to_jsonb(t) FROM {table} t ORDER BY id ASC"
+                ))
+                .fetch_all(&mut tx)
+                .await
+                .wrap_err_with(|| eyre!("Unable to load {table} rows from database"))?;
+                tables.push((table, rows));
+            }
+
+            tx.commit()
+                .await
+                .wrap_err_with(|| eyre!("Unable to commit backup"))?;
+        }
+        Database::Sqlite(db_pool) => {
+            // A read transaction of SQLite sees the database as of its first read until it ends.
+            let mut tx = db_pool
+                .begin()
+                .await
+                .wrap_err_with(|| eyre!("Unable to start transaction"))?;
+
+            for table in table::all() {
+                tables.push((table.name(), table.dump(Connection::Sqlite(&mut tx)).await?));
+            }
+
+            tx.commit()
+                .await
+                .wrap_err_with(|| eyre!("Unable to commit backup"))?;
+        }
+    }
+
+    let mut manifest = Manifest {
+        format: FORMAT,
+        schema_version: Database::schema_version(),
+        created_at: Utc::now(),
+        tables: Vec::new(),
+    };
+
+    for (table, rows) in tables {
+        let file = format!("{table}.ndjson");
+        let mut lines = String::new();
+        for row in &rows {
+            lines.push_str(&row.to_string());
+            lines.push('\n');
+        }
+
+        tokio::fs::write(dir.join(&file), lines)
+            .await
+            .wrap_err_with(|| eyre!("Unable to write {}", dir.join(&file).display()))?;
+        manifest.tables.push(TableFile {
+            table: table.to_string(),
+            file,
+            rows: rows.len(),
+        });
+    }
+
+    // Written last, a directory without manifest is an unfinished backup.
+    tokio::fs::write(dir.join(MANIFEST), serde_json::to_vec_pretty(&manifest)?)
+        .await
+        .wrap_err_with(|| eyre!("Unable to write {}", dir.join(MANIFEST).display()))?;
+
+    Ok(manifest)
+}
+
+/// Restores the backup in `dir` into the empty database `db`, in a single transaction.
+///
+/// Rows keep their keys and versions, the sequences of the keys continue after them.
+pub async fn restore(db: &Database, dir: &Path) -> Result<Manifest> {
+    let manifest = tokio::fs::read(dir.join(MANIFEST))
+        .await
+        .wrap_err_with(|| eyre!("Unable to read {}", dir.join(MANIFEST).display()))?;
+    let manifest: Manifest = serde_json::from_slice(&manifest)
+        .wrap_err_with(|| eyre!("{} is not a backup manifest", dir.join(MANIFEST).display()))?;
+
+    if manifest.format != FORMAT {
+        bail!(
+            "Backup format {} is not supported, this version reads format {FORMAT}",
+            manifest.format
+        );
+    }
+    if manifest.schema_version > Database::schema_version() {
+        bail!(
+            "Backup of schema {} is newer than the schema of this version ({})",
+            manifest.schema_version,
+            Database::schema_version()
+        );
+    }
+    if let Some(table) = table::first_with_rows(db).await? {
+        bail!("{table} already has rows, backups are only restored into an empty database");
+    }
+
+    let mut tables = Vec::new();
+    for file in &manifest.tables {
+        let is_known = table::find(&file.table).is_ok() || POSTGRES_TABLES.contains(&&*file.table);
+        if !is_known {
+            bail!("Unknown table {}", file.table);
+        }
+
+        tables.push((file.table.as_str(), read_rows(dir, file).await?));
+    }
+
+    match db {
+        Database::Postgres(db_pool) => {
+            let mut tx = db_pool
+                .begin()
+                .await
+                .wrap_err_with(|| eyre!("Unable to start transaction"))?;
+
+            for table in table::all() {
+                let rows = take_rows(&mut tables, table.name());
+                table.restore(Connection::Postgres(&mut tx), rows).await?;
+            }
+            for table in POSTGRES_TABLES {
+                let exists = sqlx::query_scalar::<_, bool>(&format!(
+                    "SELECT EXISTS (SELECT 1 FROM {table})"
+                ))
+                .fetch_one(&mut tx)
+                .await
+                .wrap_err_with(|| eyre!("Unable to load {table} from database"))?;
+                if exists {
+                    bail!("{table} already has rows, backups are only restored into an empty database");
+                }
+
+                for row in take_rows(&mut tables, table) {
+                    insert_postgres(&mut tx, table, row).await?;
+                }
+            }
+
+            let keyed = table::all()
+                .iter()
+                .map(|table| table.name())
+                .filter(|table| *table != "country")
+                .chain(POSTGRES_TABLES)
+                .collect::<Vec<_>>();
+            for table in keyed {
+                // `setval` ignores the empty tables, their `MAX` is NULL.
+                sqlx::query(&format!(
+                    "SELECT setval(pg_get_serial_sequence('{table}', 'id'), MAX(id)) FROM {table}"
+                ))
+                .execute(&mut tx)
+                .await
+                .wrap_err_with(|| eyre!("Unable to reset the sequence of {table}"))?;
+            }
+
+            tx.commit()
+                .await
+                .wrap_err_with(|| eyre!("Unable to commit restore"))?;
+        }
+        Database::Sqlite(db_pool) => {
+            let postgres_rows = tables
+                .iter()
+                .find(|(table, rows)| POSTGRES_TABLES.contains(table) && !rows.is_empty());
+            if let Some((table, _)) = postgres_rows {
+                bail!("{table} is only kept in Postgres, restore the backup into Postgres");
+            }
+
+            // `AUTOINCREMENT` keys continue after the largest key inserted.
+            let mut tx = db_pool
+                .begin()
+                .await
+                .wrap_err_with(|| eyre!("Unable to start transaction"))?;
+
+            for table in table::all() {
+                let rows = take_rows(&mut tables, table.name());
+                table.restore(Connection::Sqlite(&mut tx), rows).await?;
+            }
+
+            tx.commit()
+                .await
+                .wrap_err_with(|| eyre!("Unable to commit restore"))?;
+        }
+    }
+
+    Ok(manifest)
+}
+
+async fn read_rows(dir: &Path, file: &TableFile) -> Result<Vec<Value>> {
+    // The file is named by the manifest, it should not lead out of the backup.
+    if Path::new(&file.file).components().count() != 1 {
+        bail!("{} is not a file of the backup", file.file);
+    }
+
+    let path = dir.join(&file.file);
+    let lines = tokio::fs::read_to_string(&path)
+        .await
+        .wrap_err_with(|| eyre!("Unable to read {}", path.display()))?;
+
+    let rows = lines
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .wrap_err_with(|| eyre!("Line {} of {} is not JSON", i + 1, path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if rows.len() != file.rows {
+        bail!(
+            "{} has {} rows, the manifest lists {}",
+            path.display(),
+            rows.len(),
+            file.rows
+        );
+    }
+
+    Ok(rows)
+}
+
+/// Rows of `table` in the backup, none for the tables it does not have.
+fn take_rows(tables: &mut Vec<(&str, Vec<Value>)>, table: &str) -> Vec<Value> {
+    match tables.iter().position(|(name, _)| *name == table) {
+        Some(i) => tables.remove(i).1,
+        None => Vec::new(),
+    }
+}
+
+/// Inserts the JSON `row` into `table` of Postgres, with every column it has.
+///
+/// The columns of Postgres enums take the snake case labels of the variants.
+pub async fn insert_postgres(conn: &mut PgConnection, table: &str, mut row: Value) -> Result<()> {
+    let enum_columns = sqlx::query_scalar::<_, String>(
+        r#"SELECT a.attname::TEXT
+        FROM pg_attribute a
+        JOIN pg_type t ON t.oid = a.atttypid
+        LEFT JOIN pg_type e ON e.oid = t.typelem
+        WHERE a.attrelid = $1::TEXT::regclass AND a.attnum > 0 AND NOT a.attisdropped
+        AND (t.typtype = 'e' OR e.typtype = 'e')"#,
+    )
+    .bind(table)
+    .fetch_all(&mut *conn)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load the columns of {table} from database"))?;
+
+    for column in enum_columns {
+        match row.get_mut(&column) {
+            Some(Value::String(variant)) => *variant = snake_case(variant),
+            Some(Value::Array(variants)) => {
+                for variant in variants {
+                    if let Value::String(variant) = variant {
+                        *variant = snake_case(variant);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // `table` is a constant of the code, backups only name the tables it knows.
+    sqlx::query(&format!(
+        "INSERT INTO {table} SELECT * FROM jsonb_populate_record(NULL::{table}, $1)"
+    ))
+    .bind(row)
+    .execute(conn)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add {table} to database"))?;
+
+    Ok(())
+}
@@ -0,0 +1,210 @@
+//! CSV files of the `import` and `export` commands, as spreadsheets write them (RFC 4180).
+
+use color_eyre::{eyre::bail, Result};
+use serde::de::value::{Error, MapDeserializer};
+use serde::de::{DeserializeOwned, IntoDeserializer, Visitor};
+use serde::{forward_to_deserialize_any, Deserializer};
+
+/// Records of `text`, the first one is the header.
+pub fn parse(text: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match (quoted, char) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, char) => field.push(char),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (false, char) => field.push(char),
+        }
+    }
+
+    if quoted {
+        bail!("Unterminated quoted field in record {}", records.len() + 1);
+    }
+    // The last record may end without a line break.
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+/// Appends `fields` to `out` as a record, quoting the fields which need it.
+pub fn write_record<'a>(out: &mut String, fields: impl IntoIterator<Item = &'a str>) {
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+
+    out.push_str("\r\n");
+}
+
+/// Row of `R` from the fields of a record named by `header`.
+///
+/// Empty fields are `None` for optional columns, enums are written like in the JSON of the API.
+pub fn deserialize<R: DeserializeOwned>(header: &[String], record: &[String]) -> Result<R, Error> {
+    R::deserialize(MapDeserializer::new(
+        header
+            .iter()
+            .map(String::as_str)
+            .zip(record.iter().map(|field| Field(field))),
+    ))
+}
+
+/// Field of a record, parsed into the type the row expects.
+struct Field<'a>(&'a str);
+
+impl<'a> Field<'a> {
+    fn parse<T: std::str::FromStr>(&self, kind: &str) -> Result<T, Error> {
+        self.0
+            .trim()
+            .parse()
+            .map_err(|_| serde::de::Error::custom(format!("`{}` is not {kind}", self.0)))
+    }
+}
+
+impl<'de, 'a> Deserializer<'de> for Field<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_str(self.0)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bool(self.parse("a boolean")?)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i16(self.parse("a number")?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i32(self.parse("a number")?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i64(self.parse("a number")?)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f64(self.parse("a number")?)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0.is_empty() {
+            true => visitor.visit_none(),
+            false => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.0
+            .trim()
+            .into_deserializer()
+            .deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        i8 i128 u8 u16 u32 u64 u128 f32 char str string bytes byte_buf unit unit_struct
+        newtype_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de, 'a> IntoDeserializer<'de, Error> for Field<'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use serde::Deserialize;
+
+    use crate::model::BookStatus;
+
+    use super::{deserialize, parse, write_record};
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Row {
+        id: i32,
+        title: String,
+        status: Option<BookStatus>,
+        release: NaiveDate,
+        #[serde(default)]
+        version: i32,
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let fields = ["1", "Kobzar, 1840", "say \"hi\"", "two\nlines", ""];
+        let mut text = String::new();
+        write_record(&mut text, ["id", "a", "b", "c", "d"]);
+        write_record(&mut text, fields);
+
+        assert_eq!(
+            text,
+            "id,a,b,c,d\r\n1,\"Kobzar, 1840\",\"say \"\"hi\"\"\",\"two\nlines\",\r\n"
+        );
+        assert_eq!(parse(&text).unwrap()[1], fields);
+        assert_eq!(parse("a,b\n1,2").unwrap(), [["a", "b"], ["1", "2"]]);
+        assert!(parse("a\n\"1").is_err());
+    }
+
+    #[test]
+    fn fields_are_parsed_into_the_row() {
+        let records =
+            parse("title,id,status,release\nKobzar,7,Good,1840-04-30\nZapovit,8,,1845-12-25\n")
+                .unwrap();
+
+        let row = deserialize::<Row>(&records[0], &records[1]).unwrap();
+        assert_eq!(
+            row,
+            Row {
+                id: 7,
+                title: "Kobzar".to_string(),
+                status: Some(BookStatus::Good),
+                release: NaiveDate::from_ymd_opt(1840, 4, 30).unwrap(),
+                version: 0,
+            }
+        );
+        assert_eq!(
+            deserialize::<Row>(&records[0], &records[2]).unwrap().status,
+            None
+        );
+
+        let err = deserialize::<Row>(&records[0], &["Kobzar".into(), "seven".into()]).unwrap_err();
+        assert_eq!(err.to_string(), "`seven` is not a number");
+    }
+}
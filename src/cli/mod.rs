@@ -0,0 +1,324 @@
+//! Subcommands of the `crud` binary, over the same models and repositories as the HTTP API.
+
+use std::path::PathBuf;
+
+use chrono::{Local, NaiveDate};
+use color_eyre::{
+    eyre::{bail, eyre, Context},
+    Result,
+};
+
+use crate::database::Database;
+use crate::model::Librarian;
+use crate::scheduler::{self, Job};
+
+mod backup;
+mod csv;
+mod seed;
+mod table;
+
+pub const USAGE: &str = "\
+Usage: crud [COMMAND]
+
+Commands:
+  serve                                    Serve the API on port 3000 (the default)
+  migrate                                  Apply the pending migrations
+  create-librarian NAME LASTNAME SURNAME AGE
+                                           Add a librarian, the staff lending the books
+  import TABLE FILE                        Insert the rows of a CSV file into TABLE
+  export DIR [TABLE...]                    Write every table, or the listed ones, to DIR/TABLE.csv
+  seed [SEED] [STUDENTS] [DATE]            Fill an empty database with a demo library, the same
+                                           for the same SEED (1), sized by STUDENTS (100) and
+                                           dated relative to DATE (today)
+  backup DIR                               Write every table and a manifest to DIR, portable
+                                           between Postgres and SQLite
+  restore DIR                              Load the backup in DIR into an empty database
+  check-integrity                          Report rows breaking the rules of their table
+  purge-expired                            Expire holds and cards, delete old notifications,
+                                           job runs and webhook deliveries
+  help                                     Print this message
+
+The database is chosen by `DATABASE_URL`, like for the server.";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Serve,
+    Migrate,
+    CreateLibrarian {
+        name: String,
+        lastname: String,
+        surname: String,
+        age: i16,
+    },
+    Import {
+        table: String,
+        file: PathBuf,
+    },
+    Export {
+        dir: PathBuf,
+        // Every table when empty.
+        tables: Vec<String>,
+    },
+    Seed {
+        seed: u64,
+        students: usize,
+        date: NaiveDate,
+    },
+    Backup {
+        dir: PathBuf,
+    },
+    Restore {
+        dir: PathBuf,
+    },
+    CheckIntegrity,
+    PurgeExpired,
+    Help,
+}
+
+impl Command {
+    /// Command of the arguments following the name of the binary.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter();
+        let Some(command) = args.next() else {
+            return Ok(Self::Serve);
+        };
+        let args = args.collect::<Vec<_>>();
+
+        let command = match (command.as_str(), args.as_slice()) {
+            ("serve", []) => Self::Serve,
+            ("migrate", []) => Self::Migrate,
+            ("create-librarian", [name, lastname, surname, age]) => Self::CreateLibrarian {
+                name: name.clone(),
+                lastname: lastname.clone(),
+                surname: surname.clone(),
+                age: age
+                    .parse()
+                    .wrap_err_with(|| eyre!("AGE should be a number, got `{age}`"))?,
+            },
+            ("import", [table, file]) => Self::Import {
+                table: table.clone(),
+                file: file.into(),
+            },
+            ("export", [dir, tables @ ..]) => Self::Export {
+                dir: dir.into(),
+                tables: tables.to_vec(),
+            },
+            ("seed", [] | [_] | [_, _] | [_, _, _]) => Self::Seed {
+                seed: match args.first() {
+                    Some(seed) => seed
+                        .parse()
+                        .wrap_err_with(|| eyre!("SEED should be a number, got `{seed}`"))?,
+                    None => seed::DEFAULT_SEED,
+                },
+                students: match args.get(1) {
+                    Some(students) => students
+                        .parse()
+                        .wrap_err_with(|| eyre!("STUDENTS should be a number, got `{students}`"))?,
+                    None => seed::DEFAULT_STUDENTS,
+                },
+                date: match args.get(2) {
+                    Some(date) => date.parse().wrap_err_with(|| {
+                        eyre!("DATE should be a date like 2024-09-01, got `{date}`")
+                    })?,
+                    None => Local::now().date_naive(),
+                },
+            },
+            ("backup", [dir]) => Self::Backup { dir: dir.into() },
+            ("restore", [dir]) => Self::Restore { dir: dir.into() },
+            ("check-integrity", []) => Self::CheckIntegrity,
+            ("purge-expired", []) => Self::PurgeExpired,
+            ("help" | "--help" | "-h", _) => Self::Help,
+            _ => bail!("Unknown command `{command}` or wrong arguments\n\n{USAGE}"),
+        };
+
+        Ok(command)
+    }
+}
+
+/// Runs every command but [`Command::Serve`], which is left to `main`.
+pub async fn run(command: Command, db: &Database) -> Result<()> {
+    match command {
+        Command::Serve => bail!("`serve` is run by the binary"),
+        Command::Help => println!("{USAGE}"),
+        Command::Migrate => {
+            db.migrate().await?;
+            println!("Database is migrated");
+        }
+        Command::CreateLibrarian {
+            name,
+            lastname,
+            surname,
+            age,
+        } => {
+            let librarian = Librarian {
+                id: 0,
+                name,
+                lastname,
+                surname,
+                age,
+                branch: None,
+                version: 0,
+            };
+
+            let librarian = table::insert(db, librarian).await?;
+            println!("Added librarian {}", librarian.id);
+        }
+        Command::Import { table, file } => {
+            let table = table::find(&table)?;
+            let text = tokio::fs::read_to_string(&file)
+                .await
+                .wrap_err_with(|| eyre!("Unable to read {}", file.display()))?;
+
+            let imported = table.import(db, &text).await?;
+            println!("Imported {imported} rows into {}", table.name());
+        }
+        Command::Export { dir, tables } => {
+            let tables = match tables.is_empty() {
+                true => table::all().into_iter().collect(),
+                false => tables
+                    .iter()
+                    .map(|name| table::find(name))
+                    .collect::<Result<Vec<_>>>()?,
+            };
+
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .wrap_err_with(|| eyre!("Unable to create {}", dir.display()))?;
+
+            for table in tables {
+                let file = dir.join(format!("{}.csv", table.name()));
+                tokio::fs::write(&file, table.export(db).await?)
+                    .await
+                    .wrap_err_with(|| eyre!("Unable to write {}", file.display()))?;
+                println!("Exported {} to {}", table.name(), file.display());
+            }
+        }
+        Command::Seed {
+            seed,
+            students,
+            date,
+        } => {
+            for (table, count) in seed::seed(db, seed, students, date).await? {
+                println!("Added {count} rows to {table}");
+            }
+            println!("Dates are relative to {date}, pass it again to get the same rows");
+        }
+        Command::Backup { dir } => {
+            let manifest = backup::backup(db, &dir).await?;
+            for table in &manifest.tables {
+                println!("Backed up {} rows of {}", table.rows, table.table);
+            }
+            println!(
+                "Backup of schema {} is in {}",
+                manifest.schema_version,
+                dir.display()
+            );
+        }
+        Command::Restore { dir } => {
+            let manifest = backup::restore(db, &dir).await?;
+            for table in &manifest.tables {
+                println!("Restored {} rows of {}", table.rows, table.table);
+            }
+        }
+        Command::CheckIntegrity => {
+            let problems = check_integrity(db).await?;
+            for problem in &problems {
+                println!("{problem}");
+            }
+
+            if !problems.is_empty() {
+                bail!("Found {} integrity problems", problems.len());
+            }
+            println!("No integrity problems found");
+        }
+        Command::PurgeExpired => {
+            let Database::Postgres(db_pool) = db else {
+                bail!("`purge-expired` needs Postgres, holds and cards are expired by its jobs");
+            };
+
+            for job in [Job::HoldExpiry, Job::CardExpiry, Job::RetentionCleanup] {
+                match scheduler::run_job(db_pool, job, None).await? {
+                    Some(run) => println!("{}: {}", job.name(), run.message.unwrap_or_default()),
+                    None => println!("{}: running on another replica", job.name()),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Rows breaking the validation rules of their table, and books lent twice at the same time.
+///
+/// Rows written around the API, by hand or by older versions, are not checked otherwise.
+pub async fn check_integrity(db: &Database) -> Result<Vec<String>> {
+    let mut problems = Vec::new();
+    for table in table::all() {
+        problems.extend(table.check(db).await?);
+    }
+    problems.extend(table::books_lent_twice(db).await?);
+
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, NaiveDate};
+
+    use super::Command;
+
+    fn parse(args: &[&str]) -> color_eyre::Result<Command> {
+        Command::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn commands_are_parsed_from_the_arguments() {
+        assert_eq!(parse(&[]).unwrap(), Command::Serve);
+        assert_eq!(
+            parse(&["create-librarian", "Lesya", "Ukrainka", "Petrivna", "42"]).unwrap(),
+            Command::CreateLibrarian {
+                name: "Lesya".into(),
+                lastname: "Ukrainka".into(),
+                surname: "Petrivna".into(),
+                age: 42,
+            }
+        );
+        assert_eq!(
+            parse(&["export", "backup", "book", "author"]).unwrap(),
+            Command::Export {
+                dir: "backup".into(),
+                tables: vec!["book".into(), "author".into()],
+            }
+        );
+
+        assert_eq!(
+            parse(&["seed", "7"]).unwrap(),
+            Command::Seed {
+                seed: 7,
+                students: 100,
+                date: Local::now().date_naive(),
+            }
+        );
+        assert_eq!(
+            parse(&["seed", "7", "20", "2024-09-01"]).unwrap(),
+            Command::Seed {
+                seed: 7,
+                students: 20,
+                date: NaiveDate::from_ymd_opt(2024, 9, 1).unwrap(),
+            }
+        );
+
+        assert_eq!(
+            parse(&["restore", "backup"]).unwrap(),
+            Command::Restore {
+                dir: "backup".into()
+            }
+        );
+
+        assert!(parse(&["import", "book"]).is_err());
+        assert!(parse(&["backup"]).is_err());
+        assert!(parse(&["seed", "7", "20", "tomorrow"]).is_err());
+        assert!(parse(&["create-librarian", "Lesya", "Ukrainka", "Petrivna", "old"]).is_err());
+        assert!(parse(&["serve", "now"]).is_err());
+    }
+}
@@ -0,0 +1,740 @@
+//! Demo library for development, generated from a seed so the same seed gives the same rows.
+//!
+//! Dates are relative to a base date, today by default, so there are always loans due soon and
+//! overdue. The same seed and base date give the same rows.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use color_eyre::{eyre::bail, Result};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::database::Database;
+use crate::isbn;
+use crate::model::{
+    Author, AuthorBook, Book, BookStatus, Branch, CardState, Category, Classification, Country,
+    Curriculum, Faculty, FacultyCurriculum, Librarian, Publisher, Shelf, Student, StudentCard,
+    StudentStatus, StudentsBorrowing, Teacher, TeacherCard, TeacherStatus, TeachersBorrowing,
+};
+use crate::repository::sqlite::SqliteResource;
+
+use super::table;
+
+pub const DEFAULT_SEED: u64 = 1;
+pub const DEFAULT_STUDENTS: usize = 100;
+
+const COUNTRIES: [(&str, &str, [&str; 3]); 6] = [
+    (
+        "UA",
+        "Ukraine",
+        ["Staryi Lev", "A-BA-BA-HA-LA-MA-HA", "Folio"],
+    ),
+    ("PL", "Poland", ["Znak", "Wydawnictwo Literackie", "Czarne"]),
+    (
+        "GB",
+        "United Kingdom",
+        ["Penguin Books", "Faber and Faber", "Bloomsbury"],
+    ),
+    ("US", "United States", ["Random House", "Scribner", "Knopf"]),
+    ("DE", "Germany", ["Suhrkamp", "Rowohlt", "Hanser"]),
+    ("FR", "France", ["Gallimard", "Flammarion", "Actes Sud"]),
+];
+
+// Faculties with their letter and curricula.
+const FACULTIES: [(&str, &str, [&str; 3]); 6] = [
+    (
+        "Physics",
+        "PH",
+        ["Optics", "Astrophysics", "Nuclear Physics"],
+    ),
+    (
+        "Mathematics",
+        "MA",
+        ["Algebra", "Statistics", "Applied Mathematics"],
+    ),
+    (
+        "Philology",
+        "PL",
+        ["Ukrainian Literature", "Linguistics", "Translation"],
+    ),
+    (
+        "History",
+        "HI",
+        ["Archaeology", "Medieval History", "Modern History"],
+    ),
+    (
+        "Computer Science",
+        "CS",
+        ["Software Engineering", "Data Science", "Networks"],
+    ),
+    (
+        "Chemistry",
+        "CH",
+        ["Organic Chemistry", "Biochemistry", "Materials"],
+    ),
+];
+
+const CATEGORIES: [&str; 8] = [
+    "Fiction",
+    "Poetry",
+    "Science",
+    "History",
+    "Textbooks",
+    "Reference",
+    "Philosophy",
+    "Children",
+];
+
+// Dewey classes of the categories, in their order.
+const CATEGORY_CLASSES: [&str; 8] = ["813", "811", "500", "900", "370", "030", "100", "028.5"];
+
+const NAMES: [&str; 20] = [
+    "Taras",
+    "Lesya",
+    "Ivan",
+    "Olena",
+    "Mykola",
+    "Oksana",
+    "Andrii",
+    "Iryna",
+    "Dmytro",
+    "Sofiia",
+    "Bohdan",
+    "Kateryna",
+    "Yaroslav",
+    "Mariia",
+    "Oleksandr",
+    "Anna",
+    "Petro",
+    "Nataliia",
+    "Serhii",
+    "Yuliia",
+];
+
+const LASTNAMES: [&str; 16] = [
+    "Shevchenko",
+    "Kovalenko",
+    "Bondarenko",
+    "Tkachenko",
+    "Kravchenko",
+    "Melnyk",
+    "Boiko",
+    "Shevchuk",
+    "Koval",
+    "Polishchuk",
+    "Lysenko",
+    "Marchenko",
+    "Rudenko",
+    "Savchenko",
+    "Petrenko",
+    "Moroz",
+];
+
+const SURNAMES: [&str; 8] = [
+    "Ivanovych",
+    "Petrivna",
+    "Mykolaiovych",
+    "Andriivna",
+    "Oleksandrovych",
+    "Serhiivna",
+    "Bohdanovych",
+    "Dmytrivna",
+];
+
+const TITLE_WORDS: [&str; 16] = [
+    "Silent", "Forest", "Song", "Stone", "River", "Steppe", "Light", "Shadows", "Garden", "Winter",
+    "Letters", "Bridge", "Harvest", "Mountain", "Sea", "Memory",
+];
+
+const CONDITIONS: [BookStatus; 4] = [
+    BookStatus::Excellent,
+    BookStatus::Good,
+    BookStatus::Satisfactory,
+    BookStatus::Unsatisfactory,
+];
+
+/// Card a loan can be checked out on.
+struct Card {
+    id: i32,
+    issue_date: NaiveDate,
+}
+
+struct Seeder<'a> {
+    db: &'a Database,
+    rng: ChaCha8Rng,
+    today: NaiveDate,
+    counts: Vec<(&'static str, usize)>,
+}
+
+/// Fills an empty database with a library of `students` students, and teachers, librarians,
+/// books and loans in proportion, dated relative to `today`. Returns how many rows were added to
+/// each table.
+pub async fn seed(
+    db: &Database,
+    seed: u64,
+    students: usize,
+    today: NaiveDate,
+) -> Result<Vec<(&'static str, usize)>> {
+    if let Some(table) = table::first_with_rows(db).await? {
+        bail!("{table} already has rows, demo data is only added to an empty database");
+    }
+
+    let mut seeder = Seeder {
+        db,
+        rng: ChaCha8Rng::seed_from_u64(seed),
+        today,
+        counts: Vec::new(),
+    };
+
+    let countries = seeder.countries().await?;
+    let (faculties, faculty_curricula) = seeder.faculties(students).await?;
+    let branches = seeder.branches(&faculties).await?;
+    let student_cards = seeder.students(students, &faculty_curricula).await?;
+    let teacher_cards = seeder.teachers(students / 10 + 2, &faculties).await?;
+    let librarians = seeder.librarians(students / 50 + 2, &branches).await?;
+    let shelves = seeder.shelves(&branches).await?;
+    let books = seeder.books(students, &countries, &shelves).await?;
+    seeder
+        .loans(&books, &student_cards, &teacher_cards, &librarians)
+        .await?;
+
+    Ok(seeder.counts)
+}
+
+impl<'a> Seeder<'a> {
+    async fn insert<R: SqliteResource>(&mut self, row: R) -> Result<R> {
+        let inserted = table::insert(self.db, row).await?;
+
+        match self.counts.iter_mut().find(|(table, _)| *table == R::TABLE) {
+            Some((_, count)) => *count += 1,
+            None => self.counts.push((R::TABLE, 1)),
+        }
+
+        Ok(inserted)
+    }
+
+    fn person(&mut self) -> (String, String, String) {
+        (
+            NAMES.choose(&mut self.rng).unwrap().to_string(),
+            LASTNAMES.choose(&mut self.rng).unwrap().to_string(),
+            SURNAMES.choose(&mut self.rng).unwrap().to_string(),
+        )
+    }
+
+    fn date_before(&mut self, date: NaiveDate, max_days: i64) -> NaiveDate {
+        date - Duration::days(self.rng.gen_range(0..=max_days))
+    }
+
+    /// Codes of the countries, with one to three publishers each.
+    async fn countries(&mut self) -> Result<Vec<(String, Vec<i32>)>> {
+        let mut countries = Vec::new();
+
+        for (code, name, publishers) in COUNTRIES {
+            self.insert(Country {
+                code: code.to_string(),
+                name: name.to_string(),
+                version: 0,
+            })
+            .await?;
+
+            let count = self.rng.gen_range(1..=publishers.len());
+            let mut ids = Vec::new();
+            for name in &publishers[..count] {
+                let publisher = self
+                    .insert(Publisher {
+                        id: 0,
+                        name: name.to_string(),
+                        country: code.to_string(),
+                        version: 0,
+                    })
+                    .await?;
+                ids.push(publisher.id);
+            }
+
+            countries.push((code.to_string(), ids));
+        }
+
+        Ok(countries)
+    }
+
+    /// Faculties, one per fifty students, and the links to their curricula.
+    ///
+    /// Some curricula are also taught at another faculty.
+    async fn faculties(&mut self, students: usize) -> Result<(Vec<i32>, Vec<i32>)> {
+        let count = (students / 50).clamp(2, FACULTIES.len());
+        let mut faculties = Vec::new();
+        let mut curricula = Vec::new();
+
+        for (name, letter, curriculum_names) in &FACULTIES[..count] {
+            let faculty = self
+                .insert(Faculty {
+                    id: 0,
+                    name: name.to_string(),
+                    letter: letter.to_string(),
+                    version: 0,
+                })
+                .await?;
+            faculties.push(faculty.id);
+
+            for (i, curriculum_name) in curriculum_names.iter().enumerate() {
+                let curriculum = self
+                    .insert(Curriculum {
+                        id: 0,
+                        name: curriculum_name.to_string(),
+                        letter: format!("{letter}{}", i + 1),
+                        version: 0,
+                    })
+                    .await?;
+                curricula.push((faculty.id, curriculum.id));
+            }
+        }
+
+        let mut faculty_curricula = Vec::new();
+        for (faculty, curriculum) in curricula {
+            let mut taught_at = vec![faculty];
+            if self.rng.gen_bool(0.2) {
+                let other = *faculties.choose(&mut self.rng).unwrap();
+                if other != faculty {
+                    taught_at.push(other);
+                }
+            }
+
+            for faculty in taught_at {
+                let link = self
+                    .insert(FacultyCurriculum {
+                        id: 0,
+                        faculty,
+                        curriculum,
+                        version: 0,
+                    })
+                    .await?;
+                faculty_curricula.push(link.id);
+            }
+        }
+
+        Ok((faculties, faculty_curricula))
+    }
+
+    /// Students of the last six intakes with their cards, returns the active cards.
+    ///
+    /// Students of the two oldest intakes have graduated, a few others left.
+    async fn students(&mut self, count: usize, faculty_curricula: &[i32]) -> Result<Vec<Card>> {
+        // Studies start on the first of September.
+        let year = match self.today.month() >= 9 {
+            true => self.today.year(),
+            false => self.today.year() - 1,
+        };
+        let mut cards = Vec::new();
+
+        for i in 0..count {
+            let (name, lastname, surname) = self.person();
+            let years = self.rng.gen_range(0..6);
+            let start_study_date = NaiveDate::from_ymd_opt(year - years, 9, 1).unwrap();
+            let status = match (years, self.rng.gen_range(0..100)) {
+                (4.., _) => Some(StudentStatus::Graduated),
+                (_, 0..=4) => Some(StudentStatus::Expelled),
+                (_, 5..=7) => Some(StudentStatus::Moved),
+                _ => None,
+            };
+            let email = self.rng.gen_bool(0.8).then(|| {
+                format!(
+                    "{}.{}{i}@students.example.edu",
+                    name.to_lowercase(),
+                    lastname.to_lowercase()
+                )
+            });
+
+            let student = Student {
+                id: 0,
+                name,
+                lastname,
+                surname,
+                age: 17 + years as i16 + self.rng.gen_range(0..3),
+                faculty_curriculum: *faculty_curricula.choose(&mut self.rng).unwrap(),
+                group: self.rng.gen_range(1..=4),
+                start_study_date,
+                status,
+                email,
+                version: 0,
+            };
+            let student = self.insert(student).await?;
+
+            // Cards are valid for the four years of study.
+            let expiry_date = NaiveDate::from_ymd_opt(year - years + 4, 8, 31).unwrap();
+            let state = match status {
+                None => CardState::Active,
+                Some(StudentStatus::Graduated) => CardState::Expired,
+                Some(_) => CardState::Blocked,
+            };
+
+            let mut issue_date = start_study_date;
+            // Some active students lost their first card and got a new one.
+            if state == CardState::Active && self.rng.gen_bool(0.05) {
+                self.insert(StudentCard {
+                    id: 0,
+                    student: student.id,
+                    issue_date,
+                    expiry_date,
+                    state: CardState::Lost,
+                    version: 0,
+                })
+                .await?;
+                issue_date = self.date_before(self.today, (self.today - issue_date).num_days());
+            }
+
+            let card = self
+                .insert(StudentCard {
+                    id: 0,
+                    student: student.id,
+                    issue_date,
+                    expiry_date,
+                    state,
+                    version: 0,
+                })
+                .await?;
+            if state == CardState::Active {
+                cards.push(Card {
+                    id: card.id,
+                    issue_date,
+                });
+            }
+        }
+
+        Ok(cards)
+    }
+
+    /// Teachers with their cards, returns the active cards.
+    async fn teachers(&mut self, count: usize, faculties: &[i32]) -> Result<Vec<Card>> {
+        let mut cards = Vec::new();
+
+        for i in 0..count {
+            let (name, lastname, surname) = self.person();
+            let status = match self.rng.gen_range(0..100) {
+                0..=4 => Some(TeacherStatus::Fired),
+                5..=9 => Some(TeacherStatus::Moved),
+                _ => None,
+            };
+            let email = format!(
+                "{}.{}{i}@example.edu",
+                name.to_lowercase(),
+                lastname.to_lowercase()
+            );
+
+            let teacher = Teacher {
+                id: 0,
+                name,
+                lastname,
+                surname,
+                age: self.rng.gen_range(26..=70),
+                faculty: *faculties.choose(&mut self.rng).unwrap(),
+                status,
+                email: Some(email),
+                version: 0,
+            };
+            let teacher = self.insert(teacher).await?;
+
+            let issue_date = self.date_before(self.today, 4 * 365);
+            let state = match status {
+                None => CardState::Active,
+                Some(TeacherStatus::Fired) => CardState::Blocked,
+                Some(TeacherStatus::Moved) => CardState::Expired,
+            };
+            let card = self
+                .insert(TeacherCard {
+                    id: 0,
+                    teacher: teacher.id,
+                    issue_date,
+                    expiry_date: issue_date + Duration::days(5 * 365),
+                    state,
+                    version: 0,
+                })
+                .await?;
+            if state == CardState::Active {
+                cards.push(Card {
+                    id: card.id,
+                    issue_date,
+                });
+            }
+        }
+
+        Ok(cards)
+    }
+
+    /// The central library and the library of every faculty.
+    async fn branches(&mut self, faculties: &[i32]) -> Result<Vec<i32>> {
+        let mut branches = Vec::new();
+
+        let central = self
+            .insert(Branch {
+                id: 0,
+                name: "Central Library".to_string(),
+                faculty: None,
+                version: 0,
+            })
+            .await?;
+        branches.push(central.id);
+
+        for (faculty, (name, _, _)) in faculties.iter().zip(FACULTIES) {
+            let branch = self
+                .insert(Branch {
+                    id: 0,
+                    name: format!("{name} Library"),
+                    faculty: Some(*faculty),
+                    version: 0,
+                })
+                .await?;
+            branches.push(branch.id);
+        }
+
+        Ok(branches)
+    }
+
+    /// Two shelves of the reading room of every branch, with their branch.
+    async fn shelves(&mut self, branches: &[i32]) -> Result<Vec<(i32, i32)>> {
+        let mut shelves = Vec::new();
+
+        for branch in branches {
+            for name in ["A", "B"] {
+                let shelf = self
+                    .insert(Shelf {
+                        id: 0,
+                        branch: Some(*branch),
+                        room: "Reading Room".to_string(),
+                        name: name.to_string(),
+                        version: 0,
+                    })
+                    .await?;
+                shelves.push((shelf.id, *branch));
+            }
+        }
+
+        Ok(shelves)
+    }
+
+    /// Librarians with their branch, the first ones work at a branch each.
+    async fn librarians(&mut self, count: usize, branches: &[i32]) -> Result<Vec<(i32, i32)>> {
+        let mut librarians = Vec::new();
+
+        for i in 0..count {
+            let (name, lastname, surname) = self.person();
+            let branch = match branches.get(i) {
+                Some(branch) => *branch,
+                None => *branches.choose(&mut self.rng).unwrap(),
+            };
+            let librarian = Librarian {
+                id: 0,
+                name,
+                lastname,
+                surname,
+                age: self.rng.gen_range(20..=65),
+                branch: Some(branch),
+                version: 0,
+            };
+            let librarian = self.insert(librarian).await?;
+            librarians.push((librarian.id, branch));
+        }
+
+        Ok(librarians)
+    }
+
+    /// Books with one to three ordered authors of the country of their publisher, one per student.
+    async fn books(
+        &mut self,
+        count: usize,
+        countries: &[(String, Vec<i32>)],
+        shelves: &[(i32, i32)],
+    ) -> Result<Vec<Book>> {
+        let mut categories = Vec::new();
+        for name in CATEGORIES {
+            let category = self
+                .insert(Category {
+                    id: 0,
+                    name: name.to_string(),
+                    version: 0,
+                })
+                .await?;
+            categories.push(category.id);
+        }
+
+        let mut authors = Vec::new();
+        for _ in 0..(count / 4).max(countries.len()) {
+            let (name, lastname, surname) = self.person();
+            let (country, _) = countries.choose(&mut self.rng).unwrap();
+            let author = self
+                .insert(Author {
+                    id: 0,
+                    name,
+                    lastname,
+                    surname,
+                    country: country.clone(),
+                    version: 0,
+                })
+                .await?;
+            authors.push(author);
+        }
+
+        let mut books = Vec::new();
+        for i in 0..count.max(10) {
+            let (country, publishers) = countries.choose(&mut self.rng).unwrap();
+            let words = TITLE_WORDS
+                .choose_multiple(&mut self.rng, 2)
+                .copied()
+                .collect::<Vec<_>>();
+            // ISBNs of the Ukrainian group, numbered by book.
+            let isbn = format!("978966{:06}", i);
+            let category = self.rng.gen_range(0..categories.len());
+            let (shelf, branch) = *shelves.choose(&mut self.rng).unwrap();
+            // Class of the category with a book mark of the title.
+            let title = format!("{} of the {}", words[0], words[1]);
+            let call_number = format!(
+                "{} {}",
+                CATEGORY_CLASSES[category],
+                title[..3].to_uppercase()
+            );
+
+            let book = Book {
+                id: 0,
+                title,
+                release: self.date_before(self.today, 70 * 365),
+                publisher: *publishers.choose(&mut self.rng).unwrap(),
+                category: categories[category],
+                student_access: self.rng.gen_bool(0.85),
+                isbn: Some(format!("{isbn}{}", isbn::isbn13_check_digit(&isbn))),
+                branch: Some(branch),
+                classification: Some(Classification::Dewey),
+                call_number: Some(call_number),
+                call_number_sort: None,
+                shelf: Some(shelf),
+                version: 0,
+            };
+            let book = self.insert(book).await?;
+
+            let local = authors
+                .iter()
+                .filter(|author| author.country == *country)
+                .collect::<Vec<_>>();
+            let candidates = match local.is_empty() {
+                true => authors.iter().collect(),
+                false => local,
+            };
+            let count = self.rng.gen_range(1..=3).min(candidates.len());
+            for (num, author) in (1..).zip(candidates.choose_multiple(&mut self.rng, count)) {
+                self.insert(AuthorBook {
+                    id: 0,
+                    author_id: author.id,
+                    book_id: book.id,
+                    num,
+                    version: 0,
+                })
+                .await?;
+            }
+
+            books.push(book);
+        }
+
+        Ok(books)
+    }
+
+    /// Loans of the last year, one after another for each book.
+    ///
+    /// Most loans are returned in time, some late and some not yet, overdue or not.
+    async fn loans(
+        &mut self,
+        books: &[Book],
+        student_cards: &[Card],
+        teacher_cards: &[Card],
+        librarians: &[(i32, i32)],
+    ) -> Result<()> {
+        for book in books {
+            let mut condition = self.rng.gen_range(0..2);
+            let mut borrow_date = self.today - Duration::days(self.rng.gen_range(330..=365));
+
+            loop {
+                borrow_date += Duration::days(self.rng.gen_range(0..45));
+                if borrow_date > self.today {
+                    break;
+                }
+
+                let by_student = book.student_access && self.rng.gen_bool(0.8);
+                let cards = match by_student {
+                    true => student_cards,
+                    false => teacher_cards,
+                };
+                let cards = cards
+                    .iter()
+                    .filter(|card| card.issue_date <= borrow_date)
+                    .collect::<Vec<_>>();
+                let Some(card) = cards.choose(&mut self.rng) else {
+                    borrow_date += Duration::days(1);
+                    continue;
+                };
+
+                // A quarter of the loans are kept well past their return date.
+                let days = match self.rng.gen_bool(0.25) {
+                    true => self.rng.gen_range(20..=90),
+                    false => self.rng.gen_range(3..=14),
+                };
+                let return_date =
+                    Some(borrow_date + Duration::days(days)).filter(|date| *date <= self.today);
+                let book_status_start = CONDITIONS[condition];
+                if return_date.is_some() && condition < 3 && self.rng.gen_bool(0.1) {
+                    condition += 1;
+                }
+                let book_status_finish = return_date.map(|_| CONDITIONS[condition]);
+                // Lent by a librarian of the branch of the book, when it has one.
+                let local = librarians
+                    .iter()
+                    .filter(|(_, branch)| Some(*branch) == book.branch)
+                    .collect::<Vec<_>>();
+                let (librarian, _) = match local.choose(&mut self.rng) {
+                    Some(librarian) => **librarian,
+                    None => *librarians.choose(&mut self.rng).unwrap(),
+                };
+
+                match by_student {
+                    true => {
+                        self.insert(StudentsBorrowing {
+                            id: 0,
+                            student_card: card.id,
+                            librarian,
+                            book: book.id,
+                            book_status_start,
+                            book_status_finish,
+                            borrow_date,
+                            return_date,
+                            required_return_date: borrow_date + Duration::days(14),
+                            branch: book.branch,
+                            return_branch: return_date.and(book.branch),
+                            version: 0,
+                        })
+                        .await?;
+                    }
+                    false => {
+                        self.insert(TeachersBorrowing {
+                            id: 0,
+                            teacher_card: card.id,
+                            librarian,
+                            book: book.id,
+                            book_status_start,
+                            book_status_finish,
+                            borrow_date,
+                            return_date,
+                            branch: book.branch,
+                            return_branch: return_date.and(book.branch),
+                            version: 0,
+                        })
+                        .await?;
+                    }
+                }
+
+                match return_date {
+                    Some(return_date) => borrow_date = return_date + Duration::days(1),
+                    // The book is still lent.
+                    None => break,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
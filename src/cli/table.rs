@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use axum::async_trait;
+use color_eyre::{
+    eyre::{bail, eyre, Context},
+    Result,
+};
+use serde_json::Value;
+use validator::ValidationErrors;
+
+use crate::database::Database;
+use crate::model::{
+    Author, AuthorBook, Book, BookTransfer, Branch, Category, Country, Curriculum, Faculty,
+    FacultyCurriculum, Hold, Librarian, Publisher, Shelf, Student, StudentCard, StudentsBorrowing,
+    Teacher, TeacherCard, TeachersBorrowing,
+};
+use crate::repository::sqlite::SqliteResource;
+use crate::repository::{Shared, Storage};
+use crate::resource::label;
+use crate::web::resource::repository_error;
+
+use super::backup::{self, Connection};
+use super::csv;
+
+/// Table the commands import, export and check, over the same repositories as the HTTP routes.
+#[async_trait]
+pub trait Table: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Inserts the rows of the CSV `text`, returns how many were inserted.
+    ///
+    /// Every row is validated before the first one is inserted, and the rows are inserted in one
+    /// transaction, so a file is imported whole or not at all.
+    async fn import(&self, db: &Database, text: &str) -> Result<usize>;
+
+    /// Every row as CSV, with a header naming the columns.
+    async fn export(&self, db: &Database) -> Result<String>;
+
+    /// Rows breaking the validation rules of the table, one message per broken rule.
+    async fn check(&self, db: &Database) -> Result<Vec<String>>;
+
+    /// Whether the table has no rows.
+    async fn is_empty(&self, db: &Database) -> Result<bool>;
+
+    /// Every row as the API serializes it, ordered by key, read in the transaction of `conn`.
+    async fn dump(&self, conn: Connection<'_>) -> Result<Vec<Value>>;
+
+    /// Inserts rows of [`Table::dump`] with their keys and versions.
+    async fn restore(&self, conn: Connection<'_>, rows: Vec<Value>) -> Result<()>;
+}
+
+struct Rows<R>(PhantomData<fn() -> R>);
+
+/// Every table, referenced tables before the tables referencing them.
+pub fn all() -> [Box<dyn Table>; 20] {
+    [
+        table::<Country>(),
+        table::<Faculty>(),
+        table::<Branch>(),
+        table::<Shelf>(),
+        table::<Curriculum>(),
+        table::<FacultyCurriculum>(),
+        table::<Student>(),
+        table::<Teacher>(),
+        table::<Category>(),
+        table::<Publisher>(),
+        table::<Author>(),
+        table::<Book>(),
+        table::<AuthorBook>(),
+        table::<Librarian>(),
+        table::<StudentCard>(),
+        table::<TeacherCard>(),
+        table::<StudentsBorrowing>(),
+        table::<TeachersBorrowing>(),
+        table::<Hold>(),
+        table::<BookTransfer>(),
+    ]
+}
+
+/// First table with rows, the seeding and restore commands only fill empty databases.
+pub async fn first_with_rows(db: &Database) -> Result<Option<&'static str>> {
+    for table in all() {
+        if !table.is_empty(db).await? {
+            return Ok(Some(table.name()));
+        }
+    }
+
+    Ok(None)
+}
+
+pub fn find(name: &str) -> Result<Box<dyn Table>> {
+    match all().into_iter().find(|table| table.name() == name) {
+        Some(table) => Ok(table),
+        None => bail!("Unknown table {name}"),
+    }
+}
+
+fn table<R: SqliteResource>() -> Box<dyn Table> {
+    Box::new(Rows::<R>(PhantomData))
+}
+
+/// Repository of `R` in `db`, the one its HTTP routes are built over.
+pub fn repository<R: SqliteResource>(db: &Database) -> Shared<R> {
+    match db {
+        Database::Postgres(db_pool) => db_pool.repository(),
+        Database::Sqlite(db_pool) => db_pool.repository(),
+    }
+}
+
+async fn list<R: SqliteResource>(db: &Database) -> Result<Vec<R>> {
+    repository::<R>(db)
+        .list()
+        .await
+        .map_err(|err| eyre!(repository_error(err, &label(R::TABLE)).1))
+}
+
+/// Columns of the CSV files of `R`. Keys assigned by the database are exported but not imported.
+fn columns<R: SqliteResource>() -> Vec<&'static str> {
+    let mut columns = Vec::with_capacity(R::COLUMNS.len() + 2);
+    if !R::COLUMNS.contains(&R::KEY) {
+        columns.push(R::KEY);
+    }
+    columns.extend(R::COLUMNS);
+    columns.push("version");
+
+    columns
+}
+
+/// Messages of the rules broken by a row, like the fields of a `422` response.
+pub fn describe(errors: &ValidationErrors) -> Vec<String> {
+    let mut messages = errors
+        .field_errors()
+        .into_iter()
+        .flat_map(|(field, errors)| {
+            errors.iter().map(move |error| {
+                let message = error.message.as_deref().unwrap_or(&error.code);
+                match field {
+                    // Rules over several fields name them in their message.
+                    "__all__" => message.to_string(),
+                    field => format!("{field}: {message}"),
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    messages.sort();
+
+    messages
+}
+
+#[async_trait]
+impl<R: SqliteResource> Table for Rows<R> {
+    fn name(&self) -> &'static str {
+        R::TABLE
+    }
+
+    async fn import(&self, db: &Database, text: &str) -> Result<usize> {
+        let mut records = csv::parse(text)?.into_iter();
+        let Some(mut header) = records.next() else {
+            bail!("{} has no header", R::TABLE);
+        };
+        let fields = header.len();
+        // Inserted rows get a key from the database, the files of other systems may have none.
+        let generated_key = !R::COLUMNS.contains(&R::KEY) && !header.iter().any(|c| c == R::KEY);
+        if generated_key {
+            header.push(R::KEY.to_string());
+        }
+
+        let mut rows = Vec::new();
+        // Rows are counted after the header, fields may span several lines.
+        for (number, mut record) in (1..).zip(records) {
+            if record.len() != fields {
+                bail!(
+                    "Row {number}: expected {fields} fields, found {}",
+                    record.len()
+                );
+            }
+            if generated_key {
+                record.push("0".to_string());
+            }
+
+            let mut row = csv::deserialize::<R>(&header, &record)
+                .map_err(|err| eyre!("Row {number}: {err}"))?;
+            if let Err(errors) = row.validate() {
+                bail!("Row {number}: {}", describe(&errors).join(", "));
+            }
+            row.normalize()
+                .map_err(|(_, message)| eyre!("Row {number}: {message}"))?;
+
+            rows.push(row);
+        }
+
+        // Rows refused by the database roll back the whole file.
+        let inserted = repository::<R>(db)
+            .insert_all(&rows)
+            .await
+            .map_err(|(index, err)| {
+                let (_, message) = repository_error(err, &label(R::TABLE));
+                match index {
+                    Some(index) => eyre!("Row {}: {message}", index + 1),
+                    None => eyre!(message),
+                }
+            })?;
+
+        Ok(inserted.len())
+    }
+
+    async fn export(&self, db: &Database) -> Result<String> {
+        let rows = list::<R>(db).await?;
+
+        let columns = columns::<R>();
+        let mut text = String::new();
+        csv::write_record(&mut text, columns.iter().copied());
+
+        for row in rows {
+            let Value::Object(mut fields) = serde_json::to_value(&row)? else {
+                bail!("{} rows should serialize to objects", R::TABLE);
+            };
+            let fields = columns
+                .iter()
+                .map(|column| match fields.remove(*column) {
+                    Some(Value::String(text)) => text,
+                    Some(Value::Null) | None => String::new(),
+                    Some(value) => value.to_string(),
+                })
+                .collect::<Vec<_>>();
+
+            csv::write_record(&mut text, fields.iter().map(String::as_str));
+        }
+
+        Ok(text)
+    }
+
+    async fn check(&self, db: &Database) -> Result<Vec<String>> {
+        let rows = list::<R>(db).await?;
+
+        let mut problems = Vec::new();
+        for row in rows {
+            if let Err(errors) = row.validate() {
+                let key = match serde_json::to_value(&row)?.get(R::KEY) {
+                    Some(Value::String(key)) => key.clone(),
+                    Some(key) => key.to_string(),
+                    None => String::new(),
+                };
+                problems.extend(
+                    describe(&errors)
+                        .into_iter()
+                        .map(|message| format!("{} {key}: {message}", label(R::TABLE))),
+                );
+            }
+        }
+
+        Ok(problems)
+    }
+
+    async fn is_empty(&self, db: &Database) -> Result<bool> {
+        let rows = repository::<R>(db)
+            .find(&[], 0, Some(1))
+            .await
+            .map_err(|err| eyre!(repository_error(err, &label(R::TABLE)).1))?;
+
+        Ok(rows.is_empty())
+    }
+
+    async fn dump(&self, conn: Connection<'_>) -> Result<Vec<Value>> {
+        let rows = match conn {
+            Connection::Postgres(conn) => R::list(conn).await,
+            Connection::Sqlite(conn) => {
+                let query = format!(r#"SELECT * FROM {} ORDER BY "{}" ASC"#, R::TABLE, R::KEY);
+                sqlx::query_as::<_, R>(&query).fetch_all(conn).await
+            }
+        }
+        .wrap_err_with(|| eyre!("Unable to load {} rows from database", R::TABLE))?;
+
+        rows.iter()
+            .map(|row| Ok(serde_json::to_value(row)?))
+            .collect()
+    }
+
+    async fn restore(&self, conn: Connection<'_>, rows: Vec<Value>) -> Result<()> {
+        // Rows are read by the model, so fields missing from older backups get their defaults.
+        let rows = rows
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| {
+                serde_json::from_value::<R>(row)
+                    .map_err(|err| eyre!("Row {} of {}: {err}", i + 1, R::TABLE))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        match conn {
+            Connection::Postgres(conn) => {
+                for row in rows {
+                    backup::insert_postgres(conn, R::TABLE, serde_json::to_value(&row)?).await?;
+                }
+            }
+            Connection::Sqlite(conn) => {
+                let key_column = !R::COLUMNS.contains(&R::KEY);
+                let columns = columns::<R>();
+                let query = format!(
+                    "INSERT INTO {} ({}) VALUES ({}) RETURNING *",
+                    R::TABLE,
+                    columns
+                        .iter()
+                        .map(|column| format!(r#""{column}""#))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    vec!["?"; columns.len()].join(", ")
+                );
+
+                for row in rows {
+                    let key = match serde_json::to_value(&row)?.get(R::KEY) {
+                        Some(key) => serde_json::from_value::<R::Key>(key.clone())?,
+                        None => bail!("{} rows should have a {}", R::TABLE, R::KEY),
+                    };
+
+                    // Bound in the order of `columns`, the key, the columns of `R` and the version.
+                    let query = sqlx::query_as::<_, R>(&query);
+                    let query = match key_column {
+                        true => query.bind(&key),
+                        false => query,
+                    };
+                    row.bind(query)
+                        .bind(row.version())
+                        .fetch_one(&mut *conn)
+                        .await
+                        .wrap_err_with(|| eyre!("Unable to add {} to database", R::TABLE))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Inserts `row` with the rules of the HTTP routes, its validation, normalization and the
+/// checks of the repository.
+pub async fn insert<R: SqliteResource>(db: &Database, mut row: R) -> Result<R> {
+    if let Err(errors) = row.validate() {
+        bail!("{}", describe(&errors).join(", "));
+    }
+    row.normalize().map_err(|(_, message)| eyre!(message))?;
+
+    repository::<R>(db)
+        .insert(&row)
+        .await
+        .map_err(|err| eyre!(repository_error(err, &label(R::TABLE)).1))
+}
+
+/// Books lent more than once at the same time, across the loans of students and teachers.
+pub async fn books_lent_twice(db: &Database) -> Result<Vec<String>> {
+    let students = list::<StudentsBorrowing>(db).await?;
+    let teachers = list::<TeachersBorrowing>(db).await?;
+
+    let mut open = HashMap::<i32, usize>::new();
+    let open_loans = students
+        .iter()
+        .filter(|borrowing| borrowing.return_date.is_none())
+        .map(|borrowing| borrowing.book)
+        .chain(
+            teachers
+                .iter()
+                .filter(|borrowing| borrowing.return_date.is_none())
+                .map(|borrowing| borrowing.book),
+        );
+    for book in open_loans {
+        *open.entry(book).or_default() += 1;
+    }
+
+    let mut books = open
+        .into_iter()
+        .filter(|(_, loans)| *loans > 1)
+        .collect::<Vec<_>>();
+    books.sort();
+
+    Ok(books
+        .into_iter()
+        .map(|(book, loans)| format!("Book {book}: lent {loans} times without being returned"))
+        .collect())
+}
@@ -0,0 +1,78 @@
+use std::str::FromStr;
+
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use sqlx::migrate::Migrator;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Pool, Postgres, Sqlite};
+
+static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations_sqlite");
+
+/// Database of the server, chosen by the scheme of `DATABASE_URL`.
+///
+/// SQLite serves the CRUD routes of the tables, renewals, card reissue, book transfers and ISBN
+/// lookup. Notifications, webhooks, jobs, reports, MARC, fines and the change feed are built on
+/// Postgres and only served over it, like the renewal, notification and job run fields of GraphQL.
+/// Their integration tests are skipped over SQLite.
+#[derive(Clone)]
+pub enum Database {
+    Postgres(Pool<Postgres>),
+    Sqlite(Pool<Sqlite>),
+}
+
+impl Database {
+    /// Connects to `url`. SQLite databases are created if missing and migrated,
+    /// Postgres databases are migrated by `crud migrate`.
+    pub async fn connect(url: &str) -> Result<Self> {
+        if url.starts_with("sqlite:") {
+            let options = SqliteConnectOptions::from_str(url)
+                .wrap_err_with(|| eyre!("Env variable `DATABASE_URL` is not a valid SQLite URL"))?
+                .create_if_missing(true);
+
+            let db_pool = SqlitePoolOptions::new()
+                .max_connections(5)
+                .connect_with(options)
+                .await
+                .wrap_err_with(|| eyre!("Unable connect to database"))?;
+
+            SQLITE_MIGRATOR
+                .run(&db_pool)
+                .await
+                .wrap_err_with(|| eyre!("Unable to migrate database"))?;
+
+            return Ok(Self::Sqlite(db_pool));
+        }
+
+        let db_pool = PgPoolOptions::new()
+            .max_connections(5)
+            .connect(url)
+            .await
+            .wrap_err_with(|| eyre!("Unable connect to database"))?;
+
+        Ok(Self::Postgres(db_pool))
+    }
+
+    /// Applies the migrations not applied to the database yet.
+    pub async fn migrate(&self) -> Result<()> {
+        match self {
+            Self::Postgres(db_pool) => POSTGRES_MIGRATOR.run(db_pool).await,
+            Self::Sqlite(db_pool) => SQLITE_MIGRATOR.run(db_pool).await,
+        }
+        .wrap_err_with(|| eyre!("Unable to migrate database"))
+    }
+
+    /// Version of the schema of the models, the newest Postgres migration.
+    ///
+    /// The SQLite migrations follow the same schema, so backups of both carry this version.
+    pub fn schema_version() -> i64 {
+        POSTGRES_MIGRATOR
+            .iter()
+            .map(|migration| migration.version)
+            .max()
+            .unwrap_or_default()
+    }
+}
@@ -5,3 +5,29 @@ pub fn internal_error(err: color_eyre::Report) -> (StatusCode, String) {
 
     (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", err))
 }
+
+/// Like [`internal_error`], but reports unique and foreign key constraint violations as
+/// `409 Conflict`, the write clashes with other rows rather than failing.
+pub fn conflict_or_internal_error(err: color_eyre::Report) -> (StatusCode, String) {
+    let is_conflict = err
+        .downcast_ref::<sqlx::Error>()
+        .and_then(|err| err.as_database_error())
+        .is_some_and(|err| {
+            // `unique_violation` and `foreign_key_violation` in Postgres, `SQLITE_CONSTRAINT_UNIQUE`,
+            // `_PRIMARYKEY` and `_FOREIGNKEY` in SQLite.
+            let code = err.code();
+            matches!(
+                code.as_deref(),
+                Some("23505" | "23503" | "2067" | "1555" | "787")
+            )
+            // SQLite checks foreign keys of statements with `RETURNING` once they are done and
+            // reports failures with the generic error code.
+            || err.message() == "FOREIGN KEY constraint failed"
+        });
+
+    if is_conflict {
+        return (StatusCode::CONFLICT, format!("{:#}", err));
+    }
+
+    internal_error(err)
+}
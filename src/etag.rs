@@ -0,0 +1,46 @@
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::{header, request::Parts, HeaderName, StatusCode};
+
+/// `ETag` header carrying the `version` of a row.
+pub type ETag = [(HeaderName, String); 1];
+
+pub fn etag(version: i32) -> ETag {
+    [(header::ETAG, format!("\"{version}\""))]
+}
+
+/// Row version from the `If-Match` header, required by updates and deletes.
+pub struct IfMatch(pub i32);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let value = parts.headers.get(header::IF_MATCH).ok_or_else(|| {
+            (
+                StatusCode::PRECONDITION_REQUIRED,
+                "Header `If-Match` is required".to_string(),
+            )
+        })?;
+
+        value
+            .to_str()
+            .ok()
+            .and_then(|value| {
+                value
+                    .trim()
+                    .strip_prefix('"')?
+                    .strip_suffix('"')?
+                    .parse()
+                    .ok()
+            })
+            .map(IfMatch)
+            .ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    "Header `If-Match` should be an ETag returned by the server".to_string(),
+                )
+            })
+    }
+}
@@ -0,0 +1,115 @@
+//! Patron accounts, the fines charged on their cards and the money paid or waived.
+//!
+//! Entries name the card they were made on, a patron's balance adds up the entries of all their
+//! cards. The ledger is only kept in Postgres. Amounts are integer cents, so balances add up
+//! exactly.
+
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use sqlx::PgConnection;
+
+use crate::model::Balance;
+
+/// Column of the cards of `card_table` naming their patron, `student` or `teacher`.
+pub fn patron_column(card_table: &str) -> &'static str {
+    match card_table {
+        "teacher_card" => "teacher",
+        _ => "student",
+    }
+}
+
+/// Balance in cents of the patron holding the card `card` in `card_table`, the charges less the
+/// payments and waivers on every card of the patron, so a reissued card does not start afresh.
+///
+/// Only the entries up to the entry `until` are counted when it is set.
+pub async fn balance(
+    conn: &mut PgConnection,
+    card_table: &str,
+    card: i32,
+    until: Option<i32>,
+) -> Result<i64> {
+    // `card_table` is `student_card` or `teacher_card`, never taken from the request, and is
+    // also the column of the ledger naming the card.
+    let patron = patron_column(card_table);
+    let query = format!(
+        "SELECT COALESCE(SUM(CASE WHEN e.kind IN ('payment', 'waiver') THEN -e.amount ELSE e.amount END), 0)
+        FROM ledger_entry e JOIN {card_table} c ON c.id = e.{card_table}
+        WHERE c.{patron} = (SELECT {patron} FROM {card_table} WHERE id = $1)
+        AND ($2::INTEGER IS NULL OR e.id <= $2)"
+    );
+
+    sqlx::query_scalar::<_, i64>(&query)
+        .bind(card)
+        .bind(until)
+        .fetch_one(conn)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load ledger_entry rows from database"))
+}
+
+/// Balance of the patron of the card `card` in `card_table`, `None` when the card does not exist.
+pub async fn card_balance(
+    conn: &mut PgConnection,
+    card_table: &'static str,
+    card: i32,
+) -> Result<Option<Balance>> {
+    // `card_table` is `student_card` or `teacher_card`, never taken from the request.
+    let query = format!(
+        "SELECT {} FROM {card_table} WHERE id = $1",
+        patron_column(card_table)
+    );
+    let patron = sqlx::query_scalar::<_, i32>(&query)
+        .bind(card)
+        .fetch_optional(&mut *conn)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load {card_table} from database"))?;
+
+    let Some(patron) = patron else {
+        return Ok(None);
+    };
+
+    let balance = balance(&mut *conn, card_table, card, None).await?;
+    let max_balance = max_balance(&mut *conn, card_table).await?;
+
+    Ok(Some(Balance {
+        card_table: card_table.to_string(),
+        card,
+        patron,
+        balance,
+        max_balance,
+        blocked: max_balance.is_some_and(|max_balance| balance > i64::from(max_balance)),
+    }))
+}
+
+/// Balance above which the cards of `card_table` cannot borrow, `None` without a fine policy.
+pub async fn max_balance(conn: &mut PgConnection, card_table: &str) -> Result<Option<i32>> {
+    sqlx::query_scalar!(
+        r#"SELECT max_balance FROM fine_policy WHERE card_table = $1"#,
+        card_table
+    )
+    .fetch_optional(conn)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load fine_policy from database"))
+}
+
+/// Amount in cents as a decimal, `1250` is `12.50`.
+pub fn format_amount(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+
+    format!("{sign}{}.{:02}", cents / 100, cents % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_cents_as_decimals() {
+        assert_eq!(format_amount(0), "0.00");
+        assert_eq!(format_amount(5), "0.05");
+        assert_eq!(format_amount(1250), "12.50");
+        assert_eq!(format_amount(-40), "-0.40");
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::{Context, Error, Result};
+use color_eyre::eyre::{eyre, Context as _};
+use sqlx::{Pool, Postgres};
+
+use crate::error::internal_error;
+use crate::model::StudentsBorrowingRenewal;
+use crate::repository::{Condition, Shared, Value};
+use crate::resource::{label, Resource};
+use crate::web::resource::repository_error;
+
+use super::status_error;
+
+/// Value of a column rows are loaded by.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Column {
+    name: &'static str,
+    value: Value,
+}
+
+impl Column {
+    pub fn new(name: &'static str, value: Value) -> Self {
+        Self { name, value }
+    }
+}
+
+/// Loads the rows of `R` by the value of a column.
+///
+/// The loads of a request are batched into one [`crate::repository::Repository::find`]
+/// per column, so listing the cards of fifty students is a single query.
+pub struct Rows<R> {
+    repository: Shared<R>,
+}
+
+impl<R> Rows<R> {
+    pub fn new(repository: Shared<R>) -> Self {
+        Self { repository }
+    }
+}
+
+impl<R: Resource + Clone> Loader<Column> for Rows<R> {
+    type Value = Vec<R>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[Column]) -> Result<HashMap<Column, Vec<R>>> {
+        let mut columns = HashMap::<&'static str, Vec<Value>>::new();
+        for key in keys {
+            columns.entry(key.name).or_default().push(key.value.clone());
+        }
+
+        let mut loaded = HashMap::<_, Vec<R>>::new();
+        for (column, values) in columns {
+            // Rows are matched back to their key by the JSON of the column.
+            let keys = values
+                .iter()
+                .map(|value| (value.to_json().to_string(), value.clone()))
+                .collect::<HashMap<_, _>>();
+
+            let rows = self
+                .repository
+                .find(&[Condition { column, values }], 0, None)
+                .await
+                .map_err(|err| status_error(repository_error(err, &label(R::TABLE))))?;
+
+            for row in rows {
+                let json = serde_json::to_value(&row)
+                    .wrap_err_with(|| eyre!("Unable to serialize {}", R::TABLE))
+                    .map_err(|err| status_error(internal_error(err)))?;
+
+                if let Some(value) = keys.get(&json[column].to_string()) {
+                    loaded
+                        .entry(Column::new(column, value.clone()))
+                        .or_default()
+                        .push(row);
+                }
+            }
+        }
+
+        Ok(loaded)
+    }
+}
+
+/// Row of `R` whose `column` is `value`.
+pub async fn one<R: Resource + Clone>(
+    ctx: &Context<'_>,
+    column: &'static str,
+    value: Value,
+) -> Result<Option<R>> {
+    Ok(many(ctx, column, value).await?.into_iter().next())
+}
+
+/// Rows of `R` whose `column` is `value`, ordered by key.
+pub async fn many<R: Resource + Clone>(
+    ctx: &Context<'_>,
+    column: &'static str,
+    value: Value,
+) -> Result<Vec<R>> {
+    let loader = ctx.data::<DataLoader<Rows<R>>>()?;
+
+    Ok(loader
+        .load_one(Column::new(column, value))
+        .await?
+        .unwrap_or_default())
+}
+
+/// Loads the renewals of students borrowings by borrowing, renewals are only kept in Postgres.
+pub struct Renewals {
+    db: Pool<Postgres>,
+}
+
+impl Renewals {
+    pub fn new(db: Pool<Postgres>) -> Self {
+        Self { db }
+    }
+}
+
+impl Loader<i32> for Renewals {
+    type Value = Vec<StudentsBorrowingRenewal>;
+    type Error = Error;
+
+    async fn load(&self, borrowings: &[i32]) -> Result<HashMap<i32, Self::Value>> {
+        let renewals = sqlx::query_as!(
+            StudentsBorrowingRenewal,
+            r#"SELECT id, students_borrowing, librarian, renewal_date, previous_return_date, required_return_date
+            FROM students_borrowing_renewal WHERE students_borrowing = ANY($1) ORDER BY id ASC"#,
+            borrowings
+        )
+        .fetch_all(&self.db)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load students_borrowing_renewals from database"))
+        .map_err(|err| status_error(internal_error(err)))?;
+
+        let mut loaded = HashMap::<_, Vec<_>>::new();
+        for renewal in renewals {
+            loaded
+                .entry(renewal.students_borrowing)
+                .or_default()
+                .push(renewal);
+        }
+
+        Ok(loaded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::dataloader::DataLoader;
+    use chrono::NaiveDate;
+
+    use crate::model::{CardState, StudentCard};
+    use crate::repository::memory::MemoryDatabase;
+    use crate::repository::Value;
+
+    use super::{Column, Rows};
+
+    #[tokio::test]
+    async fn rows_are_grouped_by_the_loaded_column() {
+        let db = MemoryDatabase::default();
+        let repository = db.repository::<StudentCard>();
+
+        for (student, state) in [
+            (1, CardState::Lost),
+            (1, CardState::Active),
+            (2, CardState::Active),
+        ] {
+            repository
+                .insert(&StudentCard {
+                    id: 0,
+                    student,
+                    issue_date: NaiveDate::from_ymd_opt(2026, 9, 1).unwrap(),
+                    expiry_date: NaiveDate::from_ymd_opt(2027, 9, 1).unwrap(),
+                    state,
+                    version: 0,
+                })
+                .await
+                .unwrap();
+        }
+
+        let loader = DataLoader::new(Rows::new(repository), tokio::spawn);
+        let cards = loader
+            .load_many((1..=3).map(|student| Column::new("student", Value::Int(student))))
+            .await
+            .unwrap();
+
+        let ids = |student| {
+            cards[&Column::new("student", Value::Int(student))]
+                .iter()
+                .map(|card| card.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(1), [1, 2]);
+        assert_eq!(ids(2), [3]);
+        assert!(!cards.contains_key(&Column::new("student", Value::Int(3))));
+
+        let active = loader
+            .load_one(Column::new("state", Value::of_enum(&CardState::Active)))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(active.len(), 2);
+    }
+}
@@ -0,0 +1,103 @@
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{
+    Context, EmptySubscription, Error, ErrorExtensions, Result, Schema, SchemaBuilder,
+};
+use axum::http::StatusCode;
+use sqlx::{Pool, Postgres};
+
+use crate::cache::ListCaches;
+use crate::model::{
+    Author, AuthorBook, Book, BookTransfer, Branch, Category, Country, Curriculum, Faculty,
+    FacultyCurriculum, Hold, Librarian, Publisher, Shelf, Student, StudentCard, StudentsBorrowing,
+    Teacher, TeacherCard, TeachersBorrowing,
+};
+use crate::repository::sqlite::SqliteResource;
+use crate::repository::{Shared, Storage};
+
+use self::loader::{Renewals, Rows};
+use self::mutation::Mutation;
+use self::query::Query;
+
+mod loader;
+mod mutation;
+mod object;
+mod query;
+
+pub type LibrarySchema = Schema<Query, Mutation, EmptySubscription>;
+
+/// Deepest nesting of a query, relations can otherwise be followed back and forth forever.
+const MAX_DEPTH: usize = 12;
+
+/// Schema over the repositories of `storage`, writes invalidate the list caches of the REST routes.
+///
+/// Renewals, notifications and job runs are only kept in Postgres, `db` is `None` over SQLite
+/// and their fields fail.
+pub fn schema(
+    storage: &impl Storage,
+    caches: ListCaches,
+    db: Option<Pool<Postgres>>,
+) -> LibrarySchema {
+    let builder = Schema::build(Query, Mutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .data(caches);
+
+    let builder = [
+        register::<Student>,
+        register::<Faculty>,
+        register::<Curriculum>,
+        register::<FacultyCurriculum>,
+        register::<Teacher>,
+        register::<Book>,
+        register::<Category>,
+        register::<Author>,
+        register::<AuthorBook>,
+        register::<Librarian>,
+        register::<Branch>,
+        register::<Shelf>,
+        register::<Publisher>,
+        register::<Country>,
+        register::<StudentCard>,
+        register::<TeacherCard>,
+        register::<StudentsBorrowing>,
+        register::<TeachersBorrowing>,
+        register::<Hold>,
+        register::<BookTransfer>,
+    ]
+    .into_iter()
+    .fold(builder, |builder, register| register(builder, storage));
+
+    match db {
+        Some(db) => builder
+            .data(DataLoader::new(Renewals::new(db.clone()), tokio::spawn))
+            .data(db),
+        None => builder,
+    }
+    .finish()
+}
+
+/// Adds the repository of `R` and its loader to the data of the schema.
+fn register<R: SqliteResource + Clone>(
+    builder: SchemaBuilder<Query, Mutation, EmptySubscription>,
+    storage: &impl Storage,
+) -> SchemaBuilder<Query, Mutation, EmptySubscription> {
+    let repository: Shared<R> = storage.repository();
+
+    builder
+        .data(DataLoader::new(Rows::new(repository.clone()), tokio::spawn))
+        .data(repository)
+}
+
+/// Error of a REST response, with its status code in the `status` extension.
+fn status_error((status, message): (StatusCode, String)) -> Error {
+    Error::new(message).extend_with(|_, extensions| extensions.set("status", status.as_u16()))
+}
+
+/// Postgres database, for the tables only kept in Postgres.
+fn postgres<'a>(ctx: &Context<'a>) -> Result<&'a Pool<Postgres>> {
+    ctx.data_opt::<Pool<Postgres>>().ok_or_else(|| {
+        status_error((
+            StatusCode::NOT_IMPLEMENTED,
+            "Only served over Postgres".to_string(),
+        ))
+    })
+}
@@ -0,0 +1,498 @@
+use async_graphql::{Context, Error, ErrorExtensions, Object, Result};
+use axum::http::StatusCode;
+
+use crate::cache::ListCaches;
+use crate::model::{
+    Author, AuthorBook, Book, Branch, Category, Country, Curriculum, Faculty, FacultyCurriculum,
+    Hold, Librarian, Publisher, Shelf, Student, StudentCard, StudentsBorrowing, Teacher,
+    TeacherCard, TeachersBorrowing,
+};
+use crate::repository::Shared;
+use crate::resource::{label, Resource};
+use crate::web::resource::repository_error;
+
+use super::status_error;
+
+pub struct Mutation;
+
+/// Checks `row` with the rules of the REST routes, the validation of
+/// [`crate::validation::ValidatedJson`] and [`Resource::normalize`].
+///
+/// Failed rules are listed by field in the `fields` extension, like the body of a `422` response.
+fn validate<R: Resource>(row: &mut R) -> Result<()> {
+    if let Err(errors) = row.validate() {
+        let fields = async_graphql::Value::from_json(serde_json::to_value(&errors)?)?;
+
+        return Err(Error::new("Invalid input").extend_with(|_, extensions| {
+            extensions.set("status", StatusCode::UNPROCESSABLE_ENTITY.as_u16());
+            extensions.set("fields", fields.clone());
+        }));
+    }
+
+    row.normalize().map_err(status_error)
+}
+
+async fn create<R: Resource>(ctx: &Context<'_>, mut row: R) -> Result<R> {
+    validate(&mut row)?;
+
+    let inserted = ctx
+        .data::<Shared<R>>()?
+        .insert(&row)
+        .await
+        .map_err(|err| status_error(repository_error(err, &label(R::TABLE))))?;
+
+    ctx.data::<ListCaches>()?.invalidate(R::TABLE);
+
+    Ok(inserted)
+}
+
+async fn update<R: Resource>(
+    ctx: &Context<'_>,
+    key: R::Key,
+    version: i32,
+    mut row: R,
+) -> Result<R> {
+    validate(&mut row)?;
+
+    let updated = ctx
+        .data::<Shared<R>>()?
+        .update(&key, &row, version)
+        .await
+        .map_err(|err| {
+            status_error(repository_error(err, &format!("{} {key}", label(R::TABLE))))
+        })?;
+
+    ctx.data::<ListCaches>()?.invalidate(R::TABLE);
+
+    Ok(updated)
+}
+
+async fn delete<R: Resource>(ctx: &Context<'_>, key: R::Key, version: i32) -> Result<R> {
+    let deleted = ctx
+        .data::<Shared<R>>()?
+        .delete(&key, version)
+        .await
+        .map_err(|err| {
+            status_error(repository_error(err, &format!("{} {key}", label(R::TABLE))))
+        })?;
+
+    ctx.data::<ListCaches>()?.invalidate(R::TABLE);
+
+    Ok(deleted)
+}
+
+/// Writes of every table, `version` is the version the row is expected to be at, like `If-Match`.
+#[Object]
+impl Mutation {
+    async fn create_student(&self, ctx: &Context<'_>, input: Student) -> Result<Student> {
+        create(ctx, input).await
+    }
+
+    async fn update_student(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: Student,
+    ) -> Result<Student> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_student(&self, ctx: &Context<'_>, id: i32, version: i32) -> Result<Student> {
+        delete::<Student>(ctx, id, version).await
+    }
+
+    async fn create_faculty(&self, ctx: &Context<'_>, input: Faculty) -> Result<Faculty> {
+        create(ctx, input).await
+    }
+
+    async fn update_faculty(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: Faculty,
+    ) -> Result<Faculty> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_faculty(&self, ctx: &Context<'_>, id: i32, version: i32) -> Result<Faculty> {
+        delete::<Faculty>(ctx, id, version).await
+    }
+
+    async fn create_curriculum(&self, ctx: &Context<'_>, input: Curriculum) -> Result<Curriculum> {
+        create(ctx, input).await
+    }
+
+    async fn update_curriculum(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: Curriculum,
+    ) -> Result<Curriculum> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_curriculum(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+    ) -> Result<Curriculum> {
+        delete::<Curriculum>(ctx, id, version).await
+    }
+
+    async fn create_faculty_curriculum(
+        &self,
+        ctx: &Context<'_>,
+        input: FacultyCurriculum,
+    ) -> Result<FacultyCurriculum> {
+        create(ctx, input).await
+    }
+
+    async fn update_faculty_curriculum(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: FacultyCurriculum,
+    ) -> Result<FacultyCurriculum> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_faculty_curriculum(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+    ) -> Result<FacultyCurriculum> {
+        delete::<FacultyCurriculum>(ctx, id, version).await
+    }
+
+    async fn create_teacher(&self, ctx: &Context<'_>, input: Teacher) -> Result<Teacher> {
+        create(ctx, input).await
+    }
+
+    async fn update_teacher(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: Teacher,
+    ) -> Result<Teacher> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_teacher(&self, ctx: &Context<'_>, id: i32, version: i32) -> Result<Teacher> {
+        delete::<Teacher>(ctx, id, version).await
+    }
+
+    async fn create_book(&self, ctx: &Context<'_>, input: Book) -> Result<Book> {
+        create(ctx, input).await
+    }
+
+    async fn update_book(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: Book,
+    ) -> Result<Book> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_book(&self, ctx: &Context<'_>, id: i32, version: i32) -> Result<Book> {
+        delete::<Book>(ctx, id, version).await
+    }
+
+    async fn create_category(&self, ctx: &Context<'_>, input: Category) -> Result<Category> {
+        create(ctx, input).await
+    }
+
+    async fn update_category(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: Category,
+    ) -> Result<Category> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_category(&self, ctx: &Context<'_>, id: i32, version: i32) -> Result<Category> {
+        delete::<Category>(ctx, id, version).await
+    }
+
+    async fn create_author(&self, ctx: &Context<'_>, input: Author) -> Result<Author> {
+        create(ctx, input).await
+    }
+
+    async fn update_author(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: Author,
+    ) -> Result<Author> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_author(&self, ctx: &Context<'_>, id: i32, version: i32) -> Result<Author> {
+        delete::<Author>(ctx, id, version).await
+    }
+
+    async fn create_author_book(&self, ctx: &Context<'_>, input: AuthorBook) -> Result<AuthorBook> {
+        create(ctx, input).await
+    }
+
+    async fn update_author_book(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: AuthorBook,
+    ) -> Result<AuthorBook> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_author_book(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+    ) -> Result<AuthorBook> {
+        delete::<AuthorBook>(ctx, id, version).await
+    }
+
+    async fn create_librarian(&self, ctx: &Context<'_>, input: Librarian) -> Result<Librarian> {
+        create(ctx, input).await
+    }
+
+    async fn update_librarian(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: Librarian,
+    ) -> Result<Librarian> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_librarian(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+    ) -> Result<Librarian> {
+        delete::<Librarian>(ctx, id, version).await
+    }
+
+    async fn create_branch(&self, ctx: &Context<'_>, input: Branch) -> Result<Branch> {
+        create(ctx, input).await
+    }
+
+    async fn update_branch(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: Branch,
+    ) -> Result<Branch> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_branch(&self, ctx: &Context<'_>, id: i32, version: i32) -> Result<Branch> {
+        delete::<Branch>(ctx, id, version).await
+    }
+
+    async fn create_shelf(&self, ctx: &Context<'_>, input: Shelf) -> Result<Shelf> {
+        create(ctx, input).await
+    }
+
+    async fn update_shelf(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: Shelf,
+    ) -> Result<Shelf> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_shelf(&self, ctx: &Context<'_>, id: i32, version: i32) -> Result<Shelf> {
+        delete::<Shelf>(ctx, id, version).await
+    }
+
+    async fn create_publisher(&self, ctx: &Context<'_>, input: Publisher) -> Result<Publisher> {
+        create(ctx, input).await
+    }
+
+    async fn update_publisher(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: Publisher,
+    ) -> Result<Publisher> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_publisher(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+    ) -> Result<Publisher> {
+        delete::<Publisher>(ctx, id, version).await
+    }
+
+    async fn create_country(&self, ctx: &Context<'_>, input: Country) -> Result<Country> {
+        create(ctx, input).await
+    }
+
+    async fn update_country(
+        &self,
+        ctx: &Context<'_>,
+        code: String,
+        version: i32,
+        input: Country,
+    ) -> Result<Country> {
+        update(ctx, code, version, input).await
+    }
+
+    async fn delete_country(
+        &self,
+        ctx: &Context<'_>,
+        code: String,
+        version: i32,
+    ) -> Result<Country> {
+        delete::<Country>(ctx, code, version).await
+    }
+
+    async fn create_student_card(
+        &self,
+        ctx: &Context<'_>,
+        input: StudentCard,
+    ) -> Result<StudentCard> {
+        create(ctx, input).await
+    }
+
+    async fn update_student_card(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: StudentCard,
+    ) -> Result<StudentCard> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_student_card(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+    ) -> Result<StudentCard> {
+        delete::<StudentCard>(ctx, id, version).await
+    }
+
+    async fn create_teacher_card(
+        &self,
+        ctx: &Context<'_>,
+        input: TeacherCard,
+    ) -> Result<TeacherCard> {
+        create(ctx, input).await
+    }
+
+    async fn update_teacher_card(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: TeacherCard,
+    ) -> Result<TeacherCard> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_teacher_card(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+    ) -> Result<TeacherCard> {
+        delete::<TeacherCard>(ctx, id, version).await
+    }
+
+    async fn create_students_borrowing(
+        &self,
+        ctx: &Context<'_>,
+        input: StudentsBorrowing,
+    ) -> Result<StudentsBorrowing> {
+        create(ctx, input).await
+    }
+
+    async fn update_students_borrowing(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: StudentsBorrowing,
+    ) -> Result<StudentsBorrowing> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_students_borrowing(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+    ) -> Result<StudentsBorrowing> {
+        delete::<StudentsBorrowing>(ctx, id, version).await
+    }
+
+    async fn create_teachers_borrowing(
+        &self,
+        ctx: &Context<'_>,
+        input: TeachersBorrowing,
+    ) -> Result<TeachersBorrowing> {
+        create(ctx, input).await
+    }
+
+    async fn update_teachers_borrowing(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: TeachersBorrowing,
+    ) -> Result<TeachersBorrowing> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_teachers_borrowing(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+    ) -> Result<TeachersBorrowing> {
+        delete::<TeachersBorrowing>(ctx, id, version).await
+    }
+
+    async fn create_hold(&self, ctx: &Context<'_>, input: Hold) -> Result<Hold> {
+        create(ctx, input).await
+    }
+
+    async fn update_hold(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: i32,
+        input: Hold,
+    ) -> Result<Hold> {
+        update(ctx, id, version, input).await
+    }
+
+    async fn delete_hold(&self, ctx: &Context<'_>, id: i32, version: i32) -> Result<Hold> {
+        delete::<Hold>(ctx, id, version).await
+    }
+}
@@ -0,0 +1,567 @@
+//! Relations of the model types, loaded through the batching loaders of [`super::loader`].
+
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{ComplexObject, Context, Result};
+use axum::http::StatusCode;
+use color_eyre::eyre::{eyre, Context as _};
+
+use crate::error::internal_error;
+use crate::fine;
+use crate::model::{
+    Author, AuthorBook, Balance, Book, BookTransfer, Branch, Category, Country, Curriculum,
+    Faculty, FacultyCurriculum, Hold, LedgerEntry, Librarian, Notification, Publisher, Shelf,
+    Student, StudentCard, StudentsBorrowing, StudentsBorrowingRenewal, Teacher, TeacherCard,
+    TeachersBorrowing,
+};
+use crate::repository::Value;
+use crate::resource::label;
+
+use super::loader::{many, one, Column, Renewals, Rows};
+use super::{postgres, status_error};
+
+#[ComplexObject]
+impl Student {
+    #[graphql(name = "facultyCurriculum")]
+    async fn load_faculty_curriculum(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<FacultyCurriculum>> {
+        one(ctx, "id", Value::Int(self.faculty_curriculum)).await
+    }
+
+    async fn cards(&self, ctx: &Context<'_>) -> Result<Vec<StudentCard>> {
+        many(ctx, "student", Value::Int(self.id)).await
+    }
+}
+
+#[ComplexObject]
+impl Faculty {
+    async fn faculty_curricula(&self, ctx: &Context<'_>) -> Result<Vec<FacultyCurriculum>> {
+        many(ctx, "faculty", Value::Int(self.id)).await
+    }
+
+    async fn teachers(&self, ctx: &Context<'_>) -> Result<Vec<Teacher>> {
+        many(ctx, "faculty", Value::Int(self.id)).await
+    }
+
+    async fn branches(&self, ctx: &Context<'_>) -> Result<Vec<Branch>> {
+        many(ctx, "faculty", Value::Int(self.id)).await
+    }
+}
+
+#[ComplexObject]
+impl Curriculum {
+    async fn faculty_curricula(&self, ctx: &Context<'_>) -> Result<Vec<FacultyCurriculum>> {
+        many(ctx, "curriculum", Value::Int(self.id)).await
+    }
+}
+
+#[ComplexObject]
+impl FacultyCurriculum {
+    #[graphql(name = "faculty")]
+    async fn load_faculty(&self, ctx: &Context<'_>) -> Result<Option<Faculty>> {
+        one(ctx, "id", Value::Int(self.faculty)).await
+    }
+
+    #[graphql(name = "curriculum")]
+    async fn load_curriculum(&self, ctx: &Context<'_>) -> Result<Option<Curriculum>> {
+        one(ctx, "id", Value::Int(self.curriculum)).await
+    }
+
+    async fn students(&self, ctx: &Context<'_>) -> Result<Vec<Student>> {
+        many(ctx, "faculty_curriculum", Value::Int(self.id)).await
+    }
+}
+
+#[ComplexObject]
+impl Teacher {
+    #[graphql(name = "faculty")]
+    async fn load_faculty(&self, ctx: &Context<'_>) -> Result<Option<Faculty>> {
+        one(ctx, "id", Value::Int(self.faculty)).await
+    }
+
+    async fn cards(&self, ctx: &Context<'_>) -> Result<Vec<TeacherCard>> {
+        many(ctx, "teacher", Value::Int(self.id)).await
+    }
+}
+
+#[ComplexObject]
+impl Book {
+    #[graphql(name = "publisher")]
+    async fn load_publisher(&self, ctx: &Context<'_>) -> Result<Option<Publisher>> {
+        one(ctx, "id", Value::Int(self.publisher)).await
+    }
+
+    #[graphql(name = "category")]
+    async fn load_category(&self, ctx: &Context<'_>) -> Result<Option<Category>> {
+        one(ctx, "id", Value::Int(self.category)).await
+    }
+
+    /// Authors in the order of their `num`.
+    async fn authors(&self, ctx: &Context<'_>) -> Result<Vec<Author>> {
+        let mut author_books = many::<AuthorBook>(ctx, "book_id", Value::Int(self.id)).await?;
+        author_books.sort_by_key(|author_book| author_book.num);
+
+        let authors = ctx
+            .data::<DataLoader<Rows<Author>>>()?
+            .load_many(
+                author_books
+                    .iter()
+                    .map(|author_book| Column::new("id", Value::Int(author_book.author_id))),
+            )
+            .await?;
+
+        Ok(author_books
+            .iter()
+            .filter_map(|author_book| {
+                authors.get(&Column::new("id", Value::Int(author_book.author_id)))
+            })
+            .flatten()
+            .cloned()
+            .collect())
+    }
+
+    async fn students_borrowings(&self, ctx: &Context<'_>) -> Result<Vec<StudentsBorrowing>> {
+        many(ctx, "book", Value::Int(self.id)).await
+    }
+
+    async fn teachers_borrowings(&self, ctx: &Context<'_>) -> Result<Vec<TeachersBorrowing>> {
+        many(ctx, "book", Value::Int(self.id)).await
+    }
+
+    async fn holds(&self, ctx: &Context<'_>) -> Result<Vec<Hold>> {
+        many(ctx, "book", Value::Int(self.id)).await
+    }
+
+    #[graphql(name = "branch")]
+    async fn load_branch(&self, ctx: &Context<'_>) -> Result<Option<Branch>> {
+        match self.branch {
+            Some(branch) => one(ctx, "id", Value::Int(branch)).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn transfers(&self, ctx: &Context<'_>) -> Result<Vec<BookTransfer>> {
+        many(ctx, "book", Value::Int(self.id)).await
+    }
+
+    #[graphql(name = "shelf")]
+    async fn load_shelf(&self, ctx: &Context<'_>) -> Result<Option<Shelf>> {
+        match self.shelf {
+            Some(shelf) => one(ctx, "id", Value::Int(shelf)).await,
+            None => Ok(None),
+        }
+    }
+}
+
+#[ComplexObject]
+impl Category {
+    async fn books(&self, ctx: &Context<'_>) -> Result<Vec<Book>> {
+        many(ctx, "category", Value::Int(self.id)).await
+    }
+}
+
+#[ComplexObject]
+impl Author {
+    #[graphql(name = "country")]
+    async fn load_country(&self, ctx: &Context<'_>) -> Result<Option<Country>> {
+        one(ctx, "code", Value::Text(self.country.clone())).await
+    }
+
+    async fn books(&self, ctx: &Context<'_>) -> Result<Vec<Book>> {
+        let author_books = many::<AuthorBook>(ctx, "author_id", Value::Int(self.id)).await?;
+
+        let books = ctx
+            .data::<DataLoader<Rows<Book>>>()?
+            .load_many(
+                author_books
+                    .iter()
+                    .map(|author_book| Column::new("id", Value::Int(author_book.book_id))),
+            )
+            .await?;
+
+        Ok(author_books
+            .iter()
+            .filter_map(|author_book| {
+                books.get(&Column::new("id", Value::Int(author_book.book_id)))
+            })
+            .flatten()
+            .cloned()
+            .collect())
+    }
+}
+
+#[ComplexObject]
+impl AuthorBook {
+    #[graphql(name = "author")]
+    async fn load_author(&self, ctx: &Context<'_>) -> Result<Option<Author>> {
+        one(ctx, "id", Value::Int(self.author_id)).await
+    }
+
+    #[graphql(name = "book")]
+    async fn load_book(&self, ctx: &Context<'_>) -> Result<Option<Book>> {
+        one(ctx, "id", Value::Int(self.book_id)).await
+    }
+}
+
+#[ComplexObject]
+impl Librarian {
+    #[graphql(name = "branch")]
+    async fn load_branch(&self, ctx: &Context<'_>) -> Result<Option<Branch>> {
+        match self.branch {
+            Some(branch) => one(ctx, "id", Value::Int(branch)).await,
+            None => Ok(None),
+        }
+    }
+}
+
+#[ComplexObject]
+impl Branch {
+    #[graphql(name = "faculty")]
+    async fn load_faculty(&self, ctx: &Context<'_>) -> Result<Option<Faculty>> {
+        match self.faculty {
+            Some(faculty) => one(ctx, "id", Value::Int(faculty)).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn librarians(&self, ctx: &Context<'_>) -> Result<Vec<Librarian>> {
+        many(ctx, "branch", Value::Int(self.id)).await
+    }
+
+    async fn books(&self, ctx: &Context<'_>) -> Result<Vec<Book>> {
+        many(ctx, "branch", Value::Int(self.id)).await
+    }
+
+    /// Transfers on their way to this branch or received by it.
+    async fn incoming_transfers(&self, ctx: &Context<'_>) -> Result<Vec<BookTransfer>> {
+        many(ctx, "to_branch", Value::Int(self.id)).await
+    }
+
+    async fn outgoing_transfers(&self, ctx: &Context<'_>) -> Result<Vec<BookTransfer>> {
+        many(ctx, "from_branch", Value::Int(self.id)).await
+    }
+
+    async fn shelves(&self, ctx: &Context<'_>) -> Result<Vec<Shelf>> {
+        many(ctx, "branch", Value::Int(self.id)).await
+    }
+}
+
+#[ComplexObject]
+impl Shelf {
+    #[graphql(name = "branch")]
+    async fn load_branch(&self, ctx: &Context<'_>) -> Result<Option<Branch>> {
+        match self.branch {
+            Some(branch) => one(ctx, "id", Value::Int(branch)).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Books in the order of their call numbers, books without one last.
+    async fn books(&self, ctx: &Context<'_>) -> Result<Vec<Book>> {
+        let mut books = many::<Book>(ctx, "shelf", Value::Int(self.id)).await?;
+        books.sort_by(|a, b| {
+            let key = |book: &Book| {
+                (
+                    book.call_number_sort.is_none(),
+                    book.call_number_sort.clone(),
+                )
+            };
+            key(a).cmp(&key(b))
+        });
+
+        Ok(books)
+    }
+}
+
+#[ComplexObject]
+impl Publisher {
+    #[graphql(name = "country")]
+    async fn load_country(&self, ctx: &Context<'_>) -> Result<Option<Country>> {
+        one(ctx, "code", Value::Text(self.country.clone())).await
+    }
+
+    async fn books(&self, ctx: &Context<'_>) -> Result<Vec<Book>> {
+        many(ctx, "publisher", Value::Int(self.id)).await
+    }
+}
+
+#[ComplexObject]
+impl Country {
+    async fn publishers(&self, ctx: &Context<'_>) -> Result<Vec<Publisher>> {
+        many(ctx, "country", Value::Text(self.code.clone())).await
+    }
+
+    async fn authors(&self, ctx: &Context<'_>) -> Result<Vec<Author>> {
+        many(ctx, "country", Value::Text(self.code.clone())).await
+    }
+}
+
+#[ComplexObject]
+impl StudentCard {
+    #[graphql(name = "student")]
+    async fn load_student(&self, ctx: &Context<'_>) -> Result<Option<Student>> {
+        one(ctx, "id", Value::Int(self.student)).await
+    }
+
+    async fn borrowings(&self, ctx: &Context<'_>) -> Result<Vec<StudentsBorrowing>> {
+        many(ctx, "student_card", Value::Int(self.id)).await
+    }
+
+    async fn holds(&self, ctx: &Context<'_>) -> Result<Vec<Hold>> {
+        many(ctx, "student_card", Value::Int(self.id)).await
+    }
+
+    /// Balance of the student across all their cards, only served over Postgres.
+    async fn balance(&self, ctx: &Context<'_>) -> Result<Balance> {
+        card_balance(ctx, "student_card", self.id).await
+    }
+}
+
+#[ComplexObject]
+impl TeacherCard {
+    #[graphql(name = "teacher")]
+    async fn load_teacher(&self, ctx: &Context<'_>) -> Result<Option<Teacher>> {
+        one(ctx, "id", Value::Int(self.teacher)).await
+    }
+
+    async fn borrowings(&self, ctx: &Context<'_>) -> Result<Vec<TeachersBorrowing>> {
+        many(ctx, "teacher_card", Value::Int(self.id)).await
+    }
+
+    async fn holds(&self, ctx: &Context<'_>) -> Result<Vec<Hold>> {
+        many(ctx, "teacher_card", Value::Int(self.id)).await
+    }
+
+    /// Balance of the teacher across all their cards, only served over Postgres.
+    async fn balance(&self, ctx: &Context<'_>) -> Result<Balance> {
+        card_balance(ctx, "teacher_card", self.id).await
+    }
+}
+
+#[ComplexObject]
+impl StudentsBorrowing {
+    #[graphql(name = "studentCard")]
+    async fn load_student_card(&self, ctx: &Context<'_>) -> Result<Option<StudentCard>> {
+        one(ctx, "id", Value::Int(self.student_card)).await
+    }
+
+    #[graphql(name = "librarian")]
+    async fn load_librarian(&self, ctx: &Context<'_>) -> Result<Option<Librarian>> {
+        one(ctx, "id", Value::Int(self.librarian)).await
+    }
+
+    #[graphql(name = "book")]
+    async fn load_book(&self, ctx: &Context<'_>) -> Result<Option<Book>> {
+        one(ctx, "id", Value::Int(self.book)).await
+    }
+
+    #[graphql(name = "branch")]
+    async fn load_branch(&self, ctx: &Context<'_>) -> Result<Option<Branch>> {
+        match self.branch {
+            Some(branch) => one(ctx, "id", Value::Int(branch)).await,
+            None => Ok(None),
+        }
+    }
+
+    #[graphql(name = "returnBranch")]
+    async fn load_return_branch(&self, ctx: &Context<'_>) -> Result<Option<Branch>> {
+        match self.return_branch {
+            Some(branch) => one(ctx, "id", Value::Int(branch)).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Renewals in the order they were made, only served over Postgres.
+    async fn renewals(&self, ctx: &Context<'_>) -> Result<Vec<StudentsBorrowingRenewal>> {
+        postgres(ctx)?;
+
+        Ok(ctx
+            .data::<DataLoader<Renewals>>()?
+            .load_one(self.id)
+            .await?
+            .unwrap_or_default())
+    }
+}
+
+#[ComplexObject]
+impl TeachersBorrowing {
+    #[graphql(name = "teacherCard")]
+    async fn load_teacher_card(&self, ctx: &Context<'_>) -> Result<Option<TeacherCard>> {
+        one(ctx, "id", Value::Int(self.teacher_card)).await
+    }
+
+    #[graphql(name = "librarian")]
+    async fn load_librarian(&self, ctx: &Context<'_>) -> Result<Option<Librarian>> {
+        one(ctx, "id", Value::Int(self.librarian)).await
+    }
+
+    #[graphql(name = "book")]
+    async fn load_book(&self, ctx: &Context<'_>) -> Result<Option<Book>> {
+        one(ctx, "id", Value::Int(self.book)).await
+    }
+
+    #[graphql(name = "branch")]
+    async fn load_branch(&self, ctx: &Context<'_>) -> Result<Option<Branch>> {
+        match self.branch {
+            Some(branch) => one(ctx, "id", Value::Int(branch)).await,
+            None => Ok(None),
+        }
+    }
+
+    #[graphql(name = "returnBranch")]
+    async fn load_return_branch(&self, ctx: &Context<'_>) -> Result<Option<Branch>> {
+        match self.return_branch {
+            Some(branch) => one(ctx, "id", Value::Int(branch)).await,
+            None => Ok(None),
+        }
+    }
+}
+
+#[ComplexObject]
+impl Hold {
+    #[graphql(name = "book")]
+    async fn load_book(&self, ctx: &Context<'_>) -> Result<Option<Book>> {
+        one(ctx, "id", Value::Int(self.book)).await
+    }
+
+    #[graphql(name = "studentCard")]
+    async fn load_student_card(&self, ctx: &Context<'_>) -> Result<Option<StudentCard>> {
+        match self.student_card {
+            Some(card) => one(ctx, "id", Value::Int(card)).await,
+            None => Ok(None),
+        }
+    }
+
+    #[graphql(name = "teacherCard")]
+    async fn load_teacher_card(&self, ctx: &Context<'_>) -> Result<Option<TeacherCard>> {
+        match self.teacher_card {
+            Some(card) => one(ctx, "id", Value::Int(card)).await,
+            None => Ok(None),
+        }
+    }
+}
+
+#[ComplexObject]
+impl BookTransfer {
+    #[graphql(name = "book")]
+    async fn load_book(&self, ctx: &Context<'_>) -> Result<Option<Book>> {
+        one(ctx, "id", Value::Int(self.book)).await
+    }
+
+    #[graphql(name = "fromBranch")]
+    async fn load_from_branch(&self, ctx: &Context<'_>) -> Result<Option<Branch>> {
+        one(ctx, "id", Value::Int(self.from_branch)).await
+    }
+
+    #[graphql(name = "toBranch")]
+    async fn load_to_branch(&self, ctx: &Context<'_>) -> Result<Option<Branch>> {
+        one(ctx, "id", Value::Int(self.to_branch)).await
+    }
+}
+
+#[ComplexObject]
+impl LedgerEntry {
+    #[graphql(name = "studentCard")]
+    async fn load_student_card(&self, ctx: &Context<'_>) -> Result<Option<StudentCard>> {
+        match self.student_card {
+            Some(card) => one(ctx, "id", Value::Int(card)).await,
+            None => Ok(None),
+        }
+    }
+
+    #[graphql(name = "teacherCard")]
+    async fn load_teacher_card(&self, ctx: &Context<'_>) -> Result<Option<TeacherCard>> {
+        match self.teacher_card {
+            Some(card) => one(ctx, "id", Value::Int(card)).await,
+            None => Ok(None),
+        }
+    }
+
+    #[graphql(name = "studentsBorrowing")]
+    async fn load_students_borrowing(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<StudentsBorrowing>> {
+        match self.students_borrowing {
+            Some(borrowing) => one(ctx, "id", Value::Int(borrowing)).await,
+            None => Ok(None),
+        }
+    }
+
+    #[graphql(name = "teachersBorrowing")]
+    async fn load_teachers_borrowing(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<TeachersBorrowing>> {
+        match self.teachers_borrowing {
+            Some(borrowing) => one(ctx, "id", Value::Int(borrowing)).await,
+            None => Ok(None),
+        }
+    }
+
+    #[graphql(name = "librarian")]
+    async fn load_librarian(&self, ctx: &Context<'_>) -> Result<Option<Librarian>> {
+        match self.librarian {
+            Some(librarian) => one(ctx, "id", Value::Int(librarian)).await,
+            None => Ok(None),
+        }
+    }
+}
+
+#[ComplexObject]
+impl StudentsBorrowingRenewal {
+    #[graphql(name = "studentsBorrowing")]
+    async fn load_students_borrowing(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<StudentsBorrowing>> {
+        one(ctx, "id", Value::Int(self.students_borrowing)).await
+    }
+
+    #[graphql(name = "librarian")]
+    async fn load_librarian(&self, ctx: &Context<'_>) -> Result<Option<Librarian>> {
+        one(ctx, "id", Value::Int(self.librarian)).await
+    }
+}
+
+#[ComplexObject]
+impl Notification {
+    #[graphql(name = "studentsBorrowing")]
+    async fn load_students_borrowing(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<StudentsBorrowing>> {
+        match self.students_borrowing {
+            Some(borrowing) => one(ctx, "id", Value::Int(borrowing)).await,
+            None => Ok(None),
+        }
+    }
+
+    #[graphql(name = "hold")]
+    async fn load_hold(&self, ctx: &Context<'_>) -> Result<Option<Hold>> {
+        match self.hold {
+            Some(hold) => one(ctx, "id", Value::Int(hold)).await,
+            None => Ok(None),
+        }
+    }
+}
+
+/// Balance of the patron of the card `card` in `card_table`, the ledger is only kept in Postgres.
+async fn card_balance(ctx: &Context<'_>, card_table: &'static str, card: i32) -> Result<Balance> {
+    let mut conn = postgres(ctx)?
+        .acquire()
+        .await
+        .wrap_err_with(|| eyre!("Unable to acquire database connection"))
+        .map_err(|err| status_error(internal_error(err)))?;
+
+    fine::card_balance(&mut conn, card_table, card)
+        .await
+        .map_err(|err| status_error(internal_error(err)))?
+        .ok_or_else(|| {
+            status_error((
+                StatusCode::NOT_FOUND,
+                format!("{} {card} does not exist", label(card_table)),
+            ))
+        })
+}
@@ -0,0 +1,769 @@
+use async_graphql::{Context, InputObject, Object, Result};
+use color_eyre::eyre::{eyre, Context as _};
+
+use crate::error::internal_error;
+use crate::model::{
+    Author, AuthorBook, Book, BookTransfer, Branch, CardState, Category, Classification, Country,
+    Curriculum, Faculty, FacultyCurriculum, Hold, HoldStatus, JobRun, JobRunStatus, LedgerEntry,
+    LedgerEntryKind, Librarian, Notification, NotificationKind, NotificationStatus, Publisher,
+    Shelf, Student, StudentCard, StudentStatus, StudentsBorrowing, StudentsBorrowingRenewal,
+    Teacher, TeacherCard, TeacherStatus, TeachersBorrowing, TransferStatus,
+};
+use crate::repository::{Condition, Shared, Value};
+use crate::resource::{label, Resource};
+use crate::web::resource::repository_error;
+
+use super::{postgres, status_error};
+
+pub struct Query;
+
+/// Page of a list, rows are ordered by key.
+#[derive(InputObject)]
+pub struct Page {
+    #[graphql(default, validator(minimum = 0))]
+    offset: i64,
+    #[graphql(default = 100, validator(minimum = 1, maximum = 1000))]
+    limit: i64,
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            limit: 100,
+        }
+    }
+}
+
+/// Filter of a list, rows match every field which is set.
+trait Filter {
+    fn conditions(&self) -> Vec<Condition>;
+}
+
+fn conditions(filter: Option<impl Filter>) -> Vec<Condition> {
+    filter.map(|filter| filter.conditions()).unwrap_or_default()
+}
+
+fn condition(column: &'static str, value: Option<Value>) -> Option<Condition> {
+    value.map(|value| Condition {
+        column,
+        values: vec![value],
+    })
+}
+
+#[derive(InputObject)]
+pub struct FacultyCurriculumFilter {
+    faculty_id: Option<i32>,
+    curriculum_id: Option<i32>,
+}
+
+impl Filter for FacultyCurriculumFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        [
+            condition("faculty", self.faculty_id.map(Value::Int)),
+            condition("curriculum", self.curriculum_id.map(Value::Int)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+#[derive(InputObject)]
+pub struct StudentFilter {
+    faculty_curriculum_id: Option<i32>,
+    group: Option<i16>,
+    status: Option<StudentStatus>,
+}
+
+impl Filter for StudentFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        [
+            condition(
+                "faculty_curriculum",
+                self.faculty_curriculum_id.map(Value::Int),
+            ),
+            condition("group", self.group.map(|group| Value::Int(group.into()))),
+            condition("status", self.status.as_ref().map(Value::of_enum)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+#[derive(InputObject)]
+pub struct TeacherFilter {
+    faculty_id: Option<i32>,
+    status: Option<TeacherStatus>,
+}
+
+impl Filter for TeacherFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        [
+            condition("faculty", self.faculty_id.map(Value::Int)),
+            condition("status", self.status.as_ref().map(Value::of_enum)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+#[derive(InputObject)]
+pub struct BookFilter {
+    publisher_id: Option<i32>,
+    category_id: Option<i32>,
+    student_access: Option<bool>,
+    isbn: Option<String>,
+    branch_id: Option<i32>,
+    shelf_id: Option<i32>,
+    classification: Option<Classification>,
+}
+
+impl Filter for BookFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        [
+            condition("publisher", self.publisher_id.map(Value::Int)),
+            condition("category", self.category_id.map(Value::Int)),
+            condition("student_access", self.student_access.map(Value::Bool)),
+            condition("isbn", self.isbn.clone().map(Value::Text)),
+            condition("branch", self.branch_id.map(Value::Int)),
+            condition("shelf", self.shelf_id.map(Value::Int)),
+            condition(
+                "classification",
+                self.classification.as_ref().map(Value::of_enum),
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+/// Filter of the branches of a faculty.
+#[derive(InputObject)]
+pub struct BranchFilter {
+    faculty_id: Option<i32>,
+}
+
+impl Filter for BranchFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        condition("faculty", self.faculty_id.map(Value::Int))
+            .into_iter()
+            .collect()
+    }
+}
+
+#[derive(InputObject)]
+pub struct ShelfFilter {
+    branch_id: Option<i32>,
+}
+
+impl Filter for ShelfFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        condition("branch", self.branch_id.map(Value::Int))
+            .into_iter()
+            .collect()
+    }
+}
+
+#[derive(InputObject)]
+pub struct LibrarianFilter {
+    branch_id: Option<i32>,
+}
+
+impl Filter for LibrarianFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        condition("branch", self.branch_id.map(Value::Int))
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Filter of the authors and publishers of a country.
+#[derive(InputObject)]
+pub struct CountryFilter {
+    country_code: Option<String>,
+}
+
+impl Filter for CountryFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        condition("country", self.country_code.clone().map(Value::Text))
+            .into_iter()
+            .collect()
+    }
+}
+
+#[derive(InputObject)]
+pub struct AuthorBookFilter {
+    author_id: Option<i32>,
+    book_id: Option<i32>,
+}
+
+impl Filter for AuthorBookFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        [
+            condition("author_id", self.author_id.map(Value::Int)),
+            condition("book_id", self.book_id.map(Value::Int)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+#[derive(InputObject)]
+pub struct StudentCardFilter {
+    student_id: Option<i32>,
+    state: Option<CardState>,
+}
+
+impl Filter for StudentCardFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        [
+            condition("student", self.student_id.map(Value::Int)),
+            condition("state", self.state.as_ref().map(Value::of_enum)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+#[derive(InputObject)]
+pub struct TeacherCardFilter {
+    teacher_id: Option<i32>,
+    state: Option<CardState>,
+}
+
+impl Filter for TeacherCardFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        [
+            condition("teacher", self.teacher_id.map(Value::Int)),
+            condition("state", self.state.as_ref().map(Value::of_enum)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+#[derive(InputObject)]
+pub struct StudentsBorrowingFilter {
+    student_card_id: Option<i32>,
+    librarian_id: Option<i32>,
+    book_id: Option<i32>,
+    branch_id: Option<i32>,
+}
+
+impl Filter for StudentsBorrowingFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        [
+            condition("student_card", self.student_card_id.map(Value::Int)),
+            condition("librarian", self.librarian_id.map(Value::Int)),
+            condition("book", self.book_id.map(Value::Int)),
+            condition("branch", self.branch_id.map(Value::Int)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+#[derive(InputObject)]
+pub struct TeachersBorrowingFilter {
+    teacher_card_id: Option<i32>,
+    librarian_id: Option<i32>,
+    book_id: Option<i32>,
+    branch_id: Option<i32>,
+}
+
+impl Filter for TeachersBorrowingFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        [
+            condition("teacher_card", self.teacher_card_id.map(Value::Int)),
+            condition("librarian", self.librarian_id.map(Value::Int)),
+            condition("book", self.book_id.map(Value::Int)),
+            condition("branch", self.branch_id.map(Value::Int)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+#[derive(InputObject)]
+pub struct HoldFilter {
+    book_id: Option<i32>,
+    student_card_id: Option<i32>,
+    teacher_card_id: Option<i32>,
+    status: Option<HoldStatus>,
+}
+
+impl Filter for HoldFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        [
+            condition("book", self.book_id.map(Value::Int)),
+            condition("student_card", self.student_card_id.map(Value::Int)),
+            condition("teacher_card", self.teacher_card_id.map(Value::Int)),
+            condition("status", self.status.as_ref().map(Value::of_enum)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+#[derive(InputObject)]
+pub struct BookTransferFilter {
+    book_id: Option<i32>,
+    from_branch_id: Option<i32>,
+    to_branch_id: Option<i32>,
+    status: Option<TransferStatus>,
+}
+
+impl Filter for BookTransferFilter {
+    fn conditions(&self) -> Vec<Condition> {
+        [
+            condition("book", self.book_id.map(Value::Int)),
+            condition("from_branch", self.from_branch_id.map(Value::Int)),
+            condition("to_branch", self.to_branch_id.map(Value::Int)),
+            condition("status", self.status.as_ref().map(Value::of_enum)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+#[derive(InputObject)]
+pub struct RenewalFilter {
+    students_borrowing_id: Option<i32>,
+    librarian_id: Option<i32>,
+}
+
+#[derive(InputObject, Default)]
+pub struct LedgerEntryFilter {
+    student_card_id: Option<i32>,
+    teacher_card_id: Option<i32>,
+    /// Entries on every card of the student.
+    student_id: Option<i32>,
+    /// Entries on every card of the teacher.
+    teacher_id: Option<i32>,
+    kind: Option<LedgerEntryKind>,
+}
+
+#[derive(InputObject)]
+pub struct NotificationFilter {
+    kind: Option<NotificationKind>,
+    status: Option<NotificationStatus>,
+}
+
+#[derive(InputObject)]
+pub struct JobRunFilter {
+    job: Option<String>,
+    status: Option<JobRunStatus>,
+}
+
+/// Page of the rows of `R` matching `conditions`.
+async fn list<R: Resource>(
+    ctx: &Context<'_>,
+    conditions: Vec<Condition>,
+    page: Page,
+) -> Result<Vec<R>> {
+    ctx.data::<Shared<R>>()?
+        .find(&conditions, page.offset, Some(page.limit))
+        .await
+        .map_err(|err| status_error(repository_error(err, &label(R::TABLE))))
+}
+
+async fn get<R: Resource>(ctx: &Context<'_>, key: R::Key) -> Result<Option<R>> {
+    ctx.data::<Shared<R>>()?
+        .get(&key)
+        .await
+        .map_err(|err| status_error(repository_error(err, &format!("{} {key}", label(R::TABLE)))))
+}
+
+#[Object]
+impl Query {
+    async fn students(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<StudentFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<Student>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn student(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Student>> {
+        get(ctx, id).await
+    }
+
+    async fn faculties(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<Faculty>> {
+        list(ctx, Vec::new(), page).await
+    }
+
+    async fn faculty(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Faculty>> {
+        get(ctx, id).await
+    }
+
+    async fn curricula(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<Curriculum>> {
+        list(ctx, Vec::new(), page).await
+    }
+
+    async fn curriculum(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Curriculum>> {
+        get(ctx, id).await
+    }
+
+    async fn faculty_curricula(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<FacultyCurriculumFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<FacultyCurriculum>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn faculty_curriculum(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+    ) -> Result<Option<FacultyCurriculum>> {
+        get(ctx, id).await
+    }
+
+    async fn teachers(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<TeacherFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<Teacher>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn teacher(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Teacher>> {
+        get(ctx, id).await
+    }
+
+    async fn books(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<BookFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<Book>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn book(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Book>> {
+        get(ctx, id).await
+    }
+
+    async fn categories(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<Category>> {
+        list(ctx, Vec::new(), page).await
+    }
+
+    async fn category(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Category>> {
+        get(ctx, id).await
+    }
+
+    async fn authors(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<CountryFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<Author>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn author(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Author>> {
+        get(ctx, id).await
+    }
+
+    async fn author_books(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<AuthorBookFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<AuthorBook>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn author_book(&self, ctx: &Context<'_>, id: i32) -> Result<Option<AuthorBook>> {
+        get(ctx, id).await
+    }
+
+    async fn librarians(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<LibrarianFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<Librarian>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn librarian(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Librarian>> {
+        get(ctx, id).await
+    }
+
+    async fn branches(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<BranchFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<Branch>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn branch(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Branch>> {
+        get(ctx, id).await
+    }
+
+    async fn shelves(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<ShelfFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<Shelf>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn shelf(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Shelf>> {
+        get(ctx, id).await
+    }
+
+    async fn publishers(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<CountryFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<Publisher>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn publisher(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Publisher>> {
+        get(ctx, id).await
+    }
+
+    async fn countries(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<Country>> {
+        list(ctx, Vec::new(), page).await
+    }
+
+    async fn country(&self, ctx: &Context<'_>, code: String) -> Result<Option<Country>> {
+        get(ctx, code).await
+    }
+
+    async fn student_cards(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<StudentCardFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<StudentCard>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn student_card(&self, ctx: &Context<'_>, id: i32) -> Result<Option<StudentCard>> {
+        get(ctx, id).await
+    }
+
+    async fn teacher_cards(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<TeacherCardFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<TeacherCard>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn teacher_card(&self, ctx: &Context<'_>, id: i32) -> Result<Option<TeacherCard>> {
+        get(ctx, id).await
+    }
+
+    async fn students_borrowings(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<StudentsBorrowingFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<StudentsBorrowing>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn students_borrowing(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+    ) -> Result<Option<StudentsBorrowing>> {
+        get(ctx, id).await
+    }
+
+    async fn teachers_borrowings(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<TeachersBorrowingFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<TeachersBorrowing>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn teachers_borrowing(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+    ) -> Result<Option<TeachersBorrowing>> {
+        get(ctx, id).await
+    }
+
+    async fn holds(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<HoldFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<Hold>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn hold(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Hold>> {
+        get(ctx, id).await
+    }
+
+    async fn book_transfers(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<BookTransferFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<BookTransfer>> {
+        list(ctx, conditions(filter), page).await
+    }
+
+    async fn book_transfer(&self, ctx: &Context<'_>, id: i32) -> Result<Option<BookTransfer>> {
+        get(ctx, id).await
+    }
+
+    async fn renewals(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<RenewalFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<StudentsBorrowingRenewal>> {
+        let (students_borrowing, librarian) = filter.map_or((None, None), |filter| {
+            (filter.students_borrowing_id, filter.librarian_id)
+        });
+
+        sqlx::query_as!(
+            StudentsBorrowingRenewal,
+            r#"SELECT id, students_borrowing, librarian, renewal_date, previous_return_date, required_return_date
+            FROM students_borrowing_renewal
+            WHERE ($1::int IS NULL OR students_borrowing = $1) AND ($2::int IS NULL OR librarian = $2)
+            ORDER BY id ASC OFFSET $3 LIMIT $4"#,
+            students_borrowing,
+            librarian,
+            page.offset,
+            page.limit
+        )
+        .fetch_all(postgres(ctx)?)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load students_borrowing_renewals from database"))
+        .map_err(|err| status_error(internal_error(err)))
+    }
+
+    /// Charges, payments and waivers, only served over Postgres.
+    async fn ledger_entries(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<LedgerEntryFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<LedgerEntry>> {
+        let filter = filter.unwrap_or_default();
+
+        sqlx::query_as!(
+            LedgerEntry,
+            r#"SELECT id, student_card, teacher_card, kind as "kind: _", amount, students_borrowing,
+            teachers_borrowing, librarian, reason, created_at
+            FROM ledger_entry
+            WHERE ($1::int IS NULL OR student_card = $1) AND ($2::int IS NULL OR teacher_card = $2)
+            AND ($3::int IS NULL OR student_card IN (SELECT id FROM student_card WHERE student = $3))
+            AND ($4::int IS NULL OR teacher_card IN (SELECT id FROM teacher_card WHERE teacher = $4))
+            AND ($5::ledger_entry_kind IS NULL OR kind = $5)
+            ORDER BY id ASC OFFSET $6 LIMIT $7"#,
+            filter.student_card_id,
+            filter.teacher_card_id,
+            filter.student_id,
+            filter.teacher_id,
+            filter.kind as _,
+            page.offset,
+            page.limit
+        )
+        .fetch_all(postgres(ctx)?)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load ledger_entries from database"))
+        .map_err(|err| status_error(internal_error(err)))
+    }
+
+    async fn notifications(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<NotificationFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<Notification>> {
+        let (kind, status) = filter.map_or((None, None), |filter| (filter.kind, filter.status));
+
+        sqlx::query_as!(
+            Notification,
+            r#"SELECT id, kind as "kind: _", recipient, subject, body, students_borrowing, hold,
+            status as "status: _", attempts, last_error, created_at, next_attempt_at, sent_at
+            FROM notification
+            WHERE ($1::notification_kind IS NULL OR kind = $1)
+            AND ($2::notification_status IS NULL OR status = $2)
+            ORDER BY id ASC OFFSET $3 LIMIT $4"#,
+            kind as _,
+            status as _,
+            page.offset,
+            page.limit
+        )
+        .fetch_all(postgres(ctx)?)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load notifications from database"))
+        .map_err(|err| status_error(internal_error(err)))
+    }
+
+    async fn job_runs(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<JobRunFilter>,
+        #[graphql(default)] page: Page,
+    ) -> Result<Vec<JobRun>> {
+        let (job, status) = filter.map_or((None, None), |filter| (filter.job, filter.status));
+
+        sqlx::query_as!(
+            JobRun,
+            r#"SELECT id, job, started_at, finished_at, status as "status: _", message, scheduled_at
+            FROM job_run
+            WHERE ($1::varchar IS NULL OR job = $1) AND ($2::job_run_status IS NULL OR status = $2)
+            ORDER BY started_at DESC OFFSET $3 LIMIT $4"#,
+            job,
+            status as _,
+            page.offset,
+            page.limit
+        )
+        .fetch_all(postgres(ctx)?)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load job_runs from database"))
+        .map_err(|err| status_error(internal_error(err)))
+    }
+}
@@ -0,0 +1,128 @@
+use color_eyre::{eyre::bail, Result};
+
+/// Validates an ISBN-10 or ISBN-13 and returns it as ISBN-13 without hyphens.
+pub fn normalize(isbn: &str) -> Result<String> {
+    let digits: String = isbn
+        .chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    match digits.len() {
+        10 => {
+            if !is_valid_isbn10(&digits) {
+                bail!("`{isbn}` is not a valid ISBN-10");
+            }
+
+            let isbn13 = format!("978{}", &digits[..9]);
+            let check = isbn13_check_digit(&isbn13);
+            Ok(format!("{isbn13}{check}"))
+        }
+        13 => {
+            if !digits.chars().all(|c| c.is_ascii_digit())
+                || isbn13_check_digit(&digits[..12]) != digits.as_bytes()[12] - b'0'
+            {
+                bail!("`{isbn}` is not a valid ISBN-13");
+            }
+
+            // Other prefixes are EAN-13 codes of other goods, which share the checksum.
+            if !digits.starts_with("978") && !digits.starts_with("979") {
+                bail!("`{isbn}` is not an ISBN-13, it should start with 978 or 979");
+            }
+
+            Ok(digits)
+        }
+        _ => bail!("`{isbn}` should have 10 or 13 digits"),
+    }
+}
+
+fn is_valid_isbn10(digits: &str) -> bool {
+    let mut sum = 0;
+
+    for (i, c) in digits.chars().enumerate() {
+        let value = match c {
+            '0'..='9' => c as u32 - '0' as u32,
+            'X' if i == 9 => 10,
+            _ => return false,
+        };
+        sum += value * (10 - i as u32);
+    }
+
+    sum % 11 == 0
+}
+
+pub fn isbn13_check_digit(first_twelve: &str) -> u8 {
+    let sum: u32 = first_twelve
+        .bytes()
+        .enumerate()
+        .map(|(i, b)| (b - b'0') as u32 * if i % 2 == 0 { 1 } else { 3 })
+        .sum();
+
+    ((10 - sum % 10) % 10) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_isbn10_to_isbn13() {
+        assert_eq!(normalize("0-306-40615-2").unwrap(), "9780306406157");
+        assert_eq!(normalize("0 306 40615 2").unwrap(), "9780306406157");
+        // The check digit of ISBN-10 can be 10, and the ISBN-13 recomputes it.
+        assert_eq!(normalize("080442957X").unwrap(), "9780804429573");
+        assert_eq!(normalize("0-8044-2957-x").unwrap(), "9780804429573");
+    }
+
+    #[test]
+    fn keeps_isbn13_without_hyphens() {
+        assert_eq!(normalize("978-0-306-40615-7").unwrap(), "9780306406157");
+        assert_eq!(normalize("979-10-90636-07-1").unwrap(), "9791090636071");
+        assert_eq!(
+            normalize("978-0-306-40615-7").unwrap(),
+            normalize("0-306-40615-2").unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_check_digits() {
+        for isbn in ["0-306-40615-3", "978-0-306-40615-8", "979-10-90636-07-2"] {
+            assert!(normalize(isbn).is_err(), "{isbn}");
+        }
+    }
+
+    #[test]
+    fn rejects_misplaced_or_foreign_characters() {
+        for isbn in [
+            "X-306-40615-2",
+            "030640615Y",
+            "97803064061X7",
+            "978030640615a",
+        ] {
+            assert!(normalize(isbn).is_err(), "{isbn}");
+        }
+    }
+
+    #[test]
+    fn rejects_other_lengths() {
+        for isbn in ["", "123", "03064061", "978030640615", "97803064061570"] {
+            assert!(normalize(isbn).is_err(), "{isbn}");
+        }
+    }
+
+    #[test]
+    fn rejects_ean13_codes_of_other_goods() {
+        // Valid EAN-13 checksum, but not in the Bookland prefixes.
+        assert_eq!(
+            normalize("9770306406158").unwrap_err().to_string(),
+            "`9770306406158` is not an ISBN-13, it should start with 978 or 979"
+        );
+    }
+
+    #[test]
+    fn computes_isbn13_check_digit() {
+        assert_eq!(isbn13_check_digit("978030640615"), 7);
+        assert_eq!(isbn13_check_digit("979109063607"), 1);
+        assert_eq!(isbn13_check_digit("978186197271"), 2);
+    }
+}
@@ -0,0 +1,243 @@
+use std::path::PathBuf;
+
+use axum::{
+    extract::DefaultBodyLimit,
+    http::{header, HeaderValue, Method},
+    middleware, Router,
+};
+use color_eyre::{
+    eyre::{bail, eyre, Context},
+    Result,
+};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+pub use crate::database::Database;
+use crate::repository::Storage;
+
+mod cache;
+mod call_number;
+mod change_feed;
+mod circulation;
+pub mod cli;
+mod database;
+mod error;
+mod etag;
+mod fine;
+mod graphql;
+mod isbn;
+mod marc;
+mod model;
+pub mod notification;
+pub mod rate_limit;
+mod repository;
+mod resource;
+pub mod scheduler;
+mod validation;
+mod web;
+pub mod webhook;
+
+const DEFAULT_MAX_BODY_SIZE: usize = 1024 * 1024;
+
+pub struct Config {
+    // Larger bodies are rejected with `413 Payload Too Large`, the MARC import has its own limit.
+    pub max_body_size: usize,
+    pub rate_limit: rate_limit::RateLimitConfig,
+    // Origins of other sites allowed to call the API, `None` disables CORS for single-origin deployments.
+    pub cors: Option<AllowOrigin>,
+    // Built frontend served under `/`, next to the API under `/api`.
+    pub frontend_dir: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self> {
+        let max_body_size = match dotenvy::var("MAX_BODY_SIZE") {
+            Ok(size) => size.parse().wrap_err_with(|| {
+                eyre!("Env variable `MAX_BODY_SIZE` should be a number of bytes")
+            })?,
+            Err(_) => DEFAULT_MAX_BODY_SIZE,
+        };
+
+        let frontend_dir = dotenvy::var("FRONTEND_DIR").ok().map(PathBuf::from);
+        if let Some(dir) = &frontend_dir {
+            if !dir.join("index.html").is_file() {
+                bail!(
+                    "Env variable `FRONTEND_DIR` should be a built frontend, `{}` has no `index.html`",
+                    dir.display()
+                );
+            }
+        }
+
+        Ok(Self {
+            max_body_size,
+            rate_limit: rate_limit::RateLimitConfig::from_env()?,
+            cors: cors_from_env()?,
+            frontend_dir,
+        })
+    }
+}
+
+/// `CORS_ALLOW_ORIGIN` is `*` for any origin (the default), `none` or a comma separated list of origins.
+fn cors_from_env() -> Result<Option<AllowOrigin>> {
+    let origins = match dotenvy::var("CORS_ALLOW_ORIGIN") {
+        Ok(origins) => origins,
+        Err(_) => return Ok(Some(AllowOrigin::any())),
+    };
+
+    match origins.trim() {
+        "*" => Ok(Some(AllowOrigin::any())),
+        "none" => Ok(None),
+        origins => {
+            let origins = origins
+                .split(',')
+                .map(|origin| HeaderValue::from_str(origin.trim()))
+                .collect::<Result<Vec<_>, _>>()
+                .wrap_err_with(|| {
+                    eyre!("Env variable `CORS_ALLOW_ORIGIN` should be `*`, `none` or a list of origins")
+                })?;
+
+            Ok(Some(AllowOrigin::list(origins)))
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            rate_limit: rate_limit::RateLimitConfig::default(),
+            cors: Some(AllowOrigin::any()),
+            frontend_dir: None,
+        }
+    }
+}
+
+/// Builds the HTTP API under `/api` and the frontend, if any, under `/`.
+///
+/// The server in `main` and the integration tests serve the same router.
+pub fn app(db: Database, config: Config) -> Router {
+    let rate_limiter = rate_limit::RateLimiter::new(config.rate_limit);
+
+    // Shared with the MARC import and GraphQL, which write to the cached tables too.
+    let caches = cache::ListCaches::default();
+
+    let api = match db {
+        Database::Postgres(db_pool) => table_routes(&db_pool, &caches)
+            .merge(web::graphql::routes(graphql::schema(
+                &db_pool,
+                caches.clone(),
+                Some(db_pool.clone()),
+            )))
+            .merge(web::table::routes(db_pool.clone()))
+            .merge(web::book::isbn_routes(db_pool.clone()))
+            .merge(web::card::reissue_routes::<model::TeacherCard>(
+                db_pool.clone(),
+            ))
+            .merge(web::card::reissue_routes::<model::StudentCard>(
+                db_pool.clone(),
+            ))
+            .merge(web::book_transfer::transfer_routes(db_pool.clone()))
+            .merge(web::students_borrowing_renewal::routes(db_pool.clone()))
+            .merge(web::fine::routes(db_pool.clone()))
+            .merge(web::notification::routes(db_pool.clone()))
+            .merge(web::job::routes(db_pool.clone()))
+            .merge(web::webhook::routes(db_pool.clone()))
+            .merge(web::report::routes(db_pool.clone()))
+            .merge(web::events::routes(change_feed::ChangeFeed::new(
+                db_pool.clone(),
+            )))
+            .merge(web::marc::routes(
+                db_pool,
+                caches.table("category"),
+                caches.table("publisher"),
+            )),
+        Database::Sqlite(db_pool) => table_routes(&db_pool, &caches)
+            .merge(web::graphql::routes(graphql::schema(
+                &db_pool,
+                caches.clone(),
+                None,
+            )))
+            .merge(web::table::sqlite_routes(db_pool.clone()))
+            .merge(web::book::sqlite_isbn_routes(db_pool.clone()))
+            .merge(web::card::sqlite_reissue_routes::<model::TeacherCard>(
+                db_pool.clone(),
+            ))
+            .merge(web::card::sqlite_reissue_routes::<model::StudentCard>(
+                db_pool.clone(),
+            ))
+            .merge(web::book_transfer::sqlite_transfer_routes(db_pool.clone()))
+            .merge(web::students_borrowing_renewal::sqlite_routes(db_pool)),
+    };
+
+    let router = Router::new().nest("/api", api);
+    let router = match config.frontend_dir {
+        Some(dir) => router.merge(web::frontend::routes(dir)),
+        None => router,
+    }
+    .layer(DefaultBodyLimit::max(config.max_body_size))
+    .layer(middleware::from_fn_with_state(
+        rate_limiter,
+        rate_limit::limit,
+    ));
+
+    match config.cors {
+        Some(origin) => router.layer(
+            CorsLayer::new()
+                .allow_origin(origin)
+                .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+                .allow_headers([
+                    header::CONTENT_TYPE,
+                    header::IF_MATCH,
+                    header::IF_NONE_MATCH,
+                    header::IF_MODIFIED_SINCE,
+                ])
+                .expose_headers([header::ETAG]),
+        ),
+        None => router,
+    }
+}
+
+/// CRUD routes of every table, over the repositories of `storage`.
+fn table_routes(storage: &impl Storage, caches: &cache::ListCaches) -> Router {
+    Router::new()
+        .merge(web::student::routes(storage.repository()))
+        .merge(web::author::routes(storage.repository()))
+        .merge(web::book::routes(storage.repository()))
+        .merge(web::category::routes(
+            storage.repository(),
+            caches.table("category"),
+        ))
+        .merge(web::author_book::routes(storage.repository()))
+        .merge(web::teachers_borrowing::routes(storage.repository()))
+        .merge(web::teacher_card::routes(storage.repository()))
+        .merge(web::teacher::routes(storage.repository()))
+        .merge(web::curriculum::routes(
+            storage.repository(),
+            caches.table("curriculum"),
+        ))
+        .merge(web::faculty::routes(
+            storage.repository(),
+            caches.table("faculty"),
+        ))
+        .merge(web::faculty_curriculum::routes(storage.repository()))
+        .merge(web::publisher::routes(
+            storage.repository(),
+            caches.table("publisher"),
+        ))
+        .merge(web::branch::routes(
+            storage.repository(),
+            caches.table("branch"),
+        ))
+        .merge(web::shelf::routes(
+            storage.repository(),
+            caches.table("shelf"),
+        ))
+        .merge(web::librarian::routes(storage.repository()))
+        .merge(web::student_card::routes(storage.repository()))
+        .merge(web::students_borrowing::routes(storage.repository()))
+        .merge(web::hold::routes(storage.repository()))
+        .merge(web::book_transfer::routes(storage.repository()))
+        .merge(web::country::routes(
+            storage.repository(),
+            caches.table("country"),
+        ))
+}
@@ -1,63 +1,59 @@
 use std::net::SocketAddr;
 
-use axum::{
-    http::{header, Method},
-    Router,
-};
-use color_eyre::{
-    eyre::{eyre, Context},
-    Result,
-};
-use sqlx::postgres::PgPoolOptions;
-use tower_http::cors::{Any, CorsLayer};
-
-mod error;
-mod model;
-mod web;
+use color_eyre::Result;
+use crud::cli::{self, Command};
+use crud::{notification, scheduler, webhook, Database};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // initialize tracing
     tracing_subscriber::fmt::init();
 
+    let command = Command::parse(std::env::args().skip(1))?;
+    if command == Command::Help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+
     let db_url = dotenvy::var("DATABASE_URL").expect("Env variable `DATABASE_URL` should be set");
 
-    let db_pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&db_url)
-        .await
-        .wrap_err_with(|| eyre!("Unable connect to database"))?;
+    let db = Database::connect(&db_url).await?;
+
+    match command {
+        Command::Serve => serve(db).await,
+        command => cli::run(command, &db).await,
+    }
+}
 
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-        .allow_headers([header::CONTENT_TYPE]);
+async fn serve(db: Database) -> Result<()> {
+    match &db {
+        Database::Postgres(db_pool) => {
+            match notification::worker::SmtpConfig::from_env()? {
+                Some(smtp) => {
+                    tokio::spawn(notification::worker::run(db_pool.clone(), smtp));
+                }
+                None => tracing::warn!(
+                    "Env variable `SMTP_HOST` is not set, notifications will not be delivered"
+                ),
+            }
+
+            tokio::spawn(webhook::worker::run(db_pool.clone()));
+            scheduler::start(db_pool.clone());
+        }
+        Database::Sqlite(_) => {
+            tracing::warn!(
+                "Notifications, webhooks and scheduled jobs need Postgres and are disabled over SQLite"
+            )
+        }
+    }
 
     // build our application with a route
-    let app = Router::new()
-        .merge(web::table::routes(db_pool.clone()))
-        .merge(web::student::routes(db_pool.clone()))
-        .merge(web::author::routes(db_pool.clone()))
-        .merge(web::book::routes(db_pool.clone()))
-        .merge(web::category::routes(db_pool.clone()))
-        .merge(web::author_book::routes(db_pool.clone()))
-        .merge(web::teachers_borrowing::routes(db_pool.clone()))
-        .merge(web::teacher_card::routes(db_pool.clone()))
-        .merge(web::teacher::routes(db_pool.clone()))
-        .merge(web::curriculum::routes(db_pool.clone()))
-        .merge(web::faculty::routes(db_pool.clone()))
-        .merge(web::faculty_curriculum::routes(db_pool.clone()))
-        .merge(web::publisher::routes(db_pool.clone()))
-        .merge(web::librarian::routes(db_pool.clone()))
-        .merge(web::student_card::routes(db_pool.clone()))
-        .merge(web::students_borrowing::routes(db_pool.clone()))
-        .merge(web::country::routes(db_pool))
-        .layer(cors);
+    let app = crud::app(db, crud::Config::from_env()?);
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
     tracing::debug!("listening on {}", addr);
     axum::Server::bind(&addr)
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .unwrap();
 
@@ -0,0 +1,109 @@
+use chrono::Datelike;
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use sqlx::{Pool, Postgres};
+
+use crate::model::Classification;
+
+use super::{ControlField, DataField, Record, Subfield};
+
+// Language material, monograph, UTF-8, ISBD punctuation.
+const LEADER: &str = "00000nam a2200000 i 4500";
+
+/// Loads the whole catalogue as MARC21 records.
+pub async fn export(db: &Pool<Postgres>) -> Result<Vec<Record>> {
+    let books = sqlx::query!(
+        r#"SELECT book.id, book.title, book.release, book.isbn,
+        book.classification as "classification: Classification", book.call_number,
+        publisher.name as publisher, category.name as category,
+        COALESCE(
+            array_agg(concat_ws(' ', author.lastname || ',', author.name, NULLIF(author.surname, ''))
+                ORDER BY author_book.num) FILTER (WHERE author.id IS NOT NULL),
+            '{}'
+        ) as "authors!"
+        FROM book
+        JOIN publisher ON publisher.id = book.publisher
+        JOIN category ON category.id = book.category
+        LEFT JOIN author_book ON author_book.book_id = book.id
+        LEFT JOIN author ON author.id = author_book.author_id
+        GROUP BY book.id, publisher.name, category.name
+        ORDER BY book.id ASC"#
+    )
+    .fetch_all(db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load catalogue from database"))?;
+
+    let records = books
+        .into_iter()
+        .map(|book| {
+            let year = book.release.year();
+            let mut authors = book.authors.into_iter();
+            let mut data_fields = Vec::new();
+
+            if let Some(isbn) = book.isbn {
+                data_fields.push(field("020", ' ', ' ', &[('a', isbn)]));
+            }
+            if let (Some(classification), Some(call_number)) =
+                (book.classification, book.call_number)
+            {
+                // Class number in `$a`, the book mark in `$b`.
+                let mut subfields = Vec::new();
+                let (class, mark) = call_number.split_once(' ').unwrap_or((&call_number, ""));
+                subfields.push(('a', class.to_string()));
+                if !mark.is_empty() {
+                    subfields.push(('b', mark.to_string()));
+                }
+                data_fields.push(match classification {
+                    Classification::Udc => field("080", ' ', ' ', &subfields),
+                    Classification::Dewey => field("082", '0', '4', &subfields),
+                });
+            }
+            if let Some(author) = authors.next() {
+                data_fields.push(field("100", '1', ' ', &[('a', author)]));
+            }
+            data_fields.push(field("245", '1', '0', &[('a', book.title)]));
+            data_fields.push(field(
+                "264",
+                ' ',
+                '1',
+                &[('b', book.publisher), ('c', year.to_string())],
+            ));
+            data_fields.push(field("650", ' ', '4', &[('a', book.category)]));
+            data_fields.extend(authors.map(|author| field("700", '1', ' ', &[('a', author)])));
+
+            Record {
+                leader: LEADER.to_string(),
+                control_fields: vec![
+                    ControlField {
+                        tag: "001".to_string(),
+                        value: book.id.to_string(),
+                    },
+                    ControlField {
+                        tag: "008".to_string(),
+                        value: format!("{:6}s{year:04}{:4}xx {:17}und d", "", "", ""),
+                    },
+                ],
+                data_fields,
+            }
+        })
+        .collect();
+
+    Ok(records)
+}
+
+fn field(tag: &str, ind1: char, ind2: char, subfields: &[(char, String)]) -> DataField {
+    DataField {
+        tag: tag.to_string(),
+        ind1,
+        ind2,
+        subfields: subfields
+            .iter()
+            .map(|(code, value)| Subfield {
+                code: *code,
+                value: value.clone(),
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,339 @@
+use color_eyre::{
+    eyre::{eyre, Context, ContextCompat},
+    Result,
+};
+use serde::Serialize;
+use sqlx::types::chrono::NaiveDate;
+use sqlx::{Pool, Postgres, Transaction};
+
+use super::Record;
+use crate::isbn;
+
+// Used when a record has no publisher or subject, both are required for a book.
+const UNKNOWN_PUBLISHER: &str = "Unknown";
+const UNKNOWN_CATEGORY: &str = "Uncategorized";
+
+#[derive(Serialize, Debug, Default)]
+pub struct ImportReport {
+    pub records: usize,
+    pub books_created: usize,
+    pub books_skipped: usize,
+    pub authors_created: usize,
+    pub publishers_created: usize,
+    pub categories_created: usize,
+    pub errors: Vec<String>,
+}
+
+struct Entry {
+    title: String,
+    isbn: Option<String>,
+    release: NaiveDate,
+    publisher: String,
+    category: String,
+    authors: Vec<Heading>,
+}
+
+struct Heading {
+    name: String,
+    lastname: String,
+    surname: String,
+}
+
+/// Adds books of the records to the catalogue, reusing existing authors, publishers and categories.
+///
+/// Books already in the catalogue (same ISBN, or same title, release and publisher) are skipped.
+/// New authors and publishers get `country`, since MARC21 does not record it.
+pub async fn import(
+    db: &Pool<Postgres>,
+    records: &[Record],
+    country: &str,
+) -> Result<ImportReport> {
+    let mut report = ImportReport {
+        records: records.len(),
+        ..Default::default()
+    };
+
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))?;
+
+    for (i, record) in records.iter().enumerate() {
+        let entry = match entry(record) {
+            Ok(entry) => entry,
+            Err(err) => {
+                report.errors.push(format!("Record {}: {:#}", i + 1, err));
+                continue;
+            }
+        };
+
+        import_entry(&mut tx, entry, country, &mut report).await?;
+    }
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit import"))?;
+
+    Ok(report)
+}
+
+async fn import_entry(
+    tx: &mut Transaction<'_, Postgres>,
+    entry: Entry,
+    country: &str,
+    report: &mut ImportReport,
+) -> Result<()> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (
+            SELECT 1 FROM book
+            WHERE isbn = $1
+            OR ($1 IS NULL AND lower(title) = lower($2) AND release = $3
+                AND publisher IN (SELECT id FROM publisher WHERE lower(name) = lower($4)))
+        ) as "exists!""#,
+        entry.isbn,
+        entry.title,
+        entry.release,
+        entry.publisher
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to check book in database"))?;
+
+    if exists {
+        report.books_skipped += 1;
+        return Ok(());
+    }
+
+    let publisher = publisher_id(tx, &entry.publisher, country, report).await?;
+    let category = category_id(tx, &entry.category, report).await?;
+
+    let book = sqlx::query_scalar!(
+        r#"INSERT INTO book
+        (title, release, publisher, category, student_access, isbn)
+        VALUES ($1, $2, $3, $4, TRUE, $5)
+        RETURNING id"#,
+        entry.title,
+        entry.release,
+        publisher,
+        category,
+        entry.isbn
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add book to database"))?;
+    report.books_created += 1;
+
+    for (num, heading) in (1..).zip(&entry.authors) {
+        let author = author_id(tx, heading, country, report).await?;
+
+        sqlx::query!(
+            r#"INSERT INTO author_book (author_id, book_id, num) VALUES ($1, $2, $3)"#,
+            author,
+            book,
+            num
+        )
+        .execute(&mut *tx)
+        .await
+        .wrap_err_with(|| eyre!("Unable to add author_book to database"))?;
+    }
+
+    Ok(())
+}
+
+async fn publisher_id(
+    tx: &mut Transaction<'_, Postgres>,
+    name: &str,
+    country: &str,
+    report: &mut ImportReport,
+) -> Result<i32> {
+    let existing = sqlx::query_scalar!(
+        r#"SELECT id FROM publisher WHERE lower(name) = lower($1) ORDER BY id ASC LIMIT 1"#,
+        name
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load publisher from database"))?;
+
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    report.publishers_created += 1;
+    sqlx::query_scalar!(
+        r#"INSERT INTO publisher (name, country) VALUES ($1, $2) RETURNING id"#,
+        name,
+        country
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add publisher to database"))
+}
+
+async fn category_id(
+    tx: &mut Transaction<'_, Postgres>,
+    name: &str,
+    report: &mut ImportReport,
+) -> Result<i32> {
+    let existing = sqlx::query_scalar!(
+        r#"SELECT id FROM category WHERE lower(name) = lower($1) ORDER BY id ASC LIMIT 1"#,
+        name
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load category from database"))?;
+
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    report.categories_created += 1;
+    sqlx::query_scalar!(
+        r#"INSERT INTO category (name) VALUES ($1) RETURNING id"#,
+        name
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add category to database"))
+}
+
+async fn author_id(
+    tx: &mut Transaction<'_, Postgres>,
+    heading: &Heading,
+    country: &str,
+    report: &mut ImportReport,
+) -> Result<i32> {
+    let existing = sqlx::query_scalar!(
+        r#"SELECT id FROM author
+        WHERE lower(name) = lower($1) AND lower(lastname) = lower($2) AND lower(surname) = lower($3)
+        ORDER BY id ASC LIMIT 1"#,
+        heading.name,
+        heading.lastname,
+        heading.surname
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load author from database"))?;
+
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    report.authors_created += 1;
+    sqlx::query_scalar!(
+        r#"INSERT INTO author (name, lastname, surname, country) VALUES ($1, $2, $3, $4) RETURNING id"#,
+        heading.name,
+        heading.lastname,
+        heading.surname,
+        country
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add author to database"))
+}
+
+fn entry(record: &Record) -> Result<Entry> {
+    let title = record
+        .subfield("245", 'a')
+        .map(clean)
+        .filter(|title| !title.is_empty())
+        .wrap_err("Title (245 $a) is missing")?;
+    let title = match record.subfield("245", 'b').map(clean) {
+        Some(subtitle) if !subtitle.is_empty() => format!("{title}: {subtitle}"),
+        _ => title,
+    };
+
+    // Invalid ISBNs are common in legacy data and should not prevent the import of the book.
+    let isbn = record
+        .data_fields("020")
+        .filter_map(|field| field.subfield('a'))
+        .filter_map(|value| value.split_whitespace().next())
+        .find_map(|value| isbn::normalize(value).ok());
+
+    let year = record
+        .subfield("264", 'c')
+        .or_else(|| record.subfield("260", 'c'))
+        .and_then(year)
+        .or_else(|| {
+            record
+                .control_field("008")
+                .and_then(|field| field.get(7..11))
+                .and_then(year)
+        })
+        .wrap_err("Publication date (264 $c, 260 $c or 008/07-10) is missing")?;
+    let release = NaiveDate::from_ymd_opt(year, 1, 1)
+        .wrap_err_with(|| eyre!("Publication year {year} is invalid"))?;
+
+    let publisher = record
+        .subfield("264", 'b')
+        .or_else(|| record.subfield("260", 'b'))
+        .map(clean)
+        .filter(|publisher| !publisher.is_empty())
+        .unwrap_or_else(|| UNKNOWN_PUBLISHER.to_string());
+
+    let category = record
+        .subfield("650", 'a')
+        .map(clean)
+        .filter(|category| !category.is_empty())
+        .unwrap_or_else(|| UNKNOWN_CATEGORY.to_string());
+
+    let authors = record
+        .data_fields("100")
+        .chain(record.data_fields("700"))
+        .filter_map(|field| field.subfield('a'))
+        .filter_map(heading)
+        .collect();
+
+    Ok(Entry {
+        title,
+        isbn,
+        release,
+        publisher,
+        category,
+        authors,
+    })
+}
+
+/// Splits a personal name heading like `Shevchenko, Taras Hryhorovych` into its parts.
+fn heading(value: &str) -> Option<Heading> {
+    let value = clean(value);
+
+    let (lastname, rest) = match value.split_once(',') {
+        Some((lastname, rest)) => (lastname.trim().to_string(), rest.trim()),
+        None => {
+            let (rest, lastname) = value.rsplit_once(' ').unwrap_or(("", &value));
+            (lastname.to_string(), rest.trim())
+        }
+    };
+
+    if lastname.is_empty() {
+        return None;
+    }
+
+    let mut words = rest.split_whitespace();
+    let name = words.next().unwrap_or_default().to_string();
+    let surname = words.collect::<Vec<_>>().join(" ");
+
+    Some(Heading {
+        name,
+        lastname,
+        surname,
+    })
+}
+
+fn year(value: &str) -> Option<i32> {
+    value
+        .as_bytes()
+        .windows(4)
+        .find(|window| window.iter().all(u8::is_ascii_digit))
+        .and_then(|window| std::str::from_utf8(window).ok())
+        .and_then(|year| year.parse().ok())
+}
+
+/// Strips ISBD punctuation MARC21 keeps at the end of subfields.
+fn clean(value: &str) -> String {
+    value
+        .trim()
+        .trim_end_matches([' ', '/', ':', ';', ',', '=', '.'])
+        .trim()
+        .to_string()
+}
@@ -0,0 +1,120 @@
+use color_eyre::{
+    eyre::{bail, eyre, Context},
+    Result,
+};
+
+use super::{ControlField, DataField, Record, Subfield};
+
+const LEADER_LENGTH: usize = 24;
+const DIRECTORY_ENTRY_LENGTH: usize = 12;
+const FIELD_TERMINATOR: u8 = 0x1e;
+const RECORD_TERMINATOR: u8 = 0x1d;
+const SUBFIELD_DELIMITER: u8 = 0x1f;
+
+/// Parses a file of binary MARC21 records.
+///
+/// Data is decoded as UTF-8, MARC-8 encoded records are decoded lossily.
+pub fn parse(data: &[u8]) -> Result<Vec<Record>> {
+    let mut records = Vec::new();
+    let mut rest = data;
+
+    while let Some(start) = rest.iter().position(|b| !b.is_ascii_whitespace()) {
+        rest = &rest[start..];
+
+        let length = number(rest, 0, 5).wrap_err_with(|| eyre!("Invalid record length"))?;
+        if length < LEADER_LENGTH || length > rest.len() {
+            bail!("Record {} has invalid length {length}", records.len() + 1);
+        }
+
+        let record = parse_record(&rest[..length])
+            .wrap_err_with(|| eyre!("Unable to parse record {}", records.len() + 1))?;
+        records.push(record);
+        rest = &rest[length..];
+    }
+
+    Ok(records)
+}
+
+fn parse_record(data: &[u8]) -> Result<Record> {
+    if data.last() != Some(&RECORD_TERMINATOR) {
+        bail!("Record is not terminated");
+    }
+
+    let leader = text(&data[..LEADER_LENGTH]);
+    let base_address =
+        number(data, 12, 17).wrap_err_with(|| eyre!("Invalid base address of data"))?;
+    if base_address <= LEADER_LENGTH || base_address > data.len() {
+        bail!("Base address of data {base_address} is out of record");
+    }
+
+    // Directory ends with a field terminator right before the base address.
+    let directory = &data[LEADER_LENGTH..base_address - 1];
+    if !directory.len().is_multiple_of(DIRECTORY_ENTRY_LENGTH) {
+        bail!("Directory length is not a multiple of {DIRECTORY_ENTRY_LENGTH}");
+    }
+
+    let mut control_fields = Vec::new();
+    let mut data_fields = Vec::new();
+
+    for entry in directory.chunks(DIRECTORY_ENTRY_LENGTH) {
+        let tag = text(&entry[..3]);
+        let length = number(entry, 3, 7).wrap_err_with(|| eyre!("Invalid length of {tag}"))?;
+        let start = number(entry, 7, 12).wrap_err_with(|| eyre!("Invalid start of {tag}"))?;
+
+        let Some(field) = data.get(base_address + start..base_address + start + length) else {
+            bail!("Field {tag} is out of record");
+        };
+        let field = field.strip_suffix(&[FIELD_TERMINATOR]).unwrap_or(field);
+
+        if tag.as_str() < "010" {
+            control_fields.push(ControlField {
+                tag,
+                value: text(field),
+            });
+        } else {
+            data_fields.push(parse_data_field(tag, field)?);
+        }
+    }
+
+    Ok(Record {
+        leader,
+        control_fields,
+        data_fields,
+    })
+}
+
+fn parse_data_field(tag: String, field: &[u8]) -> Result<DataField> {
+    if field.len() < 2 {
+        bail!("Field {tag} has no indicators");
+    }
+
+    let subfields = field[2..]
+        .split(|b| *b == SUBFIELD_DELIMITER)
+        .filter(|subfield| !subfield.is_empty())
+        .map(|subfield| Subfield {
+            code: subfield[0] as char,
+            value: text(&subfield[1..]),
+        })
+        .collect();
+
+    Ok(DataField {
+        tag,
+        ind1: field[0] as char,
+        ind2: field[1] as char,
+        subfields,
+    })
+}
+
+fn number(data: &[u8], from: usize, to: usize) -> Result<usize> {
+    let digits = data
+        .get(from..to)
+        .ok_or_else(|| eyre!("Unexpected end of data"))?;
+
+    std::str::from_utf8(digits)?
+        .parse()
+        .wrap_err_with(|| eyre!("`{}` is not a number", text(digits)))
+}
+
+fn text(data: &[u8]) -> String {
+    String::from_utf8_lossy(data).into_owned()
+}
@@ -0,0 +1,65 @@
+//! MARC21 bibliographic records in ISO 2709 and MARCXML serializations.
+
+pub mod export;
+pub mod import;
+pub mod iso2709;
+pub mod xml;
+
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub leader: String,
+    pub control_fields: Vec<ControlField>,
+    pub data_fields: Vec<DataField>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ControlField {
+    pub tag: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DataField {
+    pub tag: String,
+    pub ind1: char,
+    pub ind2: char,
+    pub subfields: Vec<Subfield>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Subfield {
+    pub code: char,
+    pub value: String,
+}
+
+impl Record {
+    pub fn control_field(&self, tag: &str) -> Option<&str> {
+        self.control_fields
+            .iter()
+            .find(|field| field.tag == tag)
+            .map(|field| field.value.as_str())
+    }
+
+    pub fn data_fields<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a DataField> {
+        self.data_fields
+            .iter()
+            .filter(move |field| field.tag == tag)
+    }
+
+    /// First subfield `code` of the first field `tag` which has it.
+    pub fn subfield(&self, tag: &str, code: char) -> Option<&str> {
+        self.data_fields
+            .iter()
+            .filter(|field| field.tag == tag)
+            .find_map(|field| field.subfield(code))
+    }
+}
+
+impl DataField {
+    pub fn subfield(&self, code: char) -> Option<&str> {
+        self.subfields
+            .iter()
+            .find(|subfield| subfield.code == code)
+            .map(|subfield| subfield.value.as_str())
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt::Write;
+
+use color_eyre::{
+    eyre::{bail, eyre, Context},
+    Result,
+};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use super::{ControlField, DataField, Record, Subfield};
+
+const NAMESPACE: &str = "http://www.loc.gov/MARC21/slim";
+
+/// Parses a MARCXML `collection` or a single `record`.
+pub fn parse(data: &[u8]) -> Result<Vec<Record>> {
+    let mut reader = Reader::from_reader(data);
+    let mut buf = Vec::new();
+
+    let mut records = Vec::new();
+    let mut record: Option<Record> = None;
+    let mut text = String::new();
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .wrap_err_with(|| eyre!("Invalid XML at {}", reader.buffer_position()))?;
+
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let is_empty = matches!(event, Event::Empty(_));
+                text.clear();
+
+                match e.local_name().as_ref() {
+                    b"record" => {
+                        record = Some(Record {
+                            leader: String::new(),
+                            control_fields: Vec::new(),
+                            data_fields: Vec::new(),
+                        })
+                    }
+                    b"controlfield" => current(&mut record)?.control_fields.push(ControlField {
+                        tag: attribute(e, "tag")?,
+                        value: String::new(),
+                    }),
+                    b"datafield" => current(&mut record)?.data_fields.push(DataField {
+                        tag: attribute(e, "tag")?,
+                        ind1: indicator(e, "ind1")?,
+                        ind2: indicator(e, "ind2")?,
+                        subfields: Vec::new(),
+                    }),
+                    b"subfield" => {
+                        let code = attribute(e, "code")?
+                            .chars()
+                            .next()
+                            .ok_or_else(|| eyre!("Subfield code is empty"))?;
+                        current(&mut record)?
+                            .data_fields
+                            .last_mut()
+                            .ok_or_else(|| eyre!("Subfield is outside of datafield"))?
+                            .subfields
+                            .push(Subfield {
+                                code,
+                                value: String::new(),
+                            });
+                    }
+                    _ => {}
+                }
+
+                if is_empty && e.local_name().as_ref() == b"record" {
+                    records.extend(record.take());
+                }
+            }
+            Event::Text(e) => text.push_str(
+                &e.unescape()
+                    .wrap_err_with(|| eyre!("Invalid text at {}", reader.buffer_position()))?,
+            ),
+            Event::CData(e) => text.push_str(&String::from_utf8_lossy(&e)),
+            Event::End(e) => {
+                match e.local_name().as_ref() {
+                    b"record" => records.extend(record.take()),
+                    b"leader" => current(&mut record)?.leader = text.clone(),
+                    b"controlfield" => {
+                        if let Some(field) = current(&mut record)?.control_fields.last_mut() {
+                            field.value = text.clone();
+                        }
+                    }
+                    b"subfield" => {
+                        if let Some(subfield) = current(&mut record)?
+                            .data_fields
+                            .last_mut()
+                            .and_then(|field| field.subfields.last_mut())
+                        {
+                            subfield.value = text.clone();
+                        }
+                    }
+                    _ => {}
+                }
+                text.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+
+        buf.clear();
+    }
+
+    Ok(records)
+}
+
+fn current(record: &mut Option<Record>) -> Result<&mut Record> {
+    record
+        .as_mut()
+        .ok_or_else(|| eyre!("Field is outside of record"))
+}
+
+fn attribute(e: &BytesStart, name: &str) -> Result<String> {
+    let Some(attribute) = e.try_get_attribute(name)? else {
+        bail!(
+            "Attribute `{name}` of `{}` is missing",
+            String::from_utf8_lossy(e.local_name().as_ref())
+        );
+    };
+
+    Ok(attribute.unescape_value()?.into_owned())
+}
+
+fn indicator(e: &BytesStart, name: &str) -> Result<char> {
+    Ok(attribute(e, name)?.chars().next().unwrap_or(' '))
+}
+
+/// Serializes records as a MARCXML `collection`.
+pub fn write(records: &[Record]) -> String {
+    let mut xml = String::new();
+
+    // Writing into a `String` cannot fail.
+    let _ = writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(xml, r#"<collection xmlns="{NAMESPACE}">"#);
+
+    for record in records {
+        let _ = writeln!(xml, "  <record>");
+        let _ = writeln!(xml, "    <leader>{}</leader>", escape(&record.leader));
+
+        for field in &record.control_fields {
+            let _ = writeln!(
+                xml,
+                r#"    <controlfield tag="{}">{}</controlfield>"#,
+                escape(&field.tag),
+                escape(&field.value)
+            );
+        }
+
+        for field in &record.data_fields {
+            let _ = writeln!(
+                xml,
+                r#"    <datafield tag="{}" ind1="{}" ind2="{}">"#,
+                escape(&field.tag),
+                escape(&field.ind1.to_string()),
+                escape(&field.ind2.to_string())
+            );
+
+            for subfield in &field.subfields {
+                let _ = writeln!(
+                    xml,
+                    r#"      <subfield code="{}">{}</subfield>"#,
+                    escape(&subfield.code.to_string()),
+                    escape(&subfield.value)
+                );
+            }
+
+            let _ = writeln!(xml, "    </datafield>");
+        }
+
+        let _ = writeln!(xml, "  </record>");
+    }
+
+    let _ = writeln!(xml, "</collection>");
+
+    xml
+}
@@ -1,7 +1,13 @@
+// Models with several `schema` rules repeat `skip_on_field_errors`, which is not a duplicate.
+#![allow(clippy::duplicated_attributes)]
+
+use async_graphql::{Enum, InputObject, SimpleObject};
 use serde::{Deserialize, Serialize};
-use sqlx::types::chrono::NaiveDate;
+use sqlx::postgres::{PgHasArrayType, PgTypeInfo};
+use sqlx::types::chrono::{DateTime, NaiveDate, Utc};
+use validator::Validate;
 
-#[derive(sqlx::Type, Serialize, Deserialize, Debug)]
+#[derive(sqlx::Type, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
 #[sqlx(type_name = "book_status", rename_all = "snake_case")]
 pub enum BookStatus {
     Excellent,
@@ -10,7 +16,7 @@ pub enum BookStatus {
     Unsatisfactory,
 }
 
-#[derive(sqlx::Type, Serialize, Deserialize, Debug)]
+#[derive(sqlx::Type, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
 #[sqlx(type_name = "student_status", rename_all = "snake_case")]
 pub enum StudentStatus {
     Graduated,
@@ -18,148 +24,707 @@ pub enum StudentStatus {
     Moved,
 }
 
-#[derive(sqlx::Type, Serialize, Deserialize, Debug)]
+#[derive(sqlx::Type, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
 #[sqlx(type_name = "teacher_status", rename_all = "snake_case")]
 pub enum TeacherStatus {
     Fired,
     Moved,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(sqlx::Type, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[sqlx(type_name = "hold_status", rename_all = "snake_case")]
+pub enum HoldStatus {
+    Pending,
+    Ready,
+    Fulfilled,
+    Cancelled,
+    Expired,
+}
+
+#[derive(sqlx::Type, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[sqlx(type_name = "notification_kind", rename_all = "snake_case")]
+pub enum NotificationKind {
+    DueSoon,
+    Overdue,
+    HoldReady,
+}
+
+#[derive(sqlx::Type, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[sqlx(type_name = "notification_status", rename_all = "snake_case")]
+pub enum NotificationStatus {
+    Pending,
+    Sent,
+    Failed,
+}
+
+#[derive(sqlx::Type, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[sqlx(type_name = "job_run_status", rename_all = "snake_case")]
+pub enum JobRunStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+#[derive(sqlx::Type, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[sqlx(type_name = "webhook_event", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    LoanCreated,
+    LoanReturnedLate,
+    StudentCardIssued,
+}
+
+// Derived for enums only from sqlx 0.7 on.
+impl PgHasArrayType for WebhookEvent {
+    fn array_type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("_webhook_event")
+    }
+}
+
+#[derive(sqlx::Type, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[sqlx(type_name = "webhook_delivery_status", rename_all = "snake_case")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    Dead,
+}
+
+#[derive(sqlx::Type, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[sqlx(type_name = "ledger_entry_kind", rename_all = "snake_case")]
+pub enum LedgerEntryKind {
+    LateReturn,
+    LostBook,
+    DamagedBook,
+    Payment,
+    Waiver,
+}
+
+impl LedgerEntryKind {
+    /// Payments and waivers take from the balance, the other kinds are charges adding to it.
+    pub fn is_charge(self) -> bool {
+        !matches!(self, LedgerEntryKind::Payment | LedgerEntryKind::Waiver)
+    }
+}
+
+#[derive(sqlx::Type, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[sqlx(type_name = "card_state", rename_all = "snake_case")]
+pub enum CardState {
+    Active,
+    Lost,
+    Blocked,
+    Expired,
+}
+
+#[derive(sqlx::Type, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[sqlx(type_name = "transfer_status", rename_all = "snake_case")]
+pub enum TransferStatus {
+    InTransit,
+    Received,
+    Cancelled,
+}
+
+#[derive(sqlx::Type, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[sqlx(type_name = "classification", rename_all = "snake_case")]
+pub enum Classification {
+    Udc,
+    Dewey,
+}
+
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "StudentInput")]
 pub struct Student {
+    #[graphql(skip_input)]
     pub id: i32,
+    #[validate(length(min = 1, max = 100))]
     pub name: String,
+    #[validate(length(min = 1, max = 100))]
     pub lastname: String,
+    #[validate(length(max = 100))]
     pub surname: String,
+    #[validate(range(min = 14, max = 120))]
     pub age: i16,
+    #[graphql(name = "facultyCurriculumId")]
     pub faculty_curriculum: i32,
+    #[validate(range(min = 1))]
     pub group: i16,
     pub start_study_date: NaiveDate,
     pub status: Option<StudentStatus>,
+    #[validate(email)]
+    pub email: Option<String>,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "FacultyInput")]
 pub struct Faculty {
+    #[graphql(skip_input)]
     pub id: i32,
+    #[validate(length(min = 1, max = 100))]
     pub name: String,
+    #[validate(length(min = 1, max = 10))]
     pub letter: String,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "CurriculumInput")]
 pub struct Curriculum {
+    #[graphql(skip_input)]
     pub id: i32,
+    #[validate(length(min = 1, max = 100))]
     pub name: String,
+    #[validate(length(min = 1, max = 10))]
     pub letter: String,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "FacultyCurriculumInput")]
 pub struct FacultyCurriculum {
+    #[graphql(skip_input)]
     pub id: i32,
+    #[graphql(name = "facultyId")]
     pub faculty: i32,
+    #[graphql(name = "curriculumId")]
     pub curriculum: i32,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "TeacherInput")]
 pub struct Teacher {
+    #[graphql(skip_input)]
     pub id: i32,
+    #[validate(length(min = 1, max = 100))]
     pub name: String,
+    #[validate(length(min = 1, max = 100))]
     pub lastname: String,
+    #[validate(length(max = 100))]
     pub surname: String,
+    #[validate(range(min = 18, max = 120))]
     pub age: i16,
+    #[graphql(name = "facultyId")]
     pub faculty: i32,
     pub status: Option<TeacherStatus>,
+    #[validate(email)]
+    pub email: Option<String>,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "BookInput")]
+#[validate(schema(
+    function = "crate::validation::book_call_number",
+    skip_on_field_errors = false
+))]
 pub struct Book {
+    #[graphql(skip_input)]
     pub id: i32,
+    #[validate(length(min = 1, max = 500))]
     pub title: String,
     pub release: NaiveDate,
+    #[graphql(name = "publisherId")]
     pub publisher: i32,
+    #[graphql(name = "categoryId")]
     pub category: i32,
     pub student_access: bool,
+    pub isbn: Option<String>,
+    /// Branch the copy belongs to, it stays the same while the copy is lent or in transit.
+    #[graphql(name = "branchId")]
+    pub branch: Option<i32>,
+    /// Classification of `call_number`, set exactly when the book has one.
+    pub classification: Option<Classification>,
+    #[validate(length(min = 1, max = 100))]
+    pub call_number: Option<String>,
+    /// Filing order of `call_number`, derived from it when the book is saved.
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub call_number_sort: Option<String>,
+    #[graphql(name = "shelfId")]
+    pub shelf: Option<i32>,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "CategoryInput")]
 pub struct Category {
+    #[graphql(skip_input)]
     pub id: i32,
+    #[validate(length(min = 1, max = 100))]
     pub name: String,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "AuthorInput")]
 pub struct Author {
+    #[graphql(skip_input)]
     pub id: i32,
+    #[validate(length(min = 1, max = 100))]
     pub name: String,
+    #[validate(length(min = 1, max = 100))]
     pub lastname: String,
+    #[validate(length(max = 100))]
     pub surname: String,
+    #[validate(regex = "crate::validation::COUNTRY_CODE")]
+    #[graphql(name = "countryCode")]
     pub country: String,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "AuthorBookInput")]
 pub struct AuthorBook {
+    #[graphql(skip_input)]
     pub id: i32,
     pub author_id: i32,
     pub book_id: i32,
+    #[validate(range(min = 1))]
     pub num: i16,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "LibrarianInput")]
 pub struct Librarian {
+    #[graphql(skip_input)]
     pub id: i32,
+    #[validate(length(min = 1, max = 100))]
     pub name: String,
+    #[validate(length(min = 1, max = 100))]
     pub lastname: String,
+    #[validate(length(max = 100))]
     pub surname: String,
+    #[validate(range(min = 18, max = 120))]
     pub age: i16,
+    #[graphql(name = "branchId")]
+    pub branch: Option<i32>,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "BranchInput")]
+pub struct Branch {
+    #[graphql(skip_input)]
+    pub id: i32,
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    /// Faculty the library belongs to, `None` for the central library.
+    #[graphql(name = "facultyId")]
+    pub faculty: Option<i32>,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
+}
+
+/// Shelf of a room of a branch, books on it are filed by call number.
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "ShelfInput")]
+pub struct Shelf {
+    #[graphql(skip_input)]
+    pub id: i32,
+    #[graphql(name = "branchId")]
+    pub branch: Option<i32>,
+    #[validate(length(min = 1, max = 100))]
+    pub room: String,
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
+}
+
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "PublisherInput")]
 pub struct Publisher {
+    #[graphql(skip_input)]
     pub id: i32,
+    #[validate(length(min = 1, max = 200))]
     pub name: String,
+    #[validate(regex = "crate::validation::COUNTRY_CODE")]
+    #[graphql(name = "countryCode")]
     pub country: String,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "CountryInput")]
 pub struct Country {
+    #[validate(regex = "crate::validation::COUNTRY_CODE")]
     pub code: String,
+    #[validate(length(min = 1, max = 100))]
     pub name: String,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "StudentCardInput")]
+#[validate(schema(
+    function = "crate::validation::student_card_dates",
+    skip_on_field_errors = false
+))]
 pub struct StudentCard {
+    #[graphql(skip_input)]
     pub id: i32,
+    #[graphql(name = "studentId")]
     pub student: i32,
     pub issue_date: NaiveDate,
+    pub expiry_date: NaiveDate,
+    pub state: CardState,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "TeacherCardInput")]
+#[validate(schema(
+    function = "crate::validation::teacher_card_dates",
+    skip_on_field_errors = false
+))]
 pub struct TeacherCard {
+    #[graphql(skip_input)]
     pub id: i32,
+    #[graphql(name = "teacherId")]
     pub teacher: i32,
     pub issue_date: NaiveDate,
+    pub expiry_date: NaiveDate,
+    pub state: CardState,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "StudentsBorrowingInput")]
+#[validate(schema(
+    function = "crate::validation::students_borrowing_required_return_date",
+    skip_on_field_errors = false
+))]
+#[validate(schema(
+    function = "crate::validation::students_borrowing_return_date",
+    skip_on_field_errors = false
+))]
 pub struct StudentsBorrowing {
+    #[graphql(skip_input)]
     pub id: i32,
+    #[graphql(name = "studentCardId")]
     pub student_card: i32,
+    #[graphql(name = "librarianId")]
     pub librarian: i32,
+    #[graphql(name = "bookId")]
     pub book: i32,
     pub book_status_start: BookStatus,
     pub book_status_finish: Option<BookStatus>,
     pub borrow_date: NaiveDate,
     pub return_date: Option<NaiveDate>,
     pub required_return_date: NaiveDate,
+    #[graphql(name = "branchId")]
+    pub branch: Option<i32>,
+    #[graphql(name = "returnBranchId")]
+    pub return_branch: Option<i32>,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "TeachersBorrowingInput")]
+#[validate(schema(
+    function = "crate::validation::teachers_borrowing_return_date",
+    skip_on_field_errors = false
+))]
 pub struct TeachersBorrowing {
+    #[graphql(skip_input)]
     pub id: i32,
+    #[graphql(name = "teacherCardId")]
     pub teacher_card: i32,
+    #[graphql(name = "librarianId")]
     pub librarian: i32,
+    #[graphql(name = "bookId")]
     pub book: i32,
     pub book_status_start: BookStatus,
     pub book_status_finish: Option<BookStatus>,
     pub borrow_date: NaiveDate,
     pub return_date: Option<NaiveDate>,
+    #[graphql(name = "branchId")]
+    pub branch: Option<i32>,
+    #[graphql(name = "returnBranchId")]
+    pub return_branch: Option<i32>,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
+}
+
+#[derive(sqlx::FromRow, SimpleObject, Serialize, Deserialize, Clone, Debug)]
+#[graphql(complex, name = "Renewal")]
+pub struct StudentsBorrowingRenewal {
+    pub id: i32,
+    #[graphql(name = "studentsBorrowingId")]
+    pub students_borrowing: i32,
+    #[graphql(name = "librarianId")]
+    pub librarian: i32,
+    pub renewal_date: NaiveDate,
+    pub previous_return_date: NaiveDate,
+    pub required_return_date: NaiveDate,
+}
+
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "HoldInput")]
+#[validate(schema(
+    function = "crate::validation::hold_card",
+    skip_on_field_errors = false
+))]
+#[validate(schema(
+    function = "crate::validation::hold_expire_date",
+    skip_on_field_errors = false
+))]
+pub struct Hold {
+    #[graphql(skip_input)]
+    pub id: i32,
+    #[graphql(name = "bookId")]
+    pub book: i32,
+    #[graphql(name = "studentCardId")]
+    pub student_card: Option<i32>,
+    #[graphql(name = "teacherCardId")]
+    pub teacher_card: Option<i32>,
+    pub request_date: NaiveDate,
+    pub expire_date: Option<NaiveDate>,
+    pub status: HoldStatus,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
+}
+
+#[derive(
+    sqlx::FromRow, SimpleObject, InputObject, Serialize, Deserialize, Validate, Clone, Debug,
+)]
+#[graphql(complex, input_name = "BookTransferInput")]
+#[validate(schema(
+    function = "crate::validation::book_transfer_branches",
+    skip_on_field_errors = false
+))]
+#[validate(schema(
+    function = "crate::validation::book_transfer_received_date",
+    skip_on_field_errors = false
+))]
+pub struct BookTransfer {
+    #[graphql(skip_input)]
+    pub id: i32,
+    #[graphql(name = "bookId")]
+    pub book: i32,
+    #[graphql(name = "fromBranchId")]
+    pub from_branch: i32,
+    #[graphql(name = "toBranchId")]
+    pub to_branch: i32,
+    pub sent_date: NaiveDate,
+    pub received_date: Option<NaiveDate>,
+    pub status: TransferStatus,
+    #[graphql(skip_input)]
+    #[serde(default)]
+    pub version: i32,
+}
+
+#[derive(SimpleObject, Serialize, Deserialize, Clone, Debug)]
+#[graphql(complex)]
+pub struct Notification {
+    pub id: i32,
+    pub kind: NotificationKind,
+    pub recipient: String,
+    pub subject: String,
+    pub body: String,
+    #[graphql(name = "studentsBorrowingId")]
+    pub students_borrowing: Option<i32>,
+    #[graphql(name = "holdId")]
+    pub hold: Option<i32>,
+    pub status: NotificationStatus,
+    pub attempts: i16,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+}
+
+#[derive(SimpleObject, Serialize, Deserialize, Clone, Debug)]
+pub struct JobRun {
+    pub id: i32,
+    pub job: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub status: JobRunStatus,
+    pub message: Option<String>,
+    /// Slot of the schedule the run is for, `None` for runs started by hand.
+    pub scheduled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Validate, Debug)]
+pub struct WebhookSubscription {
+    #[serde(default)]
+    pub id: i32,
+    #[validate(custom = "crate::validation::webhook_url")]
+    pub url: String,
+    #[validate(length(min = 1))]
+    pub events: Vec<WebhookEvent>,
+    // Only written, receivers prove they know it by checking signatures.
+    #[serde(skip_serializing)]
+    #[validate(length(min = 16))]
+    pub secret: String,
+    #[serde(default = "default_active")]
+    pub active: bool,
+    #[serde(skip_deserializing)]
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub version: i32,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub subscription: i32,
+    pub event: WebhookEvent,
+    pub payload: serde_json::Value,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i16,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WebhookAttempt {
+    pub id: i32,
+    pub delivery: i32,
+    pub attempted_at: DateTime<Utc>,
+    /// Status of the response, `None` when the receiver could not be reached.
+    pub response_status: Option<i16>,
+    pub error: Option<String>,
+}
+
+#[derive(SimpleObject, Serialize, Deserialize, Validate, Clone, Debug)]
+#[graphql(complex)]
+#[validate(schema(
+    function = "crate::validation::ledger_entry_card",
+    skip_on_field_errors = false
+))]
+#[validate(schema(
+    function = "crate::validation::ledger_entry_borrowing",
+    skip_on_field_errors = false
+))]
+#[validate(schema(
+    function = "crate::validation::ledger_entry_waiver",
+    skip_on_field_errors = false
+))]
+pub struct LedgerEntry {
+    #[serde(default)]
+    pub id: i32,
+    #[graphql(name = "studentCardId")]
+    pub student_card: Option<i32>,
+    #[graphql(name = "teacherCardId")]
+    pub teacher_card: Option<i32>,
+    pub kind: LedgerEntryKind,
+    /// In cents, always positive, the kind tells whether it adds to the balance or takes from it.
+    #[validate(range(min = 1))]
+    pub amount: i32,
+    #[graphql(name = "studentsBorrowingId")]
+    pub students_borrowing: Option<i32>,
+    #[graphql(name = "teachersBorrowingId")]
+    pub teachers_borrowing: Option<i32>,
+    #[graphql(name = "librarianId")]
+    pub librarian: Option<i32>,
+    #[validate(length(min = 1))]
+    pub reason: Option<String>,
+    #[serde(skip_deserializing)]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Balance of the patron of a card in cents, across all the cards of the patron.
+#[derive(SimpleObject, Serialize, Clone, Debug)]
+pub struct Balance {
+    pub card_table: String,
+    pub card: i32,
+    /// Student or teacher holding the card.
+    pub patron: i32,
+    pub balance: i64,
+    /// Limit of the fine policy of the card's table, `None` without a policy.
+    pub max_balance: Option<i32>,
+    /// Checkouts of the patron are refused until they pay down their balance.
+    pub blocked: bool,
+}
+
+#[derive(Serialize, Deserialize, Validate, Debug)]
+pub struct FinePolicy {
+    /// Taken from the path by updates.
+    #[serde(default)]
+    pub card_table: String,
+    /// In cents, checkouts on a card owing more are refused.
+    #[validate(range(min = 0))]
+    pub max_balance: i32,
+    #[serde(default)]
+    pub version: i32,
 }
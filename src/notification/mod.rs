@@ -0,0 +1,195 @@
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use sqlx::{Pool, Postgres, Transaction};
+
+use crate::model::{Notification, NotificationKind};
+
+use self::template::Message;
+
+pub mod template;
+pub mod worker;
+
+// Students are reminded this many days before the required return date.
+const DUE_SOON_DAYS: i32 = 3;
+// Overdue reminders are repeated with this interval until the book is returned.
+const OVERDUE_REPEAT_DAYS: i32 = 7;
+
+/// Puts due-soon, overdue and hold-ready notices for patrons with an email into the outbox.
+///
+/// Only students borrowings have a required return date, so teachers get hold-ready notices only.
+pub async fn generate(db: &Pool<Postgres>) -> Result<Vec<Notification>> {
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))?;
+
+    let mut notifications = generate_due_soon(&mut tx).await?;
+    notifications.append(&mut generate_overdue(&mut tx).await?);
+    notifications.append(&mut generate_hold_ready(&mut tx).await?);
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit notifications"))?;
+
+    Ok(notifications)
+}
+
+pub async fn generate_due_soon(tx: &mut Transaction<'_, Postgres>) -> Result<Vec<Notification>> {
+    let mut notifications = Vec::new();
+
+    let due_soon = sqlx::query!(
+        r#"SELECT sb.id, sb.required_return_date, b.title, s.name, s.lastname, s.email as "email!"
+        FROM students_borrowing sb
+        JOIN student_card sc ON sc.id = sb.student_card
+        JOIN student s ON s.id = sc.student
+        JOIN book b ON b.id = sb.book
+        WHERE sb.return_date IS NULL
+        AND s.email IS NOT NULL
+        AND sb.required_return_date BETWEEN CURRENT_DATE AND CURRENT_DATE + $1::INTEGER
+        AND NOT EXISTS (
+            SELECT 1 FROM notification n
+            WHERE n.kind = 'due_soon' AND n.students_borrowing = sb.id
+            AND n.created_at >= sb.required_return_date - $1::INTEGER
+        )"#,
+        DUE_SOON_DAYS
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load due soon students_borrowings"))?;
+
+    for row in due_soon {
+        let message = template::due_soon(
+            &row.name,
+            &row.lastname,
+            &row.title,
+            row.required_return_date,
+        );
+        let notification = enqueue(
+            tx,
+            NotificationKind::DueSoon,
+            &row.email,
+            message,
+            Some(row.id),
+            None,
+        )
+        .await?;
+        notifications.push(notification);
+    }
+
+    Ok(notifications)
+}
+
+pub async fn generate_overdue(tx: &mut Transaction<'_, Postgres>) -> Result<Vec<Notification>> {
+    let mut notifications = Vec::new();
+
+    let overdue = sqlx::query!(
+        r#"SELECT sb.id, sb.required_return_date, b.title, s.name, s.lastname, s.email as "email!"
+        FROM students_borrowing sb
+        JOIN student_card sc ON sc.id = sb.student_card
+        JOIN student s ON s.id = sc.student
+        JOIN book b ON b.id = sb.book
+        WHERE sb.return_date IS NULL
+        AND s.email IS NOT NULL
+        AND sb.required_return_date < CURRENT_DATE
+        AND NOT EXISTS (
+            SELECT 1 FROM notification n
+            WHERE n.kind = 'overdue' AND n.students_borrowing = sb.id
+            AND n.created_at >= CURRENT_DATE - $1::INTEGER
+        )"#,
+        OVERDUE_REPEAT_DAYS
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load overdue students_borrowings"))?;
+
+    for row in overdue {
+        let message = template::overdue(
+            &row.name,
+            &row.lastname,
+            &row.title,
+            row.required_return_date,
+        );
+        let notification = enqueue(
+            tx,
+            NotificationKind::Overdue,
+            &row.email,
+            message,
+            Some(row.id),
+            None,
+        )
+        .await?;
+        notifications.push(notification);
+    }
+
+    Ok(notifications)
+}
+
+pub async fn generate_hold_ready(tx: &mut Transaction<'_, Postgres>) -> Result<Vec<Notification>> {
+    let mut notifications = Vec::new();
+
+    let hold_ready = sqlx::query!(
+        r#"SELECT h.id, b.title,
+        COALESCE(s.name, t.name) as "name!",
+        COALESCE(s.lastname, t.lastname) as "lastname!",
+        COALESCE(s.email, t.email) as "email!"
+        FROM hold h
+        JOIN book b ON b.id = h.book
+        LEFT JOIN student_card sc ON sc.id = h.student_card
+        LEFT JOIN student s ON s.id = sc.student
+        LEFT JOIN teacher_card tc ON tc.id = h.teacher_card
+        LEFT JOIN teacher t ON t.id = tc.teacher
+        WHERE h.status = 'ready'
+        AND COALESCE(s.email, t.email) IS NOT NULL
+        AND NOT EXISTS (
+            SELECT 1 FROM notification n WHERE n.kind = 'hold_ready' AND n.hold = h.id
+        )"#
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load ready holds"))?;
+
+    for row in hold_ready {
+        let message = template::hold_ready(&row.name, &row.lastname, &row.title);
+        let notification = enqueue(
+            tx,
+            NotificationKind::HoldReady,
+            &row.email,
+            message,
+            None,
+            Some(row.id),
+        )
+        .await?;
+        notifications.push(notification);
+    }
+
+    Ok(notifications)
+}
+
+async fn enqueue(
+    tx: &mut Transaction<'_, Postgres>,
+    kind: NotificationKind,
+    recipient: &str,
+    message: Message,
+    students_borrowing: Option<i32>,
+    hold: Option<i32>,
+) -> Result<Notification> {
+    sqlx::query_as!(
+        Notification,
+        r#"INSERT INTO notification
+        (kind, recipient, subject, body, students_borrowing, hold)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, kind as "kind: _", recipient, subject, body, students_borrowing, hold,
+        status as "status: _", attempts, last_error, created_at, next_attempt_at, sent_at"#,
+        kind as _,
+        recipient,
+        message.subject,
+        message.body,
+        students_borrowing,
+        hold,
+    )
+    .fetch_one(tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add notification to database"))
+}
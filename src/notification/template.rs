@@ -0,0 +1,41 @@
+use sqlx::types::chrono::NaiveDate;
+
+pub struct Message {
+    pub subject: String,
+    pub body: String,
+}
+
+pub fn due_soon(name: &str, lastname: &str, title: &str, due: NaiveDate) -> Message {
+    Message {
+        subject: format!("\"{title}\" is due on {due}"),
+        body: format!(
+            "Dear {name} {lastname},\n\n\
+            the book \"{title}\" you borrowed from the library has to be returned by {due}.\n\
+            Please return or renew it in time to avoid fines.\n\n\
+            Library"
+        ),
+    }
+}
+
+pub fn overdue(name: &str, lastname: &str, title: &str, due: NaiveDate) -> Message {
+    Message {
+        subject: format!("\"{title}\" is overdue"),
+        body: format!(
+            "Dear {name} {lastname},\n\n\
+            the book \"{title}\" you borrowed from the library had to be returned by {due}.\n\
+            Please return it as soon as possible.\n\n\
+            Library"
+        ),
+    }
+}
+
+pub fn hold_ready(name: &str, lastname: &str, title: &str) -> Message {
+    Message {
+        subject: format!("\"{title}\" is ready for pickup"),
+        body: format!(
+            "Dear {name} {lastname},\n\n\
+            the book \"{title}\" you have placed a hold on is ready for pickup at the library.\n\n\
+            Library"
+        ),
+    }
+}
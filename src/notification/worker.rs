@@ -0,0 +1,229 @@
+use std::time::Duration;
+
+use color_eyre::{
+    eyre::{bail, eyre, Context},
+    Result,
+};
+use lettre::{
+    message::Mailbox, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+use sqlx::types::chrono::Utc;
+use sqlx::{Pool, Postgres};
+
+use crate::model::Notification;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+const BATCH_SIZE: i64 = 20;
+// After this many failed attempts a notification is marked as failed and no longer retried.
+const MAX_ATTEMPTS: i16 = 5;
+// Claimed notifications are left to this worker for this long before others may send them.
+const LEASE_MINUTES: i32 = 5;
+
+enum SmtpSecurity {
+    None,
+    StartTls,
+    Tls,
+}
+
+pub struct SmtpConfig {
+    host: String,
+    port: Option<u16>,
+    security: SmtpSecurity,
+    credentials: Option<Credentials>,
+    from: Mailbox,
+}
+
+impl SmtpConfig {
+    /// Reads SMTP settings from the environment, returns `None` if `SMTP_HOST` is not set.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Ok(host) = dotenvy::var("SMTP_HOST") else {
+            return Ok(None);
+        };
+
+        let port = dotenvy::var("SMTP_PORT")
+            .ok()
+            .map(|port| port.parse())
+            .transpose()
+            .wrap_err_with(|| eyre!("Env variable `SMTP_PORT` should be a port number"))?;
+
+        let security = match dotenvy::var("SMTP_SECURITY").as_deref() {
+            Ok("none") => SmtpSecurity::None,
+            Ok("starttls") | Err(_) => SmtpSecurity::StartTls,
+            Ok("tls") => SmtpSecurity::Tls,
+            Ok(other) => bail!(
+                "Env variable `SMTP_SECURITY` should be one of `none`, `starttls`, `tls`, got `{other}`"
+            ),
+        };
+
+        let credentials = match (dotenvy::var("SMTP_USERNAME"), dotenvy::var("SMTP_PASSWORD")) {
+            (Ok(username), Ok(password)) => Some(Credentials::new(username, password)),
+            _ => None,
+        };
+
+        let from = dotenvy::var("SMTP_FROM")
+            .wrap_err_with(|| eyre!("Env variable `SMTP_FROM` should be set"))?
+            .parse()
+            .wrap_err_with(|| eyre!("Env variable `SMTP_FROM` should be a mailbox"))?;
+
+        Ok(Some(Self {
+            host,
+            port,
+            security,
+            credentials,
+            from,
+        }))
+    }
+
+    fn transport(&self) -> Result<AsyncSmtpTransport<Tokio1Executor>> {
+        let mut builder = match self.security {
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.host)
+            }
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.host)
+                    .wrap_err_with(|| eyre!("Unable to configure SMTP relay"))?
+            }
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&self.host)
+                .wrap_err_with(|| eyre!("Unable to configure SMTP relay"))?,
+        };
+
+        if let Some(port) = self.port {
+            builder = builder.port(port);
+        }
+
+        if let Some(credentials) = &self.credentials {
+            builder = builder.credentials(credentials.clone());
+        }
+
+        Ok(builder.build())
+    }
+}
+
+/// Delivers pending notifications from the outbox until the server stops.
+pub async fn run(db: Pool<Postgres>, config: SmtpConfig) -> Result<()> {
+    let mailer = config.transport()?;
+
+    loop {
+        if let Err(err) = deliver_pending(&db, &mailer, &config.from).await {
+            tracing::error!("{:?}", err);
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Sends the due notifications, returns how many were claimed.
+///
+/// Notifications are claimed for a lease and the claim is committed before anything is sent, so
+/// other replicas skip them without a transaction held open across the SMTP sessions. Each result
+/// is recorded on its own, a notification whose result could not be recorded is sent again once
+/// its lease runs out.
+pub async fn deliver_pending(
+    db: &Pool<Postgres>,
+    mailer: &AsyncSmtpTransport<Tokio1Executor>,
+    from: &Mailbox,
+) -> Result<usize> {
+    let mut notifications = sqlx::query_as!(
+        Notification,
+        r#"UPDATE notification SET next_attempt_at = now() + make_interval(mins => $2)
+        WHERE id IN (
+            SELECT id FROM notification
+            WHERE status = 'pending' AND next_attempt_at <= now()
+            ORDER BY id ASC
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, kind as "kind: _", recipient, subject, body, students_borrowing, hold,
+        status as "status: _", attempts, last_error, created_at, next_attempt_at, sent_at"#,
+        BATCH_SIZE,
+        LEASE_MINUTES
+    )
+    .fetch_all(db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to claim pending notifications"))?;
+
+    // `RETURNING` keeps no order, notifications are sent oldest first.
+    notifications.sort_by_key(|notification| notification.id);
+
+    for notification in &notifications {
+        let sent = send(mailer, from, notification).await;
+        if let Err(err) = record(db, notification, sent).await {
+            tracing::error!("{:?}", err);
+        }
+    }
+
+    Ok(notifications.len())
+}
+
+async fn record(db: &Pool<Postgres>, notification: &Notification, sent: Result<()>) -> Result<()> {
+    match sent {
+        Ok(()) => {
+            sqlx::query!(
+                r#"UPDATE notification SET
+                status = 'sent',
+                attempts = attempts + 1,
+                last_error = NULL,
+                sent_at = now()
+                WHERE id = $1"#,
+                notification.id
+            )
+            .execute(db)
+            .await
+            .wrap_err_with(|| eyre!("Unable to update notification in database"))?;
+        }
+        Err(err) => {
+            tracing::warn!(
+                "Unable to deliver notification {}: {:#}",
+                notification.id,
+                err
+            );
+
+            let attempts = notification.attempts + 1;
+            let next_attempt_at = Utc::now() + chrono::Duration::minutes(1 << attempts);
+
+            sqlx::query!(
+                r#"UPDATE notification SET
+                status = CASE WHEN $1::SMALLINT >= $2::SMALLINT
+                    THEN 'failed'::notification_status ELSE status END,
+                attempts = $1::SMALLINT,
+                last_error = $3,
+                next_attempt_at = $4
+                WHERE id = $5"#,
+                attempts,
+                MAX_ATTEMPTS,
+                format!("{:#}", err),
+                next_attempt_at,
+                notification.id
+            )
+            .execute(db)
+            .await
+            .wrap_err_with(|| eyre!("Unable to update notification in database"))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn send(
+    mailer: &AsyncSmtpTransport<Tokio1Executor>,
+    from: &Mailbox,
+    notification: &Notification,
+) -> Result<()> {
+    let message = Message::builder()
+        .from(from.clone())
+        .to(notification
+            .recipient
+            .parse()
+            .wrap_err_with(|| eyre!("Invalid recipient `{}`", notification.recipient))?)
+        .subject(&notification.subject)
+        .body(notification.body.clone())
+        .wrap_err_with(|| eyre!("Unable to build message"))?;
+
+    mailer
+        .send(message)
+        .await
+        .wrap_err_with(|| eyre!("Unable to send message"))?;
+
+    Ok(())
+}
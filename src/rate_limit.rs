@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::{ConnectInfo, State};
+use axum::http::{header, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use color_eyre::{
+    eyre::{bail, eyre, Context},
+    Result,
+};
+
+// Endpoints doing expensive writes, limited by the strict quota.
+const STRICT_PATHS: &[&str] = &["/api/marc/import"];
+// Buckets of clients which are idle long enough to be full again are dropped this often.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Token bucket quota: `burst` requests at once, refilled at `per_second`.
+#[derive(Debug, Clone, Copy)]
+struct Quota {
+    burst: f64,
+    per_second: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Class {
+    Default,
+    Strict,
+}
+
+pub struct RateLimitConfig {
+    default: Quota,
+    strict: Quota,
+    // Reverse proxies in front of the server, the client is then the last hop of `X-Forwarded-For`
+    // they did not add themselves. Without any, the header is ignored as clients can forge it.
+    pub trusted_proxies: Vec<IpAddr>,
+    // Clients tracked at once, new clients are refused until the next sweep when there are more.
+    pub max_buckets: usize,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            default: Quota {
+                burst: 100.0,
+                per_second: 20.0,
+            },
+            strict: Quota {
+                burst: 5.0,
+                per_second: 5.0 / 60.0,
+            },
+            trusted_proxies: Vec::new(),
+            max_buckets: 10_000,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Reads limits from the environment, unset variables fall back to defaults.
+    pub fn from_env() -> Result<Self> {
+        let default = Self::default();
+        let config = Self {
+            default: Quota {
+                burst: env_or("RATE_LIMIT_BURST", default.default.burst)?,
+                per_second: env_or("RATE_LIMIT_PER_SECOND", default.default.per_second)?,
+            },
+            strict: Quota {
+                burst: env_or("RATE_LIMIT_STRICT_BURST", default.strict.burst)?,
+                per_second: env_or(
+                    "RATE_LIMIT_STRICT_PER_MINUTE",
+                    default.strict.per_second * 60.0,
+                )? / 60.0,
+            },
+            trusted_proxies: trusted_proxies_from_env()?,
+            max_buckets: default.max_buckets,
+        };
+
+        for quota in [config.default, config.strict] {
+            if quota.burst < 1.0 || quota.per_second <= 0.0 {
+                bail!(
+                    "Rate limits should allow at least one request and refill at a positive rate"
+                );
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn quota(&self, class: Class) -> Quota {
+        match class {
+            Class::Default => self.default,
+            Class::Strict => self.strict,
+        }
+    }
+}
+
+fn env_or<T: FromStr>(name: &str, default: T) -> Result<T>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match dotenvy::var(name) {
+        Ok(value) => value
+            .parse()
+            .wrap_err_with(|| eyre!("Env variable `{name}` is invalid")),
+        Err(_) => Ok(default),
+    }
+}
+
+/// `RATE_LIMIT_TRUSTED_PROXIES` is a comma separated list of IP addresses, none by default.
+fn trusted_proxies_from_env() -> Result<Vec<IpAddr>> {
+    let Ok(proxies) = dotenvy::var("RATE_LIMIT_TRUSTED_PROXIES") else {
+        return Ok(Vec::new());
+    };
+
+    proxies
+        .split(',')
+        .map(str::trim)
+        .filter(|proxy| !proxy.is_empty())
+        .map(|proxy| {
+            proxy.parse().wrap_err_with(|| {
+                eyre!("Env variable `RATE_LIMIT_TRUSTED_PROXIES` should be a list of IP addresses")
+            })
+        })
+        .collect()
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, quota: Quota, now: Instant) {
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * quota.per_second).min(quota.burst);
+        self.updated = now;
+    }
+}
+
+struct Buckets {
+    buckets: HashMap<(Class, IpAddr), Bucket>,
+    swept: Instant,
+}
+
+/// Per client token buckets.
+///
+/// There is no authentication, so clients are told apart by their IP address only.
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: Arc<RateLimitConfig>,
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            buckets: Arc::new(Mutex::new(Buckets {
+                buckets: HashMap::new(),
+                swept: Instant::now(),
+            })),
+        }
+    }
+
+    /// Takes a token of the client, or returns how long to wait for the next one.
+    fn acquire(&self, class: Class, ip: IpAddr) -> Result<(), Duration> {
+        let quota = self.config.quota(class);
+        let now = Instant::now();
+        let mut guard = self.buckets.lock().unwrap_or_else(|err| err.into_inner());
+        let Buckets { buckets, swept } = &mut *guard;
+
+        // Swept on an interval rather than when full, so requests do not scan every bucket.
+        if now.duration_since(*swept) >= SWEEP_INTERVAL {
+            buckets.retain(|(class, _), bucket| {
+                let quota = self.config.quota(*class);
+                bucket.refill(quota, now);
+                bucket.tokens < quota.burst
+            });
+            *swept = now;
+        }
+
+        if buckets.len() >= self.config.max_buckets && !buckets.contains_key(&(class, ip)) {
+            return Err(SWEEP_INTERVAL.saturating_sub(now.duration_since(*swept)));
+        }
+
+        let bucket = buckets.entry((class, ip)).or_insert(Bucket {
+            tokens: quota.burst,
+            updated: now,
+        });
+        bucket.refill(quota, now);
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+
+        Err(Duration::from_secs_f64(
+            (1.0 - bucket.tokens) / quota.per_second,
+        ))
+    }
+
+    /// Address of the client, `None` for requests which did not come through a socket.
+    ///
+    /// Every proxy appends the address it got the request from to `X-Forwarded-For`, so the
+    /// header is read from the right and stops at the first hop which is not a trusted proxy.
+    /// Anything left of it was sent by the client and may be forged.
+    fn client_ip<B>(&self, request: &Request<B>) -> Option<IpAddr> {
+        let ConnectInfo(peer) = request.extensions().get::<ConnectInfo<SocketAddr>>()?;
+        let trusted = &self.config.trusted_proxies;
+
+        let mut client = peer.ip();
+        if !trusted.contains(&client) {
+            return Some(client);
+        }
+
+        let forwarded_for = request
+            .headers()
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .collect::<Vec<_>>();
+
+        for hop in forwarded_for.into_iter().rev() {
+            let Ok(hop) = hop.trim().parse() else {
+                break;
+            };
+            client = hop;
+            if !trusted.contains(&client) {
+                break;
+            }
+        }
+
+        Some(client)
+    }
+}
+
+/// Middleware answering `429 Too Many Requests` with `Retry-After` to clients over their quota.
+pub async fn limit<B>(
+    State(limiter): State<RateLimiter>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    // Requests which did not come through a socket, e.g. from tests, are not limited.
+    let Some(ip) = limiter.client_ip(&request) else {
+        return next.run(request).await;
+    };
+
+    let class = if STRICT_PATHS.contains(&request.uri().path()) {
+        Class::Strict
+    } else {
+        Class::Default
+    };
+
+    match limiter.acquire(class, ip) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            let seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, seconds.to_string())],
+                format!("Too many requests, retry after {seconds}s"),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,360 @@
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use axum::async_trait;
+use axum::http::StatusCode;
+use chrono::Local;
+use color_eyre::eyre::{eyre, Context};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::circulation::{self, CardValidity, Checkout};
+use crate::fine;
+use crate::model::LedgerEntry;
+use crate::resource::{label, Resource};
+
+use super::{Condition, Error, Repository, Shared};
+
+/// Tables kept in memory, to run the rules of the repositories without a database.
+///
+/// Rows are stored as their JSON, integer keys are generated like `SERIAL` columns.
+/// Unique keys are enforced, foreign keys are not.
+#[derive(Clone, Default)]
+pub struct MemoryDatabase {
+    tables: Arc<Mutex<HashMap<&'static str, Table>>>,
+}
+
+#[derive(Clone, Default)]
+struct Table {
+    rows: BTreeMap<Key, Value>,
+    last_id: i64,
+}
+
+/// Key of a stored row, ordered like the key column.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Key {
+    Id(i64),
+    Code(String),
+}
+
+impl Key {
+    fn of(value: &Value) -> Result<Self, Error> {
+        match value {
+            Value::Number(id) => id.as_i64().map(Key::Id),
+            Value::String(code) => Some(Key::Code(code.clone())),
+            _ => None,
+        }
+        .ok_or_else(|| Error::Internal(eyre!("Key {value} is neither an integer nor a string")))
+    }
+}
+
+impl MemoryDatabase {
+    /// Repository of `R` over the tables of this database.
+    pub fn repository<R: Resource>(&self) -> Shared<R> {
+        Arc::new(MemoryRepository::<R> {
+            db: self.clone(),
+            row: PhantomData,
+        })
+    }
+
+    /// Adds `entry` to the ledger the checkout rules read balances from.
+    ///
+    /// The ledger has no repository, its own routes only write it over Postgres.
+    pub fn add_ledger_entry(&self, entry: &LedgerEntry) -> Result<LedgerEntry, Error> {
+        let mut tables = self.tables();
+        let table = tables.entry("ledger_entry").or_default();
+
+        table.last_id += 1;
+        let mut value = to_value(entry)?;
+        value["id"] = json!(table.last_id);
+
+        let added = from_value(&value)?;
+        table.rows.insert(Key::Id(table.last_id), value);
+
+        Ok(added)
+    }
+
+    /// Sets the balance above which the patrons of the cards of `card_table` cannot borrow.
+    pub fn set_fine_policy(&self, card_table: &str, max_balance: i32) {
+        self.tables().entry("fine_policy").or_default().rows.insert(
+            Key::Code(card_table.to_string()),
+            json!({ "card_table": card_table, "max_balance": max_balance }),
+        );
+    }
+
+    fn tables(&self) -> MutexGuard<'_, HashMap<&'static str, Table>> {
+        self.tables.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+pub struct MemoryRepository<R> {
+    db: MemoryDatabase,
+    row: PhantomData<fn() -> R>,
+}
+
+impl<R: Resource> MemoryRepository<R> {
+    fn tables(&self) -> MutexGuard<'_, HashMap<&'static str, Table>> {
+        self.db.tables()
+    }
+
+    /// Stored row with `key` at `version`.
+    fn find(table: &Table, key: &R::Key, version: i32) -> Result<Key, Error> {
+        let key = Key::of(&to_value(key)?)?;
+        let row = table.rows.get(&key).ok_or(Error::NotFound)?;
+
+        if row["version"] != json!(version) {
+            return Err(Error::Modified);
+        }
+
+        Ok(key)
+    }
+
+    /// Inserts `row` after the checks of its rules.
+    fn insert_row(tables: &mut HashMap<&'static str, Table>, row: &R) -> Result<R, Error> {
+        if let Some(checkout) = row.checkout() {
+            check_checkout(tables, &checkout)?;
+        }
+
+        let table = tables.entry(R::TABLE).or_default();
+        let mut value = to_value(row)?;
+
+        if value[R::KEY].is_number() {
+            table.last_id += 1;
+            value[R::KEY] = json!(table.last_id);
+        }
+        value["version"] = json!(1);
+
+        let key = Key::of(&value[R::KEY])?;
+        if table.rows.contains_key(&key) {
+            return Err(Error::Rejected(
+                StatusCode::CONFLICT,
+                format!("{} {} already exists", label(R::TABLE), value[R::KEY]),
+            ));
+        }
+
+        let inserted = from_value(&value)?;
+        table.rows.insert(key, value);
+
+        Ok(inserted)
+    }
+}
+
+#[async_trait]
+impl<R: Resource> Repository<R> for MemoryRepository<R> {
+    async fn list(&self) -> Result<Vec<R>, Error> {
+        let tables = self.tables();
+        let Some(table) = tables.get(R::TABLE) else {
+            return Ok(Vec::new());
+        };
+
+        table.rows.values().map(from_value).collect()
+    }
+
+    async fn get(&self, key: &R::Key) -> Result<Option<R>, Error> {
+        let key = Key::of(&to_value(key)?)?;
+
+        self.tables()
+            .get(R::TABLE)
+            .and_then(|table| table.rows.get(&key))
+            .map(from_value)
+            .transpose()
+    }
+
+    async fn insert(&self, row: &R) -> Result<R, Error> {
+        Self::insert_row(&mut self.tables(), row)
+    }
+
+    async fn insert_all(&self, rows: &[R]) -> Result<Vec<R>, (Option<usize>, Error)> {
+        let mut tables = self.tables();
+        // Put back if a row is refused, like a rolled back transaction.
+        let previous = tables.get(R::TABLE).cloned();
+
+        let mut inserted = Vec::with_capacity(rows.len());
+        for (index, row) in rows.iter().enumerate() {
+            match Self::insert_row(&mut tables, row) {
+                Ok(row) => inserted.push(row),
+                Err(err) => {
+                    match previous {
+                        Some(table) => tables.insert(R::TABLE, table),
+                        None => tables.remove(R::TABLE),
+                    };
+                    return Err((Some(index), err));
+                }
+            }
+        }
+
+        Ok(inserted)
+    }
+
+    async fn update(&self, key: &R::Key, row: &R, version: i32) -> Result<R, Error> {
+        let mut tables = self.tables();
+
+        let previous_key = Key::of(&to_value(key)?)?;
+        let previous = tables
+            .get(R::TABLE)
+            .and_then(|table| table.rows.get(&previous_key))
+            .map(from_value::<R>)
+            .transpose()?;
+
+        if let Some(previous) = previous {
+            row.check_update(&previous)
+                .map_err(|(status, message)| Error::Rejected(status, message))?;
+
+            let moved = row.checkout().filter(|checkout| {
+                previous
+                    .checkout()
+                    .is_some_and(|previous| checkout.is_moved_from(&previous))
+            });
+            if let Some(checkout) = moved {
+                check_checkout(&tables, &checkout)?;
+            }
+        }
+
+        let table = tables.entry(R::TABLE).or_default();
+        let previous_key = Self::find(table, key, version)?;
+
+        let mut value = to_value(row)?;
+        value["version"] = json!(version + 1);
+
+        // Integer keys are generated and kept, other keys can be changed by the update.
+        let key = if value[R::KEY].is_number() {
+            value[R::KEY] = to_value(key)?;
+            previous_key
+        } else {
+            let key = Key::of(&value[R::KEY])?;
+            if key != previous_key && table.rows.contains_key(&key) {
+                return Err(Error::Rejected(
+                    StatusCode::CONFLICT,
+                    format!("{} {} already exists", label(R::TABLE), value[R::KEY]),
+                ));
+            }
+            table.rows.remove(&previous_key);
+            key
+        };
+
+        let updated = from_value(&value)?;
+        table.rows.insert(key, value);
+
+        Ok(updated)
+    }
+
+    async fn delete(&self, key: &R::Key, version: i32) -> Result<R, Error> {
+        let mut tables = self.tables();
+        let table = tables.entry(R::TABLE).or_default();
+        let key = Self::find(table, key, version)?;
+
+        let deleted = table.rows.remove(&key).ok_or(Error::NotFound)?;
+
+        from_value(&deleted)
+    }
+
+    async fn find(
+        &self,
+        conditions: &[Condition],
+        offset: i64,
+        limit: Option<i64>,
+    ) -> Result<Vec<R>, Error> {
+        let tables = self.tables();
+        let Some(table) = tables.get(R::TABLE) else {
+            return Ok(Vec::new());
+        };
+
+        table
+            .rows
+            .values()
+            .filter(|row| {
+                conditions.iter().all(|condition| {
+                    condition
+                        .values
+                        .iter()
+                        .any(|value| row[condition.column] == value.to_json())
+                })
+            })
+            .skip(offset.try_into().unwrap_or(0))
+            .take(limit.map_or(usize::MAX, |limit| limit.try_into().unwrap_or(0)))
+            .map(from_value)
+            .collect()
+    }
+}
+
+/// Checks the rules of `checkout` against the stored cards, ledger, books and transfers.
+fn check_checkout(tables: &HashMap<&'static str, Table>, checkout: &Checkout) -> Result<(), Error> {
+    let card = tables
+        .get(checkout.card_table)
+        .and_then(|table| table.rows.get(&Key::Id(checkout.card.into())))
+        .map(from_value::<CardValidity>)
+        .transpose()?;
+
+    circulation::check_card(checkout, card, Local::now().date_naive())
+        .map_err(|(status, message)| Error::Rejected(status, message))?;
+
+    let max_balance = tables
+        .get("fine_policy")
+        .and_then(|table| table.rows.get(&Key::Code(checkout.card_table.to_string())))
+        .and_then(|policy| policy["max_balance"].as_i64())
+        .and_then(|max_balance| i32::try_from(max_balance).ok());
+
+    circulation::check_balance(checkout, balance(tables, checkout)?, max_balance)
+        .map_err(|(status, message)| Error::Rejected(status, message))?;
+
+    let in_transit = tables.get("book_transfer").is_some_and(|table| {
+        table.rows.values().any(|transfer| {
+            transfer["book"] == json!(checkout.book) && transfer["status"] == "InTransit"
+        })
+    });
+
+    let book_branch = tables
+        .get("book")
+        .and_then(|table| table.rows.get(&Key::Id(checkout.book.into())))
+        .and_then(|book| book["branch"].as_i64())
+        .and_then(|branch| i32::try_from(branch).ok());
+
+    circulation::check_book(checkout, in_transit, book_branch)
+        .map_err(|(status, message)| Error::Rejected(status, message))?;
+
+    Ok(())
+}
+
+/// Balance of the patron of the card of `checkout` across all their cards, like [`fine::balance`].
+fn balance(tables: &HashMap<&'static str, Table>, checkout: &Checkout) -> Result<i64, Error> {
+    let patron = fine::patron_column(checkout.card_table);
+    let Some(cards) = tables.get(checkout.card_table) else {
+        return Ok(0);
+    };
+    let Some(holder) = cards.rows.get(&Key::Id(checkout.card.into())) else {
+        return Ok(0);
+    };
+
+    let patron_cards = cards
+        .rows
+        .values()
+        .filter(|card| card[patron] == holder[patron])
+        .map(|card| &card["id"])
+        .collect::<Vec<_>>();
+
+    let entries = tables
+        .get("ledger_entry")
+        .into_iter()
+        .flat_map(|table| table.rows.values())
+        .filter(|entry| patron_cards.contains(&&entry[checkout.card_table]))
+        .map(from_value::<LedgerEntry>)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(entries
+        .iter()
+        .map(|entry| match entry.kind.is_charge() {
+            true => i64::from(entry.amount),
+            false => -i64::from(entry.amount),
+        })
+        .sum())
+}
+
+fn to_value(value: &impl Serialize) -> Result<Value, Error> {
+    Ok(serde_json::to_value(value).wrap_err_with(|| eyre!("Unable to serialize row"))?)
+}
+
+fn from_value<T: DeserializeOwned>(value: &Value) -> Result<T, Error> {
+    Ok(T::deserialize(value).wrap_err_with(|| eyre!("Unable to deserialize row"))?)
+}
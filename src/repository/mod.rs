@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use axum::http::StatusCode;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::resource::Resource;
+
+use self::sqlite::SqliteResource;
+
+#[cfg(test)]
+pub mod memory;
+pub mod postgres;
+pub mod sqlite;
+
+/// Storage of the rows of `R`, shared by its routers as a trait object.
+///
+/// Writes check the rules of `R`, such as [`Resource::checkout`], in the same transaction.
+#[async_trait]
+pub trait Repository<R: Resource>: Send + Sync {
+    /// Every row, ordered by key.
+    async fn list(&self) -> Result<Vec<R>, Error>;
+
+    async fn get(&self, key: &R::Key) -> Result<Option<R>, Error>;
+
+    async fn insert(&self, row: &R) -> Result<R, Error>;
+
+    /// Inserts every row in one transaction, none of them is kept if one is refused.
+    ///
+    /// Errors come with the index of the refused row, `None` when the transaction itself failed.
+    async fn insert_all(&self, rows: &[R]) -> Result<Vec<R>, (Option<usize>, Error)>;
+
+    /// Updates the row with `key` if it is still at `version`.
+    async fn update(&self, key: &R::Key, row: &R, version: i32) -> Result<R, Error>;
+
+    /// Deletes the row with `key` if it is still at `version`.
+    async fn delete(&self, key: &R::Key, version: i32) -> Result<R, Error>;
+
+    /// Rows matching every condition, ordered by key, skipping `offset` rows and
+    /// returning at most `limit` of them.
+    async fn find(
+        &self,
+        conditions: &[Condition],
+        offset: i64,
+        limit: Option<i64>,
+    ) -> Result<Vec<R>, Error>;
+}
+
+/// Condition of [`Repository::find`], `column` holds one of `values`.
+///
+/// The values of a condition are all of the same kind. `column` is a constant of the code,
+/// never taken from a request.
+#[derive(Clone, Debug)]
+pub struct Condition {
+    pub column: &'static str,
+    pub values: Vec<Value>,
+}
+
+/// Value of a column compared by a [`Condition`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Value {
+    Int(i32),
+    Text(String),
+    Bool(bool),
+    /// Variant of an enum column, named like its serialized form, `DueSoon` is `due_soon` in the table.
+    Enum(String),
+}
+
+impl Value {
+    /// Value of an enum column.
+    ///
+    /// # Panics
+    ///
+    /// If `value` does not serialize to a string, as the unit variants of the model enums do.
+    pub fn of_enum(value: &impl Serialize) -> Self {
+        match serde_json::to_value(value) {
+            Ok(serde_json::Value::String(variant)) => Self::Enum(variant),
+            other => panic!("enum should serialize to a string, got {other:?}"),
+        }
+    }
+
+    /// Value as it is serialized in a row, to match rows loaded by [`Repository::find`].
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Self::Int(value) => json!(value),
+            Self::Text(value) | Self::Enum(value) => json!(value),
+            Self::Bool(value) => json!(value),
+        }
+    }
+
+    /// Text of a text or enum column as it is stored in the table.
+    fn column_text(&self) -> Option<String> {
+        match self {
+            Self::Text(value) => Some(value.clone()),
+            Self::Enum(variant) => Some(snake_case(variant)),
+            Self::Int(_) | Self::Bool(_) => None,
+        }
+    }
+}
+
+impl Condition {
+    fn ints(&self) -> Vec<i32> {
+        self.values
+            .iter()
+            .filter_map(|value| match value {
+                Value::Int(value) => Some(*value),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn bools(&self) -> Vec<bool> {
+        self.values
+            .iter()
+            .filter_map(|value| match value {
+                Value::Bool(value) => Some(*value),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn texts(&self) -> Vec<String> {
+        self.values.iter().filter_map(Value::column_text).collect()
+    }
+}
+
+/// Label of an enum variant in the tables, `DueSoon` is `due_soon`.
+pub fn snake_case(variant: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in variant.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+pub type Shared<R> = Arc<dyn Repository<R>>;
+
+/// Database the repositories of the tables are built over.
+pub trait Storage {
+    fn repository<R: SqliteResource>(&self) -> Shared<R>;
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// No row has the key.
+    NotFound,
+    /// The row has another version than the one expected by the write.
+    Modified,
+    /// A rule of the table rejected the write.
+    Rejected(StatusCode, String),
+    Internal(color_eyre::Report),
+}
+
+impl From<color_eyre::Report> for Error {
+    fn from(err: color_eyre::Report) -> Self {
+        Self::Internal(err)
+    }
+}
@@ -0,0 +1,270 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use axum::async_trait;
+use chrono::Local;
+use color_eyre::eyre::{eyre, Context};
+use sqlx::pool::PoolConnection;
+use sqlx::{PgConnection, Pool, Postgres, QueryBuilder};
+
+use crate::circulation::{self, CardValidity, Checkout};
+use crate::fine;
+use crate::resource::Resource;
+
+use super::sqlite::SqliteResource;
+use super::{Condition, Error, Repository, Shared, Storage, Value};
+
+/// Repository of `R` over the compile-time checked queries of its [`Resource`] implementation.
+pub struct PgRepository<R> {
+    db: Pool<Postgres>,
+    row: PhantomData<fn() -> R>,
+}
+
+impl<R> PgRepository<R> {
+    pub fn new(db: Pool<Postgres>) -> Self {
+        Self {
+            db,
+            row: PhantomData,
+        }
+    }
+}
+
+impl Storage for Pool<Postgres> {
+    fn repository<R: SqliteResource>(&self) -> Shared<R> {
+        Arc::new(PgRepository::new(self.clone()))
+    }
+}
+
+impl<R: Resource> PgRepository<R> {
+    async fn not_found_or_modified(&self, key: &R::Key) -> Error {
+        not_found_or_modified(&self.db, R::TABLE, R::KEY, key).await
+    }
+
+    /// Checks the rules of `checkout`, the card row stays locked until the transaction ends.
+    async fn check_checkout(conn: &mut PgConnection, checkout: &Checkout) -> Result<(), Error> {
+        // The card row is locked so it cannot be blocked while the borrowing is being written.
+        let query = format!(
+            "SELECT state, expiry_date FROM {} WHERE id = $1 FOR SHARE",
+            checkout.card_table
+        );
+
+        let card = sqlx::query_as::<_, CardValidity>(&query)
+            .bind(checkout.card)
+            .fetch_optional(&mut *conn)
+            .await
+            .wrap_err_with(|| eyre!("Unable to load {} from database", checkout.card_table))?;
+
+        circulation::check_card(checkout, card, Local::now().date_naive())
+            .map_err(|(status, message)| Error::Rejected(status, message))?;
+
+        let balance = fine::balance(&mut *conn, checkout.card_table, checkout.card, None).await?;
+        let max_balance = fine::max_balance(&mut *conn, checkout.card_table).await?;
+
+        circulation::check_balance(checkout, balance, max_balance)
+            .map_err(|(status, message)| Error::Rejected(status, message))?;
+
+        let in_transit = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM book_transfer WHERE book = $1 AND status = 'in_transit')",
+        )
+        .bind(checkout.book)
+        .fetch_one(&mut *conn)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load book_transfer from database"))?;
+
+        let book_branch =
+            sqlx::query_scalar::<_, Option<i32>>("SELECT branch FROM book WHERE id = $1")
+                .bind(checkout.book)
+                .fetch_optional(&mut *conn)
+                .await
+                .wrap_err_with(|| eyre!("Unable to load book from database"))?
+                .flatten();
+
+        circulation::check_book(checkout, in_transit, book_branch)
+            .map_err(|(status, message)| Error::Rejected(status, message))?;
+
+        Ok(())
+    }
+
+    /// Inserts `row` in the transaction of `conn`, after the checks of its rules.
+    async fn insert_row(conn: &mut PgConnection, row: &R) -> Result<R, Error> {
+        if let Some(checkout) = row.checkout() {
+            Self::check_checkout(&mut *conn, &checkout).await?;
+        }
+
+        Ok(row
+            .insert(conn)
+            .await
+            .wrap_err_with(|| eyre!("Unable to add {} to database", R::TABLE))?)
+    }
+}
+
+#[async_trait]
+impl<R: Resource> Repository<R> for PgRepository<R> {
+    async fn list(&self) -> Result<Vec<R>, Error> {
+        let mut conn = acquire(&self.db).await?;
+
+        Ok(R::list(&mut conn)
+            .await
+            .wrap_err_with(|| eyre!("Unable to load {} rows from database", R::TABLE))?)
+    }
+
+    async fn get(&self, key: &R::Key) -> Result<Option<R>, Error> {
+        let mut conn = acquire(&self.db).await?;
+
+        Ok(R::get(&mut conn, key)
+            .await
+            .wrap_err_with(|| eyre!("Unable to load {} from database", R::TABLE))?)
+    }
+
+    async fn insert(&self, row: &R) -> Result<R, Error> {
+        let mut inserted = self
+            .insert_all(std::slice::from_ref(row))
+            .await
+            .map_err(|(_, err)| err)?;
+
+        Ok(inserted.remove(0))
+    }
+
+    async fn insert_all(&self, rows: &[R]) -> Result<Vec<R>, (Option<usize>, Error)> {
+        let mut tx = self
+            .db
+            .begin()
+            .await
+            .wrap_err_with(|| eyre!("Unable to start transaction"))
+            .map_err(|err| (None, err.into()))?;
+
+        let mut inserted = Vec::with_capacity(rows.len());
+        for (index, row) in rows.iter().enumerate() {
+            let row = Self::insert_row(&mut tx, row)
+                .await
+                .map_err(|err| (Some(index), err))?;
+            inserted.push(row);
+        }
+
+        tx.commit()
+            .await
+            .wrap_err_with(|| eyre!("Unable to commit {}", R::TABLE))
+            .map_err(|err| (None, err.into()))?;
+
+        Ok(inserted)
+    }
+
+    async fn update(&self, key: &R::Key, row: &R, version: i32) -> Result<R, Error> {
+        let mut tx = self
+            .db
+            .begin()
+            .await
+            .wrap_err_with(|| eyre!("Unable to start transaction"))?;
+
+        let previous = R::get(&mut tx, key)
+            .await
+            .wrap_err_with(|| eyre!("Unable to load {} from database", R::TABLE))?;
+
+        if let Some(previous) = previous {
+            row.check_update(&previous)
+                .map_err(|(status, message)| Error::Rejected(status, message))?;
+
+            let moved = row.checkout().filter(|checkout| {
+                previous
+                    .checkout()
+                    .is_some_and(|previous| checkout.is_moved_from(&previous))
+            });
+            if let Some(checkout) = moved {
+                Self::check_checkout(&mut tx, &checkout).await?;
+            }
+        }
+
+        let updated = row
+            .update(&mut tx, key, version)
+            .await
+            .wrap_err_with(|| eyre!("Unable to update {} in database", R::TABLE))?;
+
+        tx.commit()
+            .await
+            .wrap_err_with(|| eyre!("Unable to commit {}", R::TABLE))?;
+
+        match updated {
+            Some(updated) => Ok(updated),
+            None => Err(self.not_found_or_modified(key).await),
+        }
+    }
+
+    async fn delete(&self, key: &R::Key, version: i32) -> Result<R, Error> {
+        let deleted = R::delete(&mut *acquire(&self.db).await?, key, version)
+            .await
+            .wrap_err_with(|| eyre!("Unable to delete {} from database", R::TABLE))?;
+
+        match deleted {
+            Some(deleted) => Ok(deleted),
+            None => Err(self.not_found_or_modified(key).await),
+        }
+    }
+
+    async fn find(
+        &self,
+        conditions: &[Condition],
+        offset: i64,
+        limit: Option<i64>,
+    ) -> Result<Vec<R>, Error> {
+        let mut query = QueryBuilder::new(format!("SELECT * FROM {} WHERE TRUE", R::TABLE));
+
+        for condition in conditions {
+            query.push(format_args!(r#" AND "{}""#, condition.column));
+
+            // Enum columns are compared by their text, so every variant binds as `text[]`.
+            match condition.values.first() {
+                Some(Value::Int(_)) => query.push(" = ANY(").push_bind(condition.ints()),
+                Some(Value::Bool(_)) => query.push(" = ANY(").push_bind(condition.bools()),
+                Some(Value::Text(_) | Value::Enum(_)) => {
+                    query.push("::text = ANY(").push_bind(condition.texts())
+                }
+                None => return Ok(Vec::new()),
+            };
+            query.push(")");
+        }
+
+        query
+            .push(format_args!(r#" ORDER BY "{}" ASC OFFSET "#, R::KEY))
+            .push_bind(offset)
+            .push(" LIMIT ")
+            .push_bind(limit);
+
+        Ok(query
+            .build_query_as::<R>()
+            .fetch_all(&self.db)
+            .await
+            .wrap_err_with(|| eyre!("Unable to load {} rows from database", R::TABLE))?)
+    }
+}
+
+/// Error for an update or delete of `table` which matched no row, depending on whether the row
+/// `key` exists. `table` and `column` are constants, never taken from the request.
+pub async fn not_found_or_modified<K>(
+    db: &Pool<Postgres>,
+    table: &str,
+    column: &str,
+    key: K,
+) -> Error
+where
+    K: for<'q> sqlx::Encode<'q, Postgres> + sqlx::Type<Postgres> + Send,
+{
+    let query = format!("SELECT EXISTS (SELECT 1 FROM {table} WHERE {column} = $1)");
+
+    match sqlx::query_scalar::<_, bool>(&query)
+        .bind(key)
+        .fetch_one(db)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load {table} from database"))
+    {
+        Ok(true) => Error::Modified,
+        Ok(false) => Error::NotFound,
+        Err(err) => Error::Internal(err),
+    }
+}
+
+async fn acquire(db: &Pool<Postgres>) -> Result<PoolConnection<Postgres>, Error> {
+    Ok(db
+        .acquire()
+        .await
+        .wrap_err_with(|| eyre!("Unable to acquire database connection"))?)
+}
@@ -0,0 +1,323 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use axum::async_trait;
+use chrono::Local;
+use color_eyre::eyre::{eyre, Context};
+use sqlx::query::QueryAs;
+use sqlx::sqlite::{SqliteArguments, SqliteRow};
+use sqlx::{FromRow, Pool, QueryBuilder, Sqlite, SqliteConnection};
+
+use crate::circulation::{self, CardValidity, Checkout};
+use crate::resource::Resource;
+
+use super::{Condition, Error, Repository, Shared, Storage, Value};
+
+pub type SqliteQuery<'q, R> = QueryAs<'q, Sqlite, R, SqliteArguments<'q>>;
+
+/// Table stored in SQLite.
+///
+/// The queries are built from [`SqliteResource::COLUMNS`], so only the binding of the
+/// columns is written per table. Rows are read back by column name.
+pub trait SqliteResource: Resource + for<'r> FromRow<'r, SqliteRow> {
+    /// Columns written by inserts and updates, in the order [`SqliteResource::bind`] binds them.
+    const COLUMNS: &'static [&'static str];
+
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self>;
+}
+
+/// Repository of `R` in an SQLite database migrated from `migrations_sqlite`.
+pub struct SqliteRepository<R> {
+    db: Pool<Sqlite>,
+    row: PhantomData<fn() -> R>,
+}
+
+impl<R> SqliteRepository<R> {
+    pub fn new(db: Pool<Sqlite>) -> Self {
+        Self {
+            db,
+            row: PhantomData,
+        }
+    }
+}
+
+impl Storage for Pool<Sqlite> {
+    fn repository<R: SqliteResource>(&self) -> Shared<R> {
+        Arc::new(SqliteRepository::new(self.clone()))
+    }
+}
+
+impl<R: SqliteResource> SqliteRepository<R> {
+    /// Error for an update or delete which matched no row, depending on whether the row exists.
+    async fn not_found_or_modified(&self, key: &R::Key) -> Error {
+        let query = format!(
+            r#"SELECT EXISTS (SELECT 1 FROM {} WHERE "{}" = ?)"#,
+            R::TABLE,
+            R::KEY
+        );
+
+        match sqlx::query_scalar::<_, bool>(&query)
+            .bind(key)
+            .fetch_one(&self.db)
+            .await
+            .wrap_err_with(|| eyre!("Unable to load {} from database", R::TABLE))
+        {
+            Ok(true) => Error::Modified,
+            Ok(false) => Error::NotFound,
+            Err(err) => Error::Internal(err),
+        }
+    }
+
+    /// Checks the rules of `checkout` in the transaction of the write.
+    async fn check_checkout(conn: &mut SqliteConnection, checkout: &Checkout) -> Result<(), Error> {
+        // SQLite has no row locks, a card changed by another writer before the commit
+        // makes the commit fail instead.
+        let query = format!(
+            "SELECT state, expiry_date FROM {} WHERE id = ?",
+            checkout.card_table
+        );
+
+        let card = sqlx::query_as::<_, CardValidity>(&query)
+            .bind(checkout.card)
+            .fetch_optional(&mut *conn)
+            .await
+            .wrap_err_with(|| eyre!("Unable to load {} from database", checkout.card_table))?;
+
+        circulation::check_card(checkout, card, Local::now().date_naive())
+            .map_err(|(status, message)| Error::Rejected(status, message))?;
+
+        let in_transit = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM book_transfer WHERE book = ? AND status = 'in_transit')",
+        )
+        .bind(checkout.book)
+        .fetch_one(&mut *conn)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load book_transfer from database"))?;
+
+        let book_branch =
+            sqlx::query_scalar::<_, Option<i32>>("SELECT branch FROM book WHERE id = ?")
+                .bind(checkout.book)
+                .fetch_optional(&mut *conn)
+                .await
+                .wrap_err_with(|| eyre!("Unable to load book from database"))?
+                .flatten();
+
+        circulation::check_book(checkout, in_transit, book_branch)
+            .map_err(|(status, message)| Error::Rejected(status, message))?;
+
+        Ok(())
+    }
+
+    /// Inserts `row` in the transaction of `conn`, after the checks of its rules.
+    async fn insert_row(conn: &mut SqliteConnection, row: &R) -> Result<R, Error> {
+        if let Some(checkout) = row.checkout() {
+            Self::check_checkout(&mut *conn, &checkout).await?;
+        }
+
+        let query = format!(
+            "INSERT INTO {} ({}) VALUES ({}) RETURNING *",
+            R::TABLE,
+            columns(R::COLUMNS),
+            vec!["?"; R::COLUMNS.len()].join(", ")
+        );
+
+        Ok(row
+            .bind(sqlx::query_as(&query))
+            .fetch_one(conn)
+            .await
+            .wrap_err_with(|| eyre!("Unable to add {} to database", R::TABLE))?)
+    }
+}
+
+#[async_trait]
+impl<R: SqliteResource> Repository<R> for SqliteRepository<R> {
+    async fn list(&self) -> Result<Vec<R>, Error> {
+        let query = format!(r#"SELECT * FROM {} ORDER BY "{}" ASC"#, R::TABLE, R::KEY);
+
+        Ok(sqlx::query_as::<_, R>(&query)
+            .fetch_all(&self.db)
+            .await
+            .wrap_err_with(|| eyre!("Unable to load {} rows from database", R::TABLE))?)
+    }
+
+    async fn get(&self, key: &R::Key) -> Result<Option<R>, Error> {
+        let query = format!(r#"SELECT * FROM {} WHERE "{}" = ?"#, R::TABLE, R::KEY);
+
+        Ok(sqlx::query_as::<_, R>(&query)
+            .bind(key)
+            .fetch_optional(&self.db)
+            .await
+            .wrap_err_with(|| eyre!("Unable to load {} from database", R::TABLE))?)
+    }
+
+    async fn insert(&self, row: &R) -> Result<R, Error> {
+        let mut inserted = self
+            .insert_all(std::slice::from_ref(row))
+            .await
+            .map_err(|(_, err)| err)?;
+
+        Ok(inserted.remove(0))
+    }
+
+    async fn insert_all(&self, rows: &[R]) -> Result<Vec<R>, (Option<usize>, Error)> {
+        let mut tx = self
+            .db
+            .begin()
+            .await
+            .wrap_err_with(|| eyre!("Unable to start transaction"))
+            .map_err(|err| (None, err.into()))?;
+
+        let mut inserted = Vec::with_capacity(rows.len());
+        for (index, row) in rows.iter().enumerate() {
+            let row = Self::insert_row(&mut tx, row)
+                .await
+                .map_err(|err| (Some(index), err))?;
+            inserted.push(row);
+        }
+
+        tx.commit()
+            .await
+            .wrap_err_with(|| eyre!("Unable to commit {}", R::TABLE))
+            .map_err(|err| (None, err.into()))?;
+
+        Ok(inserted)
+    }
+
+    async fn update(&self, key: &R::Key, row: &R, version: i32) -> Result<R, Error> {
+        let assignments = R::COLUMNS
+            .iter()
+            .map(|column| format!(r#""{column}" = ?"#))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // Postgres bumps the version with a trigger, SQLite triggers cannot change the updated row.
+        let query = format!(
+            r#"UPDATE {} SET {assignments}, version = version + 1
+            WHERE "{}" = ? AND version = ? RETURNING *"#,
+            R::TABLE,
+            R::KEY
+        );
+
+        let mut tx = self
+            .db
+            .begin()
+            .await
+            .wrap_err_with(|| eyre!("Unable to start transaction"))?;
+
+        let previous = sqlx::query_as::<_, R>(&format!(
+            r#"SELECT * FROM {} WHERE "{}" = ?"#,
+            R::TABLE,
+            R::KEY
+        ))
+        .bind(key)
+        .fetch_optional(&mut tx)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load {} from database", R::TABLE))?;
+
+        if let Some(previous) = previous {
+            row.check_update(&previous)
+                .map_err(|(status, message)| Error::Rejected(status, message))?;
+
+            let moved = row.checkout().filter(|checkout| {
+                previous
+                    .checkout()
+                    .is_some_and(|previous| checkout.is_moved_from(&previous))
+            });
+            if let Some(checkout) = moved {
+                Self::check_checkout(&mut tx, &checkout).await?;
+            }
+        }
+
+        let updated = row
+            .bind(sqlx::query_as(&query))
+            .bind(key)
+            .bind(version)
+            .fetch_optional(&mut tx)
+            .await
+            .wrap_err_with(|| eyre!("Unable to update {} in database", R::TABLE))?;
+
+        tx.commit()
+            .await
+            .wrap_err_with(|| eyre!("Unable to commit {}", R::TABLE))?;
+
+        match updated {
+            Some(updated) => Ok(updated),
+            None => Err(self.not_found_or_modified(key).await),
+        }
+    }
+
+    async fn delete(&self, key: &R::Key, version: i32) -> Result<R, Error> {
+        let query = format!(
+            r#"DELETE FROM {} WHERE "{}" = ? AND version = ? RETURNING *"#,
+            R::TABLE,
+            R::KEY
+        );
+
+        // The statement is run to its end, SQLite only commits it then and a statement left
+        // after its first row would keep the row visible to other connections.
+        let deleted = sqlx::query_as::<_, R>(&query)
+            .bind(key)
+            .bind(version)
+            .fetch_all(&self.db)
+            .await
+            .wrap_err_with(|| eyre!("Unable to delete {} from database", R::TABLE))?;
+
+        match deleted.into_iter().next() {
+            Some(deleted) => Ok(deleted),
+            None => Err(self.not_found_or_modified(key).await),
+        }
+    }
+
+    async fn find(
+        &self,
+        conditions: &[Condition],
+        offset: i64,
+        limit: Option<i64>,
+    ) -> Result<Vec<R>, Error> {
+        let mut query = QueryBuilder::new(format!("SELECT * FROM {} WHERE TRUE", R::TABLE));
+
+        for condition in conditions {
+            if condition.values.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            query.push(format_args!(r#" AND "{}" IN ("#, condition.column));
+            let mut values = query.separated(", ");
+            match condition.values[0] {
+                Value::Int(_) => condition.ints().into_iter().for_each(|value| {
+                    values.push_bind(value);
+                }),
+                Value::Bool(_) => condition.bools().into_iter().for_each(|value| {
+                    values.push_bind(value);
+                }),
+                Value::Text(_) | Value::Enum(_) => {
+                    condition.texts().into_iter().for_each(|value| {
+                        values.push_bind(value);
+                    })
+                }
+            }
+            query.push(")");
+        }
+
+        // A negative limit is no limit in SQLite.
+        query
+            .push(format_args!(r#" ORDER BY "{}" ASC LIMIT "#, R::KEY))
+            .push_bind(limit.unwrap_or(-1))
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        Ok(query
+            .build_query_as::<R>()
+            .fetch_all(&self.db)
+            .await
+            .wrap_err_with(|| eyre!("Unable to load {} rows from database", R::TABLE))?)
+    }
+}
+
+fn columns(columns: &[&str]) -> String {
+    columns
+        .iter()
+        .map(|column| format!(r#""{column}""#))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,93 @@
+use std::fmt::{Debug, Display};
+
+use axum::async_trait;
+use axum::http::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, PgConnection, Postgres, Sqlite};
+use validator::Validate;
+
+use crate::circulation::Checkout;
+
+/// Table served by the generic CRUD routes of [`crate::web::resource`].
+///
+/// Implementations provide the queries of [`crate::repository::postgres::PgRepository`],
+/// which stay checked at compile time by `sqlx`.
+/// Updates and deletes must match the row `version` as well as the key, and return `None`
+/// when no row matched. Rows are also read by column name, for the filtered loads of
+/// [`crate::repository::Repository::find`].
+#[async_trait]
+pub trait Resource:
+    Serialize
+    + DeserializeOwned
+    + Validate
+    + Debug
+    + for<'r> FromRow<'r, PgRow>
+    + Send
+    + Sync
+    + Unpin
+    + 'static
+{
+    /// Table name, also used in error messages.
+    const TABLE: &'static str;
+    /// Path of the list route, the item route appends the key.
+    const PATH: &'static str;
+    /// Primary key column.
+    const KEY: &'static str = "id";
+    /// Column of the branch a row belongs to, list routes of tables with one take `?branch=`.
+    const BRANCH: Option<&'static str> = None;
+
+    type Key: Serialize
+        + DeserializeOwned
+        + Display
+        + Send
+        + Sync
+        + for<'q> sqlx::Encode<'q, Postgres>
+        + sqlx::Type<Postgres>
+        + for<'q> sqlx::Encode<'q, Sqlite>
+        + sqlx::Type<Sqlite>
+        + 'static;
+
+    fn version(&self) -> i32;
+
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>>;
+
+    async fn get(conn: &mut PgConnection, key: &Self::Key) -> sqlx::Result<Option<Self>>;
+
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self>;
+
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        key: &Self::Key,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>>;
+
+    async fn delete(
+        conn: &mut PgConnection,
+        key: &Self::Key,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>>;
+
+    /// Normalizes a payload before it is inserted or updated.
+    fn normalize(&mut self) -> Result<(), (StatusCode, String)> {
+        Ok(())
+    }
+
+    /// Checks an update against the row it replaces, for the columns only other routes write.
+    fn check_update(&self, _previous: &Self) -> Result<(), (StatusCode, String)> {
+        Ok(())
+    }
+
+    /// Card the row is checked out on, checked by the repository before the row is inserted.
+    fn checkout(&self) -> Option<Checkout> {
+        None
+    }
+}
+
+/// Name of a table in messages, `students_borrowing` is `Students borrowing`.
+pub fn label(table: &str) -> String {
+    let label = table.replace('_', " ");
+    format!("{}{}", label[..1].to_uppercase(), &label[1..])
+}
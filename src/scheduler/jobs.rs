@@ -0,0 +1,120 @@
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use sqlx::{Pool, Postgres};
+
+use crate::notification;
+
+// Delivered notifications and finished job runs older than this are deleted.
+const RETENTION_DAYS: i32 = 180;
+
+pub async fn overdue_detection(db: &Pool<Postgres>) -> Result<String> {
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))?;
+
+    let overdue = notification::generate_overdue(&mut tx).await?;
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit notifications"))?;
+
+    Ok(format!("Queued {} overdue notices", overdue.len()))
+}
+
+pub async fn reminders(db: &Pool<Postgres>) -> Result<String> {
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))?;
+
+    let due_soon = notification::generate_due_soon(&mut tx).await?;
+    let hold_ready = notification::generate_hold_ready(&mut tx).await?;
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit notifications"))?;
+
+    Ok(format!(
+        "Queued {} due soon and {} hold ready notices",
+        due_soon.len(),
+        hold_ready.len()
+    ))
+}
+
+pub async fn hold_expiry(db: &Pool<Postgres>) -> Result<String> {
+    let expired = sqlx::query!(
+        r#"UPDATE hold SET status = 'expired'
+        WHERE status IN ('pending', 'ready') AND expire_date < CURRENT_DATE"#
+    )
+    .execute(db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to expire holds in database"))?
+    .rows_affected();
+
+    Ok(format!("Expired {expired} holds"))
+}
+
+pub async fn card_expiry(db: &Pool<Postgres>) -> Result<String> {
+    let student_cards = sqlx::query!(
+        r#"UPDATE student_card SET state = 'expired'
+        WHERE state = 'active' AND expiry_date < CURRENT_DATE"#
+    )
+    .execute(db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to expire student_cards in database"))?
+    .rows_affected();
+
+    let teacher_cards = sqlx::query!(
+        r#"UPDATE teacher_card SET state = 'expired'
+        WHERE state = 'active' AND expiry_date < CURRENT_DATE"#
+    )
+    .execute(db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to expire teacher_cards in database"))?
+    .rows_affected();
+
+    Ok(format!(
+        "Expired {student_cards} student cards and {teacher_cards} teacher cards"
+    ))
+}
+
+pub async fn retention_cleanup(db: &Pool<Postgres>) -> Result<String> {
+    let notifications = sqlx::query!(
+        r#"DELETE FROM notification
+        WHERE status = 'sent' AND sent_at < now() - make_interval(days => $1)"#,
+        RETENTION_DAYS
+    )
+    .execute(db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to delete notifications from database"))?
+    .rows_affected();
+
+    let job_runs = sqlx::query!(
+        r#"DELETE FROM job_run
+        WHERE status <> 'running' AND finished_at < now() - make_interval(days => $1)"#,
+        RETENTION_DAYS
+    )
+    .execute(db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to delete job_runs from database"))?
+    .rows_affected();
+
+    // Dead deliveries are kept until they are retried or their subscription is deleted.
+    let webhook_deliveries = sqlx::query!(
+        r#"DELETE FROM webhook_delivery
+        WHERE status = 'delivered' AND delivered_at < now() - make_interval(days => $1)"#,
+        RETENTION_DAYS
+    )
+    .execute(db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to delete webhook_deliveries from database"))?
+    .rows_affected();
+
+    Ok(format!(
+        "Deleted {notifications} notifications, {job_runs} job runs \
+        and {webhook_deliveries} webhook deliveries"
+    ))
+}
@@ -0,0 +1,199 @@
+use std::str::FromStr;
+
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use cron::Schedule;
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+
+use crate::model::{JobRun, JobRunStatus};
+
+mod jobs;
+
+/// Periodic maintenance jobs. Schedules are cron expressions (with seconds) in UTC.
+#[derive(Clone, Copy, Debug)]
+pub enum Job {
+    OverdueDetection,
+    Reminders,
+    HoldExpiry,
+    CardExpiry,
+    RetentionCleanup,
+}
+
+impl Job {
+    pub const ALL: [Job; 5] = [
+        Job::OverdueDetection,
+        Job::Reminders,
+        Job::HoldExpiry,
+        Job::CardExpiry,
+        Job::RetentionCleanup,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Job::OverdueDetection => "overdue-detection",
+            Job::Reminders => "reminders",
+            Job::HoldExpiry => "hold-expiry",
+            Job::CardExpiry => "card-expiry",
+            Job::RetentionCleanup => "retention-cleanup",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|job| job.name() == name)
+    }
+
+    pub fn schedule(self) -> &'static str {
+        match self {
+            Job::OverdueDetection => "0 0 1 * * *",
+            Job::Reminders => "0 0 8 * * *",
+            Job::HoldExpiry => "0 30 1 * * *",
+            Job::CardExpiry => "0 45 1 * * *",
+            Job::RetentionCleanup => "0 0 3 * * Sun",
+        }
+    }
+
+    pub fn next_run(self) -> Option<DateTime<Utc>> {
+        self.parsed_schedule().upcoming(Utc).next()
+    }
+
+    fn parsed_schedule(self) -> Schedule {
+        Schedule::from_str(self.schedule()).expect("Job schedule should be a valid cron expression")
+    }
+
+    async fn execute(self, db: &Pool<Postgres>) -> Result<String> {
+        match self {
+            Job::OverdueDetection => jobs::overdue_detection(db).await,
+            Job::Reminders => jobs::reminders(db).await,
+            Job::HoldExpiry => jobs::hold_expiry(db).await,
+            Job::CardExpiry => jobs::card_expiry(db).await,
+            Job::RetentionCleanup => jobs::retention_cleanup(db).await,
+        }
+    }
+}
+
+/// Spawns a task per job which runs it according to its schedule.
+pub fn start(db: Pool<Postgres>) {
+    for job in Job::ALL {
+        tokio::spawn(run_on_schedule(db.clone(), job));
+    }
+}
+
+async fn run_on_schedule(db: Pool<Postgres>, job: Job) {
+    while let Some(next) = job.next_run() {
+        let delay = (next - Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(delay).await;
+
+        match run_job(&db, job, Some(next)).await {
+            Ok(Some(run)) => tracing::info!("Job `{}` finished: {:?}", job.name(), run.status),
+            Ok(None) => tracing::debug!("Job `{}` ran on another replica", job.name()),
+            Err(err) => tracing::error!("{:?}", err),
+        }
+    }
+}
+
+/// Runs the job and records it in `job_run`, `scheduled_at` is the slot of its schedule.
+///
+/// Returns `None` without running the job if another replica is running it, or already ran it for
+/// `scheduled_at`.
+pub async fn run_job(
+    db: &Pool<Postgres>,
+    job: Job,
+    scheduled_at: Option<DateTime<Utc>>,
+) -> Result<Option<JobRun>> {
+    // The lock is released with the transaction, which rolls back should this future be dropped.
+    let mut lock = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))?;
+
+    let locked = sqlx::query_scalar!(
+        r#"SELECT pg_try_advisory_xact_lock(hashtext($1)) as "locked!""#,
+        job.name()
+    )
+    .fetch_one(&mut lock)
+    .await
+    .wrap_err_with(|| eyre!("Unable to lock job `{}`", job.name()))?;
+
+    if !locked {
+        return Ok(None);
+    }
+
+    fail_interrupted(db, job).await?;
+    let run = record(db, job, scheduled_at).await?;
+
+    lock.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to unlock job `{}`", job.name()))?;
+
+    Ok(run)
+}
+
+/// Marks the runs of `job` still `running` as failed, the caller holds the lock of the job.
+///
+/// Runs hold the lock until they are recorded, so those were interrupted by their replica
+/// stopping, and would otherwise be listed as running forever.
+async fn fail_interrupted(db: &Pool<Postgres>, job: Job) -> Result<()> {
+    sqlx::query!(
+        r#"UPDATE job_run SET
+        finished_at = now(),
+        status = 'failed',
+        message = 'Interrupted before it finished'
+        WHERE job = $1 AND status = 'running'"#,
+        job.name()
+    )
+    .execute(db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update job_run in database"))?;
+
+    Ok(())
+}
+
+async fn record(
+    db: &Pool<Postgres>,
+    job: Job,
+    scheduled_at: Option<DateTime<Utc>>,
+) -> Result<Option<JobRun>> {
+    // Runs started by hand have no slot, and `NULL`s never conflict.
+    let id = sqlx::query_scalar!(
+        r#"INSERT INTO job_run (job, scheduled_at) VALUES ($1, $2)
+        ON CONFLICT (job, scheduled_at) DO NOTHING
+        RETURNING id"#,
+        job.name(),
+        scheduled_at
+    )
+    .fetch_optional(db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add job_run to database"))?;
+
+    let Some(id) = id else {
+        return Ok(None);
+    };
+
+    let (status, message) = match job.execute(db).await {
+        Ok(message) => (JobRunStatus::Succeeded, message),
+        Err(err) => {
+            tracing::error!("Job `{}` failed: {:?}", job.name(), err);
+            (JobRunStatus::Failed, format!("{:#}", err))
+        }
+    };
+
+    sqlx::query_as!(
+        JobRun,
+        r#"UPDATE job_run SET
+        finished_at = now(),
+        status = $1,
+        message = $2
+        WHERE id = $3
+        RETURNING id, job, started_at, finished_at, status as "status: _", message, scheduled_at"#,
+        status as _,
+        message,
+        id
+    )
+    .fetch_one(db)
+    .await
+    .map(Some)
+    .wrap_err_with(|| eyre!("Unable to update job_run in database"))
+}
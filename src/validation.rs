@@ -0,0 +1,227 @@
+use axum::async_trait;
+use axum::extract::FromRequest;
+use axum::http::{Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use validator::{Validate, ValidationError};
+
+use crate::model::{
+    Book, BookTransfer, Hold, LedgerEntry, LedgerEntryKind, StudentCard, StudentsBorrowing,
+    TeacherCard, TeachersBorrowing, TransferStatus,
+};
+
+/// ISO 3166-1 alpha-2 code.
+pub static COUNTRY_CODE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Z]{2}$").unwrap());
+
+/// JSON body validated before reaching the handler.
+///
+/// Invalid bodies are rejected with `422 Unprocessable Entity` listing every failed rule by field,
+/// rules over several fields are listed under `__all__`. Those are declared with
+/// `skip_on_field_errors = false`, so they run and are listed even when a field is invalid.
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, S, B> FromRequest<S, B> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+    B: Send + 'static,
+    Json<T>: FromRequest<S, B>,
+    <Json<T> as FromRequest<S, B>>::Rejection: IntoResponse,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        value
+            .validate()
+            .map_err(|errors| (StatusCode::UNPROCESSABLE_ENTITY, Json(errors)).into_response())?;
+
+        Ok(Self(value))
+    }
+}
+
+fn error(code: &'static str, message: &'static str) -> ValidationError {
+    let mut error = ValidationError::new(code);
+    error.message = Some(message.into());
+    error
+}
+
+pub fn student_card_dates(card: &StudentCard) -> Result<(), ValidationError> {
+    if card.expiry_date < card.issue_date {
+        return Err(error("expiry_date", "`expiry_date` should not be before `issue_date`"));
+    }
+
+    Ok(())
+}
+
+pub fn teacher_card_dates(card: &TeacherCard) -> Result<(), ValidationError> {
+    if card.expiry_date < card.issue_date {
+        return Err(error("expiry_date", "`expiry_date` should not be before `issue_date`"));
+    }
+
+    Ok(())
+}
+
+pub fn students_borrowing_required_return_date(
+    borrowing: &StudentsBorrowing,
+) -> Result<(), ValidationError> {
+    if borrowing.required_return_date < borrowing.borrow_date {
+        return Err(error(
+            "required_return_date",
+            "`required_return_date` should not be before `borrow_date`",
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn students_borrowing_return_date(
+    borrowing: &StudentsBorrowing,
+) -> Result<(), ValidationError> {
+    if borrowing.return_date.is_some_and(|date| date < borrowing.borrow_date) {
+        return Err(error("return_date", "`return_date` should not be before `borrow_date`"));
+    }
+
+    Ok(())
+}
+
+pub fn teachers_borrowing_return_date(
+    borrowing: &TeachersBorrowing,
+) -> Result<(), ValidationError> {
+    if borrowing.return_date.is_some_and(|date| date < borrowing.borrow_date) {
+        return Err(error("return_date", "`return_date` should not be before `borrow_date`"));
+    }
+
+    Ok(())
+}
+
+pub fn hold_card(hold: &Hold) -> Result<(), ValidationError> {
+    if hold.student_card.is_some() == hold.teacher_card.is_some() {
+        return Err(error(
+            "card",
+            "Exactly one of `student_card` and `teacher_card` should be set",
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn ledger_entry_card(entry: &LedgerEntry) -> Result<(), ValidationError> {
+    if entry.student_card.is_some() == entry.teacher_card.is_some() {
+        return Err(error(
+            "card",
+            "Exactly one of `student_card` and `teacher_card` should be set",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Charges are for a loan on the same card, payments and waivers may name the loan they settle.
+pub fn ledger_entry_borrowing(entry: &LedgerEntry) -> Result<(), ValidationError> {
+    let on_other_card = (entry.students_borrowing.is_some() && entry.student_card.is_none())
+        || (entry.teachers_borrowing.is_some() && entry.teacher_card.is_none());
+    if on_other_card {
+        return Err(error(
+            "borrowing",
+            "The borrowing should be of the same kind of card as the entry",
+        ));
+    }
+
+    let has_borrowing = entry.students_borrowing.is_some() || entry.teachers_borrowing.is_some();
+    if entry.kind.is_charge() && !has_borrowing {
+        return Err(error(
+            "borrowing",
+            "Charges should name the `students_borrowing` or `teachers_borrowing` they are for",
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn ledger_entry_waiver(entry: &LedgerEntry) -> Result<(), ValidationError> {
+    let is_waiver = matches!(entry.kind, LedgerEntryKind::Waiver);
+    if is_waiver && (entry.librarian.is_none() || entry.reason.is_none()) {
+        return Err(error(
+            "waiver",
+            "Waivers should name the `librarian` granting them and their `reason`",
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn hold_expire_date(hold: &Hold) -> Result<(), ValidationError> {
+    if hold.expire_date.is_some_and(|date| date < hold.request_date) {
+        return Err(error(
+            "expire_date",
+            "`expire_date` should not be before `request_date`",
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn book_call_number(book: &Book) -> Result<(), ValidationError> {
+    if book.classification.is_some() != book.call_number.is_some() {
+        return Err(error(
+            "call_number",
+            "`call_number` should be set exactly when `classification` is",
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn book_transfer_branches(transfer: &BookTransfer) -> Result<(), ValidationError> {
+    if transfer.from_branch == transfer.to_branch {
+        return Err(error(
+            "to_branch",
+            "`to_branch` should not be the same as `from_branch`",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Only received transfers have a `received_date`, which is not before the `sent_date`.
+pub fn book_transfer_received_date(transfer: &BookTransfer) -> Result<(), ValidationError> {
+    let is_received = matches!(transfer.status, TransferStatus::Received);
+    if transfer.received_date.is_some() != is_received {
+        return Err(error(
+            "received_date",
+            "`received_date` should be set exactly when the transfer is `Received`",
+        ));
+    }
+
+    if transfer
+        .received_date
+        .is_some_and(|date| date < transfer.sent_date)
+    {
+        return Err(error(
+            "received_date",
+            "`received_date` should not be before `sent_date`",
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn webhook_url(url: &str) -> Result<(), ValidationError> {
+    let valid = url.parse::<hyper::Uri>().is_ok_and(|uri| {
+        matches!(uri.scheme_str(), Some("http" | "https")) && uri.host().is_some()
+    });
+
+    if !valid {
+        return Err(error("url", "`url` should be an absolute HTTP or HTTPS URL"));
+    }
+
+    Ok(())
+}
@@ -1,100 +1,111 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
 use crate::model::Author;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route("/author", get(get_authors).post(create_author))
-        .route("/author/:id", put(update_author).delete(delete_author))
-        .with_state(db)
+pub fn routes(repository: Shared<Author>) -> Router {
+    resource::routes(repository)
 }
 
-async fn get_authors(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<Author>>), (StatusCode, String)> {
-    let authors = sqlx::query_as!(
-        Author,
-        r#"SELECT id, name, lastname, surname, country FROM author ORDER BY id ASC"#
-    )
-    .fetch_all(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to load authors from database"))
-    .map_err(internal_error)?;
+#[async_trait]
+impl Resource for Author {
+    const TABLE: &'static str = "author";
+    const PATH: &'static str = "/author";
 
-    Ok((StatusCode::OK, Json(authors)))
-}
+    type Key = i32;
 
-async fn create_author(
-    State(db): State<Pool<Postgres>>,
-    Json(author): Json<Author>,
-) -> Result<(StatusCode, Json<Author>), (StatusCode, String)> {
-    let inserted_author = sqlx::query_as!(
-        Author,
-        r#"INSERT INTO author 
-        (name, lastname, surname, country)
-        VALUES ($1, $2, $3, $4)
-        RETURNING id, name, lastname, surname, country"#,
-        author.name,
-        author.lastname,
-        author.surname,
-        author.country,
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add author to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_author)))
-}
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Author,
+            r#"SELECT id, name, lastname, surname, country, version
+            FROM author ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
+
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Author,
+            r#"SELECT id, name, lastname, surname, country, version
+            FROM author WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-async fn update_author(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(author): Json<Author>,
-) -> Result<(StatusCode, Json<Author>), (StatusCode, String)> {
-    tracing::info!("author payload: {:?}", author);
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            Author,
+            r#"INSERT INTO author
+            (name, lastname, surname, country)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, name, lastname, surname, country, version"#,
+            self.name,
+            self.lastname,
+            self.surname,
+            self.country,
+        )
+        .fetch_one(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE author SET
-        name = $1,
-        lastname = $2,
-        surname = $3, 
-        country = $4
-        WHERE id = $5"#,
-        author.name,
-        author.lastname,
-        author.surname,
-        author.country,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update author in database"))
-    .map_err(internal_error)?;
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Author,
+            r#"UPDATE author SET
+            name = $1,
+            lastname = $2,
+            surname = $3,
+            country = $4
+            WHERE id = $5 AND version = $6
+            RETURNING id, name, lastname, surname, country, version"#,
+            self.name,
+            self.lastname,
+            self.surname,
+            self.country,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(author)))
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Author,
+            r#"DELETE FROM author WHERE id = $1 AND version = $2
+            RETURNING id, name, lastname, surname, country, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 }
 
-async fn delete_author(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<Author>), (StatusCode, String)> {
-    let deleted_author = sqlx::query_as!(
-        Author,
-        r#"DELETE FROM author WHERE id = $1 
-    RETURNING id, name, lastname, surname, country"#,
-        id
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update author in database"))
-    .map_err(internal_error)?;
+impl SqliteResource for Author {
+    const COLUMNS: &'static [&'static str] = &["name", "lastname", "surname", "country"];
 
-    Ok((StatusCode::OK, Json(deleted_author)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query
+            .bind(&self.name)
+            .bind(&self.lastname)
+            .bind(&self.surname)
+            .bind(&self.country)
+    }
 }
@@ -1,103 +1,104 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
 use crate::model::AuthorBook;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route(
-            "/author-book",
-            get(get_author_books).post(create_author_book),
-        )
-        .route(
-            "/author-book/:id",
-            put(update_author_book).delete(delete_author_book),
-        )
-        .with_state(db)
+pub fn routes(repository: Shared<AuthorBook>) -> Router {
+    resource::routes(repository)
 }
 
-async fn get_author_books(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<AuthorBook>>), (StatusCode, String)> {
-    let author_books = sqlx::query_as!(
-        AuthorBook,
-        r#"SELECT id, author_id, book_id, num FROM author_book ORDER BY id ASC"#
-    )
-    .fetch_all(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to load author_books from database"))
-    .map_err(internal_error)?;
+#[async_trait]
+impl Resource for AuthorBook {
+    const TABLE: &'static str = "author_book";
+    const PATH: &'static str = "/author-book";
 
-    Ok((StatusCode::OK, Json(author_books)))
-}
+    type Key = i32;
 
-async fn create_author_book(
-    State(db): State<Pool<Postgres>>,
-    Json(author_book): Json<AuthorBook>,
-) -> Result<(StatusCode, Json<AuthorBook>), (StatusCode, String)> {
-    let inserted_author_book = sqlx::query_as!(
-        AuthorBook,
-        r#"INSERT INTO author_book 
-        (author_id, book_id, num)
-        VALUES ($1, $2, $3)
-        RETURNING id, author_id, book_id, num"#,
-        author_book.author_id,
-        author_book.book_id,
-        author_book.num
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add author_book to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_author_book)))
-}
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            AuthorBook,
+            r#"SELECT id, author_id, book_id, num, version
+            FROM author_book ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
 
-async fn update_author_book(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(author_book): Json<AuthorBook>,
-) -> Result<(StatusCode, Json<AuthorBook>), (StatusCode, String)> {
-    tracing::info!("AuthorBook payload: {:?}", author_book);
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            AuthorBook,
+            r#"SELECT id, author_id, book_id, num, version
+            FROM author_book WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE author_book SET
-        author_id = $1,
-        book_id = $2,
-        num = $3
-        WHERE id = $4"#,
-        author_book.author_id,
-        author_book.book_id,
-        author_book.num,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update author_book in database"))
-    .map_err(internal_error)?;
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            AuthorBook,
+            r#"INSERT INTO author_book
+            (author_id, book_id, num)
+            VALUES ($1, $2, $3)
+            RETURNING id, author_id, book_id, num, version"#,
+            self.author_id,
+            self.book_id,
+            self.num,
+        )
+        .fetch_one(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(author_book)))
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            AuthorBook,
+            r#"UPDATE author_book SET
+            author_id = $1,
+            book_id = $2,
+            num = $3
+            WHERE id = $4 AND version = $5
+            RETURNING id, author_id, book_id, num, version"#,
+            self.author_id,
+            self.book_id,
+            self.num,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            AuthorBook,
+            r#"DELETE FROM author_book WHERE id = $1 AND version = $2
+            RETURNING id, author_id, book_id, num, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 }
 
-async fn delete_author_book(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<AuthorBook>), (StatusCode, String)> {
-    let deleted_author_book = sqlx::query_as!(
-        AuthorBook,
-        r#"DELETE FROM author_book WHERE id = $1 
-        RETURNING id, author_id, book_id, num"#,
-        id
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update author_book in database"))
-    .map_err(internal_error)?;
+impl SqliteResource for AuthorBook {
+    const COLUMNS: &'static [&'static str] = &["author_id", "book_id", "num"];
 
-    Ok((StatusCode::OK, Json(deleted_author_book)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query.bind(self.author_id).bind(self.book_id).bind(self.num)
+    }
 }
@@ -1,100 +1,265 @@
 use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use axum::{async_trait, extract::State, http::StatusCode, routing::get, Json, Router};
 use color_eyre::eyre::Context;
 use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use sqlx::{PgConnection, Pool, Postgres, Sqlite};
 
+use crate::call_number;
 use crate::error::internal_error;
+use crate::etag::{etag, ETag};
+use crate::isbn;
 use crate::model::Book;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
+pub fn routes(repository: Shared<Book>) -> Router {
+    resource::routes(repository)
+}
+
+/// Lookup of books by ISBN over Postgres.
+pub fn isbn_routes(db: Pool<Postgres>) -> Router {
     Router::new()
-        .route("/book", get(get_books).post(create_book))
-        .route("/book/:id", put(update_book).delete(delete_book))
+        .route("/book/by-isbn/:isbn", get(get_book_by_isbn))
         .with_state(db)
 }
 
-async fn get_books(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<Book>>), (StatusCode, String)> {
-    let books = sqlx::query_as!(Book, r#"SELECT id, title, release, publisher, category, student_access FROM book ORDER BY id ASC"#)
-        .fetch_all(&db)
-        .await
-        .wrap_err_with(|| eyre!("Unable to load books from database"))
-        .map_err(internal_error)?;
-
-    Ok((StatusCode::OK, Json(books)))
+/// Lookup of books by ISBN over SQLite.
+pub fn sqlite_isbn_routes(db: Pool<Sqlite>) -> Router {
+    Router::new()
+        .route("/book/by-isbn/:isbn", get(get_sqlite_book_by_isbn))
+        .with_state(db)
 }
 
-async fn create_book(
+async fn get_book_by_isbn(
     State(db): State<Pool<Postgres>>,
-    Json(book): Json<Book>,
-) -> Result<(StatusCode, Json<Book>), (StatusCode, String)> {
-    let inserted_book = sqlx::query_as!(
+    Path(isbn): Path<String>,
+) -> Result<(StatusCode, ETag, Json<Book>), (StatusCode, String)> {
+    let isbn = isbn::normalize(&isbn)
+        .map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, format!("{:#}", err)))?;
+
+    let book = sqlx::query_as!(
         Book,
-        r#"INSERT INTO book 
-        (title, release, publisher, category, student_access)
-        VALUES ($1, $2, $3, $4, $5)
-        RETURNING id, title, release, publisher, category, student_access"#,
-        book.title,
-        book.release,
-        book.publisher,
-        book.category,
-        book.student_access,
+        r#"SELECT id, title, release, publisher, category, student_access, isbn, branch,
+            classification as "classification: _", call_number, call_number_sort, shelf, version
+        FROM book WHERE isbn = $1"#,
+        isbn
     )
-    .fetch_one(&db)
+    .fetch_optional(&db)
     .await
-    .wrap_err_with(|| eyre!("Unable to add book to database"))
-    .map_err(internal_error)?;
+    .wrap_err_with(|| eyre!("Unable to load book from database"))
+    .map_err(internal_error)?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("Book with ISBN {isbn} does not exist"),
+        )
+    })?;
 
-    Ok((StatusCode::CREATED, Json(inserted_book)))
+    Ok((StatusCode::OK, etag(book.version), Json(book)))
 }
 
-async fn update_book(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(book): Json<Book>,
-) -> Result<(StatusCode, Json<Book>), (StatusCode, String)> {
-    tracing::info!("Book payload: {:?}", book);
-
-    sqlx::query!(
-        r#"UPDATE book SET
-        title = $1,
-        release = $2,
-        publisher = $3, 
-        category = $4, 
-        student_access = $5
-        WHERE id = $6"#,
-        book.title,
-        book.release,
-        book.publisher,
-        book.category,
-        book.student_access,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update book in database"))
-    .map_err(internal_error)?;
+async fn get_sqlite_book_by_isbn(
+    State(db): State<Pool<Sqlite>>,
+    Path(isbn): Path<String>,
+) -> Result<(StatusCode, ETag, Json<Book>), (StatusCode, String)> {
+    let isbn = isbn::normalize(&isbn)
+        .map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, format!("{:#}", err)))?;
 
-    Ok((StatusCode::OK, Json(book)))
+    let book = sqlx::query_as::<_, Book>("SELECT * FROM book WHERE isbn = ?")
+        .bind(&isbn)
+        .fetch_optional(&db)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load book from database"))
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Book with ISBN {isbn} does not exist"),
+            )
+        })?;
+
+    Ok((StatusCode::OK, etag(book.version), Json(book)))
 }
 
-async fn delete_book(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<Book>), (StatusCode, String)> {
-    let deleted_book = sqlx::query_as!(
-        Book,
-        r#"DELETE FROM book WHERE id = $1 
-    RETURNING id, title, release, publisher, category, student_access"#,
-        id
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update book in database"))
-    .map_err(internal_error)?;
+#[async_trait]
+impl Resource for Book {
+    const TABLE: &'static str = "book";
+    const PATH: &'static str = "/book";
+    const BRANCH: Option<&'static str> = Some("branch");
+
+    type Key = i32;
+
+    fn version(&self) -> i32 {
+        self.version
+    }
+
+    fn normalize(&mut self) -> Result<(), (StatusCode, String)> {
+        self.isbn = self
+            .isbn
+            .as_deref()
+            .map(isbn::normalize)
+            .transpose()
+            .map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, format!("{:#}", err)))?;
+
+        // Validation has checked that both or neither are set.
+        if let (Some(classification), Some(call_number)) = (self.classification, &self.call_number)
+        {
+            let call_number = call_number::normalize(classification, call_number)
+                .map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, format!("{:#}", err)))?;
+            self.call_number_sort = Some(call_number::sort_key(classification, &call_number));
+            self.call_number = Some(call_number);
+        } else {
+            self.call_number_sort = None;
+        }
+
+        Ok(())
+    }
+
+    /// A copy without a branch can be given one, copies of a branch only move by transfers.
+    fn check_update(&self, previous: &Self) -> Result<(), (StatusCode, String)> {
+        if previous.branch.is_some() && self.branch != previous.branch {
+            let id = previous.id;
+            return Err((
+                StatusCode::CONFLICT,
+                format!("Book {id} is moved between branches by POST /api/book/{id}/transfer"),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Book,
+            r#"SELECT id, title, release, publisher, category, student_access, isbn, branch,
+            classification as "classification: _", call_number, call_number_sort, shelf, version
+            FROM book ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
+
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Book,
+            r#"SELECT id, title, release, publisher, category, student_access, isbn, branch,
+            classification as "classification: _", call_number, call_number_sort, shelf, version
+            FROM book WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            Book,
+            r#"INSERT INTO book
+            (title, release, publisher, category, student_access, isbn, branch,
+            classification, call_number, call_number_sort, shelf)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING id, title, release, publisher, category, student_access, isbn, branch,
+            classification as "classification: _", call_number, call_number_sort, shelf, version"#,
+            self.title,
+            self.release,
+            self.publisher,
+            self.category,
+            self.student_access,
+            self.isbn,
+            self.branch,
+            self.classification as _,
+            self.call_number,
+            self.call_number_sort,
+            self.shelf,
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Book,
+            r#"UPDATE book SET
+            title = $1,
+            release = $2,
+            publisher = $3,
+            category = $4,
+            student_access = $5,
+            isbn = $6,
+            branch = $7,
+            classification = $8,
+            call_number = $9,
+            call_number_sort = $10,
+            shelf = $11
+            WHERE id = $12 AND version = $13
+            RETURNING id, title, release, publisher, category, student_access, isbn, branch,
+            classification as "classification: _", call_number, call_number_sort, shelf, version"#,
+            self.title,
+            self.release,
+            self.publisher,
+            self.category,
+            self.student_access,
+            self.isbn,
+            self.branch,
+            self.classification as _,
+            self.call_number,
+            self.call_number_sort,
+            self.shelf,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Book,
+            r#"DELETE FROM book WHERE id = $1 AND version = $2
+            RETURNING id, title, release, publisher, category, student_access, isbn, branch,
+            classification as "classification: _", call_number, call_number_sort, shelf, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+}
+
+impl SqliteResource for Book {
+    const COLUMNS: &'static [&'static str] = &[
+        "title",
+        "release",
+        "publisher",
+        "category",
+        "student_access",
+        "isbn",
+        "branch",
+        "classification",
+        "call_number",
+        "call_number_sort",
+        "shelf",
+    ];
 
-    Ok((StatusCode::OK, Json(deleted_book)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query
+            .bind(&self.title)
+            .bind(self.release)
+            .bind(self.publisher)
+            .bind(self.category)
+            .bind(self.student_access)
+            .bind(&self.isbn)
+            .bind(self.branch)
+            .bind(self.classification)
+            .bind(&self.call_number)
+            .bind(&self.call_number_sort)
+            .bind(self.shelf)
+    }
 }
@@ -0,0 +1,661 @@
+use axum::extract::Path;
+use axum::routing::post;
+use axum::{async_trait, extract::State, http::StatusCode, Json, Router};
+use chrono::{Local, NaiveDate};
+use color_eyre::eyre::Context;
+use color_eyre::{eyre::eyre, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Pool, Postgres, Sqlite, SqliteConnection};
+
+use crate::error::{conflict_or_internal_error, internal_error};
+use crate::etag::{etag, ETag};
+use crate::model::{
+    BookStatus, BookTransfer, StudentsBorrowing, TeachersBorrowing, TransferStatus,
+};
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::{label, Resource};
+use crate::web::resource;
+
+#[derive(Deserialize, Debug)]
+struct TransferRequest {
+    to_branch: i32,
+}
+
+#[derive(Deserialize, Debug)]
+struct ReturnRequest {
+    /// Branch the copy is returned at.
+    branch: i32,
+    book_status_finish: BookStatus,
+}
+
+/// Returned loan, with the transfer of the copy back to its branch when returned at another one.
+#[derive(Serialize)]
+struct Return<R> {
+    borrowing: R,
+    transfer: Option<BookTransfer>,
+}
+
+/// Transfers are only listed here, they are written by the transfer, receive and cancel routes
+/// so a copy cannot be sent while lent nor received without moving to its new branch.
+pub fn routes(repository: Shared<BookTransfer>) -> Router {
+    resource::read_routes(repository)
+}
+
+/// Transfers of copies between branches and returns at any branch over Postgres.
+pub fn transfer_routes(db: Pool<Postgres>) -> Router {
+    Router::new()
+        .route("/book/:id/transfer", post(transfer_book))
+        .route("/book-transfer/:id/receive", post(receive_book_transfer))
+        .route("/book-transfer/:id/cancel", post(cancel_book_transfer))
+        .route(
+            "/students-borrowing/:id/return",
+            post(return_loan::<StudentsBorrowing>),
+        )
+        .route(
+            "/teachers-borrowing/:id/return",
+            post(return_loan::<TeachersBorrowing>),
+        )
+        .with_state(db)
+}
+
+/// Transfers of copies between branches over SQLite, loans are returned at their own branch.
+pub fn sqlite_transfer_routes(db: Pool<Sqlite>) -> Router {
+    Router::new()
+        .route("/book/:id/transfer", post(transfer_sqlite_book))
+        .route(
+            "/book-transfer/:id/receive",
+            post(receive_sqlite_book_transfer),
+        )
+        .route(
+            "/book-transfer/:id/cancel",
+            post(cancel_sqlite_book_transfer),
+        )
+        .with_state(db)
+}
+
+/// Branch the copy `id` is sent from, a copy is sent from its own branch when it is not lent.
+///
+/// `branch` is the branch of the copy, `None` when the copy does not exist.
+fn check_transfer(
+    id: i32,
+    branch: Option<Option<i32>>,
+    to_branch: i32,
+    is_lent: bool,
+) -> Result<i32, (StatusCode, String)> {
+    let Some(branch) = branch else {
+        return Err((StatusCode::NOT_FOUND, format!("Book {id} does not exist")));
+    };
+
+    let Some(from_branch) = branch else {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Book {id} does not belong to a branch"),
+        ));
+    };
+
+    if from_branch == to_branch {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Book {id} already belongs to branch {from_branch}"),
+        ));
+    }
+
+    if is_lent {
+        return Err((StatusCode::CONFLICT, format!("Book {id} is lent")));
+    }
+
+    Ok(from_branch)
+}
+
+/// Sends the copy `id` from its branch to another one, the copy stays in transit until received.
+async fn transfer_book(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+    Json(request): Json<TransferRequest>,
+) -> Result<(StatusCode, ETag, Json<BookTransfer>), (StatusCode, String)> {
+    tracing::info!("Transfer payload: {:?}", request);
+
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))
+        .map_err(internal_error)?;
+
+    // The book row is locked so the copy cannot be lent or sent elsewhere meanwhile.
+    let branch = sqlx::query_scalar!(r#"SELECT branch FROM book WHERE id = $1 FOR UPDATE"#, id)
+        .fetch_optional(&mut tx)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load book from database"))
+        .map_err(internal_error)?;
+
+    let is_lent = sqlx::query_scalar!(
+        r#"SELECT EXISTS (
+            SELECT 1 FROM students_borrowing WHERE book = $1 AND return_date IS NULL
+            UNION ALL
+            SELECT 1 FROM teachers_borrowing WHERE book = $1 AND return_date IS NULL
+        ) as "exists!""#,
+        id
+    )
+    .fetch_one(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to check loans of book"))
+    .map_err(internal_error)?;
+
+    let from_branch = check_transfer(id, branch, request.to_branch, is_lent)?;
+    let transfer = send(&mut tx, id, from_branch, request.to_branch).await?;
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit book_transfer"))
+        .map_err(internal_error)?;
+
+    Ok((StatusCode::CREATED, etag(transfer.version), Json(transfer)))
+}
+
+/// Adds a transfer of `book` in transit from `from_branch` to `to_branch`, sent today.
+async fn send(
+    conn: &mut PgConnection,
+    book: i32,
+    from_branch: i32,
+    to_branch: i32,
+) -> Result<BookTransfer, (StatusCode, String)> {
+    check_branch(&mut *conn, to_branch).await?;
+
+    // A copy already in transit breaks the unique index of the transfers in transit.
+    sqlx::query_as!(
+        BookTransfer,
+        r#"INSERT INTO book_transfer
+        (book, from_branch, to_branch, sent_date, status)
+        VALUES ($1, $2, $3, $4, 'in_transit')
+        RETURNING id, book, from_branch, to_branch, sent_date, received_date, status as "status: _", version"#,
+        book,
+        from_branch,
+        to_branch,
+        Local::now().date_naive(),
+    )
+    .fetch_one(conn)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add book_transfer to database"))
+    .map_err(conflict_or_internal_error)
+}
+
+async fn check_branch(conn: &mut PgConnection, id: i32) -> Result<(), (StatusCode, String)> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM branch WHERE id = $1) as "exists!""#,
+        id
+    )
+    .fetch_one(conn)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load branch from database"))
+    .map_err(internal_error)?;
+
+    match exists {
+        true => Ok(()),
+        false => Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Branch {id} does not exist"),
+        )),
+    }
+}
+
+/// Ends the transfer `id` at its destination, the copy now belongs to that branch.
+async fn receive_book_transfer(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, ETag, Json<BookTransfer>), (StatusCode, String)> {
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))
+        .map_err(internal_error)?;
+
+    let transfer = finish(
+        &mut tx,
+        id,
+        TransferStatus::Received,
+        Some(Local::now().date_naive()),
+    )
+    .await?;
+
+    sqlx::query!(
+        r#"UPDATE book SET branch = $1 WHERE id = $2"#,
+        transfer.to_branch,
+        transfer.book
+    )
+    .execute(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update book in database"))
+    .map_err(internal_error)?;
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit book_transfer"))
+        .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, etag(transfer.version), Json(transfer)))
+}
+
+/// Calls off the transfer `id`, the copy stays with the branch it was sent from.
+async fn cancel_book_transfer(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, ETag, Json<BookTransfer>), (StatusCode, String)> {
+    let mut conn = db
+        .acquire()
+        .await
+        .wrap_err_with(|| eyre!("Unable to acquire database connection"))
+        .map_err(internal_error)?;
+
+    let transfer = finish(&mut conn, id, TransferStatus::Cancelled, None).await?;
+
+    Ok((StatusCode::OK, etag(transfer.version), Json(transfer)))
+}
+
+/// Moves the transfer `id` out of transit to `status`.
+async fn finish(
+    conn: &mut PgConnection,
+    id: i32,
+    status: TransferStatus,
+    received_date: Option<NaiveDate>,
+) -> Result<BookTransfer, (StatusCode, String)> {
+    let transfer = sqlx::query_as!(
+        BookTransfer,
+        r#"UPDATE book_transfer SET status = $1, received_date = $2
+        WHERE id = $3 AND status = 'in_transit'
+        RETURNING id, book, from_branch, to_branch, sent_date, received_date, status as "status: _", version"#,
+        status as _,
+        received_date,
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update book_transfer in database"))
+    .map_err(internal_error)?;
+
+    if let Some(transfer) = transfer {
+        return Ok(transfer);
+    }
+
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM book_transfer WHERE id = $1) as "exists!""#,
+        id
+    )
+    .fetch_one(conn)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load book_transfer from database"))
+    .map_err(internal_error)?;
+
+    match exists {
+        true => Err((
+            StatusCode::CONFLICT,
+            format!("Book transfer {id} is not in transit"),
+        )),
+        false => Err((
+            StatusCode::NOT_FOUND,
+            format!("Book transfer {id} does not exist"),
+        )),
+    }
+}
+
+async fn transfer_sqlite_book(
+    State(db): State<Pool<Sqlite>>,
+    Path(id): Path<i32>,
+    Json(request): Json<TransferRequest>,
+) -> Result<(StatusCode, ETag, Json<BookTransfer>), (StatusCode, String)> {
+    tracing::info!("Transfer payload: {:?}", request);
+
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))
+        .map_err(internal_error)?;
+
+    // SQLite has no row locks, a copy sent twice meanwhile breaks the unique index of the
+    // transfers in transit instead.
+    let branch = sqlx::query_scalar::<_, Option<i32>>("SELECT branch FROM book WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut tx)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load book from database"))
+        .map_err(internal_error)?;
+
+    let is_lent = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (
+            SELECT 1 FROM students_borrowing WHERE book = ?1 AND return_date IS NULL
+            UNION ALL
+            SELECT 1 FROM teachers_borrowing WHERE book = ?1 AND return_date IS NULL
+        )",
+    )
+    .bind(id)
+    .fetch_one(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to check loans of book"))
+    .map_err(internal_error)?;
+
+    let from_branch = check_transfer(id, branch, request.to_branch, is_lent)?;
+
+    let to_branch_exists =
+        sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM branch WHERE id = ?)")
+            .bind(request.to_branch)
+            .fetch_one(&mut tx)
+            .await
+            .wrap_err_with(|| eyre!("Unable to load branch from database"))
+            .map_err(internal_error)?;
+
+    if !to_branch_exists {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Branch {} does not exist", request.to_branch),
+        ));
+    }
+
+    let transfer = sqlx::query_as::<_, BookTransfer>(
+        "INSERT INTO book_transfer (book, from_branch, to_branch, sent_date, status)
+        VALUES (?, ?, ?, ?, 'in_transit') RETURNING *",
+    )
+    .bind(id)
+    .bind(from_branch)
+    .bind(request.to_branch)
+    .bind(Local::now().date_naive())
+    .fetch_one(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add book_transfer to database"))
+    .map_err(conflict_or_internal_error)?;
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit book_transfer"))
+        .map_err(internal_error)?;
+
+    Ok((StatusCode::CREATED, etag(transfer.version), Json(transfer)))
+}
+
+async fn receive_sqlite_book_transfer(
+    State(db): State<Pool<Sqlite>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, ETag, Json<BookTransfer>), (StatusCode, String)> {
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))
+        .map_err(internal_error)?;
+
+    let transfer = finish_sqlite(
+        &mut tx,
+        id,
+        TransferStatus::Received,
+        Some(Local::now().date_naive()),
+    )
+    .await?;
+
+    sqlx::query("UPDATE book SET branch = ?, version = version + 1 WHERE id = ?")
+        .bind(transfer.to_branch)
+        .bind(transfer.book)
+        .execute(&mut tx)
+        .await
+        .wrap_err_with(|| eyre!("Unable to update book in database"))
+        .map_err(internal_error)?;
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit book_transfer"))
+        .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, etag(transfer.version), Json(transfer)))
+}
+
+async fn cancel_sqlite_book_transfer(
+    State(db): State<Pool<Sqlite>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, ETag, Json<BookTransfer>), (StatusCode, String)> {
+    let mut conn = db
+        .acquire()
+        .await
+        .wrap_err_with(|| eyre!("Unable to acquire database connection"))
+        .map_err(internal_error)?;
+
+    let transfer = finish_sqlite(&mut conn, id, TransferStatus::Cancelled, None).await?;
+
+    Ok((StatusCode::OK, etag(transfer.version), Json(transfer)))
+}
+
+async fn finish_sqlite(
+    conn: &mut SqliteConnection,
+    id: i32,
+    status: TransferStatus,
+    received_date: Option<NaiveDate>,
+) -> Result<BookTransfer, (StatusCode, String)> {
+    let transfer = sqlx::query_as::<_, BookTransfer>(
+        "UPDATE book_transfer SET status = ?, received_date = ?, version = version + 1
+        WHERE id = ? AND status = 'in_transit' RETURNING *",
+    )
+    .bind(status)
+    .bind(received_date)
+    .bind(id)
+    .fetch_optional(&mut *conn)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update book_transfer in database"))
+    .map_err(internal_error)?;
+
+    if let Some(transfer) = transfer {
+        return Ok(transfer);
+    }
+
+    let exists =
+        sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM book_transfer WHERE id = ?)")
+            .bind(id)
+            .fetch_one(conn)
+            .await
+            .wrap_err_with(|| eyre!("Unable to load book_transfer from database"))
+            .map_err(internal_error)?;
+
+    match exists {
+        true => Err((
+            StatusCode::CONFLICT,
+            format!("Book transfer {id} is not in transit"),
+        )),
+        false => Err((
+            StatusCode::NOT_FOUND,
+            format!("Book transfer {id} does not exist"),
+        )),
+    }
+}
+
+/// Returns the loan `id` of `R` today at any branch.
+///
+/// A copy returned at another branch than its own is sent back to it.
+async fn return_loan<R: Resource<Key = i32>>(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+    Json(request): Json<ReturnRequest>,
+) -> Result<(StatusCode, Json<Return<R>>), (StatusCode, String)> {
+    tracing::info!("Return payload: {:?}", request);
+
+    let name = format!("{} {id}", label(R::TABLE));
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))
+        .map_err(internal_error)?;
+
+    // `TABLE` is a constant of the loan tables, never taken from the request.
+    let loan = sqlx::query_as::<_, (i32, Option<NaiveDate>)>(&format!(
+        "SELECT book, return_date FROM {} WHERE id = $1 FOR UPDATE",
+        R::TABLE
+    ))
+    .bind(id)
+    .fetch_optional(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load {} from database", R::TABLE))
+    .map_err(internal_error)?;
+
+    let book = match loan {
+        None => return Err((StatusCode::NOT_FOUND, format!("{name} does not exist"))),
+        Some((_, Some(_))) => {
+            return Err((StatusCode::CONFLICT, format!("{name} is already returned")))
+        }
+        Some((book, None)) => book,
+    };
+    check_branch(&mut tx, request.branch).await?;
+
+    let today = Local::now().date_naive();
+    sqlx::query(&format!(
+        "UPDATE {} SET return_date = $1, return_branch = $2, book_status_finish = $3 WHERE id = $4",
+        R::TABLE
+    ))
+    .bind(today)
+    .bind(request.branch)
+    .bind(request.book_status_finish)
+    .bind(id)
+    .execute(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update {} in database", R::TABLE))
+    .map_err(internal_error)?;
+
+    let home_branch = sqlx::query_scalar!(r#"SELECT branch FROM book WHERE id = $1"#, book)
+        .fetch_one(&mut tx)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load book from database"))
+        .map_err(internal_error)?;
+
+    let transfer = match home_branch {
+        Some(home_branch) if home_branch != request.branch => {
+            Some(send(&mut tx, book, request.branch, home_branch).await?)
+        }
+        _ => None,
+    };
+
+    let borrowing = R::get(&mut tx, &id)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load {} from database", R::TABLE))
+        .map_err(internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("{name} does not exist")))?;
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit return of {}", R::TABLE))
+        .map_err(internal_error)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(Return {
+            borrowing,
+            transfer,
+        }),
+    ))
+}
+
+#[async_trait]
+impl Resource for BookTransfer {
+    const TABLE: &'static str = "book_transfer";
+    const PATH: &'static str = "/book-transfer";
+
+    type Key = i32;
+
+    fn version(&self) -> i32 {
+        self.version
+    }
+
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            BookTransfer,
+            r#"SELECT id, book, from_branch, to_branch, sent_date, received_date, status as "status: _", version
+            FROM book_transfer ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
+
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            BookTransfer,
+            r#"SELECT id, book, from_branch, to_branch, sent_date, received_date, status as "status: _", version
+            FROM book_transfer WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            BookTransfer,
+            r#"INSERT INTO book_transfer
+            (book, from_branch, to_branch, sent_date, received_date, status)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, book, from_branch, to_branch, sent_date, received_date, status as "status: _", version"#,
+            self.book,
+            self.from_branch,
+            self.to_branch,
+            self.sent_date,
+            self.received_date,
+            self.status as _,
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            BookTransfer,
+            r#"UPDATE book_transfer SET
+            book = $1,
+            from_branch = $2,
+            to_branch = $3,
+            sent_date = $4,
+            received_date = $5,
+            status = $6
+            WHERE id = $7 AND version = $8
+            RETURNING id, book, from_branch, to_branch, sent_date, received_date, status as "status: _", version"#,
+            self.book,
+            self.from_branch,
+            self.to_branch,
+            self.sent_date,
+            self.received_date,
+            self.status as _,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            BookTransfer,
+            r#"DELETE FROM book_transfer WHERE id = $1 AND version = $2
+            RETURNING id, book, from_branch, to_branch, sent_date, received_date, status as "status: _", version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+}
+
+impl SqliteResource for BookTransfer {
+    const COLUMNS: &'static [&'static str] = &[
+        "book",
+        "from_branch",
+        "to_branch",
+        "sent_date",
+        "received_date",
+        "status",
+    ];
+
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query
+            .bind(self.book)
+            .bind(self.from_branch)
+            .bind(self.to_branch)
+            .bind(self.sent_date)
+            .bind(self.received_date)
+            .bind(self.status)
+    }
+}
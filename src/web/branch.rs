@@ -0,0 +1,102 @@
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
+
+use crate::cache::ListCache;
+use crate::model::Branch;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
+
+pub fn routes(repository: Shared<Branch>, cache: ListCache) -> Router {
+    resource::cached_routes(repository, cache)
+}
+
+#[async_trait]
+impl Resource for Branch {
+    const TABLE: &'static str = "branch";
+    const PATH: &'static str = "/branch";
+
+    type Key = i32;
+
+    fn version(&self) -> i32 {
+        self.version
+    }
+
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Branch,
+            r#"SELECT id, name, faculty, version
+            FROM branch ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
+
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Branch,
+            r#"SELECT id, name, faculty, version
+            FROM branch WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            Branch,
+            r#"INSERT INTO branch
+            (name, faculty)
+            VALUES ($1, $2)
+            RETURNING id, name, faculty, version"#,
+            self.name,
+            self.faculty,
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Branch,
+            r#"UPDATE branch SET
+            name = $1,
+            faculty = $2
+            WHERE id = $3 AND version = $4
+            RETURNING id, name, faculty, version"#,
+            self.name,
+            self.faculty,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Branch,
+            r#"DELETE FROM branch WHERE id = $1 AND version = $2
+            RETURNING id, name, faculty, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+}
+
+impl SqliteResource for Branch {
+    const COLUMNS: &'static [&'static str] = &["name", "faculty"];
+
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query.bind(&self.name).bind(self.faculty)
+    }
+}
@@ -0,0 +1,247 @@
+use axum::extract::Path;
+use axum::routing::post;
+use axum::{extract::State, http::StatusCode, Json, Router};
+use chrono::{Duration, Local};
+use color_eyre::eyre::Context;
+use color_eyre::{eyre::eyre, Result};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres, Sqlite};
+
+use crate::error::internal_error;
+use crate::model::{CardState, StudentCard, TeacherCard};
+use crate::repository::sqlite::SqliteResource;
+use crate::resource::label;
+
+// Validity of a reissued card.
+const CARD_VALIDITY_DAYS: i64 = 365;
+
+/// Table of the cards of a kind of patron, reissued by the shared handlers of this module.
+pub trait Card: SqliteResource<Key = i32> {
+    /// Column of the patron the card belongs to.
+    const PATRON: &'static str;
+
+    fn patron(&self) -> i32;
+}
+
+impl Card for StudentCard {
+    const PATRON: &'static str = "student";
+
+    fn patron(&self) -> i32 {
+        self.student
+    }
+}
+
+impl Card for TeacherCard {
+    const PATRON: &'static str = "teacher";
+
+    fn patron(&self) -> i32 {
+        self.teacher
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct ReissueRequest {
+    state: CardState,
+}
+
+/// Reissue of the cards of `C` over Postgres.
+pub fn reissue_routes<C: Card>(db: Pool<Postgres>) -> Router {
+    Router::new()
+        .route(&format!("{}/:id/reissue", C::PATH), post(reissue::<C>))
+        .with_state(db)
+}
+
+/// Reissue of the cards of `C` over SQLite.
+pub fn sqlite_reissue_routes<C: Card>(db: Pool<Sqlite>) -> Router {
+    Router::new()
+        .route(
+            &format!("{}/:id/reissue", C::PATH),
+            post(reissue_sqlite::<C>),
+        )
+        .with_state(db)
+}
+
+fn check_request(request: &ReissueRequest) -> Result<(), (StatusCode, String)> {
+    if matches!(request.state, CardState::Active) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Previous card cannot stay active".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Error for a reissue of card `id` which matched no card, depending on whether it exists.
+fn not_reissued<C: Card>(id: i32, exists: bool) -> (StatusCode, String) {
+    if exists {
+        (
+            StatusCode::CONFLICT,
+            format!(
+                "{} {id} is lost or blocked, it cannot be reissued",
+                label(C::TABLE)
+            ),
+        )
+    } else {
+        (
+            StatusCode::NOT_FOUND,
+            format!("{} {id} does not exist", label(C::TABLE)),
+        )
+    }
+}
+
+// Table and column names below are constants of the card, never taken from the request.
+
+async fn reissue<C: Card>(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+    Json(request): Json<ReissueRequest>,
+) -> Result<(StatusCode, Json<C>), (StatusCode, String)> {
+    tracing::info!("Reissue payload: {:?}", request);
+    check_request(&request)?;
+
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))
+        .map_err(internal_error)?;
+
+    // A lost or blocked card was already replaced, or must not be.
+    let previous_card = sqlx::query_as::<_, C>(&format!(
+        "UPDATE {} SET state = $1 WHERE id = $2 AND state NOT IN ('lost', 'blocked') RETURNING *",
+        C::TABLE
+    ))
+    .bind(request.state)
+    .bind(id)
+    .fetch_optional(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update {} in database", C::TABLE))
+    .map_err(internal_error)?;
+
+    let Some(previous_card) = previous_card else {
+        let exists = sqlx::query_scalar::<_, bool>(&format!(
+            "SELECT EXISTS (SELECT 1 FROM {} WHERE id = $1)",
+            C::TABLE
+        ))
+        .bind(id)
+        .fetch_one(&mut tx)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load {} from database", C::TABLE))
+        .map_err(internal_error)?;
+
+        return Err(not_reissued::<C>(id, exists));
+    };
+
+    // Any other card of the same patron is invalidated as well, so only the new one is active.
+    sqlx::query(&format!(
+        "UPDATE {} SET state = 'blocked' WHERE {} = $1 AND state = 'active'",
+        C::TABLE,
+        C::PATRON
+    ))
+    .bind(previous_card.patron())
+    .execute(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update {} in database", C::TABLE))
+    .map_err(internal_error)?;
+
+    let today = Local::now().date_naive();
+
+    let card = sqlx::query_as::<_, C>(&format!(
+        "INSERT INTO {} ({}, issue_date, expiry_date, state)
+        VALUES ($1, $2, $3, 'active') RETURNING *",
+        C::TABLE,
+        C::PATRON
+    ))
+    .bind(previous_card.patron())
+    .bind(today)
+    .bind(today + Duration::days(CARD_VALIDITY_DAYS))
+    .fetch_one(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add {} to database", C::TABLE))
+    .map_err(internal_error)?;
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit reissue of {}", C::TABLE))
+        .map_err(internal_error)?;
+
+    Ok((StatusCode::CREATED, Json(card)))
+}
+
+async fn reissue_sqlite<C: Card>(
+    State(db): State<Pool<Sqlite>>,
+    Path(id): Path<i32>,
+    Json(request): Json<ReissueRequest>,
+) -> Result<(StatusCode, Json<C>), (StatusCode, String)> {
+    tracing::info!("Reissue payload: {:?}", request);
+    check_request(&request)?;
+
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))
+        .map_err(internal_error)?;
+
+    // SQLite has no row locks, the state is checked by the update taking the write lock instead.
+    let previous_card = sqlx::query_as::<_, C>(&format!(
+        "UPDATE {} SET state = ?, version = version + 1
+        WHERE id = ? AND state NOT IN ('lost', 'blocked') RETURNING *",
+        C::TABLE
+    ))
+    .bind(request.state)
+    .bind(id)
+    .fetch_optional(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update {} in database", C::TABLE))
+    .map_err(internal_error)?;
+
+    let Some(previous_card) = previous_card else {
+        let exists = sqlx::query_scalar::<_, bool>(&format!(
+            "SELECT EXISTS (SELECT 1 FROM {} WHERE id = ?)",
+            C::TABLE
+        ))
+        .bind(id)
+        .fetch_one(&mut tx)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load {} from database", C::TABLE))
+        .map_err(internal_error)?;
+
+        return Err(not_reissued::<C>(id, exists));
+    };
+
+    // Any other card of the same patron is invalidated as well, so only the new one is active.
+    sqlx::query(&format!(
+        "UPDATE {} SET state = 'blocked', version = version + 1
+        WHERE {} = ? AND state = 'active'",
+        C::TABLE,
+        C::PATRON
+    ))
+    .bind(previous_card.patron())
+    .execute(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update {} in database", C::TABLE))
+    .map_err(internal_error)?;
+
+    let today = Local::now().date_naive();
+
+    let card = sqlx::query_as::<_, C>(&format!(
+        "INSERT INTO {} ({}, issue_date, expiry_date, state)
+        VALUES (?, ?, ?, 'active') RETURNING *",
+        C::TABLE,
+        C::PATRON
+    ))
+    .bind(previous_card.patron())
+    .bind(today)
+    .bind(today + Duration::days(CARD_VALIDITY_DAYS))
+    .fetch_one(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add {} to database", C::TABLE))
+    .map_err(internal_error)?;
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit reissue of {}", C::TABLE))
+        .map_err(internal_error)?;
+
+    Ok((StatusCode::CREATED, Json(card)))
+}
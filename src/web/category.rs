@@ -1,91 +1,99 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
+use crate::cache::ListCache;
 use crate::model::Category;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route("/category", get(get_categorys).post(create_category))
-        .route(
-            "/category/:id",
-            put(update_category).delete(delete_category),
-        )
-        .with_state(db)
+pub fn routes(repository: Shared<Category>, cache: ListCache) -> Router {
+    resource::cached_routes(repository, cache)
 }
 
-async fn get_categorys(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<Category>>), (StatusCode, String)> {
-    let categorys = sqlx::query_as!(Category, r#"SELECT id, name FROM category ORDER BY id ASC"#)
-        .fetch_all(&db)
-        .await
-        .wrap_err_with(|| eyre!("Unable to load categorys from database"))
-        .map_err(internal_error)?;
+#[async_trait]
+impl Resource for Category {
+    const TABLE: &'static str = "category";
+    const PATH: &'static str = "/category";
 
-    Ok((StatusCode::OK, Json(categorys)))
-}
+    type Key = i32;
 
-async fn create_category(
-    State(db): State<Pool<Postgres>>,
-    Json(category): Json<Category>,
-) -> Result<(StatusCode, Json<Category>), (StatusCode, String)> {
-    let inserted_category = sqlx::query_as!(
-        Category,
-        r#"INSERT INTO category 
-        (name)
-        VALUES ($1)
-        RETURNING id, name"#,
-        category.name,
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add category to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_category)))
-}
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Category,
+            r#"SELECT id, name, version
+            FROM category ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
 
-async fn update_category(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(category): Json<Category>,
-) -> Result<(StatusCode, Json<Category>), (StatusCode, String)> {
-    tracing::info!("Category payload: {:?}", category);
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Category,
+            r#"SELECT id, name, version
+            FROM category WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE category SET
-        name = $1
-        WHERE id = $2"#,
-        category.name,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update category in database"))
-    .map_err(internal_error)?;
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            Category,
+            r#"INSERT INTO category
+            (name)
+            VALUES ($1)
+            RETURNING id, name, version"#,
+            self.name,
+        )
+        .fetch_one(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(category)))
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Category,
+            r#"UPDATE category SET
+            name = $1
+            WHERE id = $2 AND version = $3
+            RETURNING id, name, version"#,
+            self.name,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Category,
+            r#"DELETE FROM category WHERE id = $1 AND version = $2
+            RETURNING id, name, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 }
 
-async fn delete_category(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<Category>), (StatusCode, String)> {
-    let deleted_category = sqlx::query_as!(
-        Category,
-        r#"DELETE FROM category WHERE id = $1 
-        RETURNING id, name"#,
-        id
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update category in database"))
-    .map_err(internal_error)?;
+impl SqliteResource for Category {
+    const COLUMNS: &'static [&'static str] = &["name"];
 
-    Ok((StatusCode::OK, Json(deleted_category)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query.bind(&self.name)
+    }
 }
@@ -1,94 +1,107 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
+use crate::cache::ListCache;
 use crate::model::Country;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route("/country", get(get_countrys).post(create_country))
-        .route("/country/:code", put(update_country).delete(delete_country))
-        .with_state(db)
+pub fn routes(repository: Shared<Country>, cache: ListCache) -> Router {
+    resource::cached_routes(repository, cache)
 }
 
-async fn get_countrys(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<Country>>), (StatusCode, String)> {
-    let countrys = sqlx::query_as!(
-        Country,
-        r#"SELECT code, name FROM country ORDER BY code ASC"#
-    )
-    .fetch_all(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to load countrys from database"))
-    .map_err(internal_error)?;
+#[async_trait]
+impl Resource for Country {
+    const TABLE: &'static str = "country";
+    const PATH: &'static str = "/country";
+    const KEY: &'static str = "code";
 
-    Ok((StatusCode::OK, Json(countrys)))
-}
+    type Key = String;
 
-async fn create_country(
-    State(db): State<Pool<Postgres>>,
-    Json(country): Json<Country>,
-) -> Result<(StatusCode, Json<Country>), (StatusCode, String)> {
-    let inserted_country = sqlx::query_as!(
-        Country,
-        r#"INSERT INTO country 
-        (code, name)
-        VALUES ($1, $2)
-        RETURNING code, name"#,
-        country.code,
-        country.name,
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add country to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_country)))
-}
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Country,
+            r#"SELECT code, name, version
+            FROM country ORDER BY code ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
+
+    async fn get(conn: &mut PgConnection, code: &String) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Country,
+            r#"SELECT code, name, version
+            FROM country WHERE code = $1"#,
+            code
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-async fn update_country(
-    State(db): State<Pool<Postgres>>,
-    Path(code): Path<String>,
-    Json(country): Json<Country>,
-) -> Result<(StatusCode, Json<Country>), (StatusCode, String)> {
-    tracing::info!("Country payload: {:?}", country);
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            Country,
+            r#"INSERT INTO country
+            (code, name)
+            VALUES ($1, $2)
+            RETURNING code, name, version"#,
+            self.code,
+            self.name,
+        )
+        .fetch_one(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE country SET
-        code = $1,
-        name = $2
-        WHERE code = $3"#,
-        country.code,
-        country.name,
-        code
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update country in database"))
-    .map_err(internal_error)?;
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        code: &String,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Country,
+            r#"UPDATE country SET
+            code = $1,
+            name = $2
+            WHERE code = $3 AND version = $4
+            RETURNING code, name, version"#,
+            self.code,
+            self.name,
+            code,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(country)))
+    async fn delete(
+        conn: &mut PgConnection,
+        code: &String,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Country,
+            r#"DELETE FROM country WHERE code = $1 AND version = $2
+            RETURNING code, name, version"#,
+            code,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 }
 
-async fn delete_country(
-    State(db): State<Pool<Postgres>>,
-    Path(code): Path<String>,
-) -> Result<(StatusCode, Json<Country>), (StatusCode, String)> {
-    let deleted_country = sqlx::query_as!(
-        Country,
-        r#"DELETE FROM country WHERE code = $1 
-        RETURNING code, name"#,
-        code
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update country in database"))
-    .map_err(internal_error)?;
+impl SqliteResource for Country {
+    const COLUMNS: &'static [&'static str] = &["code", "name"];
 
-    Ok((StatusCode::OK, Json(deleted_country)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query.bind(&self.code).bind(&self.name)
+    }
 }
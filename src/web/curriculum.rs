@@ -1,97 +1,102 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
+use crate::cache::ListCache;
 use crate::model::Curriculum;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route("/curriculum", get(get_curriculums).post(create_curriculum))
-        .route(
-            "/curriculum/:id",
-            put(update_curriculum).delete(delete_curriculum),
-        )
-        .with_state(db)
+pub fn routes(repository: Shared<Curriculum>, cache: ListCache) -> Router {
+    resource::cached_routes(repository, cache)
 }
 
-async fn get_curriculums(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<Curriculum>>), (StatusCode, String)> {
-    let curriculums = sqlx::query_as!(
-        Curriculum,
-        r#"SELECT id, name, letter FROM curriculum ORDER BY id ASC"#
-    )
-    .fetch_all(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to load curriculums from database"))
-    .map_err(internal_error)?;
+#[async_trait]
+impl Resource for Curriculum {
+    const TABLE: &'static str = "curriculum";
+    const PATH: &'static str = "/curriculum";
 
-    Ok((StatusCode::OK, Json(curriculums)))
-}
+    type Key = i32;
 
-async fn create_curriculum(
-    State(db): State<Pool<Postgres>>,
-    Json(curriculum): Json<Curriculum>,
-) -> Result<(StatusCode, Json<Curriculum>), (StatusCode, String)> {
-    let inserted_curriculum = sqlx::query_as!(
-        Curriculum,
-        r#"INSERT INTO curriculum 
-        (name, letter)
-        VALUES ($1, $2)
-        RETURNING id, name, letter"#,
-        curriculum.name,
-        curriculum.letter,
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add curriculum to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_curriculum)))
-}
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Curriculum,
+            r#"SELECT id, name, letter, version
+            FROM curriculum ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
 
-async fn update_curriculum(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(curriculum): Json<Curriculum>,
-) -> Result<(StatusCode, Json<Curriculum>), (StatusCode, String)> {
-    tracing::info!("Curriculum payload: {:?}", curriculum);
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Curriculum,
+            r#"SELECT id, name, letter, version
+            FROM curriculum WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE curriculum SET
-        name = $1,
-        letter = $2
-        WHERE id = $3"#,
-        curriculum.name,
-        curriculum.letter,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update curriculum in database"))
-    .map_err(internal_error)?;
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            Curriculum,
+            r#"INSERT INTO curriculum
+            (name, letter)
+            VALUES ($1, $2)
+            RETURNING id, name, letter, version"#,
+            self.name,
+            self.letter,
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Curriculum,
+            r#"UPDATE curriculum SET
+            name = $1,
+            letter = $2
+            WHERE id = $3 AND version = $4
+            RETURNING id, name, letter, version"#,
+            self.name,
+            self.letter,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(curriculum)))
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Curriculum,
+            r#"DELETE FROM curriculum WHERE id = $1 AND version = $2
+            RETURNING id, name, letter, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 }
 
-async fn delete_curriculum(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<Curriculum>), (StatusCode, String)> {
-    let deleted_curriculum = sqlx::query_as!(
-        Curriculum,
-        r#"DELETE FROM curriculum WHERE id = $1 
-        RETURNING id, name, letter"#,
-        id
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update curriculum in database"))
-    .map_err(internal_error)?;
+impl SqliteResource for Curriculum {
+    const COLUMNS: &'static [&'static str] = &["name", "letter"];
 
-    Ok((StatusCode::OK, Json(deleted_curriculum)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query.bind(&self.name).bind(&self.letter)
+    }
 }
@@ -0,0 +1,123 @@
+use std::collections::HashSet;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use futures_util::{Stream, StreamExt};
+use serde::Deserialize;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+
+use crate::change_feed::{ChangeFeed, FeedEvent, TABLES};
+use crate::error::internal_error;
+
+/// `GET /events` streams the changes of the tables as Server-Sent Events,
+/// `GET /events/ws` sends the same events as WebSocket text messages.
+pub fn routes(feed: ChangeFeed) -> Router {
+    Router::new()
+        .route("/events", get(sse_events))
+        .route("/events/ws", get(ws_events))
+        .with_state(feed)
+}
+
+#[derive(Deserialize)]
+struct Subscription {
+    /// Comma separated tables to stream, every table when missing.
+    tables: Option<String>,
+}
+
+impl Subscription {
+    fn tables(&self) -> Result<Option<HashSet<String>>, (StatusCode, String)> {
+        let Some(tables) = &self.tables else {
+            return Ok(None);
+        };
+
+        tables
+            .split(',')
+            .map(str::trim)
+            .filter(|table| !table.is_empty())
+            .map(|table| match TABLES.contains(&table) {
+                true => Ok(table.to_string()),
+                false => Err((StatusCode::BAD_REQUEST, format!("Unknown table {table}"))),
+            })
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+}
+
+/// Events of the feed about `tables`, or about every table.
+async fn events(
+    feed: &ChangeFeed,
+    subscription: Subscription,
+) -> Result<impl Stream<Item = FeedEvent>, (StatusCode, String)> {
+    let tables = subscription.tables()?;
+    let receiver = feed.subscribe().await.map_err(internal_error)?;
+
+    Ok(BroadcastStream::new(receiver).filter_map(move |event| {
+        let event = match event.as_deref() {
+            Ok(FeedEvent::Change(change)) => tables
+                .as_ref()
+                .is_none_or(|tables| tables.contains(&change.table))
+                .then(|| FeedEvent::Change(change.clone())),
+            Ok(FeedEvent::Resync) | Err(BroadcastStreamRecvError::Lagged(_)) => {
+                Some(FeedEvent::Resync)
+            }
+        };
+
+        async move { event }
+    }))
+}
+
+async fn sse_events(
+    State(feed): State<ChangeFeed>,
+    Query(subscription): Query<Subscription>,
+) -> Result<Sse<impl Stream<Item = Result<Event, serde_json::Error>>>, (StatusCode, String)> {
+    let events = events(&feed, subscription).await?;
+
+    Ok(
+        Sse::new(events.map(|event| Event::default().event(event.name()).json_data(&event)))
+            .keep_alive(KeepAlive::default()),
+    )
+}
+
+async fn ws_events(
+    State(feed): State<ChangeFeed>,
+    Query(subscription): Query<Subscription>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, (StatusCode, String)> {
+    // Subscribed before the upgrade, so a bad filter is still answered with a status.
+    let events = events(&feed, subscription).await?;
+
+    Ok(upgrade.on_upgrade(|socket| send_events(socket, events)))
+}
+
+/// Sends `events` until the client goes away, messages of the client are ignored.
+async fn send_events(mut socket: WebSocket, events: impl Stream<Item = FeedEvent>) {
+    let mut events = std::pin::pin!(events);
+
+    loop {
+        tokio::select! {
+            event = events.next() => {
+                let Some(event) = event else { break };
+                let text = match serde_json::to_string(&event) {
+                    Ok(text) => text,
+                    Err(err) => {
+                        tracing::error!("Unable to serialize {} event: {err}", event.name());
+                        continue;
+                    }
+                };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -1,94 +1,102 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
+use crate::cache::ListCache;
 use crate::model::Faculty;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route("/faculty", get(get_facultys).post(create_faculty))
-        .route("/faculty/:id", put(update_faculty).delete(delete_faculty))
-        .with_state(db)
+pub fn routes(repository: Shared<Faculty>, cache: ListCache) -> Router {
+    resource::cached_routes(repository, cache)
 }
 
-async fn get_facultys(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<Faculty>>), (StatusCode, String)> {
-    let facultys = sqlx::query_as!(
-        Faculty,
-        r#"SELECT id, name, letter FROM faculty ORDER BY id ASC"#
-    )
-    .fetch_all(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to load facultys from database"))
-    .map_err(internal_error)?;
+#[async_trait]
+impl Resource for Faculty {
+    const TABLE: &'static str = "faculty";
+    const PATH: &'static str = "/faculty";
 
-    Ok((StatusCode::OK, Json(facultys)))
-}
+    type Key = i32;
 
-async fn create_faculty(
-    State(db): State<Pool<Postgres>>,
-    Json(faculty): Json<Faculty>,
-) -> Result<(StatusCode, Json<Faculty>), (StatusCode, String)> {
-    let inserted_faculty = sqlx::query_as!(
-        Faculty,
-        r#"INSERT INTO faculty 
-        (name, letter)
-        VALUES ($1, $2)
-        RETURNING id, name, letter"#,
-        faculty.name,
-        faculty.letter,
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add faculty to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_faculty)))
-}
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Faculty,
+            r#"SELECT id, name, letter, version
+            FROM faculty ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
+
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Faculty,
+            r#"SELECT id, name, letter, version
+            FROM faculty WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-async fn update_faculty(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(faculty): Json<Faculty>,
-) -> Result<(StatusCode, Json<Faculty>), (StatusCode, String)> {
-    tracing::info!("Faculty payload: {:?}", faculty);
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            Faculty,
+            r#"INSERT INTO faculty
+            (name, letter)
+            VALUES ($1, $2)
+            RETURNING id, name, letter, version"#,
+            self.name,
+            self.letter,
+        )
+        .fetch_one(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE faculty SET
-        name = $1,
-        letter = $2
-        WHERE id = $3"#,
-        faculty.name,
-        faculty.letter,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update faculty in database"))
-    .map_err(internal_error)?;
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Faculty,
+            r#"UPDATE faculty SET
+            name = $1,
+            letter = $2
+            WHERE id = $3 AND version = $4
+            RETURNING id, name, letter, version"#,
+            self.name,
+            self.letter,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(faculty)))
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Faculty,
+            r#"DELETE FROM faculty WHERE id = $1 AND version = $2
+            RETURNING id, name, letter, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 }
 
-async fn delete_faculty(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<Faculty>), (StatusCode, String)> {
-    let deleted_faculty = sqlx::query_as!(
-        Faculty,
-        r#"DELETE FROM faculty WHERE id = $1 
-        RETURNING id, name, letter"#,
-        id
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update faculty in database"))
-    .map_err(internal_error)?;
+impl SqliteResource for Faculty {
+    const COLUMNS: &'static [&'static str] = &["name", "letter"];
 
-    Ok((StatusCode::OK, Json(deleted_faculty)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query.bind(&self.name).bind(&self.letter)
+    }
 }
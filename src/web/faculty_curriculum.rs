@@ -1,100 +1,101 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
 use crate::model::FacultyCurriculum;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route(
-            "/faculty-curriculum",
-            get(get_faculty_curriculums).post(create_faculty_curriculum),
-        )
-        .route(
-            "/faculty-curriculum/:id",
-            put(update_faculty_curriculum).delete(delete_faculty_curriculum),
-        )
-        .with_state(db)
+pub fn routes(repository: Shared<FacultyCurriculum>) -> Router {
+    resource::routes(repository)
 }
 
-async fn get_faculty_curriculums(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<FacultyCurriculum>>), (StatusCode, String)> {
-    let faculty_curriculums = sqlx::query_as!(
-        FacultyCurriculum,
-        r#"SELECT id, faculty, curriculum FROM faculty_curriculum ORDER BY id ASC"#
-    )
-    .fetch_all(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to load faculty_curriculums from database"))
-    .map_err(internal_error)?;
+#[async_trait]
+impl Resource for FacultyCurriculum {
+    const TABLE: &'static str = "faculty_curriculum";
+    const PATH: &'static str = "/faculty-curriculum";
 
-    Ok((StatusCode::OK, Json(faculty_curriculums)))
-}
+    type Key = i32;
 
-async fn create_faculty_curriculum(
-    State(db): State<Pool<Postgres>>,
-    Json(faculty_curriculum): Json<FacultyCurriculum>,
-) -> Result<(StatusCode, Json<FacultyCurriculum>), (StatusCode, String)> {
-    let inserted_faculty_curriculum = sqlx::query_as!(
-        FacultyCurriculum,
-        r#"INSERT INTO faculty_curriculum 
-        (faculty, curriculum)
-        VALUES ($1, $2)
-        RETURNING id, faculty, curriculum"#,
-        faculty_curriculum.faculty,
-        faculty_curriculum.curriculum,
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add faculty_curriculum to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_faculty_curriculum)))
-}
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            FacultyCurriculum,
+            r#"SELECT id, faculty, curriculum, version
+            FROM faculty_curriculum ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
 
-async fn update_faculty_curriculum(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(faculty_curriculum): Json<FacultyCurriculum>,
-) -> Result<(StatusCode, Json<FacultyCurriculum>), (StatusCode, String)> {
-    tracing::info!("FacultyCurriculum payload: {:?}", faculty_curriculum);
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            FacultyCurriculum,
+            r#"SELECT id, faculty, curriculum, version
+            FROM faculty_curriculum WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE faculty_curriculum SET
-        faculty = $1,
-        curriculum = $2
-        WHERE id = $3"#,
-        faculty_curriculum.faculty,
-        faculty_curriculum.curriculum,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update faculty_curriculum in database"))
-    .map_err(internal_error)?;
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            FacultyCurriculum,
+            r#"INSERT INTO faculty_curriculum
+            (faculty, curriculum)
+            VALUES ($1, $2)
+            RETURNING id, faculty, curriculum, version"#,
+            self.faculty,
+            self.curriculum,
+        )
+        .fetch_one(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(faculty_curriculum)))
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            FacultyCurriculum,
+            r#"UPDATE faculty_curriculum SET
+            faculty = $1,
+            curriculum = $2
+            WHERE id = $3 AND version = $4
+            RETURNING id, faculty, curriculum, version"#,
+            self.faculty,
+            self.curriculum,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            FacultyCurriculum,
+            r#"DELETE FROM faculty_curriculum WHERE id = $1 AND version = $2
+            RETURNING id, faculty, curriculum, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 }
 
-async fn delete_faculty_curriculum(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<FacultyCurriculum>), (StatusCode, String)> {
-    let deleted_faculty_curriculum = sqlx::query_as!(
-        FacultyCurriculum,
-        r#"DELETE FROM faculty_curriculum WHERE id = $1 
-        RETURNING id, faculty, curriculum"#,
-        id
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update faculty_curriculum in database"))
-    .map_err(internal_error)?;
+impl SqliteResource for FacultyCurriculum {
+    const COLUMNS: &'static [&'static str] = &["faculty", "curriculum"];
 
-    Ok((StatusCode::OK, Json(deleted_faculty_curriculum)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query.bind(self.faculty).bind(self.curriculum)
+    }
 }
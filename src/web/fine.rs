@@ -0,0 +1,481 @@
+use axum::extract::{Path, Query};
+use axum::routing::put;
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use color_eyre::eyre::Context;
+use color_eyre::{eyre::eyre, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Pool, Postgres};
+
+use crate::error::{conflict_or_internal_error, internal_error};
+use crate::etag::{etag, ETag, IfMatch};
+use crate::fine::{self, format_amount};
+use crate::model::{Balance, FinePolicy, LedgerEntry, LedgerEntryKind};
+use crate::repository::{self, postgres::not_found_or_modified};
+use crate::resource::label;
+use crate::validation::ValidatedJson;
+use crate::web::resource::repository_error;
+
+pub fn routes(db: Pool<Postgres>) -> Router {
+    Router::new()
+        .route(
+            "/ledger-entry",
+            get(get_ledger_entries).post(create_ledger_entry),
+        )
+        .route("/ledger-entry/:id", get(get_ledger_entry))
+        .route("/ledger-entry/:id/receipt", get(get_receipt))
+        .route("/student-card/:id/balance", get(get_student_card_balance))
+        .route("/teacher-card/:id/balance", get(get_teacher_card_balance))
+        .route(
+            "/fine-policy",
+            get(get_fine_policies).post(create_fine_policy),
+        )
+        .route(
+            "/fine-policy/:card_table",
+            put(update_fine_policy).delete(delete_fine_policy),
+        )
+        .with_state(db)
+}
+
+/// Entries of this card or patron only, `?student_card=3` is the account of a student card and
+/// `?student=2` the account of a student across their cards.
+#[derive(Deserialize)]
+struct LedgerFilter {
+    student_card: Option<i32>,
+    teacher_card: Option<i32>,
+    student: Option<i32>,
+    teacher: Option<i32>,
+}
+
+/// Receipt handed to the patron for a payment or a waiver.
+#[derive(Serialize)]
+struct Receipt {
+    number: String,
+    patron: String,
+    entry: LedgerEntry,
+    /// Balance of the patron in cents once the entry is counted.
+    balance: i64,
+}
+
+fn entry_not_found(id: i32) -> (StatusCode, String) {
+    (
+        StatusCode::NOT_FOUND,
+        format!("Ledger entry {id} does not exist"),
+    )
+}
+
+/// Table and key of the card of `entry`, validated entries have exactly one.
+fn card_of(entry: &LedgerEntry) -> (&'static str, i32) {
+    match (entry.student_card, entry.teacher_card) {
+        (Some(card), _) => ("student_card", card),
+        (None, card) => ("teacher_card", card.unwrap_or_default()),
+    }
+}
+
+/// Checks `card_table` names a table of cards, it is put into queries.
+fn check_card_table(card_table: &str) -> Result<&'static str, (StatusCode, String)> {
+    match card_table {
+        "student_card" => Ok("student_card"),
+        "teacher_card" => Ok("teacher_card"),
+        _ => Err((
+            StatusCode::NOT_FOUND,
+            format!("{card_table} is not a table of cards"),
+        )),
+    }
+}
+
+async fn get_ledger_entries(
+    State(db): State<Pool<Postgres>>,
+    Query(filter): Query<LedgerFilter>,
+) -> Result<(StatusCode, Json<Vec<LedgerEntry>>), (StatusCode, String)> {
+    let entries = sqlx::query_as!(
+        LedgerEntry,
+        r#"SELECT id, student_card, teacher_card, kind as "kind: _", amount, students_borrowing,
+        teachers_borrowing, librarian, reason, created_at
+        FROM ledger_entry
+        WHERE ($1::INTEGER IS NULL OR student_card = $1)
+        AND ($2::INTEGER IS NULL OR teacher_card = $2)
+        AND ($3::INTEGER IS NULL OR student_card IN (SELECT id FROM student_card WHERE student = $3))
+        AND ($4::INTEGER IS NULL OR teacher_card IN (SELECT id FROM teacher_card WHERE teacher = $4))
+        ORDER BY id ASC"#,
+        filter.student_card,
+        filter.teacher_card,
+        filter.student,
+        filter.teacher
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load ledger_entries from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(entries)))
+}
+
+async fn get_ledger_entry(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, Json<LedgerEntry>), (StatusCode, String)> {
+    let mut conn = db
+        .acquire()
+        .await
+        .wrap_err_with(|| eyre!("Unable to acquire database connection"))
+        .map_err(internal_error)?;
+
+    let entry = load_entry(&mut conn, id).await?;
+
+    Ok((StatusCode::OK, Json(entry)))
+}
+
+async fn load_entry(conn: &mut PgConnection, id: i32) -> Result<LedgerEntry, (StatusCode, String)> {
+    sqlx::query_as!(
+        LedgerEntry,
+        r#"SELECT id, student_card, teacher_card, kind as "kind: _", amount, students_borrowing,
+        teachers_borrowing, librarian, reason, created_at
+        FROM ledger_entry WHERE id = $1"#,
+        id
+    )
+    .fetch_optional(conn)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load ledger_entry from database"))
+    .map_err(internal_error)?
+    .ok_or_else(|| entry_not_found(id))
+}
+
+/// Adds a charge, payment or waiver to the account of a card.
+///
+/// Payments and waivers cannot take the balance of the patron below zero, the library keeps no
+/// credit.
+async fn create_ledger_entry(
+    State(db): State<Pool<Postgres>>,
+    ValidatedJson(entry): ValidatedJson<LedgerEntry>,
+) -> Result<(StatusCode, Json<LedgerEntry>), (StatusCode, String)> {
+    tracing::info!("Ledger entry payload: {:?}", entry);
+
+    let (card_table, card) = card_of(&entry);
+    let patron = fine::patron_column(card_table);
+    let name = format!("{} {card}", label(card_table));
+
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))
+        .map_err(internal_error)?;
+
+    // Every card of the patron is locked so checkouts and other entries wait for the new balance.
+    let cards = sqlx::query_scalar::<_, i32>(&format!(
+        "SELECT id FROM {card_table}
+        WHERE {patron} = (SELECT {patron} FROM {card_table} WHERE id = $1) FOR UPDATE"
+    ))
+    .bind(card)
+    .fetch_all(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load {card_table} from database"))
+    .map_err(internal_error)?;
+
+    if !cards.contains(&card) {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("{name} does not exist"),
+        ));
+    }
+
+    let borrowing = match (entry.students_borrowing, entry.teachers_borrowing) {
+        (Some(borrowing), _) => Some(("students_borrowing", borrowing)),
+        (None, Some(borrowing)) => Some(("teachers_borrowing", borrowing)),
+        (None, None) => None,
+    };
+    if let Some((borrowing_table, borrowing)) = borrowing {
+        let is_on_card = sqlx::query_scalar::<_, bool>(&format!(
+            "SELECT EXISTS (SELECT 1 FROM {borrowing_table} WHERE id = $1 AND {card_table} = $2)"
+        ))
+        .bind(borrowing)
+        .bind(card)
+        .fetch_one(&mut tx)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load {borrowing_table} from database"))
+        .map_err(internal_error)?;
+
+        if !is_on_card {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "{} {borrowing} is not a loan on {name}",
+                    label(borrowing_table)
+                ),
+            ));
+        }
+    }
+
+    if let Some(librarian) = entry.librarian {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM librarian WHERE id = $1) as "exists!""#,
+            librarian
+        )
+        .fetch_one(&mut tx)
+        .await
+        .wrap_err_with(|| eyre!("Unable to load librarian from database"))
+        .map_err(internal_error)?;
+
+        if !exists {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Librarian {librarian} does not exist"),
+            ));
+        }
+    }
+
+    if !entry.kind.is_charge() {
+        let balance = fine::balance(&mut tx, card_table, card, None)
+            .await
+            .map_err(internal_error)?;
+
+        if i64::from(entry.amount) > balance {
+            let kind = match entry.kind {
+                LedgerEntryKind::Waiver => "Waiver",
+                _ => "Payment",
+            };
+            return Err((
+                StatusCode::CONFLICT,
+                format!(
+                    "{kind} of {} is more than the balance of {} of the {patron} of {name}",
+                    format_amount(entry.amount.into()),
+                    format_amount(balance)
+                ),
+            ));
+        }
+    }
+
+    let entry = sqlx::query_as!(
+        LedgerEntry,
+        r#"INSERT INTO ledger_entry
+        (student_card, teacher_card, kind, amount, students_borrowing, teachers_borrowing, librarian, reason)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, student_card, teacher_card, kind as "kind: _", amount, students_borrowing,
+        teachers_borrowing, librarian, reason, created_at"#,
+        entry.student_card,
+        entry.teacher_card,
+        entry.kind as _,
+        entry.amount,
+        entry.students_borrowing,
+        entry.teachers_borrowing,
+        entry.librarian,
+        entry.reason
+    )
+    .fetch_one(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add ledger_entry to database"))
+    .map_err(conflict_or_internal_error)?;
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit ledger_entry"))
+        .map_err(internal_error)?;
+
+    Ok((StatusCode::CREATED, Json(entry)))
+}
+
+/// Receipt of the payment or waiver `id`, with the balance left once it was made.
+async fn get_receipt(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, Json<Receipt>), (StatusCode, String)> {
+    let mut conn = db
+        .acquire()
+        .await
+        .wrap_err_with(|| eyre!("Unable to acquire database connection"))
+        .map_err(internal_error)?;
+
+    let entry = load_entry(&mut conn, id).await?;
+    if entry.kind.is_charge() {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Ledger entry {id} is a charge, receipts are for payments and waivers"),
+        ));
+    }
+
+    let patron = sqlx::query!(
+        r#"SELECT COALESCE(s.name, t.name) as "name!", COALESCE(s.lastname, t.lastname) as "lastname!"
+        FROM ledger_entry e
+        LEFT JOIN student_card sc ON sc.id = e.student_card
+        LEFT JOIN student s ON s.id = sc.student
+        LEFT JOIN teacher_card tc ON tc.id = e.teacher_card
+        LEFT JOIN teacher t ON t.id = tc.teacher
+        WHERE e.id = $1"#,
+        id
+    )
+    .fetch_one(&mut conn)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load patron of ledger_entry from database"))
+    .map_err(internal_error)?;
+
+    let (card_table, card) = card_of(&entry);
+    let balance = fine::balance(&mut conn, card_table, card, Some(id))
+        .await
+        .map_err(internal_error)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(Receipt {
+            number: format!("R-{id:06}"),
+            patron: format!("{} {}", patron.name, patron.lastname),
+            entry,
+            balance,
+        }),
+    ))
+}
+
+async fn get_student_card_balance(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, Json<Balance>), (StatusCode, String)> {
+    card_balance(&db, "student_card", id).await
+}
+
+async fn get_teacher_card_balance(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, Json<Balance>), (StatusCode, String)> {
+    card_balance(&db, "teacher_card", id).await
+}
+
+async fn card_balance(
+    db: &Pool<Postgres>,
+    card_table: &'static str,
+    card: i32,
+) -> Result<(StatusCode, Json<Balance>), (StatusCode, String)> {
+    let mut conn = db
+        .acquire()
+        .await
+        .wrap_err_with(|| eyre!("Unable to acquire database connection"))
+        .map_err(internal_error)?;
+
+    let balance = fine::card_balance(&mut conn, card_table, card)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("{} {card} does not exist", label(card_table)),
+            )
+        })?;
+
+    Ok((StatusCode::OK, Json(balance)))
+}
+
+async fn get_fine_policies(
+    State(db): State<Pool<Postgres>>,
+) -> Result<(StatusCode, Json<Vec<FinePolicy>>), (StatusCode, String)> {
+    let policies = sqlx::query_as!(
+        FinePolicy,
+        r#"SELECT card_table, max_balance, version FROM fine_policy ORDER BY card_table ASC"#
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load fine_policies from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(policies)))
+}
+
+fn policy_not_found(card_table: &str) -> (StatusCode, String) {
+    (
+        StatusCode::NOT_FOUND,
+        format!("{} has no fine policy", label(card_table)),
+    )
+}
+
+/// Error for an update or delete of the policy of `card_table` which matched no row.
+async fn policy_not_found_or_modified(
+    db: &Pool<Postgres>,
+    card_table: &str,
+) -> (StatusCode, String) {
+    match not_found_or_modified(db, "fine_policy", "card_table", card_table).await {
+        repository::Error::NotFound => policy_not_found(card_table),
+        err => repository_error(err, &format!("Fine policy of {card_table}")),
+    }
+}
+
+/// Limits the balance of the cards of `policy.card_table`, which has no policy yet.
+async fn create_fine_policy(
+    State(db): State<Pool<Postgres>>,
+    ValidatedJson(policy): ValidatedJson<FinePolicy>,
+) -> Result<(StatusCode, ETag, Json<FinePolicy>), (StatusCode, String)> {
+    let card_table = check_card_table(&policy.card_table)
+        .map_err(|(_, message)| (StatusCode::UNPROCESSABLE_ENTITY, message))?;
+
+    let policy = sqlx::query_as!(
+        FinePolicy,
+        r#"INSERT INTO fine_policy (card_table, max_balance) VALUES ($1, $2)
+        ON CONFLICT (card_table) DO NOTHING
+        RETURNING card_table, max_balance, version"#,
+        card_table,
+        policy.max_balance
+    )
+    .fetch_optional(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add fine_policy to database"))
+    .map_err(internal_error)?
+    .ok_or_else(|| {
+        (
+            StatusCode::CONFLICT,
+            format!("{} already has a fine policy", label(card_table)),
+        )
+    })?;
+
+    Ok((StatusCode::CREATED, etag(policy.version), Json(policy)))
+}
+
+/// Sets the balance above which the cards of `card_table` cannot borrow.
+async fn update_fine_policy(
+    State(db): State<Pool<Postgres>>,
+    Path(card_table): Path<String>,
+    IfMatch(version): IfMatch,
+    ValidatedJson(policy): ValidatedJson<FinePolicy>,
+) -> Result<(StatusCode, ETag, Json<FinePolicy>), (StatusCode, String)> {
+    let card_table = check_card_table(&card_table)?;
+
+    let updated = sqlx::query_as!(
+        FinePolicy,
+        r#"UPDATE fine_policy SET max_balance = $1 WHERE card_table = $2 AND version = $3
+        RETURNING card_table, max_balance, version"#,
+        policy.max_balance,
+        card_table,
+        version
+    )
+    .fetch_optional(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update fine_policy in database"))
+    .map_err(internal_error)?;
+
+    let Some(policy) = updated else {
+        return Err(policy_not_found_or_modified(&db, card_table).await);
+    };
+
+    Ok((StatusCode::OK, etag(policy.version), Json(policy)))
+}
+
+/// Lifts the limit of the cards of `card_table`, they can borrow whatever they owe.
+async fn delete_fine_policy(
+    State(db): State<Pool<Postgres>>,
+    Path(card_table): Path<String>,
+    IfMatch(version): IfMatch,
+) -> Result<(StatusCode, Json<FinePolicy>), (StatusCode, String)> {
+    let card_table = check_card_table(&card_table)?;
+
+    let deleted = sqlx::query_as!(
+        FinePolicy,
+        r#"DELETE FROM fine_policy WHERE card_table = $1 AND version = $2
+        RETURNING card_table, max_balance, version"#,
+        card_table,
+        version
+    )
+    .fetch_optional(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to delete fine_policy from database"))
+    .map_err(internal_error)?;
+
+    let Some(policy) = deleted else {
+        return Err(policy_not_found_or_modified(&db, card_table).await);
+    };
+
+    Ok((StatusCode::OK, Json(policy)))
+}
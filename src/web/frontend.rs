@@ -0,0 +1,119 @@
+use std::path::{Component, Path, PathBuf};
+
+use axum::extract::State;
+use axum::http::{header, HeaderValue, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+
+// Vite puts the content hash in the names of the files it builds into `assets/`.
+const HASHED_ASSETS: &str = "/assets/";
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+// Other files keep their name across builds, browsers revalidate them on every use.
+const REVALIDATE: &str = "no-cache";
+
+/// Serves the built frontend in `dir` for every path the API does not handle.
+///
+/// Paths without a file extension fall back to `index.html`, they are routes of the app.
+pub fn routes(dir: PathBuf) -> Router {
+    Router::new().fallback(serve).with_state(dir)
+}
+
+async fn serve(State(dir): State<PathBuf>, method: Method, uri: Uri) -> Response {
+    let path = uri.path();
+
+    // Unknown API routes are not pages of the app.
+    if path == "/api" || path.starts_with("/api/") {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    if method != Method::GET && method != Method::HEAD {
+        return StatusCode::METHOD_NOT_ALLOWED.into_response();
+    }
+
+    let Some(file) = file_path(&dir, path) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match tokio::fs::read(&file).await {
+        Ok(content) => {
+            let cache_control = match path.starts_with(HASHED_ASSETS) {
+                true => IMMUTABLE,
+                false => REVALIDATE,
+            };
+            file_response(&file, content, cache_control)
+        }
+        Err(_) if file.extension().is_none() => index(&dir).await,
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn index(dir: &Path) -> Response {
+    let file = dir.join("index.html");
+
+    match tokio::fs::read(&file).await {
+        Ok(content) => file_response(&file, content, REVALIDATE),
+        Err(err) => {
+            tracing::error!("Unable to read {}: {err}", file.display());
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// File of `dir` at the URL `path`, `None` for paths leaving `dir`.
+fn file_path(dir: &Path, path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    match relative.as_os_str().is_empty() {
+        true => Some(dir.join("index.html")),
+        false => Some(dir.join(relative)),
+    }
+}
+
+fn file_response(file: &Path, content: Vec<u8>, cache_control: &'static str) -> Response {
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(content_type(file)),
+            ),
+            (
+                header::CACHE_CONTROL,
+                HeaderValue::from_static(cache_control),
+            ),
+        ],
+        content,
+    )
+        .into_response()
+}
+
+/// Content type of the files a Vite build produces.
+fn content_type(file: &Path) -> &'static str {
+    let extension = file
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default();
+
+    match extension {
+        "html" => "text/html; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "json" | "map" => "application/json",
+        "txt" => "text/plain; charset=utf-8",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
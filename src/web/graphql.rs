@@ -0,0 +1,25 @@
+use async_graphql::http::GraphiQLSource;
+use axum::extract::State;
+use axum::response::Html;
+use axum::routing::get;
+use axum::{Json, Router};
+
+use crate::graphql::LibrarySchema;
+
+/// `POST /graphql` executes a query, `GET /graphql` serves GraphiQL to explore the schema.
+pub fn routes(schema: LibrarySchema) -> Router {
+    Router::new()
+        .route("/graphql", get(graphiql).post(execute))
+        .with_state(schema)
+}
+
+async fn execute(
+    State(schema): State<LibrarySchema>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(schema.execute(request).await)
+}
+
+async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/api/graphql").finish())
+}
@@ -0,0 +1,126 @@
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
+
+use crate::model::Hold;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
+
+pub fn routes(repository: Shared<Hold>) -> Router {
+    resource::routes(repository)
+}
+
+#[async_trait]
+impl Resource for Hold {
+    const TABLE: &'static str = "hold";
+    const PATH: &'static str = "/hold";
+
+    type Key = i32;
+
+    fn version(&self) -> i32 {
+        self.version
+    }
+
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Hold,
+            r#"SELECT id, book, student_card, teacher_card, request_date, expire_date, status as "status: _", version
+            FROM hold ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
+
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Hold,
+            r#"SELECT id, book, student_card, teacher_card, request_date, expire_date, status as "status: _", version
+            FROM hold WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            Hold,
+            r#"INSERT INTO hold
+            (book, student_card, teacher_card, request_date, expire_date, status)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, book, student_card, teacher_card, request_date, expire_date, status as "status: _", version"#,
+            self.book,
+            self.student_card,
+            self.teacher_card,
+            self.request_date,
+            self.expire_date,
+            self.status as _,
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Hold,
+            r#"UPDATE hold SET
+            book = $1,
+            student_card = $2,
+            teacher_card = $3,
+            request_date = $4,
+            expire_date = $5,
+            status = $6
+            WHERE id = $7 AND version = $8
+            RETURNING id, book, student_card, teacher_card, request_date, expire_date, status as "status: _", version"#,
+            self.book,
+            self.student_card,
+            self.teacher_card,
+            self.request_date,
+            self.expire_date,
+            self.status as _,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Hold,
+            r#"DELETE FROM hold WHERE id = $1 AND version = $2
+            RETURNING id, book, student_card, teacher_card, request_date, expire_date, status as "status: _", version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+}
+
+impl SqliteResource for Hold {
+    const COLUMNS: &'static [&'static str] = &[
+        "book",
+        "student_card",
+        "teacher_card",
+        "request_date",
+        "expire_date",
+        "status",
+    ];
+
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query
+            .bind(self.book)
+            .bind(self.student_card)
+            .bind(self.teacher_card)
+            .bind(self.request_date)
+            .bind(self.expire_date)
+            .bind(self.status)
+    }
+}
@@ -0,0 +1,105 @@
+use axum::extract::Path;
+use axum::routing::post;
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use color_eyre::eyre::Context;
+use color_eyre::{eyre::eyre, Result};
+use serde::Serialize;
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+
+use crate::error::internal_error;
+use crate::model::JobRun;
+use crate::scheduler::{self, Job};
+
+#[derive(Serialize, Debug)]
+struct JobInfo {
+    name: &'static str,
+    schedule: &'static str,
+    next_run: Option<DateTime<Utc>>,
+    last_run: Option<JobRun>,
+}
+
+pub fn routes(db: Pool<Postgres>) -> Router {
+    Router::new()
+        .route("/job", get(get_jobs))
+        .route("/job/:name/runs", get(get_job_runs))
+        .route("/job/:name/run", post(run_job))
+        .with_state(db)
+}
+
+fn find_job(name: &str) -> Result<Job, (StatusCode, String)> {
+    Job::from_name(name).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("Job `{name}` does not exist"),
+        )
+    })
+}
+
+async fn get_jobs(
+    State(db): State<Pool<Postgres>>,
+) -> Result<(StatusCode, Json<Vec<JobInfo>>), (StatusCode, String)> {
+    let mut last_runs = sqlx::query_as!(
+        JobRun,
+        r#"SELECT DISTINCT ON (job) id, job, started_at, finished_at, status as "status: _", message, scheduled_at
+        FROM job_run ORDER BY job, started_at DESC"#
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load job_runs from database"))
+    .map_err(internal_error)?;
+
+    let jobs = Job::ALL
+        .into_iter()
+        .map(|job| JobInfo {
+            name: job.name(),
+            schedule: job.schedule(),
+            next_run: job.next_run(),
+            last_run: last_runs
+                .iter()
+                .position(|run| run.job == job.name())
+                .map(|i| last_runs.swap_remove(i)),
+        })
+        .collect();
+
+    Ok((StatusCode::OK, Json(jobs)))
+}
+
+async fn get_job_runs(
+    State(db): State<Pool<Postgres>>,
+    Path(name): Path<String>,
+) -> Result<(StatusCode, Json<Vec<JobRun>>), (StatusCode, String)> {
+    let job = find_job(&name)?;
+
+    let runs = sqlx::query_as!(
+        JobRun,
+        r#"SELECT id, job, started_at, finished_at, status as "status: _", message, scheduled_at
+        FROM job_run WHERE job = $1 ORDER BY started_at DESC"#,
+        job.name()
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load job_runs from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(runs)))
+}
+
+async fn run_job(
+    State(db): State<Pool<Postgres>>,
+    Path(name): Path<String>,
+) -> Result<(StatusCode, Json<JobRun>), (StatusCode, String)> {
+    let job = find_job(&name)?;
+
+    let run = scheduler::run_job(&db, job, None)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::CONFLICT,
+                format!("Job `{name}` is already running"),
+            )
+        })?;
+
+    Ok((StatusCode::CREATED, Json(run)))
+}
@@ -1,103 +1,116 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
 use crate::model::Librarian;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route("/librarian", get(get_librarians).post(create_librarian))
-        .route(
-            "/librarian/:id",
-            put(update_librarian).delete(delete_librarian),
-        )
-        .with_state(db)
+pub fn routes(repository: Shared<Librarian>) -> Router {
+    resource::routes(repository)
 }
 
-async fn get_librarians(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<Librarian>>), (StatusCode, String)> {
-    let librarians = sqlx::query_as!(
-        Librarian,
-        r#"SELECT id, name, lastname, surname, age FROM librarian ORDER BY id ASC"#
-    )
-    .fetch_all(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to load librarians from database"))
-    .map_err(internal_error)?;
+#[async_trait]
+impl Resource for Librarian {
+    const TABLE: &'static str = "librarian";
+    const PATH: &'static str = "/librarian";
+    const BRANCH: Option<&'static str> = Some("branch");
 
-    Ok((StatusCode::OK, Json(librarians)))
-}
+    type Key = i32;
 
-async fn create_librarian(
-    State(db): State<Pool<Postgres>>,
-    Json(librarian): Json<Librarian>,
-) -> Result<(StatusCode, Json<Librarian>), (StatusCode, String)> {
-    let inserted_librarian = sqlx::query_as!(
-        Librarian,
-        r#"INSERT INTO librarian 
-        (name, lastname, surname, age)
-        VALUES ($1, $2, $3, $4)
-        RETURNING id, name, lastname, surname, age"#,
-        librarian.name,
-        librarian.lastname,
-        librarian.surname,
-        librarian.age
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add librarian to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_librarian)))
-}
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Librarian,
+            r#"SELECT id, name, lastname, surname, age, branch, version
+            FROM librarian ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
 
-async fn update_librarian(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(librarian): Json<Librarian>,
-) -> Result<(StatusCode, Json<Librarian>), (StatusCode, String)> {
-    tracing::info!("Librarian payload: {:?}", librarian);
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Librarian,
+            r#"SELECT id, name, lastname, surname, age, branch, version
+            FROM librarian WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE librarian SET
-        name = $1,
-        lastname = $2,
-        surname = $3, 
-        age = $4
-        WHERE id = $5"#,
-        librarian.name,
-        librarian.lastname,
-        librarian.surname,
-        librarian.age,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update librarian in database"))
-    .map_err(internal_error)?;
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            Librarian,
+            r#"INSERT INTO librarian
+            (name, lastname, surname, age, branch)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, name, lastname, surname, age, branch, version"#,
+            self.name,
+            self.lastname,
+            self.surname,
+            self.age,
+            self.branch,
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Librarian,
+            r#"UPDATE librarian SET
+            name = $1,
+            lastname = $2,
+            surname = $3,
+            age = $4,
+            branch = $5
+            WHERE id = $6 AND version = $7
+            RETURNING id, name, lastname, surname, age, branch, version"#,
+            self.name,
+            self.lastname,
+            self.surname,
+            self.age,
+            self.branch,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(librarian)))
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Librarian,
+            r#"DELETE FROM librarian WHERE id = $1 AND version = $2
+            RETURNING id, name, lastname, surname, age, branch, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 }
 
-async fn delete_librarian(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<Librarian>), (StatusCode, String)> {
-    let deleted_librarian = sqlx::query_as!(
-        Librarian,
-        r#"DELETE FROM librarian WHERE id = $1 
-    RETURNING id, name, lastname, surname, age"#,
-        id
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update librarian in database"))
-    .map_err(internal_error)?;
+impl SqliteResource for Librarian {
+    const COLUMNS: &'static [&'static str] = &["name", "lastname", "surname", "age", "branch"];
 
-    Ok((StatusCode::OK, Json(deleted_librarian)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query
+            .bind(&self.name)
+            .bind(&self.lastname)
+            .bind(&self.surname)
+            .bind(self.age)
+            .bind(self.branch)
+    }
 }
@@ -0,0 +1,109 @@
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, FromRef, Query};
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::post;
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use color_eyre::eyre::Context;
+use color_eyre::{eyre::eyre, Result};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use crate::cache::ListCache;
+use crate::error::internal_error;
+use crate::marc::{self, import::ImportReport};
+
+// Catalogue dumps are much larger than the default limit of 2 MB.
+const MAX_IMPORT_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Deserialize, Debug)]
+struct ImportQuery {
+    /// Country of authors and publishers created by the import.
+    country: String,
+}
+
+/// Router state, with caches of the tables the import adds rows to.
+#[derive(Clone)]
+struct MarcState {
+    db: Pool<Postgres>,
+    category_cache: ListCache,
+    publisher_cache: ListCache,
+}
+
+impl FromRef<MarcState> for Pool<Postgres> {
+    fn from_ref(state: &MarcState) -> Self {
+        state.db.clone()
+    }
+}
+
+pub fn routes(db: Pool<Postgres>, category_cache: ListCache, publisher_cache: ListCache) -> Router {
+    Router::new()
+        .route("/marc/import", post(import))
+        .route("/marc/export", get(export))
+        .layer(DefaultBodyLimit::max(MAX_IMPORT_SIZE))
+        .with_state(MarcState {
+            db,
+            category_cache,
+            publisher_cache,
+        })
+}
+
+async fn import(
+    State(state): State<MarcState>,
+    Query(query): Query<ImportQuery>,
+    body: Bytes,
+) -> Result<(StatusCode, Json<ImportReport>), (StatusCode, String)> {
+    let country_exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM country WHERE code = $1) as "exists!""#,
+        query.country
+    )
+    .fetch_one(&state.db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load country from database"))
+    .map_err(internal_error)?;
+
+    if !country_exists {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Country {} does not exist", query.country),
+        ));
+    }
+
+    // MARCXML starts with a tag, ISO 2709 with the record length.
+    let is_xml = body.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'<');
+    let records = if is_xml {
+        marc::xml::parse(&body)
+    } else {
+        marc::iso2709::parse(&body)
+    }
+    .map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, format!("{:#}", err)))?;
+
+    let report = marc::import::import(&state.db, &records, &query.country)
+        .await
+        .wrap_err_with(|| eyre!("Unable to import MARC records"))
+        .map_err(internal_error)?;
+
+    if report.categories_created > 0 {
+        state.category_cache.invalidate();
+    }
+    if report.publishers_created > 0 {
+        state.publisher_cache.invalidate();
+    }
+
+    Ok((StatusCode::OK, Json(report)))
+}
+
+async fn export(
+    State(db): State<Pool<Postgres>>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let records = marc::export::export(&db)
+        .await
+        .wrap_err_with(|| eyre!("Unable to export MARC records"))
+        .map_err(internal_error)?;
+
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/marcxml+xml")],
+        marc::xml::write(&records),
+    ))
+}
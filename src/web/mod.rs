@@ -1,17 +1,33 @@
 pub mod author;
 pub mod author_book;
 pub mod book;
+pub mod book_transfer;
+pub mod branch;
+pub mod card;
 pub mod category;
 pub mod country;
 pub mod curriculum;
+pub mod events;
 pub mod faculty;
 pub mod faculty_curriculum;
+pub mod fine;
+pub mod frontend;
+pub mod graphql;
+pub mod hold;
+pub mod job;
 pub mod librarian;
+pub mod marc;
+pub mod notification;
 pub mod publisher;
+pub mod report;
+pub mod resource;
+pub mod shelf;
 pub mod student;
 pub mod student_card;
 pub mod students_borrowing;
+pub mod students_borrowing_renewal;
 pub mod table;
 pub mod teacher;
 pub mod teacher_card;
 pub mod teachers_borrowing;
+pub mod webhook;
@@ -0,0 +1,98 @@
+use axum::extract::Path;
+use axum::routing::post;
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use color_eyre::eyre::Context;
+use color_eyre::{eyre::eyre, Result};
+use sqlx::{Pool, Postgres};
+
+use crate::error::internal_error;
+use crate::model::Notification;
+use crate::notification;
+
+pub fn routes(db: Pool<Postgres>) -> Router {
+    Router::new()
+        .route("/notification", get(get_notifications))
+        .route("/notification/generate", post(generate_notifications))
+        .route("/notification/:id", get(get_notification))
+        .route("/notification/:id/retry", post(retry_notification))
+        .with_state(db)
+}
+
+async fn get_notifications(
+    State(db): State<Pool<Postgres>>,
+) -> Result<(StatusCode, Json<Vec<Notification>>), (StatusCode, String)> {
+    let notifications = sqlx::query_as!(
+        Notification,
+        r#"SELECT id, kind as "kind: _", recipient, subject, body, students_borrowing, hold,
+        status as "status: _", attempts, last_error, created_at, next_attempt_at, sent_at
+        FROM notification ORDER BY id ASC"#
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load notifications from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(notifications)))
+}
+
+async fn get_notification(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, Json<Notification>), (StatusCode, String)> {
+    let notification = sqlx::query_as!(
+        Notification,
+        r#"SELECT id, kind as "kind: _", recipient, subject, body, students_borrowing, hold,
+        status as "status: _", attempts, last_error, created_at, next_attempt_at, sent_at
+        FROM notification WHERE id = $1"#,
+        id
+    )
+    .fetch_optional(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load notification from database"))
+    .map_err(internal_error)?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("Notification {id} does not exist"),
+        )
+    })?;
+
+    Ok((StatusCode::OK, Json(notification)))
+}
+
+async fn generate_notifications(
+    State(db): State<Pool<Postgres>>,
+) -> Result<(StatusCode, Json<Vec<Notification>>), (StatusCode, String)> {
+    let notifications = notification::generate(&db).await.map_err(internal_error)?;
+
+    Ok((StatusCode::CREATED, Json(notifications)))
+}
+
+async fn retry_notification(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, Json<Notification>), (StatusCode, String)> {
+    let notification = sqlx::query_as!(
+        Notification,
+        r#"UPDATE notification SET
+        status = 'pending',
+        attempts = 0,
+        next_attempt_at = now()
+        WHERE id = $1 AND status = 'failed'
+        RETURNING id, kind as "kind: _", recipient, subject, body, students_borrowing, hold,
+        status as "status: _", attempts, last_error, created_at, next_attempt_at, sent_at"#,
+        id
+    )
+    .fetch_optional(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update notification in database"))
+    .map_err(internal_error)?
+    .ok_or_else(|| {
+        (
+            StatusCode::CONFLICT,
+            format!("Notification {id} does not exist or is not failed"),
+        )
+    })?;
+
+    Ok((StatusCode::OK, Json(notification)))
+}
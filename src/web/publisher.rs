@@ -1,97 +1,102 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
+use crate::cache::ListCache;
 use crate::model::Publisher;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route("/publisher", get(get_publishers).post(create_publisher))
-        .route(
-            "/publisher/:id",
-            put(update_publisher).delete(delete_publisher),
-        )
-        .with_state(db)
+pub fn routes(repository: Shared<Publisher>, cache: ListCache) -> Router {
+    resource::cached_routes(repository, cache)
 }
 
-async fn get_publishers(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<Publisher>>), (StatusCode, String)> {
-    let publishers = sqlx::query_as!(
-        Publisher,
-        r#"SELECT id, name, country FROM publisher ORDER BY id ASC"#
-    )
-    .fetch_all(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to load publishers from database"))
-    .map_err(internal_error)?;
+#[async_trait]
+impl Resource for Publisher {
+    const TABLE: &'static str = "publisher";
+    const PATH: &'static str = "/publisher";
 
-    Ok((StatusCode::OK, Json(publishers)))
-}
+    type Key = i32;
 
-async fn create_publisher(
-    State(db): State<Pool<Postgres>>,
-    Json(publisher): Json<Publisher>,
-) -> Result<(StatusCode, Json<Publisher>), (StatusCode, String)> {
-    let inserted_publisher = sqlx::query_as!(
-        Publisher,
-        r#"INSERT INTO publisher 
-        (name, country)
-        VALUES ($1, $2)
-        RETURNING id, name, country"#,
-        publisher.name,
-        publisher.country,
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add publisher to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_publisher)))
-}
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Publisher,
+            r#"SELECT id, name, country, version
+            FROM publisher ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
 
-async fn update_publisher(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(publisher): Json<Publisher>,
-) -> Result<(StatusCode, Json<Publisher>), (StatusCode, String)> {
-    tracing::info!("Publisher payload: {:?}", publisher);
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Publisher,
+            r#"SELECT id, name, country, version
+            FROM publisher WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE publisher SET
-        name = $1,
-        country = $2
-        WHERE id = $3"#,
-        publisher.name,
-        publisher.country,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update publisher in database"))
-    .map_err(internal_error)?;
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            Publisher,
+            r#"INSERT INTO publisher
+            (name, country)
+            VALUES ($1, $2)
+            RETURNING id, name, country, version"#,
+            self.name,
+            self.country,
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Publisher,
+            r#"UPDATE publisher SET
+            name = $1,
+            country = $2
+            WHERE id = $3 AND version = $4
+            RETURNING id, name, country, version"#,
+            self.name,
+            self.country,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(publisher)))
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Publisher,
+            r#"DELETE FROM publisher WHERE id = $1 AND version = $2
+            RETURNING id, name, country, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 }
 
-async fn delete_publisher(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<Publisher>), (StatusCode, String)> {
-    let deleted_publisher = sqlx::query_as!(
-        Publisher,
-        r#"DELETE FROM publisher WHERE id = $1 
-        RETURNING id, name, country"#,
-        id
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update publisher in database"))
-    .map_err(internal_error)?;
+impl SqliteResource for Publisher {
+    const COLUMNS: &'static [&'static str] = &["name", "country"];
 
-    Ok((StatusCode::OK, Json(deleted_publisher)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query.bind(&self.name).bind(&self.country)
+    }
 }
@@ -0,0 +1,368 @@
+use axum::extract::Query;
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use color_eyre::eyre::Context;
+use color_eyre::{eyre::eyre, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::NaiveDate;
+use sqlx::{Pool, Postgres};
+
+use crate::error::internal_error;
+use crate::model::Classification;
+
+const DEFAULT_LIMIT: i64 = 10;
+const MAX_LIMIT: i64 = 1000;
+
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+enum Period {
+    Day,
+    Week,
+    #[default]
+    Month,
+    Year,
+}
+
+impl Period {
+    fn unit(self) -> &'static str {
+        match self {
+            Period::Day => "day",
+            Period::Week => "week",
+            Period::Month => "month",
+            Period::Year => "year",
+        }
+    }
+}
+
+/// Date range on `borrow_date`, both ends are inclusive and optional, the period of loans per
+/// period and the rows of top lists. Reports refuse the parameters they do not use.
+#[derive(Deserialize, Debug)]
+struct ReportQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    group_by: Option<Period>,
+    limit: Option<i64>,
+}
+
+/// Books of the shelf list, all conditions are optional.
+#[derive(Deserialize, Debug)]
+struct ShelfListQuery {
+    branch: Option<i32>,
+    shelf: Option<i32>,
+    category: Option<i32>,
+    classification: Option<Classification>,
+}
+
+#[derive(Serialize, Debug)]
+struct LoansPerPeriod {
+    period: NaiveDate,
+    students: i64,
+    teachers: i64,
+    total: i64,
+}
+
+#[derive(Serialize, Debug)]
+struct BookLoans {
+    id: i32,
+    title: String,
+    loans: i64,
+}
+
+#[derive(Serialize, Debug)]
+struct AuthorLoans {
+    id: i32,
+    name: String,
+    lastname: String,
+    surname: String,
+    loans: i64,
+}
+
+#[derive(Serialize, Debug)]
+struct GroupLoans {
+    id: i32,
+    name: String,
+    letter: String,
+    loans: i64,
+}
+
+#[derive(Serialize, Debug)]
+struct ShelfListEntry {
+    id: i32,
+    title: String,
+    classification: Classification,
+    call_number: String,
+    category: String,
+    branch: Option<String>,
+    room: Option<String>,
+    shelf: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct LoanDuration {
+    returned_loans: i64,
+    average_loan_days: Option<f64>,
+    late_returns: i64,
+    late_return_rate: Option<f64>,
+    overdue_loans: i64,
+}
+
+pub fn routes(db: Pool<Postgres>) -> Router {
+    Router::new()
+        .route("/reports/loans", get(get_loans_per_period))
+        .route("/reports/top-books", get(get_top_books))
+        .route("/reports/top-authors", get(get_top_authors))
+        .route("/reports/loans-by-faculty", get(get_loans_by_faculty))
+        .route("/reports/loans-by-curriculum", get(get_loans_by_curriculum))
+        .route("/reports/loan-duration", get(get_loan_duration))
+        .route("/reports/shelf-list", get(get_shelf_list))
+        .with_state(db)
+}
+
+/// Checks `query` for a report which takes the optional parameters `params` besides the range.
+fn check_query(query: &ReportQuery, params: &[&str]) -> Result<(), (StatusCode, String)> {
+    let unsupported = [
+        ("group_by", query.group_by.is_some()),
+        ("limit", query.limit.is_some()),
+    ]
+    .into_iter()
+    .find(|(param, is_set)| *is_set && !params.contains(param));
+    if let Some((param, _)) = unsupported {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Parameter `{param}` is not supported by this report"),
+        ));
+    }
+
+    if let Some(limit) = query.limit.filter(|limit| !(1..=MAX_LIMIT).contains(limit)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Limit {limit} should be between 1 and {MAX_LIMIT}"),
+        ));
+    }
+
+    match (query.from, query.to) {
+        (Some(from), Some(to)) if from > to => Err((
+            StatusCode::BAD_REQUEST,
+            format!("Range start {from} is after its end {to}"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+async fn get_loans_per_period(
+    State(db): State<Pool<Postgres>>,
+    Query(query): Query<ReportQuery>,
+) -> Result<(StatusCode, Json<Vec<LoansPerPeriod>>), (StatusCode, String)> {
+    check_query(&query, &["group_by"])?;
+
+    let loans = sqlx::query_as!(
+        LoansPerPeriod,
+        r#"WITH loan AS (
+            SELECT borrow_date, 'student' as patron FROM students_borrowing
+            UNION ALL
+            SELECT borrow_date, 'teacher' as patron FROM teachers_borrowing
+        )
+        SELECT date_trunc($3, borrow_date::TIMESTAMP)::DATE as "period!",
+        COUNT(*) FILTER (WHERE patron = 'student') as "students!",
+        COUNT(*) FILTER (WHERE patron = 'teacher') as "teachers!",
+        COUNT(*) as "total!"
+        FROM loan
+        WHERE ($1::DATE IS NULL OR borrow_date >= $1) AND ($2::DATE IS NULL OR borrow_date <= $2)
+        GROUP BY 1 ORDER BY 1 ASC"#,
+        query.from,
+        query.to,
+        query.group_by.unwrap_or_default().unit(),
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load loans per period from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(loans)))
+}
+
+async fn get_top_books(
+    State(db): State<Pool<Postgres>>,
+    Query(query): Query<ReportQuery>,
+) -> Result<(StatusCode, Json<Vec<BookLoans>>), (StatusCode, String)> {
+    check_query(&query, &["limit"])?;
+
+    let books = sqlx::query_as!(
+        BookLoans,
+        r#"WITH loan AS (
+            SELECT book, borrow_date FROM students_borrowing
+            UNION ALL
+            SELECT book, borrow_date FROM teachers_borrowing
+        )
+        SELECT b.id, b.title, COUNT(*) as "loans!"
+        FROM loan l
+        JOIN book b ON b.id = l.book
+        WHERE ($1::DATE IS NULL OR l.borrow_date >= $1) AND ($2::DATE IS NULL OR l.borrow_date <= $2)
+        GROUP BY b.id
+        ORDER BY 3 DESC, b.id ASC
+        LIMIT $3"#,
+        query.from,
+        query.to,
+        query.limit.unwrap_or(DEFAULT_LIMIT),
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load most borrowed books from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(books)))
+}
+
+async fn get_top_authors(
+    State(db): State<Pool<Postgres>>,
+    Query(query): Query<ReportQuery>,
+) -> Result<(StatusCode, Json<Vec<AuthorLoans>>), (StatusCode, String)> {
+    check_query(&query, &["limit"])?;
+
+    let authors = sqlx::query_as!(
+        AuthorLoans,
+        r#"WITH loan AS (
+            SELECT book, borrow_date FROM students_borrowing
+            UNION ALL
+            SELECT book, borrow_date FROM teachers_borrowing
+        )
+        SELECT a.id, a.name, a.lastname, a.surname, COUNT(*) as "loans!"
+        FROM loan l
+        JOIN author_book ab ON ab.book_id = l.book
+        JOIN author a ON a.id = ab.author_id
+        WHERE ($1::DATE IS NULL OR l.borrow_date >= $1) AND ($2::DATE IS NULL OR l.borrow_date <= $2)
+        GROUP BY a.id
+        ORDER BY 5 DESC, a.id ASC
+        LIMIT $3"#,
+        query.from,
+        query.to,
+        query.limit.unwrap_or(DEFAULT_LIMIT),
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load most borrowed authors from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(authors)))
+}
+
+async fn get_loans_by_faculty(
+    State(db): State<Pool<Postgres>>,
+    Query(query): Query<ReportQuery>,
+) -> Result<(StatusCode, Json<Vec<GroupLoans>>), (StatusCode, String)> {
+    check_query(&query, &[])?;
+
+    let faculties = sqlx::query_as!(
+        GroupLoans,
+        r#"SELECT f.id, f.name, f.letter, COUNT(*) as "loans!"
+        FROM students_borrowing sb
+        JOIN student_card sc ON sc.id = sb.student_card
+        JOIN student s ON s.id = sc.student
+        JOIN faculty_curriculum fc ON fc.id = s.faculty_curriculum
+        JOIN faculty f ON f.id = fc.faculty
+        WHERE ($1::DATE IS NULL OR sb.borrow_date >= $1) AND ($2::DATE IS NULL OR sb.borrow_date <= $2)
+        GROUP BY f.id
+        ORDER BY 4 DESC, f.id ASC"#,
+        query.from,
+        query.to,
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load loans per faculty from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(faculties)))
+}
+
+async fn get_loans_by_curriculum(
+    State(db): State<Pool<Postgres>>,
+    Query(query): Query<ReportQuery>,
+) -> Result<(StatusCode, Json<Vec<GroupLoans>>), (StatusCode, String)> {
+    check_query(&query, &[])?;
+
+    let curriculums = sqlx::query_as!(
+        GroupLoans,
+        r#"SELECT c.id, c.name, c.letter, COUNT(*) as "loans!"
+        FROM students_borrowing sb
+        JOIN student_card sc ON sc.id = sb.student_card
+        JOIN student s ON s.id = sc.student
+        JOIN faculty_curriculum fc ON fc.id = s.faculty_curriculum
+        JOIN curriculum c ON c.id = fc.curriculum
+        WHERE ($1::DATE IS NULL OR sb.borrow_date >= $1) AND ($2::DATE IS NULL OR sb.borrow_date <= $2)
+        GROUP BY c.id
+        ORDER BY 4 DESC, c.id ASC"#,
+        query.from,
+        query.to,
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load loans per curriculum from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(curriculums)))
+}
+
+async fn get_loan_duration(
+    State(db): State<Pool<Postgres>>,
+    Query(query): Query<ReportQuery>,
+) -> Result<(StatusCode, Json<LoanDuration>), (StatusCode, String)> {
+    check_query(&query, &[])?;
+
+    // Only students borrowings have a required return date, so lateness is computed for them only.
+    let duration = sqlx::query_as!(
+        LoanDuration,
+        r#"WITH loan AS (
+            SELECT borrow_date, return_date, required_return_date FROM students_borrowing
+            UNION ALL
+            SELECT borrow_date, return_date, NULL FROM teachers_borrowing
+        )
+        SELECT
+        COUNT(return_date) as "returned_loans!",
+        AVG(return_date - borrow_date)::FLOAT8 as average_loan_days,
+        COUNT(*) FILTER (WHERE return_date > required_return_date) as "late_returns!",
+        (COUNT(*) FILTER (WHERE return_date > required_return_date))::FLOAT8
+            / NULLIF(COUNT(*) FILTER (WHERE return_date IS NOT NULL AND required_return_date IS NOT NULL), 0)
+            as late_return_rate,
+        COUNT(*) FILTER (WHERE return_date IS NULL AND required_return_date < CURRENT_DATE) as "overdue_loans!"
+        FROM loan
+        WHERE ($1::DATE IS NULL OR borrow_date >= $1) AND ($2::DATE IS NULL OR borrow_date <= $2)"#,
+        query.from,
+        query.to,
+    )
+    .fetch_one(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load loan duration from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(duration)))
+}
+
+/// Books with a call number in filing order, as they stand on the shelves.
+async fn get_shelf_list(
+    State(db): State<Pool<Postgres>>,
+    Query(query): Query<ShelfListQuery>,
+) -> Result<(StatusCode, Json<Vec<ShelfListEntry>>), (StatusCode, String)> {
+    let books = sqlx::query_as!(
+        ShelfListEntry,
+        r#"SELECT b.id, b.title, b.classification as "classification!: Classification",
+        b.call_number as "call_number!", c.name as category,
+        br.name as "branch?", s.room as "room?", s.name as "shelf?"
+        FROM book b
+        JOIN category c ON c.id = b.category
+        LEFT JOIN branch br ON br.id = b.branch
+        LEFT JOIN shelf s ON s.id = b.shelf
+        WHERE b.call_number IS NOT NULL
+        AND ($1::INT IS NULL OR b.branch = $1) AND ($2::INT IS NULL OR b.shelf = $2)
+        AND ($3::INT IS NULL OR b.category = $3) AND ($4::classification IS NULL OR b.classification = $4)
+        ORDER BY b.classification ASC, b.call_number_sort ASC, b.id ASC"#,
+        query.branch,
+        query.shelf,
+        query.category,
+        query.classification as Option<Classification>,
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load shelf list from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(books)))
+}
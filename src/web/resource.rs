@@ -0,0 +1,261 @@
+use axum::extract::{Path, Query};
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use color_eyre::Result;
+use serde::Deserialize;
+
+use crate::cache::ListCache;
+use crate::error::conflict_or_internal_error;
+use crate::etag::{etag, ETag, IfMatch};
+use crate::repository::{self, Condition, Shared, Value};
+use crate::resource::{label, Resource};
+use crate::validation::ValidatedJson;
+
+/// Router state of a resource, with the cache of its list if it is cached.
+struct ResourceState<R> {
+    repository: Shared<R>,
+    cache: Option<ListCache>,
+}
+
+impl<R> Clone for ResourceState<R> {
+    fn clone(&self) -> Self {
+        Self {
+            repository: self.repository.clone(),
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+impl<R> ResourceState<R> {
+    fn invalidate(&self) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate();
+        }
+    }
+}
+
+/// Query of the list routes.
+#[derive(Deserialize, Debug)]
+struct ListQuery {
+    /// Only the rows of this branch, for the tables with a [`Resource::BRANCH`] column.
+    branch: Option<i32>,
+}
+
+/// List, get, create, update and delete routes of `R`.
+pub fn routes<R: Resource>(repository: Shared<R>) -> Router {
+    router(ResourceState {
+        repository,
+        cache: None,
+    })
+}
+
+/// Like [`routes`], with the list served from `cache` until the next write.
+pub fn cached_routes<R: Resource>(repository: Shared<R>, cache: ListCache) -> Router {
+    router(ResourceState {
+        repository,
+        cache: Some(cache),
+    })
+}
+
+/// List and get routes of `R`, for tables only written by the routes of their workflow.
+pub fn read_routes<R: Resource>(repository: Shared<R>) -> Router {
+    let state = ResourceState {
+        repository,
+        cache: None,
+    };
+
+    Router::new()
+        .route(R::PATH, get(list::<R>))
+        .route(&format!("{}/:{}", R::PATH, R::KEY), get(get_one::<R>))
+        .with_state(state)
+}
+
+fn router<R: Resource>(state: ResourceState<R>) -> Router {
+    Router::new()
+        .route(R::PATH, get(list::<R>).post(create::<R>))
+        .route(
+            &format!("{}/:{}", R::PATH, R::KEY),
+            get(get_one::<R>).put(update::<R>).delete(delete::<R>),
+        )
+        .with_state(state)
+}
+
+/// Response to an error of the repository, `name` names the row in messages.
+pub fn repository_error(err: repository::Error, name: &str) -> (StatusCode, String) {
+    match err {
+        repository::Error::NotFound => (StatusCode::NOT_FOUND, format!("{name} does not exist")),
+        repository::Error::Modified => (
+            StatusCode::PRECONDITION_FAILED,
+            format!("{name} was modified, reload it and try again"),
+        ),
+        repository::Error::Rejected(status, message) => (status, message),
+        repository::Error::Internal(err) => conflict_or_internal_error(err),
+    }
+}
+
+async fn list<R: Resource>(
+    State(state): State<ResourceState<R>>,
+    Query(query): Query<ListQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    if let Some(branch) = query.branch {
+        let Some(column) = R::BRANCH else {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("{} rows do not belong to branches", label(R::TABLE)),
+            ));
+        };
+
+        // Scoped lists are not cached, the cached tables have no branch.
+        let rows = state
+            .repository
+            .find(
+                &[Condition {
+                    column,
+                    values: vec![Value::Int(branch)],
+                }],
+                0,
+                None,
+            )
+            .await
+            .map_err(|err| repository_error(err, &label(R::TABLE)))?;
+
+        return Ok((StatusCode::OK, Json(rows)).into_response());
+    }
+
+    let load = async {
+        state
+            .repository
+            .list()
+            .await
+            .map_err(|err| repository_error(err, &label(R::TABLE)))
+    };
+
+    match &state.cache {
+        Some(cache) => cache.respond(&headers, load).await,
+        None => Ok((StatusCode::OK, Json(load.await?)).into_response()),
+    }
+}
+
+async fn get_one<R: Resource>(
+    State(state): State<ResourceState<R>>,
+    Path(key): Path<R::Key>,
+) -> Result<(StatusCode, ETag, Json<R>), (StatusCode, String)> {
+    let name = format!("{} {key}", label(R::TABLE));
+
+    let row = state
+        .repository
+        .get(&key)
+        .await
+        .map_err(|err| repository_error(err, &name))?
+        .ok_or_else(|| repository_error(repository::Error::NotFound, &name))?;
+
+    Ok((StatusCode::OK, etag(row.version()), Json(row)))
+}
+
+async fn create<R: Resource>(
+    State(state): State<ResourceState<R>>,
+    ValidatedJson(mut row): ValidatedJson<R>,
+) -> Result<(StatusCode, ETag, Json<R>), (StatusCode, String)> {
+    row.normalize()?;
+
+    let inserted = state
+        .repository
+        .insert(&row)
+        .await
+        .map_err(|err| repository_error(err, &label(R::TABLE)))?;
+
+    state.invalidate();
+
+    Ok((
+        StatusCode::CREATED,
+        etag(inserted.version()),
+        Json(inserted),
+    ))
+}
+
+async fn update<R: Resource>(
+    State(state): State<ResourceState<R>>,
+    Path(key): Path<R::Key>,
+    IfMatch(version): IfMatch,
+    ValidatedJson(mut row): ValidatedJson<R>,
+) -> Result<(StatusCode, ETag, Json<R>), (StatusCode, String)> {
+    tracing::info!("{} payload: {:?}", label(R::TABLE), row);
+
+    row.normalize()?;
+
+    let updated = state
+        .repository
+        .update(&key, &row, version)
+        .await
+        .map_err(|err| repository_error(err, &format!("{} {key}", label(R::TABLE))))?;
+
+    state.invalidate();
+
+    Ok((StatusCode::OK, etag(updated.version()), Json(updated)))
+}
+
+async fn delete<R: Resource>(
+    State(state): State<ResourceState<R>>,
+    Path(key): Path<R::Key>,
+    IfMatch(version): IfMatch,
+) -> Result<(StatusCode, Json<R>), (StatusCode, String)> {
+    let deleted = state
+        .repository
+        .delete(&key, version)
+        .await
+        .map_err(|err| repository_error(err, &format!("{} {key}", label(R::TABLE))))?;
+
+    state.invalidate();
+
+    Ok((StatusCode::OK, Json(deleted)))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{header, Method, Request, StatusCode};
+    use tower::ServiceExt;
+
+    use crate::model::Category;
+    use crate::repository::memory::MemoryDatabase;
+
+    async fn send(router: &axum::Router, method: Method, uri: &str, if_match: &str) -> StatusCode {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::IF_MATCH, if_match)
+            .body(Body::from(r#"{"id": 0, "name": "Poetry"}"#))
+            .unwrap();
+
+        router.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn routes_serve_any_repository() {
+        let router = super::routes::<Category>(MemoryDatabase::default().repository());
+
+        assert_eq!(
+            send(&router, Method::POST, "/category", "").await,
+            StatusCode::CREATED
+        );
+        assert_eq!(
+            send(&router, Method::PUT, "/category/1", "\"1\"").await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            send(&router, Method::PUT, "/category/1", "\"1\"").await,
+            StatusCode::PRECONDITION_FAILED
+        );
+        assert_eq!(
+            send(&router, Method::DELETE, "/category/2", "\"1\"").await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            send(&router, Method::DELETE, "/category/1", "\"2\"").await,
+            StatusCode::OK
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
+
+use crate::cache::ListCache;
+use crate::model::Shelf;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
+
+pub fn routes(repository: Shared<Shelf>, cache: ListCache) -> Router {
+    resource::cached_routes(repository, cache)
+}
+
+#[async_trait]
+impl Resource for Shelf {
+    const TABLE: &'static str = "shelf";
+    const PATH: &'static str = "/shelf";
+    const BRANCH: Option<&'static str> = Some("branch");
+
+    type Key = i32;
+
+    fn version(&self) -> i32 {
+        self.version
+    }
+
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Shelf,
+            r#"SELECT id, branch, room, name, version
+            FROM shelf ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
+
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Shelf,
+            r#"SELECT id, branch, room, name, version
+            FROM shelf WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            Shelf,
+            r#"INSERT INTO shelf
+            (branch, room, name)
+            VALUES ($1, $2, $3)
+            RETURNING id, branch, room, name, version"#,
+            self.branch,
+            self.room,
+            self.name,
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Shelf,
+            r#"UPDATE shelf SET
+            branch = $1,
+            room = $2,
+            name = $3
+            WHERE id = $4 AND version = $5
+            RETURNING id, branch, room, name, version"#,
+            self.branch,
+            self.room,
+            self.name,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Shelf,
+            r#"DELETE FROM shelf WHERE id = $1 AND version = $2
+            RETURNING id, branch, room, name, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+}
+
+impl SqliteResource for Shelf {
+    const COLUMNS: &'static [&'static str] = &["branch", "room", "name"];
+
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query.bind(self.branch).bind(&self.room).bind(&self.name)
+    }
+}
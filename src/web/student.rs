@@ -1,104 +1,141 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
 use crate::model::Student;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route("/student", get(get_students).post(create_student))
-        .route("/student/:id", put(update_student).delete(delete_student))
-        .with_state(db)
+pub fn routes(repository: Shared<Student>) -> Router {
+    resource::routes(repository)
 }
 
-async fn get_students(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<Student>>), (StatusCode, String)> {
-    let students = sqlx::query_as!(Student, r#"SELECT id, name, lastname, surname, age, faculty_curriculum, "group", start_study_date, status as "status: _" FROM student ORDER BY id ASC"#)
-        .fetch_all(&db)
-        .await
-        .wrap_err_with(|| eyre!("Unable to load students from database"))
-        .map_err(internal_error)?;
+#[async_trait]
+impl Resource for Student {
+    const TABLE: &'static str = "student";
+    const PATH: &'static str = "/student";
 
-    Ok((StatusCode::OK, Json(students)))
-}
+    type Key = i32;
 
-async fn create_student(
-    State(db): State<Pool<Postgres>>,
-    Json(student): Json<Student>,
-) -> Result<(StatusCode, Json<Student>), (StatusCode, String)> {
-    let inserted_student = sqlx::query_as!(Student,
-        r#"INSERT INTO student 
-        (name, lastname, surname, age, faculty_curriculum, "group", start_study_date, status)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-        RETURNING id, name, lastname, surname, age, faculty_curriculum, "group", start_study_date, status as "status: _""#,
-        student.name,
-        student.lastname,
-        student.surname,
-        student.age,
-        student.faculty_curriculum,
-        student.group,
-        student.start_study_date,
-        student.status as _
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add student to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_student)))
-}
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Student,
+            r#"SELECT id, name, lastname, surname, age, faculty_curriculum, "group", start_study_date, status as "status: _", email, version
+            FROM student ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
+
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Student,
+            r#"SELECT id, name, lastname, surname, age, faculty_curriculum, "group", start_study_date, status as "status: _", email, version
+            FROM student WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-async fn update_student(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(student): Json<Student>,
-) -> Result<(StatusCode, Json<Student>), (StatusCode, String)> {
-    tracing::info!("Student payload: {:?}", student);
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            Student,
+            r#"INSERT INTO student
+            (name, lastname, surname, age, faculty_curriculum, "group", start_study_date, status, email)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, name, lastname, surname, age, faculty_curriculum, "group", start_study_date, status as "status: _", email, version"#,
+            self.name,
+            self.lastname,
+            self.surname,
+            self.age,
+            self.faculty_curriculum,
+            self.group,
+            self.start_study_date,
+            self.status as _,
+            self.email,
+        )
+        .fetch_one(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE student SET
-        name = $1,
-        lastname = $2,
-        surname = $3, 
-        age = $4, 
-        faculty_curriculum = $5, 
-        "group" = $6, 
-        start_study_date = $7, 
-        status = $8
-        WHERE id = $9"#,
-        student.name,
-        student.lastname,
-        student.surname,
-        student.age,
-        student.faculty_curriculum,
-        student.group,
-        student.start_study_date,
-        student.status as _,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update student in database"))
-    .map_err(internal_error)?;
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Student,
+            r#"UPDATE student SET
+            name = $1,
+            lastname = $2,
+            surname = $3,
+            age = $4,
+            faculty_curriculum = $5,
+            "group" = $6,
+            start_study_date = $7,
+            status = $8,
+            email = $9
+            WHERE id = $10 AND version = $11
+            RETURNING id, name, lastname, surname, age, faculty_curriculum, "group", start_study_date, status as "status: _", email, version"#,
+            self.name,
+            self.lastname,
+            self.surname,
+            self.age,
+            self.faculty_curriculum,
+            self.group,
+            self.start_study_date,
+            self.status as _,
+            self.email,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(student)))
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Student,
+            r#"DELETE FROM student WHERE id = $1 AND version = $2
+            RETURNING id, name, lastname, surname, age, faculty_curriculum, "group", start_study_date, status as "status: _", email, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 }
 
-async fn delete_student(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<Student>), (StatusCode, String)> {
-    let deleted_student = sqlx::query_as!(Student, r#"DELETE FROM student WHERE id = $1 
-    RETURNING id, name, lastname, surname, age, faculty_curriculum, "group", start_study_date, status as "status: _""#, id)
-        .fetch_one(&db)
-        .await
-        .wrap_err_with(|| eyre!("Unable to update student in database"))
-        .map_err(internal_error)?;
+impl SqliteResource for Student {
+    const COLUMNS: &'static [&'static str] = &[
+        "name",
+        "lastname",
+        "surname",
+        "age",
+        "faculty_curriculum",
+        "group",
+        "start_study_date",
+        "status",
+        "email",
+    ];
 
-    Ok((StatusCode::OK, Json(deleted_student)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query
+            .bind(&self.name)
+            .bind(&self.lastname)
+            .bind(&self.surname)
+            .bind(self.age)
+            .bind(self.faculty_curriculum)
+            .bind(self.group)
+            .bind(self.start_study_date)
+            .bind(self.status)
+            .bind(&self.email)
+    }
 }
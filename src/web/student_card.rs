@@ -1,100 +1,111 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
 use crate::model::StudentCard;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route(
-            "/student-card",
-            get(get_student_cards).post(create_student_card),
-        )
-        .route(
-            "/student-card/:id",
-            put(update_student_card).delete(delete_student_card),
-        )
-        .with_state(db)
+pub fn routes(repository: Shared<StudentCard>) -> Router {
+    resource::routes(repository)
 }
 
-async fn get_student_cards(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<StudentCard>>), (StatusCode, String)> {
-    let student_cards = sqlx::query_as!(
-        StudentCard,
-        r#"SELECT id, student, issue_date FROM student_card ORDER BY id ASC"#
-    )
-    .fetch_all(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to load student_cards from database"))
-    .map_err(internal_error)?;
+#[async_trait]
+impl Resource for StudentCard {
+    const TABLE: &'static str = "student_card";
+    const PATH: &'static str = "/student-card";
 
-    Ok((StatusCode::OK, Json(student_cards)))
-}
+    type Key = i32;
 
-async fn create_student_card(
-    State(db): State<Pool<Postgres>>,
-    Json(student_card): Json<StudentCard>,
-) -> Result<(StatusCode, Json<StudentCard>), (StatusCode, String)> {
-    let inserted_student_card = sqlx::query_as!(
-        StudentCard,
-        r#"INSERT INTO student_card 
-        (student, issue_date)
-        VALUES ($1, $2)
-        RETURNING  id, student, issue_date"#,
-        student_card.student,
-        student_card.issue_date
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add student_card to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_student_card)))
-}
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            StudentCard,
+            r#"SELECT id, student, issue_date, expiry_date, state as "state: _", version
+            FROM student_card ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
 
-async fn update_student_card(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(student_card): Json<StudentCard>,
-) -> Result<(StatusCode, Json<StudentCard>), (StatusCode, String)> {
-    tracing::info!("StudentCard payload: {:?}", student_card);
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            StudentCard,
+            r#"SELECT id, student, issue_date, expiry_date, state as "state: _", version
+            FROM student_card WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE student_card SET
-        student = $1,
-        issue_date = $2
-        WHERE id = $3"#,
-        student_card.student,
-        student_card.issue_date,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update student_card in database"))
-    .map_err(internal_error)?;
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            StudentCard,
+            r#"INSERT INTO student_card
+            (student, issue_date, expiry_date, state)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, student, issue_date, expiry_date, state as "state: _", version"#,
+            self.student,
+            self.issue_date,
+            self.expiry_date,
+            self.state as _,
+        )
+        .fetch_one(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(student_card)))
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            StudentCard,
+            r#"UPDATE student_card SET
+            student = $1,
+            issue_date = $2,
+            expiry_date = $3,
+            state = $4
+            WHERE id = $5 AND version = $6
+            RETURNING id, student, issue_date, expiry_date, state as "state: _", version"#,
+            self.student,
+            self.issue_date,
+            self.expiry_date,
+            self.state as _,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            StudentCard,
+            r#"DELETE FROM student_card WHERE id = $1 AND version = $2
+            RETURNING id, student, issue_date, expiry_date, state as "state: _", version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 }
 
-async fn delete_student_card(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<StudentCard>), (StatusCode, String)> {
-    let deleted_student_card = sqlx::query_as!(
-        StudentCard,
-        r#"DELETE FROM student_card WHERE id = $1 
-        RETURNING id, student, issue_date"#,
-        id
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update student_card in database"))
-    .map_err(internal_error)?;
+impl SqliteResource for StudentCard {
+    const COLUMNS: &'static [&'static str] = &["student", "issue_date", "expiry_date", "state"];
 
-    Ok((StatusCode::OK, Json(deleted_student_card)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query
+            .bind(self.student)
+            .bind(self.issue_date)
+            .bind(self.expiry_date)
+            .bind(self.state)
+    }
 }
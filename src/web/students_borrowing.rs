@@ -1,116 +1,157 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
+use crate::circulation::Checkout;
 use crate::model::StudentsBorrowing;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route(
-            "/students-borrowing",
-            get(get_students_borrowings).post(create_students_borrowing),
-        )
-        .route(
-            "/students-borrowing/:id",
-            put(update_students_borrowing).delete(delete_students_borrowing),
-        )
-        .with_state(db)
+pub fn routes(repository: Shared<StudentsBorrowing>) -> Router {
+    resource::routes(repository)
 }
 
-async fn get_students_borrowings(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<StudentsBorrowing>>), (StatusCode, String)> {
-    let students_borrowings = sqlx::query_as!(StudentsBorrowing, 
-        r#"SELECT id, student_card, librarian, book,
-        book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date, required_return_date
-        FROM students_borrowing ORDER BY id ASC"#)
-        .fetch_all(&db)
-        .await
-        .wrap_err_with(|| eyre!("Unable to load students_borrowings from database"))
-        .map_err(internal_error)?;
+#[async_trait]
+impl Resource for StudentsBorrowing {
+    const TABLE: &'static str = "students_borrowing";
+    const PATH: &'static str = "/students-borrowing";
+    const BRANCH: Option<&'static str> = Some("branch");
 
-    Ok((StatusCode::OK, Json(students_borrowings)))
-}
+    type Key = i32;
 
-async fn create_students_borrowing(
-    State(db): State<Pool<Postgres>>,
-    Json(students_borrowing): Json<StudentsBorrowing>,
-) -> Result<(StatusCode, Json<StudentsBorrowing>), (StatusCode, String)> {
-    let inserted_students_borrowing = sqlx::query_as!(StudentsBorrowing,
-        r#"INSERT INTO students_borrowing 
-        (student_card, librarian, book,
-        book_status_start, book_status_finish, borrow_date, return_date, required_return_date)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-        RETURNING id, student_card, librarian, book,
-        book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date, required_return_date"#,
-        students_borrowing.student_card,
-        students_borrowing.librarian,
-        students_borrowing.book,
-        students_borrowing.book_status_start as _,
-        students_borrowing.book_status_finish as _,
-        students_borrowing.borrow_date,
-        students_borrowing.return_date,
-        students_borrowing.required_return_date,
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add students_borrowing to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_students_borrowing)))
-}
+    fn checkout(&self) -> Option<Checkout> {
+        Some(Checkout {
+            card_table: "student_card",
+            card: self.student_card,
+            book: self.book,
+            branch: self.branch,
+        })
+    }
+
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            StudentsBorrowing,
+            r#"SELECT id, student_card, librarian, book, book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date, required_return_date, branch, return_branch, version
+            FROM students_borrowing ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
+
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            StudentsBorrowing,
+            r#"SELECT id, student_card, librarian, book, book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date, required_return_date, branch, return_branch, version
+            FROM students_borrowing WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-async fn update_students_borrowing(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(students_borrowing): Json<StudentsBorrowing>,
-) -> Result<(StatusCode, Json<StudentsBorrowing>), (StatusCode, String)> {
-    tracing::info!("StudentsBorrowing payload: {:?}", students_borrowing);
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            StudentsBorrowing,
+            r#"INSERT INTO students_borrowing
+            (student_card, librarian, book, book_status_start, book_status_finish, borrow_date, return_date, required_return_date, branch, return_branch)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id, student_card, librarian, book, book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date, required_return_date, branch, return_branch, version"#,
+            self.student_card,
+            self.librarian,
+            self.book,
+            self.book_status_start as _,
+            self.book_status_finish as _,
+            self.borrow_date,
+            self.return_date,
+            self.required_return_date,
+            self.branch,
+            self.return_branch,
+        )
+        .fetch_one(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE students_borrowing SET
-        student_card = $1,
-        librarian = $2,
-        book = $3, 
-        book_status_start = $4, 
-        book_status_finish = $5, 
-        borrow_date = $6, 
-        return_date = $7, 
-        required_return_date = $8
-        WHERE id = $9"#,
-        students_borrowing.student_card,
-        students_borrowing.librarian,
-        students_borrowing.book,
-        students_borrowing.book_status_start as _,
-        students_borrowing.book_status_finish as _,
-        students_borrowing.borrow_date,
-        students_borrowing.return_date,
-        students_borrowing.required_return_date,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update students_borrowing in database"))
-    .map_err(internal_error)?;
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            StudentsBorrowing,
+            r#"UPDATE students_borrowing SET
+            student_card = $1,
+            librarian = $2,
+            book = $3,
+            book_status_start = $4,
+            book_status_finish = $5,
+            borrow_date = $6,
+            return_date = $7,
+            required_return_date = $8,
+            branch = $9,
+            return_branch = $10
+            WHERE id = $11 AND version = $12
+            RETURNING id, student_card, librarian, book, book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date, required_return_date, branch, return_branch, version"#,
+            self.student_card,
+            self.librarian,
+            self.book,
+            self.book_status_start as _,
+            self.book_status_finish as _,
+            self.borrow_date,
+            self.return_date,
+            self.required_return_date,
+            self.branch,
+            self.return_branch,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(students_borrowing)))
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            StudentsBorrowing,
+            r#"DELETE FROM students_borrowing WHERE id = $1 AND version = $2
+            RETURNING id, student_card, librarian, book, book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date, required_return_date, branch, return_branch, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 }
 
-async fn delete_students_borrowing(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<StudentsBorrowing>), (StatusCode, String)> {
-    let deleted_students_borrowing = sqlx::query_as!(StudentsBorrowing, r#"DELETE FROM students_borrowing WHERE id = $1 
-        RETURNING id, student_card, librarian, book,
-        book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date, required_return_date"#, id)
-        .fetch_one(&db)
-        .await
-        .wrap_err_with(|| eyre!("Unable to update students_borrowing in database"))
-        .map_err(internal_error)?;
+impl SqliteResource for StudentsBorrowing {
+    const COLUMNS: &'static [&'static str] = &[
+        "student_card",
+        "librarian",
+        "book",
+        "book_status_start",
+        "book_status_finish",
+        "borrow_date",
+        "return_date",
+        "required_return_date",
+        "branch",
+        "return_branch",
+    ];
 
-    Ok((StatusCode::OK, Json(deleted_students_borrowing)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query
+            .bind(self.student_card)
+            .bind(self.librarian)
+            .bind(self.book)
+            .bind(self.book_status_start)
+            .bind(self.book_status_finish)
+            .bind(self.borrow_date)
+            .bind(self.return_date)
+            .bind(self.required_return_date)
+            .bind(self.branch)
+            .bind(self.return_branch)
+    }
 }
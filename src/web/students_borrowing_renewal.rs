@@ -0,0 +1,294 @@
+use axum::extract::Path;
+use axum::routing::post;
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use chrono::{Duration, Local, NaiveDate};
+use color_eyre::eyre::Context;
+use color_eyre::{eyre::eyre, Result};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres, Sqlite};
+
+use crate::error::{conflict_or_internal_error, internal_error};
+use crate::model::StudentsBorrowingRenewal;
+
+// Each renewal moves the required return date forward by this many days.
+const RENEWAL_PERIOD_DAYS: i64 = 14;
+const MAX_RENEWALS: i64 = 2;
+
+#[derive(Deserialize, Debug)]
+struct RenewalRequest {
+    librarian: i32,
+}
+
+/// Loan being renewed.
+#[derive(sqlx::FromRow)]
+struct Loan {
+    book: i32,
+    return_date: Option<NaiveDate>,
+    required_return_date: NaiveDate,
+}
+
+pub fn routes(db: Pool<Postgres>) -> Router {
+    Router::new()
+        .route("/borrowing/:id/renew", post(renew_students_borrowing))
+        .route(
+            "/borrowing/:id/renewals",
+            get(get_students_borrowing_renewals),
+        )
+        .with_state(db)
+}
+
+pub fn sqlite_routes(db: Pool<Sqlite>) -> Router {
+    Router::new()
+        .route(
+            "/borrowing/:id/renew",
+            post(renew_sqlite_students_borrowing),
+        )
+        .route(
+            "/borrowing/:id/renewals",
+            get(get_sqlite_students_borrowing_renewals),
+        )
+        .with_state(db)
+}
+
+/// New required return date of the loan `id`, or why it cannot be renewed.
+fn check_renewal(
+    id: i32,
+    borrowing: &Loan,
+    renewals: i64,
+    has_pending_holds: bool,
+    today: NaiveDate,
+) -> Result<NaiveDate, (StatusCode, String)> {
+    if borrowing.return_date.is_some() {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Students borrowing {id} is already returned"),
+        ));
+    }
+
+    if borrowing.required_return_date < today {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Students borrowing {id} is overdue and cannot be renewed"),
+        ));
+    }
+
+    if renewals >= MAX_RENEWALS {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Students borrowing {id} has reached the limit of {MAX_RENEWALS} renewals"),
+        ));
+    }
+
+    if has_pending_holds {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Book {} has pending holds", borrowing.book),
+        ));
+    }
+
+    Ok(borrowing.required_return_date + Duration::days(RENEWAL_PERIOD_DAYS))
+}
+
+async fn get_students_borrowing_renewals(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, Json<Vec<StudentsBorrowingRenewal>>), (StatusCode, String)> {
+    let renewals = sqlx::query_as!(
+        StudentsBorrowingRenewal,
+        r#"SELECT id, students_borrowing, librarian, renewal_date, previous_return_date, required_return_date
+        FROM students_borrowing_renewal WHERE students_borrowing = $1 ORDER BY id ASC"#,
+        id
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load students_borrowing_renewals from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(renewals)))
+}
+
+async fn renew_students_borrowing(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+    Json(request): Json<RenewalRequest>,
+) -> Result<(StatusCode, Json<StudentsBorrowingRenewal>), (StatusCode, String)> {
+    tracing::info!("Renewal payload: {:?}", request);
+
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))
+        .map_err(internal_error)?;
+
+    let borrowing = sqlx::query_as!(
+        Loan,
+        r#"SELECT book, return_date, required_return_date
+        FROM students_borrowing WHERE id = $1 FOR UPDATE"#,
+        id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load students_borrowing from database"))
+    .map_err(internal_error)?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("Students borrowing {id} does not exist"),
+        )
+    })?;
+
+    let renewals = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM students_borrowing_renewal WHERE students_borrowing = $1"#,
+        id
+    )
+    .fetch_one(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to count renewals of students_borrowing"))
+    .map_err(internal_error)?;
+
+    let has_pending_holds = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM hold WHERE book = $1 AND status = 'pending') as "exists!""#,
+        borrowing.book
+    )
+    .fetch_one(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to check holds of book"))
+    .map_err(internal_error)?;
+
+    let today = Local::now().date_naive();
+    let required_return_date = check_renewal(id, &borrowing, renewals, has_pending_holds, today)?;
+
+    sqlx::query!(
+        r#"UPDATE students_borrowing SET required_return_date = $1 WHERE id = $2"#,
+        required_return_date,
+        id
+    )
+    .execute(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update students_borrowing in database"))
+    .map_err(internal_error)?;
+
+    let renewal = sqlx::query_as!(
+        StudentsBorrowingRenewal,
+        r#"INSERT INTO students_borrowing_renewal
+        (students_borrowing, librarian, renewal_date, previous_return_date, required_return_date)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, students_borrowing, librarian, renewal_date, previous_return_date, required_return_date"#,
+        id,
+        request.librarian,
+        today,
+        borrowing.required_return_date,
+        required_return_date,
+    )
+    .fetch_one(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add students_borrowing_renewal to database"))
+    .map_err(conflict_or_internal_error)?;
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit renewal"))
+        .map_err(internal_error)?;
+
+    Ok((StatusCode::CREATED, Json(renewal)))
+}
+
+async fn get_sqlite_students_borrowing_renewals(
+    State(db): State<Pool<Sqlite>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, Json<Vec<StudentsBorrowingRenewal>>), (StatusCode, String)> {
+    let renewals = sqlx::query_as::<_, StudentsBorrowingRenewal>(
+        "SELECT * FROM students_borrowing_renewal WHERE students_borrowing = ? ORDER BY id ASC",
+    )
+    .bind(id)
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load students_borrowing_renewals from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(renewals)))
+}
+
+async fn renew_sqlite_students_borrowing(
+    State(db): State<Pool<Sqlite>>,
+    Path(id): Path<i32>,
+    Json(request): Json<RenewalRequest>,
+) -> Result<(StatusCode, Json<StudentsBorrowingRenewal>), (StatusCode, String)> {
+    tracing::info!("Renewal payload: {:?}", request);
+
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))
+        .map_err(internal_error)?;
+
+    // SQLite has no row locks, a loan renewed by another writer before the commit makes the
+    // commit fail instead.
+    let borrowing = sqlx::query_as::<_, Loan>(
+        "SELECT book, return_date, required_return_date FROM students_borrowing WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_optional(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load students_borrowing from database"))
+    .map_err(internal_error)?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("Students borrowing {id} does not exist"),
+        )
+    })?;
+
+    let renewals = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM students_borrowing_renewal WHERE students_borrowing = ?",
+    )
+    .bind(id)
+    .fetch_one(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to count renewals of students_borrowing"))
+    .map_err(internal_error)?;
+
+    let has_pending_holds = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM hold WHERE book = ? AND status = 'pending')",
+    )
+    .bind(borrowing.book)
+    .fetch_one(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to check holds of book"))
+    .map_err(internal_error)?;
+
+    let today = Local::now().date_naive();
+    let required_return_date = check_renewal(id, &borrowing, renewals, has_pending_holds, today)?;
+
+    sqlx::query(
+        "UPDATE students_borrowing SET required_return_date = ?, version = version + 1 WHERE id = ?",
+    )
+    .bind(required_return_date)
+    .bind(id)
+    .execute(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update students_borrowing in database"))
+    .map_err(internal_error)?;
+
+    let renewal = sqlx::query_as::<_, StudentsBorrowingRenewal>(
+        "INSERT INTO students_borrowing_renewal
+        (students_borrowing, librarian, renewal_date, previous_return_date, required_return_date)
+        VALUES (?, ?, ?, ?, ?) RETURNING *",
+    )
+    .bind(id)
+    .bind(request.librarian)
+    .bind(today)
+    .bind(borrowing.required_return_date)
+    .bind(required_return_date)
+    .fetch_one(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add students_borrowing_renewal to database"))
+    .map_err(conflict_or_internal_error)?;
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit renewal"))
+        .map_err(internal_error)?;
+
+    Ok((StatusCode::CREATED, Json(renewal)))
+}
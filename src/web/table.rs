@@ -3,7 +3,7 @@ use color_eyre::{
     eyre::{eyre, Context},
     Result,
 };
-use sqlx::{Pool, Postgres};
+use sqlx::{Pool, Postgres, Sqlite};
 
 use crate::error::internal_error;
 
@@ -13,6 +13,12 @@ pub fn routes(db: Pool<Postgres>) -> Router {
         .with_state(db)
 }
 
+pub fn sqlite_routes(db: Pool<Sqlite>) -> Router {
+    Router::new()
+        .route("/table", get(get_sqlite_tables))
+        .with_state(db)
+}
+
 async fn get_tables(
     State(db): State<Pool<Postgres>>,
 ) -> Result<Json<Vec<String>>, (StatusCode, String)> {
@@ -29,3 +35,18 @@ async fn get_tables(
 
     Ok(Json(tables))
 }
+
+async fn get_sqlite_tables(
+    State(db): State<Pool<Sqlite>>,
+) -> Result<Json<Vec<String>>, (StatusCode, String)> {
+    // `sqlite_` tables are internal to SQLite, like the schemas other than `public` in Postgres.
+    let tables = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\'",
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load tables from database"))
+    .map_err(internal_error)?;
+
+    Ok(Json(tables))
+}
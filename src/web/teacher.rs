@@ -1,104 +1,125 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
 use crate::model::Teacher;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route("/teacher", get(get_teachers).post(create_teacher))
-        .route("/teacher/:id", put(update_teacher).delete(delete_teacher))
-        .with_state(db)
+pub fn routes(repository: Shared<Teacher>) -> Router {
+    resource::routes(repository)
 }
 
-async fn get_teachers(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<Teacher>>), (StatusCode, String)> {
-    let teachers = sqlx::query_as!(Teacher,
-         r#"SELECT id, name, lastname, surname, age, faculty, status as "status: _" FROM teacher ORDER BY id ASC"#)
-        .fetch_all(&db)
-        .await
-        .wrap_err_with(|| eyre!("Unable to load teachers from database"))
-        .map_err(internal_error)?;
+#[async_trait]
+impl Resource for Teacher {
+    const TABLE: &'static str = "teacher";
+    const PATH: &'static str = "/teacher";
 
-    Ok((StatusCode::OK, Json(teachers)))
-}
+    type Key = i32;
 
-async fn create_teacher(
-    State(db): State<Pool<Postgres>>,
-    Json(teacher): Json<Teacher>,
-) -> Result<(StatusCode, Json<Teacher>), (StatusCode, String)> {
-    let inserted_teacher = sqlx::query_as!(
-        Teacher,
-        r#"INSERT INTO teacher 
-        (name, lastname, surname, age, faculty, status)
-        VALUES ($1, $2, $3, $4, $5, $6)
-        RETURNING id, name, lastname, surname, age, faculty, status as "status: _""#,
-        teacher.name,
-        teacher.lastname,
-        teacher.surname,
-        teacher.age,
-        teacher.faculty,
-        teacher.status as _
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add teacher to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_teacher)))
-}
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            Teacher,
+            r#"SELECT id, name, lastname, surname, age, faculty, status as "status: _", email, version
+            FROM teacher ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
 
-async fn update_teacher(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(teacher): Json<Teacher>,
-) -> Result<(StatusCode, Json<Teacher>), (StatusCode, String)> {
-    tracing::info!("Teacher payload: {:?}", teacher);
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Teacher,
+            r#"SELECT id, name, lastname, surname, age, faculty, status as "status: _", email, version
+            FROM teacher WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE teacher SET
-        name = $1,
-        lastname = $2,
-        surname = $3, 
-        age = $4, 
-        faculty = $5, 
-        status = $6
-        WHERE id = $7"#,
-        teacher.name,
-        teacher.lastname,
-        teacher.surname,
-        teacher.age,
-        teacher.faculty,
-        teacher.status as _,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update teacher in database"))
-    .map_err(internal_error)?;
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            Teacher,
+            r#"INSERT INTO teacher
+            (name, lastname, surname, age, faculty, status, email)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, name, lastname, surname, age, faculty, status as "status: _", email, version"#,
+            self.name,
+            self.lastname,
+            self.surname,
+            self.age,
+            self.faculty,
+            self.status as _,
+            self.email,
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Teacher,
+            r#"UPDATE teacher SET
+            name = $1,
+            lastname = $2,
+            surname = $3,
+            age = $4,
+            faculty = $5,
+            status = $6,
+            email = $7
+            WHERE id = $8 AND version = $9
+            RETURNING id, name, lastname, surname, age, faculty, status as "status: _", email, version"#,
+            self.name,
+            self.lastname,
+            self.surname,
+            self.age,
+            self.faculty,
+            self.status as _,
+            self.email,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(teacher)))
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            Teacher,
+            r#"DELETE FROM teacher WHERE id = $1 AND version = $2
+            RETURNING id, name, lastname, surname, age, faculty, status as "status: _", email, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 }
 
-async fn delete_teacher(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<Teacher>), (StatusCode, String)> {
-    let deleted_teacher = sqlx::query_as!(
-        Teacher,
-        r#"DELETE FROM teacher WHERE id = $1 
-        RETURNING id, name, lastname, surname, age, faculty, status as "status: _""#,
-        id
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update teacher in database"))
-    .map_err(internal_error)?;
+impl SqliteResource for Teacher {
+    const COLUMNS: &'static [&'static str] = &[
+        "name", "lastname", "surname", "age", "faculty", "status", "email",
+    ];
 
-    Ok((StatusCode::OK, Json(deleted_teacher)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query
+            .bind(&self.name)
+            .bind(&self.lastname)
+            .bind(&self.surname)
+            .bind(self.age)
+            .bind(self.faculty)
+            .bind(self.status)
+            .bind(&self.email)
+    }
 }
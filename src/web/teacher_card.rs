@@ -1,100 +1,111 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
 use crate::model::TeacherCard;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route(
-            "/teacher-card",
-            get(get_teacher_cards).post(create_teacher_card),
-        )
-        .route(
-            "/teacher-card/:id",
-            put(update_teacher_card).delete(delete_teacher_card),
-        )
-        .with_state(db)
+pub fn routes(repository: Shared<TeacherCard>) -> Router {
+    resource::routes(repository)
 }
 
-async fn get_teacher_cards(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<TeacherCard>>), (StatusCode, String)> {
-    let teacher_cards = sqlx::query_as!(
-        TeacherCard,
-        r#"SELECT id, teacher, issue_date FROM teacher_card ORDER BY id ASC"#
-    )
-    .fetch_all(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to load teacher_cards from database"))
-    .map_err(internal_error)?;
+#[async_trait]
+impl Resource for TeacherCard {
+    const TABLE: &'static str = "teacher_card";
+    const PATH: &'static str = "/teacher-card";
 
-    Ok((StatusCode::OK, Json(teacher_cards)))
-}
+    type Key = i32;
 
-async fn create_teacher_card(
-    State(db): State<Pool<Postgres>>,
-    Json(teacher_card): Json<TeacherCard>,
-) -> Result<(StatusCode, Json<TeacherCard>), (StatusCode, String)> {
-    let inserted_teacher_card = sqlx::query_as!(
-        TeacherCard,
-        r#"INSERT INTO teacher_card 
-        (teacher, issue_date)
-        VALUES ($1, $2)
-        RETURNING  id, teacher, issue_date"#,
-        teacher_card.teacher,
-        teacher_card.issue_date
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add teacher_card to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_teacher_card)))
-}
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            TeacherCard,
+            r#"SELECT id, teacher, issue_date, expiry_date, state as "state: _", version
+            FROM teacher_card ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
 
-async fn update_teacher_card(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(teacher_card): Json<TeacherCard>,
-) -> Result<(StatusCode, Json<TeacherCard>), (StatusCode, String)> {
-    tracing::info!("TeacherCard payload: {:?}", teacher_card);
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            TeacherCard,
+            r#"SELECT id, teacher, issue_date, expiry_date, state as "state: _", version
+            FROM teacher_card WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE teacher_card SET
-        teacher = $1,
-        issue_date = $2
-        WHERE id = $3"#,
-        teacher_card.teacher,
-        teacher_card.issue_date,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update teacher_card in database"))
-    .map_err(internal_error)?;
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            TeacherCard,
+            r#"INSERT INTO teacher_card
+            (teacher, issue_date, expiry_date, state)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, teacher, issue_date, expiry_date, state as "state: _", version"#,
+            self.teacher,
+            self.issue_date,
+            self.expiry_date,
+            self.state as _,
+        )
+        .fetch_one(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(teacher_card)))
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            TeacherCard,
+            r#"UPDATE teacher_card SET
+            teacher = $1,
+            issue_date = $2,
+            expiry_date = $3,
+            state = $4
+            WHERE id = $5 AND version = $6
+            RETURNING id, teacher, issue_date, expiry_date, state as "state: _", version"#,
+            self.teacher,
+            self.issue_date,
+            self.expiry_date,
+            self.state as _,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            TeacherCard,
+            r#"DELETE FROM teacher_card WHERE id = $1 AND version = $2
+            RETURNING id, teacher, issue_date, expiry_date, state as "state: _", version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 }
 
-async fn delete_teacher_card(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<TeacherCard>), (StatusCode, String)> {
-    let deleted_teacher_card = sqlx::query_as!(
-        TeacherCard,
-        r#"DELETE FROM teacher_card WHERE id = $1 
-        RETURNING id, teacher, issue_date"#,
-        id
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update teacher_card in database"))
-    .map_err(internal_error)?;
+impl SqliteResource for TeacherCard {
+    const COLUMNS: &'static [&'static str] = &["teacher", "issue_date", "expiry_date", "state"];
 
-    Ok((StatusCode::OK, Json(deleted_teacher_card)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query
+            .bind(self.teacher)
+            .bind(self.issue_date)
+            .bind(self.expiry_date)
+            .bind(self.state)
+    }
 }
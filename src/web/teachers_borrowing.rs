@@ -1,117 +1,152 @@
-use axum::extract::Path;
-use axum::routing::put;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use color_eyre::eyre::Context;
-use color_eyre::{eyre::eyre, Result};
-use sqlx::{Pool, Postgres};
+use axum::{async_trait, Router};
+use sqlx::PgConnection;
 
-use crate::error::internal_error;
+use crate::circulation::Checkout;
 use crate::model::TeachersBorrowing;
+use crate::repository::sqlite::{SqliteQuery, SqliteResource};
+use crate::repository::Shared;
+use crate::resource::Resource;
+use crate::web::resource;
 
-pub fn routes(db: Pool<Postgres>) -> Router {
-    Router::new()
-        .route(
-            "/teachers-borrowing",
-            get(get_teachers_borrowings).post(create_teachers_borrowing),
-        )
-        .route(
-            "/teachers-borrowing/:id",
-            put(update_teachers_borrowing).delete(delete_teachers_borrowing),
-        )
-        .with_state(db)
+pub fn routes(repository: Shared<TeachersBorrowing>) -> Router {
+    resource::routes(repository)
 }
 
-async fn get_teachers_borrowings(
-    State(db): State<Pool<Postgres>>,
-) -> Result<(StatusCode, Json<Vec<TeachersBorrowing>>), (StatusCode, String)> {
-    let teachers_borrowings = sqlx::query_as!(
-        TeachersBorrowing,
-        r#"SELECT id, teacher_card, librarian, book,
-        book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date 
-        FROM teachers_borrowing ORDER BY id ASC"#
-    )
-    .fetch_all(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to load teachers_borrowings from database"))
-    .map_err(internal_error)?;
+#[async_trait]
+impl Resource for TeachersBorrowing {
+    const TABLE: &'static str = "teachers_borrowing";
+    const PATH: &'static str = "/teachers-borrowing";
+    const BRANCH: Option<&'static str> = Some("branch");
 
-    Ok((StatusCode::OK, Json(teachers_borrowings)))
-}
+    type Key = i32;
 
-async fn create_teachers_borrowing(
-    State(db): State<Pool<Postgres>>,
-    Json(teachers_borrowing): Json<TeachersBorrowing>,
-) -> Result<(StatusCode, Json<TeachersBorrowing>), (StatusCode, String)> {
-    let inserted_teachers_borrowing = sqlx::query_as!(TeachersBorrowing,
-        r#"INSERT INTO teachers_borrowing 
-        (teacher_card, librarian, book,
-        book_status_start, book_status_finish, borrow_date, return_date)
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
-        RETURNING id, teacher_card, librarian, book,
-        book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date "#,
-        teachers_borrowing.teacher_card,
-        teachers_borrowing.librarian,
-        teachers_borrowing.book,
-        teachers_borrowing.book_status_start as _,
-        teachers_borrowing.book_status_finish as _,
-        teachers_borrowing.borrow_date,
-        teachers_borrowing.return_date,
-    )
-    .fetch_one(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to add teachers_borrowing to database"))
-    .map_err(internal_error)?;
+    fn version(&self) -> i32 {
+        self.version
+    }
 
-    Ok((StatusCode::CREATED, Json(inserted_teachers_borrowing)))
-}
+    fn checkout(&self) -> Option<Checkout> {
+        Some(Checkout {
+            card_table: "teacher_card",
+            card: self.teacher_card,
+            book: self.book,
+            branch: self.branch,
+        })
+    }
 
-async fn update_teachers_borrowing(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-    Json(teachers_borrowing): Json<TeachersBorrowing>,
-) -> Result<(StatusCode, Json<TeachersBorrowing>), (StatusCode, String)> {
-    tracing::info!("TeachersBorrowing payload: {:?}", teachers_borrowing);
+    async fn list(conn: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query_as!(
+            TeachersBorrowing,
+            r#"SELECT id, teacher_card, librarian, book, book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date, branch, return_branch, version
+            FROM teachers_borrowing ORDER BY id ASC"#
+        )
+        .fetch_all(conn)
+        .await
+    }
 
-    sqlx::query!(
-        r#"UPDATE teachers_borrowing SET
-        teacher_card = $1,
-        librarian = $2,
-        book = $3, 
-        book_status_start = $4, 
-        book_status_finish = $5, 
-        borrow_date = $6, 
-        return_date = $7
-        WHERE id = $8"#,
-        teachers_borrowing.teacher_card,
-        teachers_borrowing.librarian,
-        teachers_borrowing.book,
-        teachers_borrowing.book_status_start as _,
-        teachers_borrowing.book_status_finish as _,
-        teachers_borrowing.borrow_date,
-        teachers_borrowing.return_date,
-        id
-    )
-    .execute(&db)
-    .await
-    .wrap_err_with(|| eyre!("Unable to update teachers_borrowing in database"))
-    .map_err(internal_error)?;
+    async fn get(conn: &mut PgConnection, id: &i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            TeachersBorrowing,
+            r#"SELECT id, teacher_card, librarian, book, book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date, branch, return_branch, version
+            FROM teachers_borrowing WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-    Ok((StatusCode::OK, Json(teachers_borrowing)))
-}
+    async fn insert(&self, conn: &mut PgConnection) -> sqlx::Result<Self> {
+        sqlx::query_as!(
+            TeachersBorrowing,
+            r#"INSERT INTO teachers_borrowing
+            (teacher_card, librarian, book, book_status_start, book_status_finish, borrow_date, return_date, branch, return_branch)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, teacher_card, librarian, book, book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date, branch, return_branch, version"#,
+            self.teacher_card,
+            self.librarian,
+            self.book,
+            self.book_status_start as _,
+            self.book_status_finish as _,
+            self.borrow_date,
+            self.return_date,
+            self.branch,
+            self.return_branch,
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    async fn update(
+        &self,
+        conn: &mut PgConnection,
+        id: &i32,
+        version: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            TeachersBorrowing,
+            r#"UPDATE teachers_borrowing SET
+            teacher_card = $1,
+            librarian = $2,
+            book = $3,
+            book_status_start = $4,
+            book_status_finish = $5,
+            borrow_date = $6,
+            return_date = $7,
+            branch = $8,
+            return_branch = $9
+            WHERE id = $10 AND version = $11
+            RETURNING id, teacher_card, librarian, book, book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date, branch, return_branch, version"#,
+            self.teacher_card,
+            self.librarian,
+            self.book,
+            self.book_status_start as _,
+            self.book_status_finish as _,
+            self.borrow_date,
+            self.return_date,
+            self.branch,
+            self.return_branch,
+            id,
+            version
+        )
+        .fetch_optional(conn)
+        .await
+    }
 
-async fn delete_teachers_borrowing(
-    State(db): State<Pool<Postgres>>,
-    Path(id): Path<i32>,
-) -> Result<(StatusCode, Json<TeachersBorrowing>), (StatusCode, String)> {
-    let deleted_teachers_borrowing = sqlx::query_as!(
-        TeachersBorrowing, 
-        r#"DELETE FROM teachers_borrowing WHERE id = $1 
-        RETURNING id, teacher_card, librarian, book,
-        book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date"#, id)
-        .fetch_one(&db)
+    async fn delete(conn: &mut PgConnection, id: &i32, version: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as!(
+            TeachersBorrowing,
+            r#"DELETE FROM teachers_borrowing WHERE id = $1 AND version = $2
+            RETURNING id, teacher_card, librarian, book, book_status_start as "book_status_start: _", book_status_finish as "book_status_finish: _", borrow_date, return_date, branch, return_branch, version"#,
+            id,
+            version
+        )
+        .fetch_optional(conn)
         .await
-        .wrap_err_with(|| eyre!("Unable to update teachers_borrowing in database"))
-        .map_err(internal_error)?;
+    }
+}
+
+impl SqliteResource for TeachersBorrowing {
+    const COLUMNS: &'static [&'static str] = &[
+        "teacher_card",
+        "librarian",
+        "book",
+        "book_status_start",
+        "book_status_finish",
+        "borrow_date",
+        "return_date",
+        "branch",
+        "return_branch",
+    ];
 
-    Ok((StatusCode::OK, Json(deleted_teachers_borrowing)))
+    fn bind<'q>(&'q self, query: SqliteQuery<'q, Self>) -> SqliteQuery<'q, Self> {
+        query
+            .bind(self.teacher_card)
+            .bind(self.librarian)
+            .bind(self.book)
+            .bind(self.book_status_start)
+            .bind(self.book_status_finish)
+            .bind(self.borrow_date)
+            .bind(self.return_date)
+            .bind(self.branch)
+            .bind(self.return_branch)
+    }
 }
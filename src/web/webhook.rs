@@ -0,0 +1,322 @@
+use axum::extract::{Path, Query};
+use axum::routing::post;
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use color_eyre::eyre::Context;
+use color_eyre::{eyre::eyre, Result};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use crate::error::internal_error;
+use crate::etag::{etag, ETag, IfMatch};
+use crate::model::{
+    WebhookAttempt, WebhookDelivery, WebhookDeliveryStatus, WebhookEvent, WebhookSubscription,
+};
+use crate::repository::postgres::not_found_or_modified;
+use crate::validation::ValidatedJson;
+use crate::web::resource::repository_error;
+
+pub fn routes(db: Pool<Postgres>) -> Router {
+    Router::new()
+        .route("/webhook", get(get_subscriptions).post(create_subscription))
+        .route(
+            "/webhook/:id",
+            get(get_subscription)
+                .put(update_subscription)
+                .delete(delete_subscription),
+        )
+        .route("/webhook/:id/deliveries", get(get_subscription_deliveries))
+        .route("/webhook-delivery", get(get_deliveries))
+        .route("/webhook-delivery/:id", get(get_delivery))
+        .route("/webhook-delivery/:id/attempts", get(get_attempts))
+        .route("/webhook-delivery/:id/retry", post(retry_delivery))
+        .with_state(db)
+}
+
+/// Deliveries with this status only, `?status=Dead` lists the dead letters.
+#[derive(Deserialize)]
+struct DeliveryFilter {
+    status: Option<WebhookDeliveryStatus>,
+}
+
+fn subscription_not_found(id: i32) -> (StatusCode, String) {
+    (
+        StatusCode::NOT_FOUND,
+        format!("Webhook subscription {id} does not exist"),
+    )
+}
+
+/// Error for an update or delete of subscription `id` which matched no row.
+async fn subscription_not_found_or_modified(db: &Pool<Postgres>, id: i32) -> (StatusCode, String) {
+    let err = not_found_or_modified(db, "webhook_subscription", "id", id).await;
+    repository_error(err, &format!("Webhook subscription {id}"))
+}
+
+fn delivery_not_found(id: i32) -> (StatusCode, String) {
+    (
+        StatusCode::NOT_FOUND,
+        format!("Webhook delivery {id} does not exist"),
+    )
+}
+
+async fn get_subscriptions(
+    State(db): State<Pool<Postgres>>,
+) -> Result<(StatusCode, Json<Vec<WebhookSubscription>>), (StatusCode, String)> {
+    let subscriptions = sqlx::query_as!(
+        WebhookSubscription,
+        r#"SELECT id, url, events as "events: Vec<WebhookEvent>", secret, active, created_at,
+        version FROM webhook_subscription ORDER BY id ASC"#
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load webhook_subscriptions from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(subscriptions)))
+}
+
+async fn get_subscription(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, ETag, Json<WebhookSubscription>), (StatusCode, String)> {
+    let subscription = sqlx::query_as!(
+        WebhookSubscription,
+        r#"SELECT id, url, events as "events: Vec<WebhookEvent>", secret, active, created_at,
+        version FROM webhook_subscription WHERE id = $1"#,
+        id
+    )
+    .fetch_optional(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load webhook_subscription from database"))
+    .map_err(internal_error)?
+    .ok_or_else(|| subscription_not_found(id))?;
+
+    Ok((
+        StatusCode::OK,
+        etag(subscription.version),
+        Json(subscription),
+    ))
+}
+
+async fn create_subscription(
+    State(db): State<Pool<Postgres>>,
+    ValidatedJson(subscription): ValidatedJson<WebhookSubscription>,
+) -> Result<(StatusCode, ETag, Json<WebhookSubscription>), (StatusCode, String)> {
+    let subscription = sqlx::query_as!(
+        WebhookSubscription,
+        r#"INSERT INTO webhook_subscription (url, events, secret, active)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, url, events as "events: Vec<WebhookEvent>", secret, active, created_at,
+        version"#,
+        subscription.url,
+        subscription.events as _,
+        subscription.secret,
+        subscription.active
+    )
+    .fetch_one(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add webhook_subscription to database"))
+    .map_err(internal_error)?;
+
+    Ok((
+        StatusCode::CREATED,
+        etag(subscription.version),
+        Json(subscription),
+    ))
+}
+
+async fn update_subscription(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+    IfMatch(version): IfMatch,
+    ValidatedJson(subscription): ValidatedJson<WebhookSubscription>,
+) -> Result<(StatusCode, ETag, Json<WebhookSubscription>), (StatusCode, String)> {
+    // Deliveries already queued keep the events they were queued for.
+    let updated = sqlx::query_as!(
+        WebhookSubscription,
+        r#"UPDATE webhook_subscription SET url = $1, events = $2, secret = $3, active = $4
+        WHERE id = $5 AND version = $6
+        RETURNING id, url, events as "events: Vec<WebhookEvent>", secret, active, created_at,
+        version"#,
+        subscription.url,
+        subscription.events as _,
+        subscription.secret,
+        subscription.active,
+        id,
+        version
+    )
+    .fetch_optional(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update webhook_subscription in database"))
+    .map_err(internal_error)?;
+
+    let Some(subscription) = updated else {
+        return Err(subscription_not_found_or_modified(&db, id).await);
+    };
+
+    Ok((
+        StatusCode::OK,
+        etag(subscription.version),
+        Json(subscription),
+    ))
+}
+
+async fn delete_subscription(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+    IfMatch(version): IfMatch,
+) -> Result<(StatusCode, Json<WebhookSubscription>), (StatusCode, String)> {
+    let deleted = sqlx::query_as!(
+        WebhookSubscription,
+        r#"DELETE FROM webhook_subscription WHERE id = $1 AND version = $2
+        RETURNING id, url, events as "events: Vec<WebhookEvent>", secret, active, created_at,
+        version"#,
+        id,
+        version
+    )
+    .fetch_optional(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to delete webhook_subscription from database"))
+    .map_err(internal_error)?;
+
+    let Some(subscription) = deleted else {
+        return Err(subscription_not_found_or_modified(&db, id).await);
+    };
+
+    Ok((StatusCode::OK, Json(subscription)))
+}
+
+async fn get_subscription_deliveries(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+    Query(filter): Query<DeliveryFilter>,
+) -> Result<(StatusCode, Json<Vec<WebhookDelivery>>), (StatusCode, String)> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM webhook_subscription WHERE id = $1) as "exists!""#,
+        id
+    )
+    .fetch_one(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load webhook_subscription from database"))
+    .map_err(internal_error)?;
+    if !exists {
+        return Err(subscription_not_found(id));
+    }
+
+    let deliveries = sqlx::query_as!(
+        WebhookDelivery,
+        r#"SELECT id, subscription, event as "event: _", payload, status as "status: _",
+        attempts, last_error, created_at, next_attempt_at, delivered_at
+        FROM webhook_delivery
+        WHERE subscription = $1 AND ($2::webhook_delivery_status IS NULL OR status = $2)
+        ORDER BY id ASC"#,
+        id,
+        filter.status as _
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load webhook_deliveries from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(deliveries)))
+}
+
+async fn get_deliveries(
+    State(db): State<Pool<Postgres>>,
+    Query(filter): Query<DeliveryFilter>,
+) -> Result<(StatusCode, Json<Vec<WebhookDelivery>>), (StatusCode, String)> {
+    let deliveries = sqlx::query_as!(
+        WebhookDelivery,
+        r#"SELECT id, subscription, event as "event: _", payload, status as "status: _",
+        attempts, last_error, created_at, next_attempt_at, delivered_at
+        FROM webhook_delivery
+        WHERE $1::webhook_delivery_status IS NULL OR status = $1
+        ORDER BY id ASC"#,
+        filter.status as _
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load webhook_deliveries from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(deliveries)))
+}
+
+async fn get_delivery(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, Json<WebhookDelivery>), (StatusCode, String)> {
+    let delivery = sqlx::query_as!(
+        WebhookDelivery,
+        r#"SELECT id, subscription, event as "event: _", payload, status as "status: _",
+        attempts, last_error, created_at, next_attempt_at, delivered_at
+        FROM webhook_delivery WHERE id = $1"#,
+        id
+    )
+    .fetch_optional(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load webhook_delivery from database"))
+    .map_err(internal_error)?
+    .ok_or_else(|| delivery_not_found(id))?;
+
+    Ok((StatusCode::OK, Json(delivery)))
+}
+
+async fn get_attempts(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, Json<Vec<WebhookAttempt>>), (StatusCode, String)> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM webhook_delivery WHERE id = $1) as "exists!""#,
+        id
+    )
+    .fetch_one(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load webhook_delivery from database"))
+    .map_err(internal_error)?;
+    if !exists {
+        return Err(delivery_not_found(id));
+    }
+
+    let attempts = sqlx::query_as!(
+        WebhookAttempt,
+        r#"SELECT id, delivery, attempted_at, response_status, error
+        FROM webhook_attempt WHERE delivery = $1 ORDER BY id ASC"#,
+        id
+    )
+    .fetch_all(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to load webhook_attempts from database"))
+    .map_err(internal_error)?;
+
+    Ok((StatusCode::OK, Json(attempts)))
+}
+
+/// Queues a dead delivery again with a fresh set of attempts.
+async fn retry_delivery(
+    State(db): State<Pool<Postgres>>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, Json<WebhookDelivery>), (StatusCode, String)> {
+    let delivery = sqlx::query_as!(
+        WebhookDelivery,
+        r#"UPDATE webhook_delivery SET
+        status = 'pending',
+        attempts = 0,
+        next_attempt_at = now()
+        WHERE id = $1 AND status = 'dead'
+        RETURNING id, subscription, event as "event: _", payload, status as "status: _",
+        attempts, last_error, created_at, next_attempt_at, delivered_at"#,
+        id
+    )
+    .fetch_optional(&db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to update webhook_delivery in database"))
+    .map_err(internal_error)?
+    .ok_or_else(|| {
+        (
+            StatusCode::CONFLICT,
+            format!("Webhook delivery {id} does not exist or is not dead"),
+        )
+    })?;
+
+    Ok((StatusCode::OK, Json(delivery)))
+}
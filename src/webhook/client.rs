@@ -0,0 +1,110 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use color_eyre::{
+    eyre::{bail, eyre, Context},
+    Result,
+};
+use hyper::header::{CONTENT_TYPE, HOST};
+use hyper::{Body, Request, StatusCode, Uri};
+use once_cell::sync::Lazy;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+// Receivers which do not answer in time are retried like unreachable ones.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+static TLS: Lazy<TlsConnector> = Lazy::new(|| {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .expect("Default TLS versions should be supported")
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    TlsConnector::from(Arc::new(config))
+});
+
+/// Posts the JSON `body` to `url` over a new connection and returns the status of the response.
+pub async fn post(
+    url: &str,
+    headers: &[(&'static str, String)],
+    body: Vec<u8>,
+) -> Result<StatusCode> {
+    tokio::time::timeout(TIMEOUT, send(url, headers, body))
+        .await
+        .map_err(|_| eyre!("No response within {} seconds", TIMEOUT.as_secs()))?
+}
+
+async fn send(url: &str, headers: &[(&'static str, String)], body: Vec<u8>) -> Result<StatusCode> {
+    let uri = url
+        .parse::<Uri>()
+        .wrap_err_with(|| eyre!("Invalid URL `{url}`"))?;
+    let (Some(host), Some(authority)) = (uri.host(), uri.authority()) else {
+        bail!("URL `{url}` has no host");
+    };
+    let https = match uri.scheme_str() {
+        Some("https") => true,
+        Some("http") => false,
+        _ => bail!("URL `{url}` is not an HTTP or HTTPS URL"),
+    };
+    let port = uri.port_u16().unwrap_or(if https { 443 } else { 80 });
+
+    let mut request = Request::post(uri.path_and_query().map_or("/", |path| path.as_str()))
+        .header(HOST, authority.as_str())
+        .header(CONTENT_TYPE, "application/json");
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+    let request = request
+        .body(Body::from(body))
+        .wrap_err_with(|| eyre!("Unable to build request"))?;
+
+    // IPv6 hosts are bracketed in URLs.
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let stream = TcpStream::connect((host, port))
+        .await
+        .wrap_err_with(|| eyre!("Unable to connect to {authority}"))?;
+
+    if !https {
+        return exchange(stream, request).await;
+    }
+
+    let name = ServerName::try_from(host.to_string())
+        .wrap_err_with(|| eyre!("Invalid TLS server name `{host}`"))?;
+    let stream = TLS
+        .connect(name, stream)
+        .await
+        .wrap_err_with(|| eyre!("Unable to establish TLS with {authority}"))?;
+
+    exchange(stream, request).await
+}
+
+async fn exchange<S>(stream: S, request: Request<Body>) -> Result<StatusCode>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (mut sender, connection) = hyper::client::conn::handshake(stream)
+        .await
+        .wrap_err_with(|| eyre!("Unable to start HTTP connection"))?;
+
+    // Driven in the background, it ends once the response is dropped.
+    tokio::spawn(async move {
+        if let Err(err) = connection.await {
+            tracing::debug!("Webhook connection closed with an error: {err}");
+        }
+    });
+
+    let response = sender
+        .send_request(request)
+        .await
+        .wrap_err_with(|| eyre!("Unable to send request"))?;
+
+    Ok(response.status())
+}
@@ -0,0 +1,47 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub mod client;
+pub mod worker;
+
+/// `sha256=` followed by the hex HMAC of `{timestamp}.{body}`, keyed with the secret of the subscription.
+pub const SIGNATURE_HEADER: &str = "x-webhook-signature";
+/// Unix time of the attempt, signed with the body so receivers can reject replays.
+pub const TIMESTAMP_HEADER: &str = "x-webhook-timestamp";
+pub const EVENT_HEADER: &str = "x-webhook-event";
+/// Id of the delivery, the same on every attempt so receivers can skip duplicates.
+pub const DELIVERY_HEADER: &str = "x-webhook-delivery";
+
+/// Signature of a delivery of `body` at `timestamp`, the value of [`SIGNATURE_HEADER`].
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC should accept any key");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sign;
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let signature = sign("0123456789abcdef", 1_700_000_000, br#"{"id":1}"#);
+
+        assert_eq!(
+            signature,
+            "sha256=4bcaced68dfea90a68df035b89cb7fb26692d899d32a1ccb1b0616cf48e4d1ed"
+        );
+        assert_ne!(
+            sign("0123456789abcdef", 1_700_000_001, br#"{"id":1}"#),
+            signature
+        );
+        assert_ne!(
+            sign("0123456789abcdef", 1_700_000_000, br#"{"id":2}"#),
+            signature
+        );
+    }
+}
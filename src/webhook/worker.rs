@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use serde_json::json;
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+
+use crate::model::WebhookEvent;
+
+use super::{client, sign, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+const BATCH_SIZE: i64 = 20;
+// After this many failed attempts a delivery is dead and only retried on request.
+const MAX_ATTEMPTS: i16 = 8;
+// Claimed deliveries are left to this worker for this long before others may post them.
+const LEASE_MINUTES: i32 = 5;
+
+/// Delivers pending webhooks from the outbox until the server stops.
+pub async fn run(db: Pool<Postgres>) {
+    loop {
+        if let Err(err) = deliver_pending(&db).await {
+            tracing::error!("{:?}", err);
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Delivery claimed by this worker, with the subscription it is sent to.
+struct Delivery {
+    id: i32,
+    event: WebhookEvent,
+    /// Row which raised the event as Postgres serializes it, sent as `data`, see the triggers.
+    payload: serde_json::Value,
+    attempts: i16,
+    created_at: DateTime<Utc>,
+    url: String,
+    secret: String,
+}
+
+/// Attempts the deliveries which are due, returns how many were attempted.
+///
+/// Deliveries are claimed for a lease and the claim is committed before anything is posted, so
+/// other replicas skip them without a transaction held open across the requests. Each attempt
+/// is recorded on its own, a delivery whose attempt could not be recorded is posted again once
+/// its lease runs out.
+pub async fn deliver_pending(db: &Pool<Postgres>) -> Result<usize> {
+    let mut deliveries = sqlx::query_as!(
+        Delivery,
+        r#"UPDATE webhook_delivery d SET next_attempt_at = now() + make_interval(mins => $2)
+        FROM webhook_subscription s
+        WHERE s.id = d.subscription AND d.id IN (
+            SELECT id FROM webhook_delivery
+            WHERE status = 'pending' AND next_attempt_at <= now()
+            ORDER BY id ASC
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING d.id, d.event as "event: _", d.payload, d.attempts, d.created_at, s.url, s.secret"#,
+        BATCH_SIZE,
+        LEASE_MINUTES
+    )
+    .fetch_all(db)
+    .await
+    .wrap_err_with(|| eyre!("Unable to claim pending webhook_deliveries"))?;
+
+    // `RETURNING` keeps no order, deliveries are posted oldest first.
+    deliveries.sort_by_key(|delivery| delivery.id);
+
+    for delivery in &deliveries {
+        let (response_status, error) = post(delivery).await;
+        if let Err(err) = record(db, delivery, response_status, error).await {
+            tracing::error!("{:?}", err);
+        }
+    }
+
+    Ok(deliveries.len())
+}
+
+/// Posts `delivery`, returns the status the receiver answered and why the attempt failed, if it did.
+async fn post(delivery: &Delivery) -> (Option<i16>, Option<String>) {
+    let body = json!({
+        "id": delivery.id,
+        "event": delivery.event,
+        "created_at": delivery.created_at,
+        "data": delivery.payload,
+    })
+    .to_string()
+    .into_bytes();
+
+    let timestamp = Utc::now().timestamp();
+    let headers = [
+        (SIGNATURE_HEADER, sign(&delivery.secret, timestamp, &body)),
+        (TIMESTAMP_HEADER, timestamp.to_string()),
+        (
+            EVENT_HEADER,
+            json!(delivery.event)
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        ),
+        (DELIVERY_HEADER, delivery.id.to_string()),
+    ];
+
+    match client::post(&delivery.url, &headers, body).await {
+        Ok(status) if status.is_success() => (Some(status.as_u16() as i16), None),
+        Ok(status) => (
+            Some(status.as_u16() as i16),
+            Some(format!("Receiver answered {status}")),
+        ),
+        Err(err) => (None, Some(format!("{:#}", err))),
+    }
+}
+
+async fn record(
+    db: &Pool<Postgres>,
+    delivery: &Delivery,
+    response_status: Option<i16>,
+    error: Option<String>,
+) -> Result<()> {
+    let mut tx = db
+        .begin()
+        .await
+        .wrap_err_with(|| eyre!("Unable to start transaction"))?;
+
+    sqlx::query!(
+        r#"INSERT INTO webhook_attempt (delivery, response_status, error)
+        VALUES ($1, $2, $3)"#,
+        delivery.id,
+        response_status,
+        error
+    )
+    .execute(&mut tx)
+    .await
+    .wrap_err_with(|| eyre!("Unable to add webhook_attempt to database"))?;
+
+    match error {
+        None => {
+            sqlx::query!(
+                r#"UPDATE webhook_delivery SET
+                status = 'delivered',
+                attempts = attempts + 1,
+                last_error = NULL,
+                delivered_at = now()
+                WHERE id = $1"#,
+                delivery.id
+            )
+            .execute(&mut tx)
+            .await
+            .wrap_err_with(|| eyre!("Unable to update webhook_delivery in database"))?;
+        }
+        Some(error) => {
+            tracing::warn!("Unable to deliver webhook {}: {}", delivery.id, error);
+
+            let attempts = delivery.attempts + 1;
+            let next_attempt_at = Utc::now() + chrono::Duration::minutes(1 << attempts);
+
+            sqlx::query!(
+                r#"UPDATE webhook_delivery SET
+                status = CASE WHEN $1::SMALLINT >= $2::SMALLINT
+                    THEN 'dead'::webhook_delivery_status ELSE status END,
+                attempts = $1::SMALLINT,
+                last_error = $3,
+                next_attempt_at = $4
+                WHERE id = $5"#,
+                attempts,
+                MAX_ATTEMPTS,
+                error,
+                next_attempt_at,
+                delivery.id
+            )
+            .execute(&mut tx)
+            .await
+            .wrap_err_with(|| eyre!("Unable to update webhook_delivery in database"))?;
+        }
+    }
+
+    tx.commit()
+        .await
+        .wrap_err_with(|| eyre!("Unable to commit webhook_attempt"))?;
+
+    Ok(())
+}
@@ -0,0 +1,358 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{assert_crud, book_body, days_from_today, today, TestApp};
+
+async fn branch(app: &TestApp, name: &str) -> Value {
+    app.create("/branch", json!({ "id": 0, "name": name, "faculty": null }))
+        .await
+}
+
+/// Book of a new publisher and category belonging to `branch`, `country` must not exist yet.
+async fn book_at(app: &TestApp, country: &str, branch: &Value) -> Value {
+    let publisher = app.publisher(country).await;
+    let category = app.category().await;
+
+    let mut body = book_body(
+        publisher["id"].as_i64().unwrap(),
+        category["id"].as_i64().unwrap(),
+    );
+    body["branch"] = branch["id"].clone();
+    app.create("/book", body).await
+}
+
+fn transfer_body(book: &Value, from_branch: &Value, to_branch: &Value) -> Value {
+    json!({
+        "id": 0,
+        "book": book["id"],
+        "from_branch": from_branch["id"],
+        "to_branch": to_branch["id"],
+        "sent_date": today(),
+        "received_date": null,
+        "status": "InTransit",
+    })
+}
+
+async fn checkout(app: &TestApp, book: &Value) -> common::TestResponse {
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+    let librarian = app.librarian().await;
+
+    app.post(
+        "/students-borrowing",
+        json!({
+            "id": 0,
+            "student_card": card["id"],
+            "librarian": librarian["id"],
+            "book": book["id"],
+            "book_status_start": "Good",
+            "book_status_finish": null,
+            "borrow_date": today(),
+            "return_date": null,
+            "required_return_date": days_from_today(14),
+        }),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn branch_crud() {
+    let app = TestApp::new().await;
+    let faculty = app.faculty().await;
+
+    assert_crud(
+        &app,
+        "/branch",
+        "id",
+        json!({ "id": 0, "name": "Physics Library", "faculty": faculty["id"] }),
+        "name",
+        json!("Central Library"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn lists_are_scoped_by_branch() {
+    let app = TestApp::new().await;
+    let central = branch(&app, "Central Library").await;
+    let physics = branch(&app, "Physics Library").await;
+    let kobzar = book_at(&app, "UA", &central).await;
+    book_at(&app, "PL", &physics).await;
+
+    let books = app
+        .get(&format!("/book?branch={}", central["id"]))
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(books, json!([kobzar]));
+
+    let books = app.get("/book").await.assert_status(StatusCode::OK).json();
+    assert_eq!(books.as_array().unwrap().len(), 2);
+
+    let librarians = app
+        .get(&format!("/librarian?branch={}", physics["id"]))
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(librarians, json!([]));
+
+    let response = app.get("/category?branch=1").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(response.text(), "Category rows do not belong to branches");
+}
+
+#[tokio::test]
+async fn book_transfers_are_read_only() {
+    let app = TestApp::new().await;
+    let central = branch(&app, "Central Library").await;
+    let physics = branch(&app, "Physics Library").await;
+    let book = book_at(&app, "UA", &central).await;
+
+    app.post("/book-transfer", transfer_body(&book, &central, &physics))
+        .await
+        .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+
+    let transfer = app
+        .post(
+            &format!("/book/{}/transfer", book["id"]),
+            json!({ "to_branch": physics["id"] }),
+        )
+        .await
+        .assert_status(StatusCode::CREATED)
+        .json();
+    let transfer_uri = format!("/book-transfer/{}", transfer["id"]);
+
+    let mut received = transfer_body(&book, &central, &physics);
+    received["status"] = json!("Received");
+    received["received_date"] = json!(today());
+    app.put(&transfer_uri, 1, received)
+        .await
+        .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+    app.delete(&transfer_uri, 1)
+        .await
+        .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+
+    let transfers = app
+        .get("/book-transfer")
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(transfers, json!([transfer]));
+    let book = app.get(&format!("/book/{}", book["id"])).await.json();
+    assert_eq!(book["branch"], central["id"]);
+}
+
+#[tokio::test]
+async fn book_update_does_not_move_book() {
+    let app = TestApp::new().await;
+    let central = branch(&app, "Central Library").await;
+    let physics = branch(&app, "Physics Library").await;
+    let book = book_at(&app, "UA", &central).await;
+    let uri = format!("/book/{}", book["id"]);
+
+    let mut moved = book.clone();
+    moved["branch"] = physics["id"].clone();
+    let response = app.put(&uri, 1, moved).await;
+    response.assert_status(StatusCode::CONFLICT);
+    assert_eq!(
+        response.text(),
+        format!(
+            "Book {} is moved between branches by POST /api/book/{}/transfer",
+            book["id"], book["id"]
+        )
+    );
+
+    let mut renamed = book.clone();
+    renamed["title"] = json!("Renamed");
+    let renamed = app
+        .put(&uri, 1, renamed)
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(renamed["branch"], central["id"]);
+}
+
+#[tokio::test]
+async fn checkout_rejects_book_of_another_branch() {
+    let app = TestApp::new().await;
+    let central = branch(&app, "Central Library").await;
+    let physics = branch(&app, "Physics Library").await;
+    let book = book_at(&app, "UA", &central).await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+    let librarian = app.librarian().await;
+    let body = |branch: &Value| {
+        json!({
+            "id": 0,
+            "student_card": card["id"],
+            "librarian": librarian["id"],
+            "book": book["id"],
+            "book_status_start": "Good",
+            "book_status_finish": null,
+            "borrow_date": today(),
+            "return_date": null,
+            "required_return_date": days_from_today(14),
+            "branch": branch["id"],
+        })
+    };
+
+    let response = app.post("/students-borrowing", body(&physics)).await;
+    response.assert_status(StatusCode::CONFLICT);
+    assert_eq!(
+        response.text(),
+        format!(
+            "Book {} belongs to branch {}, it cannot be lent at branch {}",
+            book["id"], central["id"], physics["id"]
+        )
+    );
+
+    let borrowing = app
+        .post("/students-borrowing", body(&central))
+        .await
+        .assert_status(StatusCode::CREATED)
+        .json();
+    assert_eq!(borrowing["branch"], central["id"]);
+}
+
+#[tokio::test]
+async fn transfer_moves_book_when_received() {
+    let app = TestApp::new().await;
+    let central = branch(&app, "Central Library").await;
+    let physics = branch(&app, "Physics Library").await;
+    let book = book_at(&app, "UA", &central).await;
+    let transfer_uri = format!("/book/{}/transfer", book["id"]);
+
+    let transfer = app
+        .post(&transfer_uri, json!({ "to_branch": physics["id"] }))
+        .await
+        .assert_status(StatusCode::CREATED)
+        .json();
+    assert_eq!(transfer["from_branch"], central["id"]);
+    assert_eq!(transfer["to_branch"], physics["id"]);
+    assert_eq!(transfer["status"], "InTransit");
+
+    app.post(&transfer_uri, json!({ "to_branch": physics["id"] }))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+    checkout(&app, &book)
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    let received = app
+        .post(
+            &format!("/book-transfer/{}/receive", transfer["id"]),
+            json!({}),
+        )
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(received["status"], "Received");
+    assert_eq!(received["received_date"], today());
+
+    let moved = app
+        .get(&format!("/book/{}", book["id"]))
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(moved["branch"], physics["id"]);
+
+    app.post(
+        &format!("/book-transfer/{}/cancel", transfer["id"]),
+        json!({}),
+    )
+    .await
+    .assert_status(StatusCode::CONFLICT);
+
+    checkout(&app, &book)
+        .await
+        .assert_status(StatusCode::CREATED);
+    let response = app
+        .post(&transfer_uri, json!({ "to_branch": central["id"] }))
+        .await;
+    response.assert_status(StatusCode::CONFLICT);
+    assert_eq!(response.text(), format!("Book {} is lent", book["id"]));
+}
+
+#[tokio::test]
+async fn transfer_errors() {
+    let app = TestApp::new().await;
+    let central = branch(&app, "Central Library").await;
+    let book = book_at(&app, "UA", &central).await;
+    let transfer_uri = format!("/book/{}/transfer", book["id"]);
+
+    app.post("/book/999/transfer", json!({ "to_branch": central["id"] }))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.post(&transfer_uri, json!({ "to_branch": central["id"] }))
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let response = app.post(&transfer_uri, json!({ "to_branch": 999 })).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.text(), "Branch 999 does not exist");
+
+    let unassigned = app.book("PL").await;
+    app.post(
+        &format!("/book/{}/transfer", unassigned["id"]),
+        json!({ "to_branch": central["id"] }),
+    )
+    .await
+    .assert_status(StatusCode::CONFLICT);
+
+    app.post("/book-transfer/999/receive", json!({}))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn return_at_another_branch_sends_book_home() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let central = branch(&app, "Central Library").await;
+    let physics = branch(&app, "Physics Library").await;
+    let book = book_at(&app, "UA", &central).await;
+    let borrowing = app.students_borrowing(&book, 14).await;
+    let return_uri = format!("/students-borrowing/{}/return", borrowing["id"]);
+
+    app.post(
+        &return_uri,
+        json!({ "branch": 999, "book_status_finish": "Good" }),
+    )
+    .await
+    .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    let returned = app
+        .post(
+            &return_uri,
+            json!({ "branch": physics["id"], "book_status_finish": "Good" }),
+        )
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(returned["borrowing"]["return_date"], today());
+    assert_eq!(returned["borrowing"]["return_branch"], physics["id"]);
+    assert_eq!(returned["transfer"]["from_branch"], physics["id"]);
+    assert_eq!(returned["transfer"]["to_branch"], central["id"]);
+    assert_eq!(returned["transfer"]["status"], "InTransit");
+
+    app.post(
+        &return_uri,
+        json!({ "branch": physics["id"], "book_status_finish": "Good" }),
+    )
+    .await
+    .assert_status(StatusCode::CONFLICT);
+
+    let other = book_at(&app, "PL", &central).await;
+    let borrowing = app.students_borrowing(&other, 14).await;
+    let returned = app
+        .post(
+            &format!("/students-borrowing/{}/return", borrowing["id"]),
+            json!({ "branch": central["id"], "book_status_finish": "Good" }),
+        )
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(returned["transfer"], Value::Null);
+}
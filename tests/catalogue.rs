@@ -0,0 +1,376 @@
+mod common;
+
+use axum::http::{header, StatusCode};
+use serde_json::json;
+
+use common::{assert_crud, assert_foreign_key_violation, book_body, TestApp};
+
+const MARCXML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<collection xmlns="http://www.loc.gov/MARC21/slim">
+  <record>
+    <leader>00000nam a2200000 a 4500</leader>
+    <datafield tag="020" ind1=" " ind2=" "><subfield code="a">0306406152</subfield></datafield>
+    <datafield tag="100" ind1="1" ind2=" "><subfield code="a">Shevchenko, Taras Hryhorovych</subfield></datafield>
+    <datafield tag="245" ind1="1" ind2="0"><subfield code="a">Kobzar /</subfield></datafield>
+    <datafield tag="264" ind1=" " ind2="1"><subfield code="b">Dnipro,</subfield><subfield code="c">1985.</subfield></datafield>
+    <datafield tag="650" ind1=" " ind2="0"><subfield code="a">Poetry.</subfield></datafield>
+  </record>
+</collection>"#;
+
+#[tokio::test]
+async fn author_crud() {
+    let app = TestApp::new().await;
+    app.country("UA").await;
+
+    assert_crud(
+        &app,
+        "/author",
+        "id",
+        json!({ "id": 0, "name": "Ivan", "lastname": "Franko", "surname": "", "country": "UA" }),
+        "surname",
+        json!("Yakovych"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn author_rejects_missing_country() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post(
+            "/author",
+            json!({ "id": 0, "name": "Ivan", "lastname": "Franko", "surname": "", "country": "ZZ" }),
+        )
+        .await;
+
+    assert_foreign_key_violation(&response);
+}
+
+#[tokio::test]
+async fn book_crud() {
+    let app = TestApp::new().await;
+    let publisher = app.publisher("UA").await;
+    let category = app.category().await;
+
+    assert_crud(
+        &app,
+        "/book",
+        "id",
+        book_body(
+            publisher["id"].as_i64().unwrap(),
+            category["id"].as_i64().unwrap(),
+        ),
+        "student_access",
+        json!(false),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn book_rejects_missing_publisher() {
+    let app = TestApp::new().await;
+    let category = app.category().await;
+
+    let response = app
+        .post("/book", book_body(999, category["id"].as_i64().unwrap()))
+        .await;
+
+    assert_foreign_key_violation(&response);
+}
+
+#[tokio::test]
+async fn book_isbn_is_normalized_and_unique() {
+    let app = TestApp::new().await;
+    let publisher = app.publisher("UA").await;
+    let category = app.category().await;
+
+    let mut body = book_body(
+        publisher["id"].as_i64().unwrap(),
+        category["id"].as_i64().unwrap(),
+    );
+    body["isbn"] = json!("0-306-40615-2");
+
+    let book = app.create("/book", body.clone()).await;
+    assert_eq!(book["isbn"], "9780306406157");
+
+    body["isbn"] = json!("978-0-306-40615-7");
+    app.post("/book", body.clone())
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    body["isbn"] = json!("0-306-40615-3");
+    app.post("/book", body)
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn book_by_isbn() {
+    let app = TestApp::new().await;
+    let publisher = app.publisher("UA").await;
+    let category = app.category().await;
+
+    let mut body = book_body(
+        publisher["id"].as_i64().unwrap(),
+        category["id"].as_i64().unwrap(),
+    );
+    body["isbn"] = json!("9780306406157");
+    let book = app.create("/book", body).await;
+
+    let found = app.get("/book/by-isbn/0-306-40615-2").await;
+    found.assert_status(StatusCode::OK);
+    assert_eq!(found.json(), book);
+    assert_eq!(found.version(), 1);
+
+    app.get("/book/by-isbn/9781861972712")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.get("/book/by-isbn/123")
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn book_call_number_is_normalized_with_sort_key() {
+    let app = TestApp::new().await;
+    let publisher = app.publisher("UA").await;
+    let category = app.category().await;
+
+    let mut body = book_body(
+        publisher["id"].as_i64().unwrap(),
+        category["id"].as_i64().unwrap(),
+    );
+    body["classification"] = json!("Udc");
+    body["call_number"] = json!(" 821.161.2-1   she ");
+
+    let book = app.create("/book", body.clone()).await;
+    assert_eq!(book["call_number"], "821.161.2-1 she");
+    assert_eq!(book["call_number_sort"], "8211612*1#SHE");
+
+    body["call_number"] = json!("(477)821");
+    app.post("/book", body.clone())
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    body["classification"] = json!(null);
+    body["call_number"] = json!("821");
+    app.post("/book", body)
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn book_lists_field_and_schema_errors_together() {
+    let app = TestApp::new().await;
+    let publisher = app.publisher("UA").await;
+    let category = app.category().await;
+
+    let mut body = book_body(
+        publisher["id"].as_i64().unwrap(),
+        category["id"].as_i64().unwrap(),
+    );
+    body["title"] = json!("");
+    body["classification"] = json!("Udc");
+    body["call_number"] = json!(null);
+
+    let response = app.post("/book", body).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let errors = response.json();
+    assert_eq!(errors["title"][0]["code"], "length");
+    assert_eq!(errors["__all__"][0]["code"], "call_number");
+}
+
+#[tokio::test]
+async fn shelf_crud() {
+    let app = TestApp::new().await;
+    let branch = app
+        .create(
+            "/branch",
+            json!({ "id": 0, "name": "Central Library", "faculty": null }),
+        )
+        .await;
+
+    assert_crud(
+        &app,
+        "/shelf",
+        "id",
+        json!({ "id": 0, "branch": branch["id"], "room": "Reading Room", "name": "A" }),
+        "name",
+        json!("B"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn shelf_list_is_in_call_number_order() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let publisher = app.publisher("UA").await;
+    let category = app.category().await;
+    let shelf = app
+        .create(
+            "/shelf",
+            json!({ "id": 0, "branch": null, "room": "Reading Room", "name": "A" }),
+        )
+        .await;
+
+    let mut body = book_body(
+        publisher["id"].as_i64().unwrap(),
+        category["id"].as_i64().unwrap(),
+    );
+    // Text order would put `821.161.21` before `821.161.2-1`.
+    for call_number in ["821.161.21 KOB", "821.161.2-1 SHE", "821.161.2 FRA"] {
+        body["title"] = json!(call_number);
+        body["classification"] = json!("Udc");
+        body["call_number"] = json!(call_number);
+        body["shelf"] = shelf["id"].clone();
+        app.create("/book", body.clone()).await;
+    }
+    body["classification"] = json!(null);
+    body["call_number"] = json!(null);
+    app.create("/book", body).await;
+
+    let list = app.get("/reports/shelf-list").await;
+    list.assert_status(StatusCode::OK);
+    let call_numbers = list
+        .json()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["call_number"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        call_numbers,
+        ["821.161.2 FRA", "821.161.2-1 SHE", "821.161.21 KOB"]
+    );
+
+    let entry = &list.json()[0];
+    assert_eq!(entry["classification"], "Udc");
+    assert_eq!(entry["category"], "Science");
+    assert_eq!(entry["room"], "Reading Room");
+    assert_eq!(entry["shelf"], "A");
+
+    let list = app
+        .get(&format!(
+            "/reports/shelf-list?shelf={}",
+            shelf["id"].as_i64().unwrap() + 1
+        ))
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(list, json!([]));
+    let list = app
+        .get("/reports/shelf-list?classification=Dewey")
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(list, json!([]));
+}
+
+#[tokio::test]
+async fn author_book_crud() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+    let author = app.author("UA").await;
+
+    assert_crud(
+        &app,
+        "/author-book",
+        "id",
+        json!({ "id": 0, "author_id": author["id"], "book_id": book["id"], "num": 1 }),
+        "num",
+        json!(2),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn author_book_rejects_missing_author() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+
+    let response = app
+        .post(
+            "/author-book",
+            json!({ "id": 0, "author_id": 999, "book_id": book["id"], "num": 1 }),
+        )
+        .await;
+
+    assert_foreign_key_violation(&response);
+}
+
+#[tokio::test]
+async fn marc_import_and_export() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    app.country("UA").await;
+
+    let report = app.post_bytes("/marc/import?country=UA", MARCXML).await;
+    report.assert_status(StatusCode::OK);
+
+    let report = report.json();
+    assert_eq!(report["records"], 1);
+    assert_eq!(report["books_created"], 1);
+    assert_eq!(report["authors_created"], 1);
+    assert_eq!(report["publishers_created"], 1);
+    assert_eq!(report["categories_created"], 1);
+
+    let book = app.get("/book/by-isbn/9780306406157").await;
+    book.assert_status(StatusCode::OK);
+    assert_eq!(book.json()["title"], "Kobzar");
+
+    // Importing the same records again skips the existing books.
+    let report = app
+        .post_bytes("/marc/import?country=UA", MARCXML)
+        .await
+        .json();
+    assert_eq!(report["books_created"], 0);
+    assert_eq!(report["books_skipped"], 1);
+
+    let export = app.get("/marc/export").await;
+    export.assert_status(StatusCode::OK);
+    assert_eq!(
+        export.headers[header::CONTENT_TYPE],
+        "application/marcxml+xml"
+    );
+    assert!(export.text().contains("Kobzar"));
+    assert!(export.text().contains("9780306406157"));
+
+    let book = book.json();
+    let mut body = book.clone();
+    body["classification"] = json!("Dewey");
+    body["call_number"] = json!("891.79 SHE");
+    app.put(
+        &format!("/book/{}", book["id"]),
+        book["version"].as_i64().unwrap() as i32,
+        body,
+    )
+    .await
+    .assert_status(StatusCode::OK);
+    let export = app.get("/marc/export").await.text();
+    assert!(export.contains(r#"<datafield tag="082" ind1="0" ind2="4">"#));
+    assert!(export.contains(r#"<subfield code="b">SHE</subfield>"#));
+}
+
+#[tokio::test]
+async fn marc_import_errors() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+
+    app.post_bytes("/marc/import?country=ZZ", MARCXML)
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    app.post_bytes("/marc/import", MARCXML)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    app.country("UA").await;
+    app.post_bytes("/marc/import?country=UA", "00042nam not a MARC record")
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -0,0 +1,652 @@
+mod common;
+
+use axum::http::StatusCode;
+use crud::scheduler::{self, Job};
+use serde_json::{json, Value};
+use sqlx::types::chrono::{TimeZone, Utc};
+
+use common::{assert_crud, assert_foreign_key_violation, days_from_today, today, TestApp};
+
+fn students_borrowing_body(card: &Value, librarian: &Value, book: &Value) -> Value {
+    json!({
+        "id": 0,
+        "student_card": card["id"],
+        "librarian": librarian["id"],
+        "book": book["id"],
+        "book_status_start": "Good",
+        "book_status_finish": null,
+        "borrow_date": today(),
+        "return_date": null,
+        "required_return_date": days_from_today(14),
+    })
+}
+
+fn teachers_borrowing_body(card: &Value, librarian: &Value, book: &Value) -> Value {
+    json!({
+        "id": 0,
+        "teacher_card": card["id"],
+        "librarian": librarian["id"],
+        "book": book["id"],
+        "book_status_start": "Excellent",
+        "book_status_finish": null,
+        "borrow_date": today(),
+        "return_date": null,
+    })
+}
+
+fn hold_body(book: &Value, card: &Value) -> Value {
+    json!({
+        "id": 0,
+        "book": book["id"],
+        "student_card": card["id"],
+        "teacher_card": null,
+        "request_date": today(),
+        "expire_date": null,
+        "status": "Pending",
+    })
+}
+
+#[tokio::test]
+async fn students_borrowing_crud() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+    let librarian = app.librarian().await;
+
+    assert_crud(
+        &app,
+        "/students-borrowing",
+        "id",
+        students_borrowing_body(&card, &librarian, &book),
+        "return_date",
+        json!(today()),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn students_borrowing_requires_active_card() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+    let librarian = app.librarian().await;
+
+    let mut body = students_borrowing_body(&card, &librarian, &book);
+    body["student_card"] = json!(999);
+    app.post("/students-borrowing", body)
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    app.post(
+        &format!("/student-card/{}/reissue", card["id"]),
+        json!({ "state": "Lost" }),
+    )
+    .await
+    .assert_status(StatusCode::CREATED);
+
+    app.post(
+        "/students-borrowing",
+        students_borrowing_body(&card, &librarian, &book),
+    )
+    .await
+    .assert_status(StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn students_borrowing_requires_card_not_marked_lost() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+    let librarian = app.librarian().await;
+
+    let mut lost_card = card.clone();
+    lost_card["state"] = json!("Lost");
+    app.put(&format!("/student-card/{}", card["id"]), 1, lost_card)
+        .await
+        .assert_status(StatusCode::OK);
+
+    app.post(
+        "/students-borrowing",
+        students_borrowing_body(&card, &librarian, &book),
+    )
+    .await
+    .assert_status(StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn students_borrowing_cannot_move_to_inactive_card() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+    let librarian = app.librarian().await;
+    let other_student = app.student().await;
+    let expired_card = app
+        .create(
+            "/student-card",
+            json!({
+                "id": 0,
+                "student": other_student["id"],
+                "issue_date": days_from_today(-400),
+                "expiry_date": days_from_today(-35),
+                "state": "Active",
+            }),
+        )
+        .await;
+
+    // A loan back-dated into the life of the card still needs a card active today.
+    let mut back_dated = students_borrowing_body(&expired_card, &librarian, &book);
+    back_dated["borrow_date"] = json!(days_from_today(-40));
+    back_dated["required_return_date"] = json!(days_from_today(-26));
+    app.post("/students-borrowing", back_dated)
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    let borrowing = app
+        .create(
+            "/students-borrowing",
+            students_borrowing_body(&card, &librarian, &book),
+        )
+        .await;
+    let uri = format!("/students-borrowing/{}", borrowing["id"]);
+
+    let mut moved = borrowing.clone();
+    moved["student_card"] = expired_card["id"].clone();
+    app.put(&uri, 1, moved)
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    // Returning the copy is allowed on a card blocked since the checkout.
+    let mut blocked_card = card.clone();
+    blocked_card["state"] = json!("Blocked");
+    app.put(&format!("/student-card/{}", card["id"]), 1, blocked_card)
+        .await
+        .assert_status(StatusCode::OK);
+
+    let mut returned = borrowing.clone();
+    returned["return_date"] = json!(today());
+    returned["book_status_finish"] = json!("Good");
+    app.put(&uri, 1, returned)
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn students_borrowing_rejects_invalid_dates() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+    let librarian = app.librarian().await;
+
+    let mut body = students_borrowing_body(&card, &librarian, &book);
+    body["required_return_date"] = json!(days_from_today(-1));
+    body["return_date"] = json!(days_from_today(-1));
+
+    let response = app.post("/students-borrowing", body).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.json()["__all__"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn students_borrowing_rejects_missing_book() {
+    let app = TestApp::new().await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+    let librarian = app.librarian().await;
+
+    let response = app
+        .post(
+            "/students-borrowing",
+            students_borrowing_body(&card, &librarian, &json!({ "id": 999 })),
+        )
+        .await;
+
+    assert_foreign_key_violation(&response);
+}
+
+#[tokio::test]
+async fn teachers_borrowing_crud() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+    let teacher = app.teacher().await;
+    let card = app.teacher_card(&teacher).await;
+    let librarian = app.librarian().await;
+
+    assert_crud(
+        &app,
+        "/teachers-borrowing",
+        "id",
+        teachers_borrowing_body(&card, &librarian, &book),
+        "book_status_finish",
+        json!("Satisfactory"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn teachers_borrowing_requires_existing_card_and_librarian() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+    let teacher = app.teacher().await;
+    let card = app.teacher_card(&teacher).await;
+
+    app.post(
+        "/teachers-borrowing",
+        teachers_borrowing_body(&json!({ "id": 999 }), &json!({ "id": 999 }), &book),
+    )
+    .await
+    .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = app
+        .post(
+            "/teachers-borrowing",
+            teachers_borrowing_body(&card, &json!({ "id": 999 }), &book),
+        )
+        .await;
+    assert_foreign_key_violation(&response);
+}
+
+#[tokio::test]
+async fn renewal_moves_required_return_date() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+    let borrowing = app.students_borrowing(&book, 3).await;
+    let librarian = app.librarian().await;
+    let uri = format!("/borrowing/{}/renew", borrowing["id"]);
+
+    let renewal = app
+        .post(&uri, json!({ "librarian": librarian["id"] }))
+        .await;
+    renewal.assert_status(StatusCode::CREATED);
+
+    let renewal = renewal.json();
+    assert_eq!(renewal["previous_return_date"], days_from_today(3));
+    assert_eq!(renewal["required_return_date"], days_from_today(17));
+
+    app.post(&uri, json!({ "librarian": librarian["id"] }))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    // Only two renewals are allowed.
+    app.post(&uri, json!({ "librarian": librarian["id"] }))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    let renewals = app
+        .get(&format!("/borrowing/{}/renewals", borrowing["id"]))
+        .await;
+    renewals.assert_status(StatusCode::OK);
+    assert_eq!(renewals.json().as_array().unwrap().len(), 2);
+
+    let borrowing = app
+        .get(&format!("/students-borrowing/{}", borrowing["id"]))
+        .await
+        .json();
+    assert_eq!(borrowing["required_return_date"], days_from_today(31));
+}
+
+#[tokio::test]
+async fn renewal_errors() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+    let librarian = app.librarian().await;
+    let body = json!({ "librarian": librarian["id"] });
+
+    app.post("/borrowing/999/renew", body.clone())
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    let overdue = app.students_borrowing(&book, -1).await;
+    app.post(&format!("/borrowing/{}/renew", overdue["id"]), body.clone())
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    let mut returned = app.students_borrowing(&book, 3).await;
+    let uri = format!("/students-borrowing/{}", returned["id"]);
+    returned["return_date"] = json!(today());
+    app.put(&uri, 1, returned.clone())
+        .await
+        .assert_status(StatusCode::OK);
+    app.post(
+        &format!("/borrowing/{}/renew", returned["id"]),
+        body.clone(),
+    )
+    .await
+    .assert_status(StatusCode::CONFLICT);
+
+    let held = app.students_borrowing(&book, 3).await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+    app.create("/hold", hold_body(&book, &card)).await;
+    app.post(&format!("/borrowing/{}/renew", held["id"]), body)
+        .await
+        .assert_status(StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn renewal_rejects_missing_librarian() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+    let borrowing = app.students_borrowing(&book, 3).await;
+
+    let response = app
+        .post(
+            &format!("/borrowing/{}/renew", borrowing["id"]),
+            json!({ "librarian": 999 }),
+        )
+        .await;
+
+    assert_foreign_key_violation(&response);
+}
+
+#[tokio::test]
+async fn hold_crud() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+
+    assert_crud(
+        &app,
+        "/hold",
+        "id",
+        hold_body(&book, &card),
+        "status",
+        json!("Ready"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn hold_requires_exactly_one_card() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+    let teacher = app.teacher().await;
+    let teacher_card = app.teacher_card(&teacher).await;
+
+    let mut body = hold_body(&book, &card);
+    body["teacher_card"] = teacher_card["id"].clone();
+    body["expire_date"] = json!(days_from_today(-1));
+
+    let response = app.post("/hold", body).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.json()["__all__"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn hold_rejects_missing_book() {
+    let app = TestApp::new().await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+
+    let response = app
+        .post("/hold", hold_body(&json!({ "id": 999 }), &card))
+        .await;
+
+    assert_foreign_key_violation(&response);
+}
+
+#[tokio::test]
+async fn notifications_are_generated_once() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let book = app.book("UA").await;
+    let due_soon = app.students_borrowing(&book, 2).await;
+    let overdue = app.students_borrowing(&book, -1).await;
+    app.students_borrowing(&book, 30).await;
+
+    let generated = app.post("/notification/generate", json!({})).await;
+    generated.assert_status(StatusCode::CREATED);
+
+    let generated = generated.json();
+    let generated = generated.as_array().unwrap();
+    assert_eq!(generated.len(), 2);
+    assert_eq!(generated[0]["kind"], "DueSoon");
+    assert_eq!(generated[0]["students_borrowing"], due_soon["id"]);
+    assert_eq!(generated[0]["recipient"], "taras@example.com");
+    assert_eq!(generated[1]["kind"], "Overdue");
+    assert_eq!(generated[1]["students_borrowing"], overdue["id"]);
+
+    let generated_again = app.post("/notification/generate", json!({})).await.json();
+    assert!(generated_again.as_array().unwrap().is_empty());
+
+    let list = app.get("/notification").await;
+    list.assert_status(StatusCode::OK);
+    assert_eq!(list.json().as_array().unwrap().len(), 2);
+
+    let notification = app
+        .get(&format!("/notification/{}", generated[0]["id"]))
+        .await;
+    notification.assert_status(StatusCode::OK);
+    assert_eq!(notification.json()["status"], "Pending");
+}
+
+#[tokio::test]
+async fn notification_errors() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let book = app.book("UA").await;
+    app.students_borrowing(&book, 1).await;
+
+    app.get("/notification/999")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.post("/notification/999/retry", json!({}))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    let generated = app.post("/notification/generate", json!({})).await.json();
+    let id = &generated[0]["id"];
+
+    // Only failed notifications can be retried.
+    app.post(&format!("/notification/{id}/retry"), json!({}))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    sqlx::query("UPDATE notification SET status = 'failed', attempts = 5")
+        .execute(app.pg())
+        .await
+        .unwrap();
+
+    let retried = app
+        .post(&format!("/notification/{id}/retry"), json!({}))
+        .await;
+    retried.assert_status(StatusCode::OK);
+    assert_eq!(retried.json()["status"], "Pending");
+    assert_eq!(retried.json()["attempts"], 0);
+}
+
+#[tokio::test]
+async fn jobs_run_and_record_runs() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+
+    let jobs = app.get("/job").await;
+    jobs.assert_status(StatusCode::OK);
+
+    let jobs = jobs.json();
+    let jobs = jobs.as_array().unwrap();
+    assert_eq!(jobs.len(), 5);
+    assert!(jobs.iter().all(|job| job["last_run"].is_null()));
+
+    let run = app.post("/job/hold-expiry/run", json!({})).await;
+    run.assert_status(StatusCode::CREATED);
+    assert_eq!(run.json()["status"], "Succeeded");
+
+    let runs = app.get("/job/hold-expiry/runs").await;
+    runs.assert_status(StatusCode::OK);
+    assert_eq!(runs.json().as_array().unwrap().len(), 1);
+
+    let jobs = app.get("/job").await.json();
+    let hold_expiry = jobs
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|job| job["name"] == "hold-expiry")
+        .unwrap();
+    assert_eq!(hold_expiry["last_run"]["id"], run.json()["id"]);
+
+    app.get("/job/unknown/runs")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.post("/job/unknown/run", json!({}))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn scheduled_jobs_run_once_per_slot() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let job = Job::OverdueDetection;
+    let slot = Utc.with_ymd_and_hms(2026, 10, 19, 1, 0, 0).unwrap();
+
+    let run = scheduler::run_job(app.pg(), job, Some(slot))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(run.scheduled_at, Some(slot));
+    assert!(scheduler::run_job(app.pg(), job, Some(slot))
+        .await
+        .unwrap()
+        .is_none());
+
+    // The job is skipped while another replica holds its lock, and runs once it is released.
+    let mut replica = app.pg().begin().await.unwrap();
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('overdue-detection'))")
+        .execute(&mut replica)
+        .await
+        .unwrap();
+    assert!(scheduler::run_job(app.pg(), job, None)
+        .await
+        .unwrap()
+        .is_none());
+    replica.rollback().await.unwrap();
+
+    let run = scheduler::run_job(app.pg(), job, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(run.scheduled_at, None);
+}
+
+#[tokio::test]
+async fn interrupted_job_runs_are_failed_by_next_run() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let slot = Utc.with_ymd_and_hms(2026, 10, 19, 1, 0, 0).unwrap();
+
+    // Left behind by replicas which stopped while running the jobs.
+    for job in ["overdue-detection", "reminders"] {
+        sqlx::query("INSERT INTO job_run (job, scheduled_at) VALUES ($1, $2)")
+            .bind(job)
+            .bind(slot)
+            .execute(app.pg())
+            .await
+            .unwrap();
+    }
+
+    scheduler::run_job(app.pg(), Job::OverdueDetection, None)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let runs = app
+        .get("/job/overdue-detection/runs")
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    let interrupted = runs
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|run| run["scheduled_at"] != Value::Null)
+        .unwrap();
+    assert_eq!(interrupted["status"], "Failed");
+    assert_eq!(interrupted["message"], "Interrupted before it finished");
+    assert_ne!(interrupted["finished_at"], Value::Null);
+
+    let runs = app.get("/job/reminders/runs").await.json();
+    assert_eq!(runs[0]["status"], "Running");
+}
+
+#[tokio::test]
+async fn reports_count_loans() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let book = app.book("UA").await;
+    app.students_borrowing(&book, -1).await;
+    app.students_borrowing(&book, 7).await;
+
+    let loans = app.get("/reports/loans?group_by=year").await;
+    loans.assert_status(StatusCode::OK);
+    let total: i64 = loans
+        .json()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|period| period["total"].as_i64().unwrap())
+        .sum();
+    assert_eq!(total, 2);
+
+    let top_books = app.get("/reports/top-books?limit=1").await;
+    top_books.assert_status(StatusCode::OK);
+    assert_eq!(top_books.json()[0]["id"], book["id"]);
+    assert_eq!(top_books.json()[0]["loans"], 2);
+
+    let duration = app.get("/reports/loan-duration").await;
+    duration.assert_status(StatusCode::OK);
+    assert_eq!(duration.json()["overdue_loans"], 1);
+
+    for uri in [
+        "/reports/top-authors",
+        "/reports/loans-by-faculty",
+        "/reports/loans-by-curriculum",
+    ] {
+        app.get(uri).await.assert_status(StatusCode::OK);
+    }
+
+    let from = today();
+    let to = days_from_today(-1);
+    app.get(&format!("/reports/loans?from={from}&to={to}"))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    for limit in ["-1", "0", "1001"] {
+        let response = app.get(&format!("/reports/top-books?limit={limit}")).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.text(),
+            format!("Limit {limit} should be between 1 and 1000")
+        );
+    }
+    app.get("/reports/top-authors?limit=-5")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    for (uri, param) in [
+        ("/reports/loans?limit=5", "limit"),
+        ("/reports/top-books?group_by=year", "group_by"),
+        ("/reports/loans-by-faculty?limit=5", "limit"),
+        ("/reports/loan-duration?group_by=day", "group_by"),
+    ] {
+        let response = app.get(uri).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.text(),
+            format!("Parameter `{param}` is not supported by this report")
+        );
+    }
+}
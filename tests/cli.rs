@@ -0,0 +1,338 @@
+mod common;
+
+use std::path::PathBuf;
+
+use crud::cli::{self, Command};
+use serde_json::json;
+
+use common::TestApp;
+
+/// Directory in the temporary directory, named after the test using it.
+fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("crud_cli_{}_{test}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+#[tokio::test]
+async fn tables_are_imported_and_exported_as_csv() {
+    let app = TestApp::new().await;
+    let db = app.database().await;
+    let dir = temp_dir("csv");
+    app.country("UA").await;
+
+    let file = dir.join("authors.csv");
+    std::fs::write(
+        &file,
+        "name,lastname,surname,country\r\nIvan,Franko,Yakovych,UA\r\n\"Lesya, Larysa\",Ukrainka,\"\",UA\r\n",
+    )
+    .unwrap();
+    cli::run(
+        Command::Import {
+            table: "author".into(),
+            file: file.clone(),
+        },
+        &db,
+    )
+    .await
+    .unwrap();
+
+    let authors = app.get("/author").await.json();
+    assert_eq!(authors.as_array().unwrap().len(), 2);
+    assert_eq!(authors[1]["name"], "Lesya, Larysa");
+    assert_eq!(authors[1]["surname"], "");
+
+    // Rows are all validated before any is inserted.
+    std::fs::write(
+        &file,
+        "name,lastname,surname,country\nTaras,Shevchenko,,UA\nMarko,Vovchok,,ukr\n",
+    )
+    .unwrap();
+    let err = cli::run(
+        Command::Import {
+            table: "author".into(),
+            file: file.clone(),
+        },
+        &db,
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().starts_with("Row 2: country"), "{err}");
+    assert_eq!(app.get("/author").await.json().as_array().unwrap().len(), 2);
+
+    // Rows refused by the database roll back the rows before them.
+    std::fs::write(
+        &file,
+        "name,lastname,surname,country\nTaras,Shevchenko,,UA\nMarko,Vovchok,,PL\n",
+    )
+    .unwrap();
+    let err = cli::run(
+        Command::Import {
+            table: "author".into(),
+            file: file.clone(),
+        },
+        &db,
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().starts_with("Row 2: "), "{err}");
+    assert_eq!(app.get("/author").await.json().as_array().unwrap().len(), 2);
+
+    cli::run(
+        Command::Export {
+            dir: dir.clone(),
+            tables: vec!["author".into(), "country".into()],
+        },
+        &db,
+    )
+    .await
+    .unwrap();
+
+    let authors_csv = std::fs::read_to_string(dir.join("author.csv")).unwrap();
+    assert_eq!(
+        authors_csv,
+        format!(
+            "id,name,lastname,surname,country,version\r\n\
+            {},Ivan,Franko,Yakovych,UA,1\r\n\
+            {},\"Lesya, Larysa\",Ukrainka,,UA,1\r\n",
+            authors[0]["id"], authors[1]["id"]
+        )
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.join("country.csv")).unwrap(),
+        "code,name,version\r\nUA,Country UA,1\r\n"
+    );
+
+    let err = cli::run(
+        Command::Export {
+            dir: dir.clone(),
+            tables: vec!["warehouse".into()],
+        },
+        &db,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.to_string(), "Unknown table warehouse");
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn integrity_check_reports_rows_written_around_the_api() {
+    let app = TestApp::new().await;
+    let db = app.database().await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+
+    assert_eq!(
+        cli::check_integrity(&db).await.unwrap(),
+        Vec::<String>::new()
+    );
+    cli::run(Command::CheckIntegrity, &db).await.unwrap();
+
+    let expiry_date = chrono::Local::now().date_naive() - chrono::Duration::days(400);
+    let update = "UPDATE student_card SET expiry_date = $1 WHERE id = $2";
+    match &db {
+        crud::Database::Postgres(db_pool) => sqlx::query(update)
+            .bind(expiry_date)
+            .bind(card["id"].as_i64().unwrap() as i32)
+            .execute(db_pool)
+            .await
+            .map(|_| ()),
+        crud::Database::Sqlite(db_pool) => sqlx::query(update)
+            .bind(expiry_date)
+            .bind(card["id"].as_i64().unwrap() as i32)
+            .execute(db_pool)
+            .await
+            .map(|_| ()),
+    }
+    .unwrap();
+
+    assert_eq!(
+        cli::check_integrity(&db).await.unwrap(),
+        [format!(
+            "Student card {}: `expiry_date` should not be before `issue_date`",
+            card["id"]
+        )]
+    );
+    let err = cli::run(Command::CheckIntegrity, &db).await.unwrap_err();
+    assert_eq!(err.to_string(), "Found 1 integrity problems");
+}
+
+#[tokio::test]
+async fn librarians_are_created_with_the_rules_of_the_api() {
+    let app = TestApp::new().await;
+    let db = app.database().await;
+
+    let librarian = |age| Command::CreateLibrarian {
+        name: "Olena".into(),
+        lastname: "Koval".into(),
+        surname: "Petrivna".into(),
+        age,
+    };
+
+    let err = cli::run(librarian(12), &db).await.unwrap_err();
+    assert_eq!(err.to_string(), "age: range");
+    assert_eq!(app.get("/librarian").await.json(), json!([]));
+
+    cli::run(librarian(40), &db).await.unwrap();
+    let librarians = app.get("/librarian").await.json();
+    assert_eq!(librarians[0]["name"], "Olena");
+    assert_eq!(librarians[0]["surname"], "Petrivna");
+    assert_eq!(librarians[0]["age"], 40);
+}
+
+#[tokio::test]
+async fn demo_data_is_the_same_for_the_same_seed() {
+    let mut exports = Vec::new();
+    // Taken once, so every run is dated the same even across midnight.
+    let date = chrono::Local::now().date_naive();
+
+    for (test, seed) in [("seed_a", 7), ("seed_b", 7), ("seed_c", 8)] {
+        let app = TestApp::new().await;
+        let db = app.database().await;
+        let dir = temp_dir(test);
+
+        let seed = Command::Seed {
+            seed,
+            students: 20,
+            date,
+        };
+        cli::run(seed, &db).await.unwrap();
+        assert_eq!(
+            cli::check_integrity(&db).await.unwrap(),
+            Vec::<String>::new()
+        );
+
+        let students = app.get("/student").await.json();
+        assert_eq!(students.as_array().unwrap().len(), 20);
+        let loans = app.get("/students-borrowing").await.json();
+        let today = date.to_string();
+        assert!(loans.as_array().unwrap().iter().any(|loan| {
+            loan["return_date"].is_null() && loan["required_return_date"].as_str() < Some(&today)
+        }));
+
+        let err = cli::run(
+            Command::Seed {
+                seed: 7,
+                students: 20,
+                date,
+            },
+            &db,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "country already has rows, demo data is only added to an empty database"
+        );
+
+        cli::run(
+            Command::Export {
+                dir: dir.clone(),
+                tables: Vec::new(),
+            },
+            &db,
+        )
+        .await
+        .unwrap();
+        exports.push(
+            [
+                "student",
+                "book",
+                "author_book",
+                "students_borrowing",
+                "teachers_borrowing",
+            ]
+            .map(|table| std::fs::read_to_string(dir.join(format!("{table}.csv"))).unwrap()),
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    assert_eq!(exports[0], exports[1]);
+    assert_ne!(exports[0], exports[2]);
+}
+
+#[tokio::test]
+async fn backups_restore_into_an_empty_database() {
+    let app = TestApp::new().await;
+    let db = app.database().await;
+    let dir = temp_dir("backup");
+    let seed = Command::Seed {
+        seed: 3,
+        students: 10,
+        date: chrono::Local::now().date_naive(),
+    };
+    cli::run(seed, &db).await.unwrap();
+    let author = app.get("/author").await.json()[0].clone();
+    app.put(&format!("/author/{}", author["id"]), 1, author.clone())
+        .await
+        .assert_status(axum::http::StatusCode::OK);
+
+    let backup = dir.join("backup");
+    cli::run(
+        Command::Backup {
+            dir: backup.clone(),
+        },
+        &db,
+    )
+    .await
+    .unwrap();
+
+    let restored = TestApp::new().await;
+    let restored_db = restored.database().await;
+    cli::run(
+        Command::Restore {
+            dir: backup.clone(),
+        },
+        &restored_db,
+    )
+    .await
+    .unwrap();
+
+    for (app, db, export) in [(&app, &db, "before"), (&restored, &restored_db, "after")] {
+        assert_eq!(
+            cli::check_integrity(db).await.unwrap(),
+            Vec::<String>::new()
+        );
+        let export = Command::Export {
+            dir: dir.join(export),
+            tables: Vec::new(),
+        };
+        cli::run(export, db).await.unwrap();
+        assert_eq!(app.get("/author").await.json()[0]["version"], 2);
+    }
+    for file in std::fs::read_dir(dir.join("before")).unwrap() {
+        let file = file.unwrap().file_name();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("before").join(&file)).unwrap(),
+            std::fs::read_to_string(dir.join("after").join(&file)).unwrap(),
+            "{file:?}"
+        );
+    }
+
+    // Keys continue after the restored rows.
+    let country = app.get("/country").await.json()[0]["code"].clone();
+    let new_author = json!({
+        "id": 0,
+        "name": "Ivan",
+        "lastname": "Bahrianyi",
+        "surname": "Pavlovych",
+        "country": country,
+    });
+    let added = app.post("/author", new_author.clone()).await.json();
+    let restored_added = restored.post("/author", new_author).await.json();
+    assert_eq!(restored_added["id"], added["id"]);
+
+    let err = cli::run(Command::Restore { dir: backup }, &restored_db)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "country already has rows, backups are only restored into an empty database"
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,744 @@
+// Every test binary uses a different subset of the helpers.
+#![allow(dead_code)]
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderMap, Method, Request, StatusCode};
+use axum::Router;
+use hyper::body::HttpBody;
+use serde_json::{json, Value};
+use sqlx::migrate::Migrator;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Connection, Executor, PgConnection, Pool, Postgres};
+use tokio::sync::OnceCell;
+use tower::ServiceExt;
+
+static MIGRATOR: Migrator = sqlx::migrate!();
+static TEMPLATE: OnceCell<String> = OnceCell::const_new();
+static DATABASES: AtomicUsize = AtomicUsize::new(0);
+
+/// Router of the API over a database of its own, dropped with it.
+pub struct TestApp {
+    router: Router,
+    database: TestDatabase,
+}
+
+enum TestDatabase {
+    Postgres { db: Pool<Postgres>, name: String },
+    Sqlite { path: PathBuf },
+}
+
+/// Body of a Server-Sent Events response, read one event at a time.
+pub struct EventStream {
+    body: axum::body::BoxBody,
+    buffer: String,
+}
+
+impl EventStream {
+    /// Data of the next event, panicking if none comes within five seconds.
+    pub async fn next(&mut self) -> Value {
+        loop {
+            if let Some(end) = self.buffer.find("\n\n") {
+                let event = self.buffer[..end].to_string();
+                self.buffer.drain(..end + 2);
+
+                // Keep-alive comments have no data.
+                if let Some(data) = event.lines().find_map(|line| line.strip_prefix("data:")) {
+                    return serde_json::from_str(data).unwrap();
+                }
+                continue;
+            }
+
+            let chunk = tokio::time::timeout(Duration::from_secs(5), self.body.data())
+                .await
+                .expect("No event within five seconds")
+                .expect("Event stream ended")
+                .unwrap();
+            self.buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+    }
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl TestResponse {
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body)
+            .unwrap_or_else(|err| panic!("Response is not JSON ({err}): {}", self.text()))
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Version of the row from the `ETag` header.
+    pub fn version(&self) -> i32 {
+        self.headers[header::ETAG]
+            .to_str()
+            .unwrap()
+            .trim_matches('"')
+            .parse()
+            .unwrap()
+    }
+
+    #[track_caller]
+    pub fn assert_status(&self, status: StatusCode) -> &Self {
+        assert_eq!(self.status, status, "unexpected response: {}", self.text());
+        self
+    }
+}
+
+/// URI of the API route at `uri`, the helpers of [`TestApp`] take routes relative to `/api`.
+fn api(uri: &str) -> String {
+    format!("/api{uri}")
+}
+
+/// Whether the tests run over SQLite, selected with a `sqlite:` URL in `TEST_DATABASE_URL`.
+///
+/// SQLite databases are created in the temporary directory whatever the rest of the URL is.
+fn is_sqlite() -> bool {
+    dotenvy::var("TEST_DATABASE_URL").is_ok_and(|url| url.starts_with("sqlite:"))
+}
+
+/// Server the tests connect to, `TEST_DATABASE_URL` or else `DATABASE_URL`.
+fn admin_url() -> String {
+    dotenvy::var("TEST_DATABASE_URL")
+        .or_else(|_| dotenvy::var("DATABASE_URL"))
+        .expect("TEST_DATABASE_URL or DATABASE_URL should be set to run the integration tests")
+}
+
+fn database_url(database: &str) -> String {
+    let admin_url = admin_url();
+    let server = admin_url
+        .rsplit_once('/')
+        .map_or(admin_url.as_str(), |(server, _)| server);
+
+    format!("{server}/{database}")
+}
+
+/// Creates the migrated template database once per schema, test databases are cloned from it.
+///
+/// Test binaries run concurrently, so the template is created under an advisory lock.
+async fn template() -> &'static str {
+    TEMPLATE
+        .get_or_init(|| async {
+            let mut hasher = DefaultHasher::new();
+            for migration in MIGRATOR.iter() {
+                migration.version.hash(&mut hasher);
+                migration.checksum.hash(&mut hasher);
+            }
+            let template = format!("crud_test_template_{:016x}", hasher.finish());
+
+            let mut admin = PgConnection::connect(&admin_url()).await.unwrap();
+            admin
+                .execute("SELECT pg_advisory_lock(hashtext('crud_test_template'))")
+                .await
+                .unwrap();
+
+            let exists = sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS (SELECT 1 FROM pg_database WHERE datname = $1)",
+            )
+            .bind(&template)
+            .fetch_one(&mut admin)
+            .await
+            .unwrap();
+
+            if !exists {
+                // Built under another name, so a half migrated template is never cloned.
+                let building = format!("{template}_building");
+                admin
+                    .execute(format!(r#"DROP DATABASE IF EXISTS "{building}""#).as_str())
+                    .await
+                    .unwrap();
+                admin
+                    .execute(format!(r#"CREATE DATABASE "{building}""#).as_str())
+                    .await
+                    .unwrap();
+
+                let mut conn = PgConnection::connect(&database_url(&building))
+                    .await
+                    .unwrap();
+                MIGRATOR.run(&mut conn).await.unwrap();
+                conn.close().await.unwrap();
+
+                admin
+                    .execute(
+                        format!(r#"ALTER DATABASE "{building}" RENAME TO "{template}""#).as_str(),
+                    )
+                    .await
+                    .unwrap();
+            }
+
+            admin
+                .execute("SELECT pg_advisory_unlock(hashtext('crud_test_template'))")
+                .await
+                .unwrap();
+            admin.close().await.unwrap();
+
+            template
+        })
+        .await
+}
+
+impl TestApp {
+    pub async fn new() -> Self {
+        Self::with_config(crud::Config::default()).await
+    }
+
+    pub async fn with_config(config: crud::Config) -> Self {
+        if is_sqlite() {
+            Self::new_sqlite(config).await
+        } else {
+            Self::new_postgres(config).await
+        }
+    }
+
+    /// App of a test of a feature only served over Postgres, `None` when running over SQLite.
+    pub async fn postgres() -> Option<Self> {
+        if is_sqlite() {
+            eprintln!("Skipped, the test needs Postgres");
+            return None;
+        }
+
+        Some(Self::new_postgres(crud::Config::default()).await)
+    }
+
+    async fn new_postgres(config: crud::Config) -> Self {
+        let template = template().await;
+        let database = format!(
+            "crud_test_{}_{}",
+            std::process::id(),
+            DATABASES.fetch_add(1, Ordering::Relaxed)
+        );
+
+        let mut admin = PgConnection::connect(&admin_url()).await.unwrap();
+        admin
+            .execute(format!(r#"DROP DATABASE IF EXISTS "{database}""#).as_str())
+            .await
+            .unwrap();
+        admin
+            .execute(format!(r#"CREATE DATABASE "{database}" TEMPLATE "{template}""#).as_str())
+            .await
+            .unwrap();
+        admin.close().await.unwrap();
+
+        let db = PgPoolOptions::new()
+            .max_connections(5)
+            .connect(&database_url(&database))
+            .await
+            .unwrap();
+
+        Self {
+            router: crud::app(crud::Database::Postgres(db.clone()), config),
+            database: TestDatabase::Postgres { db, name: database },
+        }
+    }
+
+    async fn new_sqlite(config: crud::Config) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "crud_test_{}_{}.db",
+            std::process::id(),
+            DATABASES.fetch_add(1, Ordering::Relaxed)
+        ));
+        remove_sqlite_files(&path);
+
+        let db = crud::Database::connect(&format!("sqlite://{}", path.display()))
+            .await
+            .unwrap();
+
+        Self {
+            router: crud::app(db, config),
+            database: TestDatabase::Sqlite { path },
+        }
+    }
+
+    /// Pool of the Postgres database, for tests of [`TestApp::postgres`] changing rows directly.
+    pub fn pg(&self) -> &Pool<Postgres> {
+        match &self.database {
+            TestDatabase::Postgres { db, .. } => db,
+            TestDatabase::Sqlite { .. } => panic!("The test app does not run over Postgres"),
+        }
+    }
+
+    /// Database of the app, for tests of the commands of the binary.
+    pub async fn database(&self) -> crud::Database {
+        match &self.database {
+            TestDatabase::Postgres { db, .. } => crud::Database::Postgres(db.clone()),
+            TestDatabase::Sqlite { path } => {
+                crud::Database::connect(&format!("sqlite://{}", path.display()))
+                    .await
+                    .unwrap()
+            }
+        }
+    }
+
+    pub async fn request(
+        &self,
+        method: Method,
+        uri: &str,
+        headers: &[(header::HeaderName, &str)],
+        body: Option<Value>,
+    ) -> TestResponse {
+        let mut request = Request::builder().method(method).uri(api(uri));
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        self.send(request).await
+    }
+
+    /// Sends a request as if it came through a socket from `peer`, so it is rate limited.
+    pub async fn request_from(
+        &self,
+        peer: SocketAddr,
+        method: Method,
+        uri: &str,
+        headers: &[(header::HeaderName, &str)],
+        body: impl Into<Body>,
+    ) -> TestResponse {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(api(uri))
+            .extension(ConnectInfo(peer));
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+
+        self.send(request.body(body.into()).unwrap()).await
+    }
+
+    async fn send(&self, request: Request<Body>) -> TestResponse {
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+        TestResponse {
+            status,
+            headers,
+            body: body.to_vec(),
+        }
+    }
+
+    pub async fn get(&self, uri: &str) -> TestResponse {
+        self.request(Method::GET, uri, &[], None).await
+    }
+
+    pub async fn post(&self, uri: &str, body: Value) -> TestResponse {
+        self.request(Method::POST, uri, &[], Some(body)).await
+    }
+
+    pub async fn put(&self, uri: &str, version: i32, body: Value) -> TestResponse {
+        let if_match = format!("\"{version}\"");
+        self.request(
+            Method::PUT,
+            uri,
+            &[(header::IF_MATCH, &if_match)],
+            Some(body),
+        )
+        .await
+    }
+
+    pub async fn delete(&self, uri: &str, version: i32) -> TestResponse {
+        let if_match = format!("\"{version}\"");
+        self.request(Method::DELETE, uri, &[(header::IF_MATCH, &if_match)], None)
+            .await
+    }
+
+    /// Sends a request to `uri` as is, outside of the API.
+    pub async fn get_raw(&self, uri: &str) -> TestResponse {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+        self.send(request).await
+    }
+
+    /// Posts a body which is not JSON, like MARC records.
+    pub async fn post_bytes(&self, uri: &str, body: impl Into<Body>) -> TestResponse {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(api(uri))
+            .body(body.into())
+            .unwrap();
+
+        self.send(request).await
+    }
+
+    /// Opens the Server-Sent Events stream at `uri`, panicking unless the API answers `200 OK`.
+    pub async fn events(&self, uri: &str) -> EventStream {
+        let request = Request::builder()
+            .uri(api(uri))
+            .body(Body::empty())
+            .unwrap();
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        EventStream {
+            body: response.into_body(),
+            buffer: String::new(),
+        }
+    }
+
+    /// Executes a GraphQL request and returns its response, with `data` and `errors`.
+    pub async fn graphql(&self, query: &str, variables: Value) -> Value {
+        self.post(
+            "/graphql",
+            json!({ "query": query, "variables": variables }),
+        )
+        .await
+        .assert_status(StatusCode::OK)
+        .json()
+    }
+
+    /// Creates a row and returns it, panicking unless the API answers `201 Created`.
+    pub async fn create(&self, uri: &str, body: Value) -> Value {
+        self.post(uri, body)
+            .await
+            .assert_status(StatusCode::CREATED)
+            .json()
+    }
+
+    pub async fn country(&self, code: &str) -> Value {
+        self.create(
+            "/country",
+            json!({ "code": code, "name": format!("Country {code}") }),
+        )
+        .await
+    }
+
+    pub async fn faculty(&self) -> Value {
+        self.create(
+            "/faculty",
+            json!({ "id": 0, "name": "Physics", "letter": "P" }),
+        )
+        .await
+    }
+
+    pub async fn curriculum(&self) -> Value {
+        self.create(
+            "/curriculum",
+            json!({ "id": 0, "name": "Optics", "letter": "O" }),
+        )
+        .await
+    }
+
+    pub async fn faculty_curriculum(&self) -> Value {
+        let faculty = self.faculty().await;
+        let curriculum = self.curriculum().await;
+
+        self.create(
+            "/faculty-curriculum",
+            json!({ "id": 0, "faculty": faculty["id"], "curriculum": curriculum["id"] }),
+        )
+        .await
+    }
+
+    pub async fn student(&self) -> Value {
+        let faculty_curriculum = self.faculty_curriculum().await;
+
+        self.create(
+            "/student",
+            student_body(faculty_curriculum["id"].as_i64().unwrap()),
+        )
+        .await
+    }
+
+    pub async fn teacher(&self) -> Value {
+        let faculty = self.faculty().await;
+
+        self.create("/teacher", teacher_body(faculty["id"].as_i64().unwrap()))
+            .await
+    }
+
+    pub async fn librarian(&self) -> Value {
+        self.create(
+            "/librarian",
+            json!({ "id": 0, "name": "Olena", "lastname": "Koval", "surname": "", "age": 40 }),
+        )
+        .await
+    }
+
+    pub async fn category(&self) -> Value {
+        self.create("/category", json!({ "id": 0, "name": "Science" }))
+            .await
+    }
+
+    /// Publisher in a new country `code`.
+    pub async fn publisher(&self, code: &str) -> Value {
+        self.country(code).await;
+
+        self.create(
+            "/publisher",
+            json!({ "id": 0, "name": "Nauka", "country": code }),
+        )
+        .await
+    }
+
+    pub async fn author(&self, code: &str) -> Value {
+        self.create(
+            "/author",
+            json!({ "id": 0, "name": "Ivan", "lastname": "Franko", "surname": "", "country": code }),
+        )
+        .await
+    }
+
+    /// Book of a new publisher and category, `country` must not exist yet.
+    pub async fn book(&self, country: &str) -> Value {
+        let publisher = self.publisher(country).await;
+        let category = self.category().await;
+
+        self.create(
+            "/book",
+            book_body(
+                publisher["id"].as_i64().unwrap(),
+                category["id"].as_i64().unwrap(),
+            ),
+        )
+        .await
+    }
+
+    pub async fn student_card(&self, student: &Value) -> Value {
+        self.create(
+            "/student-card",
+            json!({
+                "id": 0,
+                "student": student["id"],
+                "issue_date": today(),
+                "expiry_date": days_from_today(365),
+                "state": "Active",
+            }),
+        )
+        .await
+    }
+
+    pub async fn teacher_card(&self, teacher: &Value) -> Value {
+        self.create(
+            "/teacher-card",
+            json!({
+                "id": 0,
+                "teacher": teacher["id"],
+                "issue_date": today(),
+                "expiry_date": days_from_today(365),
+                "state": "Active",
+            }),
+        )
+        .await
+    }
+
+    /// Borrowing of `book` by a new student, due `due_in_days` from today.
+    pub async fn students_borrowing(&self, book: &Value, due_in_days: i64) -> Value {
+        let student = self.student().await;
+        let card = self.student_card(&student).await;
+        let librarian = self.librarian().await;
+
+        self.create(
+            "/students-borrowing",
+            json!({
+                "id": 0,
+                "student_card": card["id"],
+                "librarian": librarian["id"],
+                "book": book["id"],
+                "book_status_start": "Good",
+                "book_status_finish": null,
+                "borrow_date": days_from_today(due_in_days.min(0) - 7),
+                "return_date": null,
+                "required_return_date": days_from_today(due_in_days),
+            }),
+        )
+        .await
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        let database = match &self.database {
+            TestDatabase::Postgres { name, .. } => name.clone(),
+            TestDatabase::Sqlite { path } => return remove_sqlite_files(path),
+        };
+
+        // Drop cannot await and the test runtime may be single threaded, so the database is
+        // dropped from a runtime of its own. `FORCE` closes the connections of the pool.
+        std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(async {
+                    if let Ok(mut admin) = PgConnection::connect(&admin_url()).await {
+                        let _ = admin
+                            .execute(
+                                format!(r#"DROP DATABASE IF EXISTS "{database}" WITH (FORCE)"#)
+                                    .as_str(),
+                            )
+                            .await;
+                    }
+                });
+        })
+        .join()
+        .unwrap();
+    }
+}
+
+/// Removes an SQLite database with its write-ahead log.
+fn remove_sqlite_files(path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let mut file = path.as_os_str().to_owned();
+        file.push(suffix);
+        let _ = std::fs::remove_file(file);
+    }
+}
+
+pub fn student_body(faculty_curriculum: i64) -> Value {
+    json!({
+        "id": 0,
+        "name": "Taras",
+        "lastname": "Shevchenko",
+        "surname": "Hryhorovych",
+        "age": 20,
+        "faculty_curriculum": faculty_curriculum,
+        "group": 1,
+        "start_study_date": "2024-09-01",
+        "status": null,
+        "email": "taras@example.com",
+    })
+}
+
+pub fn teacher_body(faculty: i64) -> Value {
+    json!({
+        "id": 0,
+        "name": "Lesya",
+        "lastname": "Ukrainka",
+        "surname": "",
+        "age": 45,
+        "faculty": faculty,
+        "status": null,
+        "email": "lesya@example.com",
+    })
+}
+
+pub fn book_body(publisher: i64, category: i64) -> Value {
+    json!({
+        "id": 0,
+        "title": "Kobzar",
+        "release": "1840-04-18",
+        "publisher": publisher,
+        "category": category,
+        "student_access": true,
+        "isbn": null,
+    })
+}
+
+pub fn today() -> String {
+    days_from_today(0)
+}
+
+pub fn days_from_today(days: i64) -> String {
+    (chrono::Local::now().date_naive() + chrono::Duration::days(days)).to_string()
+}
+
+/// Checks the read, update and delete routes of `uri` on a row created from `body`,
+/// including stale and missing `If-Match` headers and missing rows.
+///
+/// `key` is the primary key field and `field` a field changed to `value` by the update.
+pub async fn assert_crud(
+    app: &TestApp,
+    uri: &str,
+    key: &str,
+    mut body: Value,
+    field: &str,
+    value: Value,
+) {
+    let created = app.post(uri, body.clone()).await;
+    created.assert_status(StatusCode::CREATED);
+    let row = created.json();
+    let version = created.version();
+    assert_eq!(version, 1);
+
+    let id = match &row[key] {
+        Value::String(id) => id.clone(),
+        id => id.to_string(),
+    };
+    let item_uri = format!("{uri}/{id}");
+
+    let list = app.get(uri).await;
+    list.assert_status(StatusCode::OK);
+    assert!(list.json().as_array().unwrap().contains(&row));
+
+    let fetched = app.get(&item_uri).await;
+    fetched.assert_status(StatusCode::OK);
+    assert_eq!(fetched.json(), row);
+    assert_eq!(fetched.version(), version);
+
+    body[field] = value.clone();
+
+    app.request(Method::PUT, &item_uri, &[], Some(body.clone()))
+        .await
+        .assert_status(StatusCode::PRECONDITION_REQUIRED);
+    app.request(
+        Method::PUT,
+        &item_uri,
+        &[(header::IF_MATCH, "1")],
+        Some(body.clone()),
+    )
+    .await
+    .assert_status(StatusCode::BAD_REQUEST);
+
+    let updated = app.put(&item_uri, version, body.clone()).await;
+    updated.assert_status(StatusCode::OK);
+    assert_eq!(updated.json()[field], value);
+    assert_eq!(updated.version(), version + 1);
+
+    app.put(&item_uri, version, body.clone())
+        .await
+        .assert_status(StatusCode::PRECONDITION_FAILED);
+    app.delete(&item_uri, version)
+        .await
+        .assert_status(StatusCode::PRECONDITION_FAILED);
+
+    let deleted = app.delete(&item_uri, version + 1).await;
+    deleted.assert_status(StatusCode::OK);
+    assert_eq!(deleted.json()[field], value);
+
+    app.get(&item_uri)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.put(&item_uri, version + 1, body)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.delete(&item_uri, version + 1)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+/// Asserts the write was rejected by a foreign key constraint with `409 Conflict`.
+#[track_caller]
+pub fn assert_foreign_key_violation(response: &TestResponse) {
+    response.assert_status(StatusCode::CONFLICT);
+    assert!(
+        // Postgres and SQLite spell it differently.
+        response
+            .text()
+            .to_lowercase()
+            .contains("foreign key constraint"),
+        "unexpected response: {}",
+        response.text()
+    );
+}
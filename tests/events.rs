@@ -0,0 +1,106 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::TestApp;
+
+#[tokio::test]
+async fn events_stream_changes_of_subscribed_tables() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let mut events = app.events("/events?tables=category,book").await;
+
+    let category = app.category().await;
+    // Countries are not subscribed to, their changes are skipped.
+    app.country("UA").await;
+    app.put(
+        &format!("/category/{}", category["id"]),
+        1,
+        json!({ "id": category["id"], "name": "Prose" }),
+    )
+    .await
+    .assert_status(StatusCode::OK);
+
+    assert_eq!(
+        events.next().await,
+        json!({
+            "type": "change",
+            "table": "category",
+            "action": "insert",
+            "key": category["id"],
+            "version": 1,
+        })
+    );
+    assert_eq!(
+        events.next().await,
+        json!({
+            "type": "change",
+            "table": "category",
+            "action": "update",
+            "key": category["id"],
+            "version": 2,
+        })
+    );
+
+    // Writes made in SQL are streamed too.
+    sqlx::query("DELETE FROM category WHERE id = $1")
+        .bind(category["id"].as_i64().unwrap() as i32)
+        .execute(app.pg())
+        .await
+        .unwrap();
+    assert_eq!(
+        events.next().await,
+        json!({
+            "type": "change",
+            "table": "category",
+            "action": "delete",
+            "key": category["id"],
+            "version": 2,
+        })
+    );
+}
+
+#[tokio::test]
+async fn events_resync_after_lost_connection() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let mut events = app.events("/events?tables=category").await;
+
+    // Only the feed listens in the database of the test.
+    sqlx::query(
+        "SELECT pg_terminate_backend(pid) FROM pg_stat_activity
+        WHERE datname = current_database() AND query LIKE 'LISTEN%'",
+    )
+    .execute(app.pg())
+    .await
+    .unwrap();
+
+    assert_eq!(events.next().await, json!({ "type": "resync" }));
+
+    // The feed listens again once it told subscribers about the changes it missed.
+    let category = app.category().await;
+    assert_eq!(
+        events.next().await,
+        json!({
+            "type": "change",
+            "table": "category",
+            "action": "insert",
+            "key": category["id"],
+            "version": 1,
+        })
+    );
+}
+
+#[tokio::test]
+async fn events_reject_unknown_tables() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+
+    let response = app.get("/events?tables=category,warehouse").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(response.text(), "Unknown table warehouse");
+}
@@ -0,0 +1,371 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use serde_json::{json, Value};
+
+use common::{days_from_today, today, TestApp};
+
+fn charge(borrowing: &Value, kind: &str, amount: i32) -> Value {
+    json!({
+        "student_card": borrowing["student_card"],
+        "kind": kind,
+        "amount": amount,
+        "students_borrowing": borrowing["id"],
+    })
+}
+
+fn payment(borrowing: &Value, amount: i32) -> Value {
+    json!({
+        "student_card": borrowing["student_card"],
+        "kind": "Payment",
+        "amount": amount,
+        "librarian": borrowing["librarian"],
+    })
+}
+
+async fn set_max_balance(app: &TestApp, card_table: &str, max_balance: i32) -> Value {
+    app.create(
+        "/fine-policy",
+        json!({ "card_table": card_table, "max_balance": max_balance }),
+    )
+    .await
+}
+
+async fn balance(app: &TestApp, borrowing: &Value) -> Value {
+    app.get(&format!(
+        "/student-card/{}/balance",
+        borrowing["student_card"]
+    ))
+    .await
+    .assert_status(StatusCode::OK)
+    .json()
+}
+
+#[tokio::test]
+async fn ledger_keeps_balance_of_card() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let book = app.book("UA").await;
+    let borrowing = app.students_borrowing(&book, -2).await;
+
+    app.create("/ledger-entry", charge(&borrowing, "LateReturn", 250))
+        .await;
+    app.create("/ledger-entry", charge(&borrowing, "LostBook", 1500))
+        .await;
+    let paid = app.create("/ledger-entry", payment(&borrowing, 1000)).await;
+    assert_eq!(paid["kind"], "Payment");
+    assert_eq!(paid["amount"], 1000);
+
+    let mut waiver = payment(&borrowing, 250);
+    waiver["kind"] = json!("Waiver");
+    app.post("/ledger-entry", waiver.clone())
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    waiver["reason"] = json!("Returned during the flood");
+    app.create("/ledger-entry", waiver).await;
+
+    let balance = balance(&app, &borrowing).await;
+    assert_eq!(balance["balance"], 500);
+    assert_eq!(balance["max_balance"], Value::Null);
+    assert_eq!(balance["blocked"], false);
+
+    let response = app.post("/ledger-entry", payment(&borrowing, 501)).await;
+    response.assert_status(StatusCode::CONFLICT);
+    assert_eq!(
+        response.text(),
+        format!(
+            "Payment of 5.01 is more than the balance of 5.00 of the student of Student card {}",
+            borrowing["student_card"]
+        )
+    );
+
+    let entries = app
+        .get(&format!(
+            "/ledger-entry?student_card={}",
+            borrowing["student_card"]
+        ))
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(entries.as_array().unwrap().len(), 4);
+    assert_eq!(entries[2], paid);
+
+    let receipt = app
+        .get(&format!("/ledger-entry/{}/receipt", paid["id"]))
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(
+        receipt["number"],
+        format!("R-{:06}", paid["id"].as_i64().unwrap())
+    );
+    assert_eq!(receipt["patron"], "Taras Shevchenko");
+    assert_eq!(receipt["entry"], paid);
+    assert_eq!(receipt["balance"], 750);
+
+    app.get(&format!("/ledger-entry/{}/receipt", entries[0]["id"]))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn checkout_is_refused_above_max_balance() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let book = app.book("UA").await;
+    let borrowing = app.students_borrowing(&book, -2).await;
+    let other = app.book("PL").await;
+    let checkout = json!({
+        "id": 0,
+        "student_card": borrowing["student_card"],
+        "librarian": borrowing["librarian"],
+        "book": other["id"],
+        "book_status_start": "Good",
+        "book_status_finish": null,
+        "borrow_date": today(),
+        "return_date": null,
+        "required_return_date": days_from_today(14),
+    });
+
+    let policy = set_max_balance(&app, "student_card", 1000).await;
+    assert_eq!(
+        policy,
+        json!({ "card_table": "student_card", "max_balance": 1000, "version": 1 })
+    );
+    app.create("/ledger-entry", charge(&borrowing, "DamagedBook", 1250))
+        .await;
+    assert_eq!(balance(&app, &borrowing).await["blocked"], true);
+
+    let response = app.post("/students-borrowing", checkout.clone()).await;
+    response.assert_status(StatusCode::CONFLICT);
+    assert_eq!(
+        response.text(),
+        format!(
+            "The student of Student card {} owes 12.50, more than the limit of 10.00",
+            borrowing["student_card"]
+        )
+    );
+
+    app.create("/ledger-entry", payment(&borrowing, 250)).await;
+    app.post("/students-borrowing", checkout)
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    app.request(Method::DELETE, "/fine-policy/student_card", &[], None)
+        .await
+        .assert_status(StatusCode::PRECONDITION_REQUIRED);
+    app.delete("/fine-policy/student_card", 1)
+        .await
+        .assert_status(StatusCode::OK);
+    let policies = app
+        .get("/fine-policy")
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(policies, json!([]));
+}
+
+#[tokio::test]
+async fn balance_is_kept_across_cards_of_patron() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let book = app.book("UA").await;
+    let borrowing = app.students_borrowing(&book, -2).await;
+    set_max_balance(&app, "student_card", 1000).await;
+    let charged = app
+        .create("/ledger-entry", charge(&borrowing, "LostBook", 1250))
+        .await;
+
+    let card = app
+        .post(
+            &format!("/student-card/{}/reissue", borrowing["student_card"]),
+            json!({ "state": "Lost" }),
+        )
+        .await
+        .assert_status(StatusCode::CREATED)
+        .json();
+    let balance = app
+        .get(&format!("/student-card/{}/balance", card["id"]))
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(balance["patron"], card["student"]);
+    assert_eq!(balance["balance"], 1250);
+    assert_eq!(balance["blocked"], true);
+
+    let checkout = json!({
+        "id": 0,
+        "student_card": card["id"],
+        "librarian": borrowing["librarian"],
+        "book": app.book("PL").await["id"],
+        "book_status_start": "Good",
+        "book_status_finish": null,
+        "borrow_date": today(),
+        "return_date": null,
+        "required_return_date": days_from_today(14),
+    });
+    let response = app.post("/students-borrowing", checkout.clone()).await;
+    response.assert_status(StatusCode::CONFLICT);
+    assert_eq!(
+        response.text(),
+        format!(
+            "The student of Student card {} owes 12.50, more than the limit of 10.00",
+            card["id"]
+        )
+    );
+
+    let mut paid = payment(&borrowing, 250);
+    paid["student_card"] = card["id"].clone();
+    let paid = app.create("/ledger-entry", paid).await;
+    app.post("/students-borrowing", checkout)
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let entries = app
+        .get(&format!("/ledger-entry?student={}", card["student"]))
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(entries, json!([charged, paid]));
+    let receipt = app
+        .get(&format!("/ledger-entry/{}/receipt", paid["id"]))
+        .await
+        .json();
+    assert_eq!(receipt["balance"], 1000);
+}
+
+#[tokio::test]
+async fn ledger_entry_errors() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let book = app.book("UA").await;
+    let borrowing = app.students_borrowing(&book, -2).await;
+    let other = app.students_borrowing(&app.book("PL").await, 7).await;
+
+    let mut both_cards = charge(&borrowing, "LateReturn", 250);
+    both_cards["teacher_card"] = json!(1);
+    app.post("/ledger-entry", both_cards)
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    let mut unlinked = charge(&borrowing, "LateReturn", 250);
+    unlinked["students_borrowing"] = Value::Null;
+    app.post("/ledger-entry", unlinked)
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    app.post("/ledger-entry", charge(&borrowing, "LateReturn", 0))
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    let mut other_loan = charge(&borrowing, "LateReturn", 250);
+    other_loan["students_borrowing"] = other["id"].clone();
+    let response = app.post("/ledger-entry", other_loan).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        response.text(),
+        format!(
+            "Students borrowing {} is not a loan on Student card {}",
+            other["id"], borrowing["student_card"]
+        )
+    );
+
+    let mut missing_card = payment(&borrowing, 100);
+    missing_card["student_card"] = json!(999);
+    let response = app.post("/ledger-entry", missing_card).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.text(), "Student card 999 does not exist");
+
+    app.get("/student-card/999/balance")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.get("/ledger-entry/999/receipt")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.put("/fine-policy/book", 1, json!({ "max_balance": 100 }))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.post(
+        "/fine-policy",
+        json!({ "card_table": "book", "max_balance": 100 }),
+    )
+    .await
+    .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    app.post(
+        "/fine-policy",
+        json!({ "card_table": "teacher_card", "max_balance": -1 }),
+    )
+    .await
+    .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let response = app
+        .put(
+            "/fine-policy/teacher_card",
+            1,
+            json!({ "max_balance": 100 }),
+        )
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+    assert_eq!(response.text(), "Teacher card has no fine policy");
+}
+
+#[tokio::test]
+async fn fine_policy_writes_check_version() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    set_max_balance(&app, "teacher_card", 1000).await;
+
+    let response = app
+        .post(
+            "/fine-policy",
+            json!({ "card_table": "teacher_card", "max_balance": 500 }),
+        )
+        .await;
+    response.assert_status(StatusCode::CONFLICT);
+    assert_eq!(response.text(), "Teacher card already has a fine policy");
+
+    app.request(
+        Method::PUT,
+        "/fine-policy/teacher_card",
+        &[],
+        Some(json!({ "max_balance": 500 })),
+    )
+    .await
+    .assert_status(StatusCode::PRECONDITION_REQUIRED);
+    let updated = app
+        .put(
+            "/fine-policy/teacher_card",
+            1,
+            json!({ "max_balance": 500 }),
+        )
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(
+        updated,
+        json!({ "card_table": "teacher_card", "max_balance": 500, "version": 2 })
+    );
+
+    let stale = app
+        .put(
+            "/fine-policy/teacher_card",
+            1,
+            json!({ "max_balance": 2000 }),
+        )
+        .await;
+    stale.assert_status(StatusCode::PRECONDITION_FAILED);
+    assert_eq!(
+        stale.text(),
+        "Fine policy of teacher_card was modified, reload it and try again"
+    );
+    app.delete("/fine-policy/teacher_card", 1)
+        .await
+        .assert_status(StatusCode::PRECONDITION_FAILED);
+    app.delete("/fine-policy/teacher_card", 2)
+        .await
+        .assert_status(StatusCode::OK);
+}
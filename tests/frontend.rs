@@ -0,0 +1,86 @@
+mod common;
+
+use std::path::PathBuf;
+
+use axum::http::{header, Method, StatusCode};
+use serde_json::json;
+
+use common::TestApp;
+
+const INDEX: &str = "<!doctype html><div id=\"root\"></div>";
+const SCRIPT: &str = "console.log('library')";
+
+/// Built frontend in the temporary directory, named after the test using it.
+fn frontend(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("crud_frontend_{}_{test}", std::process::id()));
+    std::fs::create_dir_all(dir.join("assets")).unwrap();
+    std::fs::write(dir.join("index.html"), INDEX).unwrap();
+    std::fs::write(dir.join("assets/index-4f2a9c1e.js"), SCRIPT).unwrap();
+
+    dir
+}
+
+#[tokio::test]
+async fn frontend_is_served_next_to_the_api() {
+    let dir = frontend("served");
+    let app = TestApp::with_config(crud::Config {
+        frontend_dir: Some(dir.clone()),
+        ..crud::Config::default()
+    })
+    .await;
+
+    for uri in ["/", "/books/42"] {
+        let page = app.get_raw(uri).await;
+        page.assert_status(StatusCode::OK);
+        assert_eq!(page.text(), INDEX);
+        assert_eq!(
+            page.headers[header::CONTENT_TYPE],
+            "text/html; charset=utf-8"
+        );
+        assert_eq!(page.headers[header::CACHE_CONTROL], "no-cache");
+    }
+
+    let script = app.get_raw("/assets/index-4f2a9c1e.js").await;
+    script.assert_status(StatusCode::OK);
+    assert_eq!(script.text(), SCRIPT);
+    assert_eq!(
+        script.headers[header::CONTENT_TYPE],
+        "text/javascript; charset=utf-8"
+    );
+    assert_eq!(
+        script.headers[header::CACHE_CONTROL],
+        "public, max-age=31536000, immutable"
+    );
+
+    // Missing files and unknown API routes are not pages of the app.
+    for uri in ["/assets/index-00000000.js", "/api/warehouse", "/../Cargo.toml"] {
+        app.get_raw(uri).await.assert_status(StatusCode::NOT_FOUND);
+    }
+
+    app.get_raw("/api/category")
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(app.get("/category").await.json(), json!([]));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn cors_can_be_disabled() {
+    let origin = [(header::ORIGIN, "https://library.example")];
+
+    let app = TestApp::new().await;
+    let response = app.request(Method::GET, "/category", &origin, None).await;
+    assert_eq!(response.headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+
+    let app = TestApp::with_config(crud::Config {
+        cors: None,
+        ..crud::Config::default()
+    })
+    .await;
+    let response = app.request(Method::GET, "/category", &origin, None).await;
+    response.assert_status(StatusCode::OK);
+    assert!(!response
+        .headers
+        .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+}
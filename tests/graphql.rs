@@ -0,0 +1,276 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{days_from_today, today, TestApp};
+
+/// Status in the `status` extension of the first error of a GraphQL response.
+fn error_status(response: &Value) -> &Value {
+    &response["errors"][0]["extensions"]["status"]
+}
+
+#[tokio::test]
+async fn graphql_follows_relations() {
+    let app = TestApp::new().await;
+    let book = app.book("UA").await;
+    let author = app.author("UA").await;
+    app.create(
+        "/author-book",
+        json!({ "id": 0, "author_id": author["id"], "book_id": book["id"], "num": 1 }),
+    )
+    .await;
+    let borrowing = app.students_borrowing(&book, 14).await;
+
+    let response = app
+        .graphql(
+            r#"{
+                studentsBorrowings {
+                    id
+                    studentCard { state student { lastname facultyCurriculum { faculty { name } } } }
+                    book { title publisher { country { code } } authors { lastname } }
+                }
+            }"#,
+            json!({}),
+        )
+        .await;
+
+    assert_eq!(response["errors"], Value::Null);
+    assert_eq!(
+        response["data"]["studentsBorrowings"],
+        json!([{
+            "id": borrowing["id"],
+            "studentCard": {
+                "state": "ACTIVE",
+                "student": {
+                    "lastname": "Shevchenko",
+                    "facultyCurriculum": { "faculty": { "name": "Physics" } },
+                },
+            },
+            "book": {
+                "title": "Kobzar",
+                "publisher": { "country": { "code": "UA" } },
+                "authors": [{ "lastname": "Franko" }],
+            },
+        }])
+    );
+}
+
+#[tokio::test]
+async fn graphql_filters_and_pages_lists() {
+    let app = TestApp::new().await;
+    app.book("UA").await;
+    let second = app.book("PL").await;
+    app.book("DE").await;
+
+    let response = app
+        .graphql(
+            r#"query($category: Int!) {
+                byCategory: books(filter: { categoryId: $category }) { id }
+                page: books(page: { offset: 1, limit: 1 }) { id }
+                polish: publishers(filter: { countryCode: "PL" }) { id }
+            }"#,
+            json!({ "category": second["category"] }),
+        )
+        .await;
+
+    assert_eq!(response["errors"], Value::Null);
+    assert_eq!(
+        response["data"]["byCategory"],
+        json!([{ "id": second["id"] }])
+    );
+    assert_eq!(response["data"]["page"], json!([{ "id": second["id"] }]));
+    assert_eq!(
+        response["data"]["polish"],
+        json!([{ "id": second["publisher"] }])
+    );
+
+    let response = app
+        .graphql("{ books(page: { limit: 5000 }) { id } }", json!({}))
+        .await;
+    assert_ne!(response["errors"], Value::Null);
+}
+
+#[tokio::test]
+async fn graphql_mutations_share_rest_rules() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .graphql(
+            r#"mutation { createCategory(input: { name: "" }) { id } }"#,
+            json!({}),
+        )
+        .await;
+    assert_eq!(error_status(&response), 422);
+    assert_ne!(
+        response["errors"][0]["extensions"]["fields"]["name"],
+        Value::Null
+    );
+
+    // The REST list is cached, GraphQL writes invalidate it.
+    app.get("/category").await.assert_status(StatusCode::OK);
+    let response = app
+        .graphql(
+            r#"mutation { createCategory(input: { name: "Poetry" }) { id version } }"#,
+            json!({}),
+        )
+        .await;
+    let category = &response["data"]["createCategory"];
+    assert_eq!(category["version"], 1);
+    assert_eq!(
+        app.get("/category").await.json(),
+        json!([{ "id": category["id"], "name": "Poetry", "version": 1 }])
+    );
+
+    let response = app
+        .graphql(
+            r#"mutation($id: Int!) { updateCategory(id: $id, version: 7, input: { name: "Prose" }) { id } }"#,
+            json!({ "id": category["id"] }),
+        )
+        .await;
+    assert_eq!(
+        error_status(&response),
+        StatusCode::PRECONDITION_FAILED.as_u16()
+    );
+
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+    let response = app
+        .graphql(
+            r#"mutation($id: Int!, $input: StudentCardInput!) {
+                updateStudentCard(id: $id, version: 1, input: $input) { state version }
+            }"#,
+            json!({
+                "id": card["id"],
+                "input": {
+                    "studentId": student["id"],
+                    "issueDate": today(),
+                    "expiryDate": days_from_today(365),
+                    "state": "LOST",
+                },
+            }),
+        )
+        .await;
+    assert_eq!(
+        response["data"]["updateStudentCard"],
+        json!({ "state": "LOST", "version": 2 })
+    );
+
+    let response = app
+        .graphql(
+            r#"{
+                lost: studentCards(filter: { state: LOST }) { id }
+                active: studentCards(filter: { state: ACTIVE }) { id }
+            }"#,
+            json!({}),
+        )
+        .await;
+    assert_eq!(response["data"]["lost"], json!([{ "id": card["id"] }]));
+    assert_eq!(response["data"]["active"], json!([]));
+
+    // Books are only checked out on active cards, like over REST.
+    let book = app.book("UA").await;
+    let librarian = app.librarian().await;
+    let response = app
+        .graphql(
+            r#"mutation($input: StudentsBorrowingInput!) {
+                createStudentsBorrowing(input: $input) { id }
+            }"#,
+            json!({
+                "input": {
+                    "studentCardId": card["id"],
+                    "librarianId": librarian["id"],
+                    "bookId": book["id"],
+                    "bookStatusStart": "GOOD",
+                    "borrowDate": today(),
+                    "requiredReturnDate": days_from_today(14),
+                },
+            }),
+        )
+        .await;
+    assert_eq!(error_status(&response), StatusCode::CONFLICT.as_u16());
+}
+
+#[tokio::test]
+async fn graphql_serves_renewals_over_postgres() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let book = app.book("UA").await;
+    let borrowing = app.students_borrowing(&book, 3).await;
+    let librarian = app.librarian().await;
+    app.post(
+        &format!("/borrowing/{}/renew", borrowing["id"]),
+        json!({ "librarian": librarian["id"] }),
+    )
+    .await
+    .assert_status(StatusCode::CREATED);
+
+    let response = app
+        .graphql(
+            r#"query($borrowing: Int!) {
+                studentsBorrowings { renewals { requiredReturnDate librarian { lastname } } }
+                renewals(filter: { studentsBorrowingId: $borrowing }) { id }
+            }"#,
+            json!({ "borrowing": borrowing["id"] }),
+        )
+        .await;
+
+    assert_eq!(response["errors"], Value::Null);
+    assert_eq!(
+        response["data"]["studentsBorrowings"],
+        json!([{
+            "renewals": [{
+                "requiredReturnDate": days_from_today(17),
+                "librarian": { "lastname": "Koval" },
+            }],
+        }])
+    );
+    assert_eq!(response["data"]["renewals"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn graphql_serves_ledger_over_postgres() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let book = app.book("UA").await;
+    let borrowing = app.students_borrowing(&book, -2).await;
+    app.create(
+        "/ledger-entry",
+        json!({
+            "student_card": borrowing["student_card"],
+            "kind": "LateReturn",
+            "amount": 250,
+            "students_borrowing": borrowing["id"],
+        }),
+    )
+    .await;
+    let student = app
+        .get(&format!("/student-card/{}", borrowing["student_card"]))
+        .await
+        .json()["student"]
+        .clone();
+
+    let response = app
+        .graphql(
+            r#"query($student: Int!) {
+                ledgerEntries(filter: { studentId: $student }) {
+                    kind amount studentsBorrowing { id } studentCard { balance { balance blocked } }
+                }
+            }"#,
+            json!({ "student": student }),
+        )
+        .await;
+
+    assert_eq!(response["errors"], Value::Null);
+    assert_eq!(
+        response["data"]["ledgerEntries"],
+        json!([{
+            "kind": "LATE_RETURN",
+            "amount": 250,
+            "studentsBorrowing": { "id": borrowing["id"] },
+            "studentCard": { "balance": { "balance": 250, "blocked": false } },
+        }])
+    );
+}
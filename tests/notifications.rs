@@ -0,0 +1,115 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use lettre::message::Mailbox;
+use lettre::{AsyncSmtpTransport, Tokio1Executor};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use common::TestApp;
+
+/// SMTP server keeping the messages it accepts, recipients starting with `reject@` are refused.
+#[derive(Clone, Default)]
+struct Sink {
+    messages: Arc<Mutex<Vec<String>>>,
+}
+
+impl Sink {
+    /// Serves the sink on a free local port, returns the port.
+    async fn start(&self) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let sink = self.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(sink.clone().session(stream));
+            }
+        });
+
+        port
+    }
+
+    async fn session(self, stream: TcpStream) {
+        let (read, mut write) = stream.into_split();
+        let mut lines = BufReader::new(read).lines();
+        write.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            let command = line.to_ascii_uppercase();
+            let reply: &[u8] = if command.starts_with("RCPT") && command.contains("<REJECT@") {
+                b"550 No such user\r\n"
+            } else if command.starts_with("DATA") {
+                write.write_all(b"354 End data with .\r\n").await.unwrap();
+
+                let mut message = String::new();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    if line == "." {
+                        break;
+                    }
+                    message.push_str(&line);
+                    message.push('\n');
+                }
+                self.messages.lock().unwrap().push(message);
+
+                b"250 Queued\r\n"
+            } else if command.starts_with("QUIT") {
+                let _ = write.write_all(b"221 Bye\r\n").await;
+                return;
+            } else {
+                b"250 OK\r\n"
+            };
+
+            write.write_all(reply).await.unwrap();
+        }
+    }
+
+    fn messages(&self) -> Vec<String> {
+        self.messages.lock().unwrap().clone()
+    }
+}
+
+#[tokio::test]
+async fn notifications_are_delivered_once() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let sink = Sink::default();
+    let mailer = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous("127.0.0.1")
+        .port(sink.start().await)
+        .build();
+    let from: Mailbox = "Library <library@example.com>".parse().unwrap();
+
+    sqlx::query(
+        "INSERT INTO notification (kind, recipient, subject, body) VALUES
+        ('overdue', 'taras@example.com', 'Overdue book', 'Please return Kobzar'),
+        ('overdue', 'reject@example.com', 'Overdue book', 'Please return Kobzar')",
+    )
+    .execute(app.pg())
+    .await
+    .unwrap();
+
+    let claimed = crud::notification::worker::deliver_pending(app.pg(), &mailer, &from)
+        .await
+        .unwrap();
+    assert_eq!(claimed, 2);
+
+    let messages = sink.messages();
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].contains("To: taras@example.com"));
+    assert!(messages[0].contains("Subject: Overdue book"));
+
+    let notifications = app.get("/notification").await.json();
+    assert_eq!(notifications[0]["status"], "Sent");
+    assert_eq!(notifications[1]["status"], "Pending");
+    assert_eq!(notifications[1]["attempts"], 1);
+    assert!(notifications[1]["last_error"].is_string());
+
+    // The sent notification is done and the refused one waits for its retry.
+    let claimed = crud::notification::worker::deliver_pending(app.pg(), &mailer, &from)
+        .await
+        .unwrap();
+    assert_eq!(claimed, 0);
+    assert_eq!(sink.messages().len(), 1);
+}
@@ -0,0 +1,302 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{
+    assert_crud, assert_foreign_key_violation, days_from_today, student_body, teacher_body, today,
+    TestApp,
+};
+
+#[tokio::test]
+async fn student_crud() {
+    let app = TestApp::new().await;
+    let faculty_curriculum = app.faculty_curriculum().await;
+
+    assert_crud(
+        &app,
+        "/student",
+        "id",
+        student_body(faculty_curriculum["id"].as_i64().unwrap()),
+        "status",
+        json!("Graduated"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn student_rejects_invalid_fields() {
+    let app = TestApp::new().await;
+    let faculty_curriculum = app.faculty_curriculum().await;
+
+    let mut body = student_body(faculty_curriculum["id"].as_i64().unwrap());
+    body["age"] = json!(7);
+    body["email"] = json!("not an email");
+
+    let response = app.post("/student", body).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    let errors = response.json();
+    assert!(errors.get("age").is_some());
+    assert!(errors.get("email").is_some());
+}
+
+#[tokio::test]
+async fn student_rejects_missing_faculty_curriculum() {
+    let app = TestApp::new().await;
+
+    assert_foreign_key_violation(&app.post("/student", student_body(999)).await);
+}
+
+#[tokio::test]
+async fn teacher_crud() {
+    let app = TestApp::new().await;
+    let faculty = app.faculty().await;
+
+    assert_crud(
+        &app,
+        "/teacher",
+        "id",
+        teacher_body(faculty["id"].as_i64().unwrap()),
+        "email",
+        json!("ukrainka@example.com"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn teacher_rejects_missing_faculty() {
+    let app = TestApp::new().await;
+
+    assert_foreign_key_violation(&app.post("/teacher", teacher_body(999)).await);
+}
+
+#[tokio::test]
+async fn librarian_crud() {
+    let app = TestApp::new().await;
+
+    assert_crud(
+        &app,
+        "/librarian",
+        "id",
+        json!({ "id": 0, "name": "Olena", "lastname": "Koval", "surname": "", "age": 40 }),
+        "age",
+        json!(41),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn student_card_crud() {
+    let app = TestApp::new().await;
+    let student = app.student().await;
+
+    assert_crud(
+        &app,
+        "/student-card",
+        "id",
+        json!({
+            "id": 0,
+            "student": student["id"],
+            "issue_date": today(),
+            "expiry_date": days_from_today(365),
+            "state": "Active",
+        }),
+        "state",
+        json!("Lost"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn student_card_rejects_expiry_before_issue() {
+    let app = TestApp::new().await;
+    let student = app.student().await;
+
+    let response = app
+        .post(
+            "/student-card",
+            json!({
+                "id": 0,
+                "student": student["id"],
+                "issue_date": today(),
+                "expiry_date": days_from_today(-1),
+                "state": "Active",
+            }),
+        )
+        .await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    assert!(response.json().get("__all__").is_some());
+}
+
+#[tokio::test]
+async fn student_card_rejects_missing_student() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post(
+            "/student-card",
+            json!({
+                "id": 0,
+                "student": 999,
+                "issue_date": today(),
+                "expiry_date": days_from_today(365),
+                "state": "Active",
+            }),
+        )
+        .await;
+
+    assert_foreign_key_violation(&response);
+}
+
+#[tokio::test]
+async fn student_card_reissue_blocks_previous_card() {
+    let app = TestApp::new().await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+
+    let reissued = app
+        .post(
+            &format!("/student-card/{}/reissue", card["id"]),
+            json!({ "state": "Lost" }),
+        )
+        .await;
+    reissued.assert_status(StatusCode::CREATED);
+
+    let new_card = reissued.json();
+    assert_eq!(new_card["student"], student["id"]);
+    assert_eq!(new_card["state"], "Active");
+    assert_eq!(new_card["issue_date"], today());
+
+    let previous_card = app
+        .get(&format!("/student-card/{}", card["id"]))
+        .await
+        .json();
+    assert_eq!(previous_card["state"], "Lost");
+}
+
+#[tokio::test]
+async fn student_card_reissue_errors() {
+    let app = TestApp::new().await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+
+    app.post(
+        &format!("/student-card/{}/reissue", card["id"]),
+        json!({ "state": "Active" }),
+    )
+    .await
+    .assert_status(StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post("/student-card/999/reissue", json!({ "state": "Lost" }))
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+    assert_eq!(response.text(), "Student card 999 does not exist");
+
+    app.post(
+        &format!("/student-card/{}/reissue", card["id"]),
+        json!({ "state": "Lost" }),
+    )
+    .await
+    .assert_status(StatusCode::CREATED);
+    let response = app
+        .post(
+            &format!("/student-card/{}/reissue", card["id"]),
+            json!({ "state": "Lost" }),
+        )
+        .await;
+    response.assert_status(StatusCode::CONFLICT);
+    assert_eq!(
+        response.text(),
+        format!(
+            "Student card {} is lost or blocked, it cannot be reissued",
+            card["id"]
+        )
+    );
+}
+
+#[tokio::test]
+async fn teacher_card_crud() {
+    let app = TestApp::new().await;
+    let teacher = app.teacher().await;
+
+    assert_crud(
+        &app,
+        "/teacher-card",
+        "id",
+        json!({
+            "id": 0,
+            "teacher": teacher["id"],
+            "issue_date": today(),
+            "expiry_date": days_from_today(365),
+            "state": "Active",
+        }),
+        "state",
+        json!("Blocked"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn teacher_card_rejects_missing_teacher() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post(
+            "/teacher-card",
+            json!({
+                "id": 0,
+                "teacher": 999,
+                "issue_date": today(),
+                "expiry_date": days_from_today(365),
+                "state": "Active",
+            }),
+        )
+        .await;
+
+    assert_foreign_key_violation(&response);
+}
+
+#[tokio::test]
+async fn teacher_card_reissue_blocks_previous_card() {
+    let app = TestApp::new().await;
+    let teacher = app.teacher().await;
+    let card = app.teacher_card(&teacher).await;
+
+    let reissued = app
+        .post(
+            &format!("/teacher-card/{}/reissue", card["id"]),
+            json!({ "state": "Blocked" }),
+        )
+        .await;
+    reissued.assert_status(StatusCode::CREATED);
+    assert_eq!(reissued.json()["teacher"], teacher["id"]);
+
+    let previous_card = app
+        .get(&format!("/teacher-card/{}", card["id"]))
+        .await
+        .json();
+    assert_eq!(previous_card["state"], "Blocked");
+
+    let response = app
+        .post(
+            &format!("/teacher-card/{}/reissue", card["id"]),
+            json!({ "state": "Lost" }),
+        )
+        .await;
+    response.assert_status(StatusCode::CONFLICT);
+    assert_eq!(
+        response.text(),
+        format!(
+            "Teacher card {} is lost or blocked, it cannot be reissued",
+            card["id"]
+        )
+    );
+    let response = app
+        .post("/teacher-card/999/reissue", json!({ "state": "Lost" }))
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+    assert_eq!(response.text(), "Teacher card 999 does not exist");
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use std::net::{IpAddr, SocketAddr};
+
+use axum::http::{header, Method, StatusCode};
+use serde_json::json;
+
+use common::{TestApp, TestResponse};
+
+const CLIENT: &str = "203.0.113.7";
+const OTHER_CLIENT: &str = "203.0.113.8";
+const PROXY: &str = "10.0.0.1";
+
+fn peer(ip: &str) -> SocketAddr {
+    SocketAddr::new(ip.parse().unwrap(), 50_000)
+}
+
+fn assert_too_many_requests(response: &TestResponse) {
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = response.headers[header::RETRY_AFTER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(retry_after >= 1);
+    assert_eq!(
+        response.text(),
+        format!("Too many requests, retry after {retry_after}s")
+    );
+}
+
+async fn import(app: &TestApp, from: &str, forwarded_for: Option<&str>) -> TestResponse {
+    let headers = forwarded_for
+        .map(|hops| vec![(header::HeaderName::from_static("x-forwarded-for"), hops)])
+        .unwrap_or_default();
+
+    app.request_from(peer(from), Method::POST, "/marc/import", &headers, "")
+        .await
+}
+
+#[tokio::test]
+async fn clients_over_quota_are_told_to_retry() {
+    let app = TestApp::new().await;
+
+    // The bucket refills while the requests are sent, so send until it runs out.
+    let mut sent = 0;
+    let response = loop {
+        let response = app
+            .request_from(peer(CLIENT), Method::GET, "/country", &[], "")
+            .await;
+        sent += 1;
+        if response.status != StatusCode::OK || sent > 1000 {
+            break response;
+        }
+    };
+    assert!(sent > 100);
+    assert_too_many_requests(&response);
+
+    // The header is ignored without a trusted proxy, so it cannot be used to get a new bucket.
+    let forged = [(
+        header::HeaderName::from_static("x-forwarded-for"),
+        OTHER_CLIENT,
+    )];
+    let response = app
+        .request_from(peer(CLIENT), Method::GET, "/country", &forged, "")
+        .await;
+    assert_too_many_requests(&response);
+
+    app.request_from(peer(OTHER_CLIENT), Method::GET, "/country", &[], "")
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn marc_import_has_strict_quota() {
+    let app = TestApp::new().await;
+
+    for _ in 0..5 {
+        let response = import(&app, CLIENT, None).await;
+        assert_ne!(response.status, StatusCode::TOO_MANY_REQUESTS);
+    }
+    assert_too_many_requests(&import(&app, CLIENT, None).await);
+
+    // Other endpoints keep their own quota.
+    app.request_from(peer(CLIENT), Method::GET, "/country", &[], "")
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn clients_behind_trusted_proxy_are_told_apart() {
+    let mut config = crud::Config::default();
+    config.rate_limit.trusted_proxies = vec![PROXY.parse::<IpAddr>().unwrap()];
+    let app = TestApp::with_config(config).await;
+
+    for _ in 0..5 {
+        let response = import(&app, PROXY, Some(CLIENT)).await;
+        assert_ne!(response.status, StatusCode::TOO_MANY_REQUESTS);
+    }
+    assert_too_many_requests(&import(&app, PROXY, Some(CLIENT)).await);
+
+    // Hops left of the one the proxy added come from the client.
+    let forged = format!("{OTHER_CLIENT}, {CLIENT}");
+    assert_too_many_requests(&import(&app, PROXY, Some(&forged)).await);
+
+    let response = import(&app, PROXY, Some(OTHER_CLIENT)).await;
+    assert_ne!(response.status, StatusCode::TOO_MANY_REQUESTS);
+
+    // Peers which are not proxies are limited by their own address whatever the header says.
+    let response = import(&app, OTHER_CLIENT, Some(CLIENT)).await;
+    assert_ne!(response.status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn new_clients_are_refused_when_every_bucket_is_taken() {
+    let mut config = crud::Config::default();
+    config.rate_limit.max_buckets = 2;
+    let app = TestApp::with_config(config).await;
+    let third_client = "203.0.113.9";
+
+    for client in [CLIENT, OTHER_CLIENT] {
+        app.request_from(peer(client), Method::GET, "/country", &[], "")
+            .await
+            .assert_status(StatusCode::OK);
+    }
+    assert_too_many_requests(
+        &app.request_from(peer(third_client), Method::GET, "/country", &[], "")
+            .await,
+    );
+
+    // Clients already tracked keep their quota.
+    app.request_from(peer(CLIENT), Method::GET, "/country", &[], "")
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn large_bodies_are_rejected() {
+    let config = crud::Config {
+        max_body_size: 64,
+        ..crud::Config::default()
+    };
+    let app = TestApp::with_config(config).await;
+
+    app.post("/country", json!({ "code": "WL", "name": "x".repeat(100) }))
+        .await
+        .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    app.post("/country", json!({ "code": "WL", "name": "Wales" }))
+        .await
+        .assert_status(StatusCode::CREATED);
+}
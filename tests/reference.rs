@@ -0,0 +1,252 @@
+mod common;
+
+use axum::http::{header, Method, StatusCode};
+use serde_json::json;
+
+use common::{assert_crud, assert_foreign_key_violation, TestApp};
+
+#[tokio::test]
+async fn table_lists_schema_tables() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/table").await;
+    response.assert_status(StatusCode::OK);
+
+    let tables = response.json();
+    for table in ["country", "student", "book", "hold", "notification"] {
+        assert!(tables.as_array().unwrap().contains(&json!(table)));
+    }
+}
+
+#[tokio::test]
+async fn country_crud() {
+    let app = TestApp::new().await;
+
+    assert_crud(
+        &app,
+        "/country",
+        "code",
+        json!({ "code": "UA", "name": "Ukraine" }),
+        "name",
+        json!("Україна"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn country_rejects_invalid_code() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post("/country", json!({ "code": "ukr", "name": "" }))
+        .await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    let errors = response.json();
+    assert!(errors.get("code").is_some());
+    assert!(errors.get("name").is_some());
+}
+
+#[tokio::test]
+async fn country_in_use_cannot_be_deleted() {
+    let app = TestApp::new().await;
+    app.publisher("UA").await;
+
+    assert_foreign_key_violation(&app.delete("/country/UA", 1).await);
+}
+
+#[tokio::test]
+async fn country_list_answers_conditional_requests() {
+    let app = TestApp::new().await;
+    app.country("UA").await;
+
+    let list = app.get("/country").await;
+    list.assert_status(StatusCode::OK);
+    let etag = list.headers[header::ETAG].to_str().unwrap().to_string();
+    let modified = list.headers[header::LAST_MODIFIED]
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    app.request(
+        Method::GET,
+        "/country",
+        &[(header::IF_NONE_MATCH, &etag)],
+        None,
+    )
+    .await
+    .assert_status(StatusCode::NOT_MODIFIED);
+    app.request(
+        Method::GET,
+        "/country",
+        &[(header::IF_MODIFIED_SINCE, &modified)],
+        None,
+    )
+    .await
+    .assert_status(StatusCode::NOT_MODIFIED);
+
+    // Writes invalidate the cached list.
+    app.country("PL").await;
+
+    let list = app
+        .request(
+            Method::GET,
+            "/country",
+            &[(header::IF_NONE_MATCH, &etag)],
+            None,
+        )
+        .await;
+    list.assert_status(StatusCode::OK);
+    assert_eq!(list.json().as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn country_list_is_modified_by_write_in_same_second() {
+    let app = TestApp::new().await;
+    app.country("UA").await;
+
+    let list = app.get("/country").await;
+    let modified = list.headers[header::LAST_MODIFIED]
+        .to_str()
+        .unwrap()
+        .to_string();
+    app.country("PL").await;
+
+    let list = app
+        .request(
+            Method::GET,
+            "/country",
+            &[(header::IF_MODIFIED_SINCE, &modified)],
+            None,
+        )
+        .await;
+    list.assert_status(StatusCode::OK);
+    assert_ne!(list.headers[header::LAST_MODIFIED], modified.as_str());
+    assert_eq!(list.json().as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn category_crud() {
+    let app = TestApp::new().await;
+
+    assert_crud(
+        &app,
+        "/category",
+        "id",
+        json!({ "id": 0, "name": "Poetry" }),
+        "name",
+        json!("Prose"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn faculty_crud() {
+    let app = TestApp::new().await;
+
+    assert_crud(
+        &app,
+        "/faculty",
+        "id",
+        json!({ "id": 0, "name": "Physics", "letter": "P" }),
+        "letter",
+        json!("F"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn curriculum_crud() {
+    let app = TestApp::new().await;
+
+    assert_crud(
+        &app,
+        "/curriculum",
+        "id",
+        json!({ "id": 0, "name": "Optics", "letter": "O" }),
+        "name",
+        json!("Acoustics"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn faculty_curriculum_crud() {
+    let app = TestApp::new().await;
+    let faculty = app.faculty().await;
+    let curriculum = app.curriculum().await;
+    let other_curriculum = app.curriculum().await;
+
+    assert_crud(
+        &app,
+        "/faculty-curriculum",
+        "id",
+        json!({ "id": 0, "faculty": faculty["id"], "curriculum": curriculum["id"] }),
+        "curriculum",
+        other_curriculum["id"].clone(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn faculty_curriculum_rejects_missing_faculty() {
+    let app = TestApp::new().await;
+    let curriculum = app.curriculum().await;
+
+    let response = app
+        .post(
+            "/faculty-curriculum",
+            json!({ "id": 0, "faculty": 999, "curriculum": curriculum["id"] }),
+        )
+        .await;
+
+    assert_foreign_key_violation(&response);
+}
+
+#[tokio::test]
+async fn faculty_in_use_cannot_be_deleted() {
+    let app = TestApp::new().await;
+    let faculty_curriculum = app.faculty_curriculum().await;
+    let faculty = app
+        .get(&format!("/faculty/{}", faculty_curriculum["faculty"]))
+        .await;
+
+    let response = app
+        .delete(
+            &format!("/faculty/{}", faculty_curriculum["faculty"]),
+            faculty.version(),
+        )
+        .await;
+
+    assert_foreign_key_violation(&response);
+}
+
+#[tokio::test]
+async fn publisher_crud() {
+    let app = TestApp::new().await;
+    app.country("UA").await;
+
+    assert_crud(
+        &app,
+        "/publisher",
+        "id",
+        json!({ "id": 0, "name": "Nauka", "country": "UA" }),
+        "name",
+        json!("Osnovy"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn publisher_rejects_missing_country() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post(
+            "/publisher",
+            json!({ "id": 0, "name": "Nauka", "country": "ZZ" }),
+        )
+        .await;
+
+    assert_foreign_key_violation(&response);
+}
@@ -0,0 +1,285 @@
+mod common;
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, Method, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use serde_json::{json, Value};
+
+use common::{days_from_today, today, TestApp};
+
+const SECRET: &str = "0123456789abcdef";
+
+/// HTTP server recording the webhooks it receives and answering them with `status`.
+#[derive(Clone, Default)]
+struct Receiver {
+    received: Arc<Mutex<Vec<(HeaderMap, Bytes)>>>,
+    status: Arc<AtomicU16>,
+}
+
+impl Receiver {
+    /// Serves the receiver on a free local port, returns the URL webhooks should be sent to.
+    fn start(&self, status: StatusCode) -> String {
+        self.status.store(status.as_u16(), Ordering::Relaxed);
+
+        let router = Router::new()
+            .route("/hook", post(receive))
+            .with_state(self.clone());
+        let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .serve(router.into_make_service());
+        let url = format!("http://{}/hook", server.local_addr());
+        tokio::spawn(server);
+
+        url
+    }
+
+    fn answer(&self, status: StatusCode) {
+        self.status.store(status.as_u16(), Ordering::Relaxed);
+    }
+
+    fn received(&self) -> Vec<(HeaderMap, Bytes)> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+async fn receive(State(receiver): State<Receiver>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    receiver.received.lock().unwrap().push((headers, body));
+    StatusCode::from_u16(receiver.status.load(Ordering::Relaxed)).unwrap()
+}
+
+async fn deliver(app: &TestApp) -> usize {
+    crud::webhook::worker::deliver_pending(app.pg())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn webhooks_deliver_signed_events() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let receiver = Receiver::default();
+    let url = receiver.start(StatusCode::OK);
+
+    app.post(
+        "/webhook",
+        json!({ "url": "ftp://example.com", "events": [], "secret": "short" }),
+    )
+    .await
+    .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    let subscription = app
+        .create(
+            "/webhook",
+            json!({
+                "url": url,
+                "events": ["LoanCreated", "LoanReturnedLate"],
+                "secret": SECRET,
+            }),
+        )
+        .await;
+    assert_eq!(subscription["active"], true);
+    assert_eq!(subscription["secret"], Value::Null);
+
+    // The card is issued too, but nobody subscribed to cards.
+    let book = app.book("UA").await;
+    let mut borrowing = app.students_borrowing(&book, -2).await;
+    borrowing["return_date"] = json!(today());
+    app.put(
+        &format!("/students-borrowing/{}", borrowing["id"]),
+        1,
+        borrowing.clone(),
+    )
+    .await
+    .assert_status(StatusCode::OK);
+
+    assert_eq!(deliver(&app).await, 2);
+    assert_eq!(deliver(&app).await, 0);
+
+    let received = receiver.received();
+    let events = received
+        .iter()
+        .map(|(headers, body)| {
+            let timestamp = headers["x-webhook-timestamp"].to_str().unwrap();
+            assert_eq!(
+                headers["x-webhook-signature"],
+                crud::webhook::sign(SECRET, timestamp.parse().unwrap(), body)
+            );
+
+            let body: Value = serde_json::from_slice(body).unwrap();
+            assert_eq!(headers["x-webhook-event"], body["event"].as_str().unwrap());
+            assert_eq!(headers["x-webhook-delivery"], body["id"].to_string());
+            assert_eq!(body["data"]["id"], borrowing["id"]);
+            assert_eq!(body["data"]["patron"], "student");
+
+            body
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(events[0]["event"], "LoanCreated");
+    assert_eq!(
+        events[0]["data"]["required_return_date"],
+        days_from_today(-2)
+    );
+    assert_eq!(events[1]["event"], "LoanReturnedLate");
+    assert_eq!(events[1]["data"]["days_late"], 2);
+
+    let deliveries = app
+        .get(&format!("/webhook/{}/deliveries", subscription["id"]))
+        .await
+        .json();
+    assert_eq!(deliveries.as_array().unwrap().len(), 2);
+    assert!(deliveries
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|delivery| delivery["status"] == "Delivered" && delivery["attempts"] == 1));
+
+    let attempts = app
+        .get(&format!(
+            "/webhook-delivery/{}/attempts",
+            deliveries[0]["id"]
+        ))
+        .await
+        .json();
+    assert_eq!(attempts[0]["response_status"], 200);
+    assert_eq!(attempts[0]["error"], Value::Null);
+}
+
+#[tokio::test]
+async fn subscription_writes_check_version() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let body = json!({
+        "url": "https://example.com/hook",
+        "events": ["LoanCreated"],
+        "secret": SECRET,
+    });
+    let subscription = app.create("/webhook", body.clone()).await;
+    assert_eq!(subscription["version"], 1);
+    let uri = format!("/webhook/{}", subscription["id"]);
+
+    app.request(Method::PUT, &uri, &[], Some(body.clone()))
+        .await
+        .assert_status(StatusCode::PRECONDITION_REQUIRED);
+
+    let mut inactive = body.clone();
+    inactive["active"] = json!(false);
+    let updated = app.put(&uri, 1, inactive.clone()).await;
+    updated.assert_status(StatusCode::OK);
+    assert_eq!(updated.json()["version"], 2);
+
+    let stale = app.put(&uri, 1, inactive).await;
+    stale.assert_status(StatusCode::PRECONDITION_FAILED);
+    assert_eq!(
+        stale.text(),
+        format!(
+            "Webhook subscription {} was modified, reload it and try again",
+            subscription["id"]
+        )
+    );
+    app.delete(&uri, 1)
+        .await
+        .assert_status(StatusCode::PRECONDITION_FAILED);
+    app.delete(&uri, 2).await.assert_status(StatusCode::OK);
+    app.delete(&uri, 2)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn failed_webhooks_are_retried_until_dead() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let receiver = Receiver::default();
+    let url = receiver.start(StatusCode::INTERNAL_SERVER_ERROR);
+
+    app.create(
+        "/webhook",
+        json!({ "url": url, "events": ["StudentCardIssued"], "secret": SECRET }),
+    )
+    .await;
+    let student = app.student().await;
+    let card = app.student_card(&student).await;
+
+    assert_eq!(deliver(&app).await, 1);
+    let delivery = &app.get("/webhook-delivery").await.json()[0];
+    assert_eq!(delivery["status"], "Pending");
+    assert_eq!(delivery["attempts"], 1);
+    assert_eq!(delivery["payload"]["id"], card["id"]);
+    assert_eq!(
+        delivery["last_error"],
+        "Receiver answered 500 Internal Server Error"
+    );
+    // Retried later, not on the next run.
+    assert_eq!(deliver(&app).await, 0);
+
+    let uri = format!("/webhook-delivery/{}", delivery["id"]);
+    app.post(&format!("{uri}/retry"), json!({}))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+
+    for _ in 1..8 {
+        sqlx::query("UPDATE webhook_delivery SET next_attempt_at = now()")
+            .execute(app.pg())
+            .await
+            .unwrap();
+        assert_eq!(deliver(&app).await, 1);
+    }
+
+    let dead = app.get("/webhook-delivery?status=Dead").await.json();
+    assert_eq!(dead[0]["id"], delivery["id"]);
+    assert_eq!(dead[0]["attempts"], 8);
+    let attempts = app.get(&format!("{uri}/attempts")).await.json();
+    assert_eq!(attempts.as_array().unwrap().len(), 8);
+    sqlx::query("UPDATE webhook_delivery SET next_attempt_at = now()")
+        .execute(app.pg())
+        .await
+        .unwrap();
+    assert_eq!(deliver(&app).await, 0);
+
+    receiver.answer(StatusCode::NO_CONTENT);
+    let retried = app.post(&format!("{uri}/retry"), json!({})).await;
+    retried.assert_status(StatusCode::OK);
+    assert_eq!(retried.json()["status"], "Pending");
+    assert_eq!(deliver(&app).await, 1);
+    assert_eq!(app.get(&uri).await.json()["status"], "Delivered");
+    assert_eq!(receiver.received().len(), 9);
+}
+
+#[tokio::test]
+async fn webhooks_are_claimed_by_one_worker() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let receiver = Receiver::default();
+    let url = receiver.start(StatusCode::OK);
+
+    app.create(
+        "/webhook",
+        json!({ "url": url, "events": ["StudentCardIssued"], "secret": SECRET }),
+    )
+    .await;
+    for _ in 0..3 {
+        let student = app.student().await;
+        app.student_card(&student).await;
+    }
+
+    // The second run starts while the first one posts, the claimed deliveries are skipped.
+    let (first, second) = tokio::join!(deliver(&app), deliver(&app));
+    assert_eq!(first + second, 3);
+    assert_eq!(receiver.received().len(), 3);
+
+    let deliveries = app.get("/webhook-delivery").await.json();
+    assert!(deliveries
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|delivery| delivery["status"] == "Delivered" && delivery["attempts"] == 1));
+}